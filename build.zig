@@ -14,6 +14,11 @@ pub fn build(b: *std.Build) !void {
         "release-version",
         "Release version for artifact naming (default: build.zig.zon .version)",
     ) orelse default_release_version;
+    const otlp = b.option(
+        bool,
+        "otlp",
+        "Compile the OTLP metrics exporter for fleet setups",
+    ) orelse false;
 
     const raylib_dep = b.dependency("raylib_zig", .{
         .target = target,
@@ -27,6 +32,7 @@ pub fn build(b: *std.Build) !void {
         .target = target,
         .optimize = optimize,
         .raylib = raylib,
+        .otlp = otlp,
     };
     const root_module = app_build.createRootModule(app_ctx);
 
@@ -6,6 +6,8 @@ pub const Context = struct {
     target: std.Build.ResolvedTarget,
     optimize: std.builtin.OptimizeMode,
     raylib: *std.Build.Module,
+    /// Compile the OTLP metrics exporter (fleet setups).
+    otlp: bool = false,
 };
 
 pub fn createRootModule(ctx: Context) *std.Build.Module {
@@ -13,8 +15,15 @@ pub fn createRootModule(ctx: Context) *std.Build.Module {
         .root_source_file = ctx.b.path(config.root_source_file),
         .target = ctx.target,
         .optimize = ctx.optimize,
+        .link_libc = true,
     });
     root_module.addImport("raylib", ctx.raylib);
+    const options = ctx.b.addOptions();
+    options.addOption(bool, "otlp_export", ctx.otlp);
+    root_module.addOptions("build_options", options);
+    for (config.system_libraries) |library| {
+        root_module.linkSystemLibrary(library, .{});
+    }
     return root_module;
 }
 
@@ -31,6 +40,7 @@ pub fn addUnitTests(ctx: Context) *std.Build.Step.Run {
         .target = ctx.b.graph.host,
         .optimize = ctx.optimize,
         .raylib = ctx.raylib,
+        .otlp = ctx.otlp,
     });
 
     const unit_tests = ctx.b.addTest(.{
@@ -2,6 +2,19 @@ pub const app_name = "waystream";
 pub const test_name = app_name ++ "-tests";
 pub const root_source_file = "src/main.zig";
 
+pub const system_libraries = [_][]const u8{
+    "gstreamer-1.0",
+    "gstapp-1.0",
+    "gstvideo-1.0",
+    "gobject-2.0",
+    "gio-2.0",
+    "glib-2.0",
+    "lcms2",
+    "wayland-client",
+    "gbm",
+    "libdrm",
+};
+
 pub const fmt_paths = [_][]const u8{
     "build.zig",
     "build",
@@ -0,0 +1,137 @@
+//! Profile bundles: one file containing a profile definition plus its media,
+//! for moving wallpaper setups between machines.
+//!
+//! The container is deliberately simple: the magic `WAYBNDL1`, then entries
+//! of [name_len: u32le][name][data_len: u64le][data]. Entry `profile.name`
+//! is the profile name, `profile.video` the media entry it references, and
+//! `media/<basename>` the media bytes. Import unpacks media under
+//! XDG_DATA_HOME and registers the profile in the config.
+
+const std = @import("std");
+const profiles_mod = @import("config/profiles.zig");
+
+const magic = "WAYBNDL1";
+
+pub const BundleError = error{
+    ProfileNotFound,
+    MalformedBundle,
+};
+
+/// `bundle export <profile> <out>`: packages the profile and its video.
+pub fn exportBundle(allocator: std.mem.Allocator, profile_name: []const u8, out_path: []const u8) !void {
+    var config = try profiles_mod.ProfilesConfig.load(allocator, null);
+    defer config.deinit();
+
+    const profile = config.findProfile(profile_name) orelse return BundleError.ProfileNotFound;
+
+    const media = try std.fs.cwd().readFileAlloc(allocator, profile.video, 4 * 1024 * 1024 * 1024);
+    defer allocator.free(media);
+    const media_name = try std.fmt.allocPrint(allocator, "media/{s}", .{
+        std.fs.path.basename(profile.video),
+    });
+    defer allocator.free(media_name);
+
+    const file = try std.fs.cwd().createFile(out_path, .{});
+    defer file.close();
+
+    try file.writeAll(magic);
+    try writeEntry(file, "profile.name", profile.name);
+    try writeEntry(file, "profile.video", media_name);
+    try writeEntry(file, media_name, media);
+
+    std.log.info("exported profile {s} to {s}", .{ profile.name, out_path });
+}
+
+/// `bundle import <in>`: unpacks media and registers the profile.
+pub fn importBundle(allocator: std.mem.Allocator, bundle_path: []const u8) !void {
+    const data = try std.fs.cwd().readFileAlloc(allocator, bundle_path, 4 * 1024 * 1024 * 1024);
+    defer allocator.free(data);
+
+    if (!std.mem.startsWith(u8, data, magic)) return BundleError.MalformedBundle;
+
+    var profile_name: ?[]const u8 = null;
+    var media_path: ?[]u8 = null;
+    defer if (media_path) |p| allocator.free(p);
+
+    var offset: usize = magic.len;
+    while (offset < data.len) {
+        const entry = try readEntry(data, &offset);
+        if (std.mem.eql(u8, entry.name, "profile.name")) {
+            profile_name = entry.data;
+        } else if (std.mem.eql(u8, entry.name, "profile.video")) {
+            // Resolved when the media entry is unpacked.
+        } else if (std.mem.startsWith(u8, entry.name, "media/")) {
+            media_path = try unpackMedia(allocator, entry.name["media/".len..], entry.data);
+        } else {
+            // Unknown entries are skipped for forward compatibility.
+        }
+    }
+
+    const name = profile_name orelse return BundleError.MalformedBundle;
+    const video = media_path orelse return BundleError.MalformedBundle;
+
+    var config = try profiles_mod.ProfilesConfig.load(allocator, null);
+    defer config.deinit();
+    try config.addProfile(.{ .name = name, .video = video });
+
+    std.log.info("imported profile {s} (media at {s})", .{ name, video });
+}
+
+const Entry = struct {
+    name: []const u8,
+    data: []const u8,
+};
+
+fn writeEntry(file: std.fs.File, name: []const u8, data: []const u8) !void {
+    var header: [4]u8 = undefined;
+    std.mem.writeInt(u32, &header, @intCast(name.len), .little);
+    try file.writeAll(&header);
+    try file.writeAll(name);
+    var size: [8]u8 = undefined;
+    std.mem.writeInt(u64, &size, data.len, .little);
+    try file.writeAll(&size);
+    try file.writeAll(data);
+}
+
+fn readEntry(data: []const u8, offset: *usize) BundleError!Entry {
+    if (data.len - offset.* < 4) return BundleError.MalformedBundle;
+    const name_len = std.mem.readInt(u32, data[offset.*..][0..4], .little);
+    offset.* += 4;
+    // Compare against the remaining bytes rather than adding to the
+    // offset: the lengths are attacker-controlled and the sum overflows.
+    if (name_len > data.len - offset.*) return BundleError.MalformedBundle;
+    const name = data[offset.*..][0..name_len];
+    offset.* += name_len;
+    if (data.len - offset.* < 8) return BundleError.MalformedBundle;
+    const data_len = std.mem.readInt(u64, data[offset.*..][0..8], .little);
+    offset.* += 8;
+    if (data_len > data.len - offset.*) return BundleError.MalformedBundle;
+    const payload = data[offset.*..][0..data_len];
+    offset.* += data_len;
+    return .{ .name = name, .data = payload };
+}
+
+/// Writes media bytes under XDG_DATA_HOME and returns the absolute path.
+fn unpackMedia(allocator: std.mem.Allocator, basename: []const u8, data: []const u8) ![]u8 {
+    // Reject anything trying to escape the media directory.
+    if (std.mem.indexOf(u8, basename, "/") != null or std.mem.eql(u8, basename, "..")) {
+        return BundleError.MalformedBundle;
+    }
+
+    const data_home = std.posix.getenv("XDG_DATA_HOME");
+    const path = if (data_home) |dh|
+        try std.fmt.allocPrint(allocator, "{s}/waystream/media/{s}", .{ dh, basename })
+    else blk: {
+        const home = std.posix.getenv("HOME") orelse "/";
+        break :blk try std.fmt.allocPrint(allocator, "{s}/.local/share/waystream/media/{s}", .{ home, basename });
+    };
+    errdefer allocator.free(path);
+
+    if (std.fs.path.dirname(path)) |dir| {
+        try std.fs.cwd().makePath(dir);
+    }
+    const file = try std.fs.cwd().createFile(path, .{});
+    defer file.close();
+    try file.writeAll(data);
+    return path;
+}
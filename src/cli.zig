@@ -0,0 +1,518 @@
+//! Command line parsing for the waystream binary.
+
+const std = @import("std");
+const player = @import("player.zig");
+const gui = @import("gui.zig");
+const supervisor = @import("supervisor.zig");
+const blend = @import("render/blend.zig");
+const playlist = @import("playback/playlist.zig");
+const swapchain = @import("render/swapchain.zig");
+const pathprobe = @import("render/pathprobe.zig");
+const layout = @import("render/layout.zig");
+const power = @import("metrics/power.zig");
+
+pub const Command = union(enum) {
+    play: player.Options,
+    gui: gui.Options,
+    status,
+    doctor,
+    migrate,
+    /// name null means clear the override (the global one, or the named
+    /// output's when `output` is set).
+    set_profile: struct { name: ?[]const u8, duration: ?[]const u8, output: ?[]const u8 },
+    bundle_export: struct { profile: []const u8, out_path: []const u8 },
+    bundle_import: struct { bundle_path: []const u8 },
+    config_import: struct { tool: ImportTool, source: ?[]const u8 },
+    help,
+};
+
+pub const ImportTool = enum { mpvpaper, hyprpaper, swww };
+
+pub const usage =
+    \\Usage: waystream <command> [options]
+    \\
+    \\Commands:
+    \\  play <video>... Play one or more videos as the background surface
+    \\                  (several positionals form a playlist advanced on EOS;
+    \\                  a directory of images runs as a slideshow,
+    \\                  v4l2:///dev/videoN plays a camera feed, and
+    \\                  pipewire://<node> mirrors a portal screencast)
+    \\  gui             Show live playback metrics
+    \\  status          List running players
+    \\  doctor          Report decoder/protocol capabilities of this system
+    \\  migrate         Rewrite the profiles config at the current version
+    \\  set-profile <name> [--for <dur>] [--output <out>] Override the
+    \\                  scheduled profile, optionally expiring after e.g.
+    \\                  2h and optionally for a single output; --clear
+    \\                  (with optional --output) removes the override
+    \\  bundle export <profile> <out>   Package a profile and its media
+    \\  bundle import <file>            Unpack a bundle and register its profile
+    \\  import <tool> [src]             Convert an mpvpaper command line, a
+    \\                                  hyprpaper.conf, or swww state into
+    \\                                  profiles (tool: mpvpaper|hyprpaper|swww)
+    \\  help            Show this help
+    \\
+    \\Play options:
+    \\  --no-loop             Exit at end of stream instead of looping
+    \\  --target <name>       Name this playback runs under (default: default)
+    \\  --control-socket      Accept JSON commands on the target's Unix socket
+    \\  --metrics-listen <a>  Serve Prometheus metrics over HTTP on addr:port
+    \\  --metrics-stream      Push metric updates to the target's metrics
+    \\                        socket (newline-delimited JSON)
+    \\  --metrics-history <s> Append one snapshot every s seconds to a
+    \\                        size-rotated JSONL log under the state dir
+    \\  --otlp-endpoint <a>   Push OTLP metrics to this collector (host:port;
+    \\                        defaults to OTEL_EXPORTER_OTLP_ENDPOINT; needs
+    \\                        a build with -Dotlp)
+    \\  --dbus                Emit MetricsUpdated on the session bus for
+    \\                        status bars (no file polling needed)
+    \\  --hdr                 Negotiate HDR-capable formats (needs compositor support)
+    \\  --dump-dot <dir>      Write pipeline DOT graphs on state changes and errors
+    \\  --icc-profile <path>  Apply this display ICC profile to decoded frames
+    \\  --max-players <n>     Soft limit on running players (default: 8)
+    \\  --audio               Play the audio track (default: silent)
+    \\  --audio-sink <name>   Route audio to this PulseAudio/PipeWire sink
+    \\  --volume <v>          Audio volume, 0.0-1.0 (default: 1.0)
+    \\  --blend-to <video>    Blend towards a second source over a time window
+    \\  --blend-window <w>    Window as HH:MM-HH:MM (required with --blend-to)
+    \\  --pipeline <desc>     Custom gst-launch pipeline with an appsink named
+    \\                        waystream-sink (the video argument is optional)
+    \\  --full-decode         Decode at source resolution instead of scaling
+    \\                        to the output inside the pipeline
+    \\  --adaptive-fps <n>    Step decode resolution down when FPS stays
+    \\                        below n (requires in-pipeline scaling)
+    \\  --force-rgba          Request RGBA from the pipeline instead of
+    \\                        native NV12/I420 converted at blit time
+    \\  --no-gpu-postproc     Keep scaling/conversion on the CPU even when
+    \\                        vapostproc or v4l2convert is available
+    \\  --net-buffer <bytes>  Download buffer size for network streams
+    \\  --net-timeout <s>     Connection/read timeout for network streams
+    \\                        (default: 10)
+    \\  --no-reconnect        Exit on network stream failure instead of
+    \\                        retrying with backoff
+    \\  --max-retries <n>     Pipeline rebuilds attempted after local decode
+    \\                        errors before exiting (default: 3)
+    \\  --order <mode>        Playlist order: sequential (default), shuffle, or
+    \\                        weighted (entries may carry a :N weight suffix)
+    \\  --repeat <mode>       Playlist repeat: all (default), one, or off
+    \\  --slide-duration <s>  Seconds per image when playing a directory of
+    \\                        images as a slideshow (default: 60)
+    \\  --fade <s>            Crossfade length between slides (default: 1)
+    \\  --transition-fade <ms> Fade the incoming source in over ms when the
+    \\                        video is swapped at runtime (default: hard cut)
+    \\  --frame-step <s>      Low-power mode: decode one frame every s seconds
+    \\                        and keep the pipeline paused in between
+    \\  --on-battery <p>      Policy while discharging: continue (default),
+    \\                        static (frame stepping), or pause
+    \\  --buffers <n>         Buffer depth per surface on the dmabuf path,
+    \\                        2-4 (default: 3; 2 saves memory, 3 rides out
+    \\                        compositor holds)
+    \\  --buffer-mode <m>     Frame path: auto (measure at startup, default),
+    \\                        shm, or dmabuf (layer-shell surfaces with
+    \\                        zero-copy buffer import; falls back to the
+    \\                        window when the compositor lacks support)
+    \\  --scale-mode <m>      Placement: fit (letterbox, default), fill
+    \\                        (crop), or stretch
+    \\  --mem-cap <mb>        Cap buffer memory; decode resolution steps
+    \\                        down when playback exceeds it
+    \\  --cpu-budget <pct>    Cap CPU use at pct of one core; skips frames
+    \\                        and downscales to stay under it
+    \\  --waylandsink         Present through waylandsink on a shared display
+    \\                        connection (zero-copy; sink manages buffers)
+    \\
+    \\Gui options:
+    \\  --target <name>         Playback target to watch (default: default)
+    \\  --metrics-file <path>   Read this snapshot file instead
+    \\
+    \\Signals (play):
+    \\  SIGUSR1         Toggle pause
+    \\  SIGUSR2         Force redraw/re-sync of the current frame
+    \\
+;
+
+pub const ParseError = error{
+    MissingCommand,
+    UnknownCommand,
+    MissingVideo,
+    UnknownOption,
+    MissingOptionValue,
+    InvalidOptionValue,
+};
+
+pub const Error = ParseError || std.mem.Allocator.Error;
+
+pub fn parse(allocator: std.mem.Allocator, args: []const []const u8) Error!Command {
+    if (args.len < 2) return ParseError.MissingCommand;
+
+    const command = args[1];
+    if (std.mem.eql(u8, command, "help") or std.mem.eql(u8, command, "--help")) {
+        return .help;
+    }
+    if (std.mem.eql(u8, command, "play")) {
+        return .{ .play = try parsePlay(allocator, args[2..]) };
+    }
+    if (std.mem.eql(u8, command, "gui")) {
+        return .{ .gui = try parseGui(args[2..]) };
+    }
+    if (std.mem.eql(u8, command, "status")) {
+        return .status;
+    }
+    if (std.mem.eql(u8, command, "doctor")) {
+        return .doctor;
+    }
+    if (std.mem.eql(u8, command, "migrate")) {
+        return .migrate;
+    }
+    if (std.mem.eql(u8, command, "set-profile")) {
+        const rest = args[2..];
+        if (rest.len == 0) return ParseError.MissingCommand;
+        var name: ?[]const u8 = null;
+        var duration: ?[]const u8 = null;
+        var output: ?[]const u8 = null;
+        var clear = false;
+        var index: usize = 0;
+        while (index < rest.len) : (index += 1) {
+            const arg = rest[index];
+            if (std.mem.eql(u8, arg, "--clear")) {
+                clear = true;
+            } else if (std.mem.eql(u8, arg, "--for")) {
+                index += 1;
+                if (index >= rest.len) return ParseError.MissingOptionValue;
+                duration = rest[index];
+            } else if (std.mem.eql(u8, arg, "--output")) {
+                index += 1;
+                if (index >= rest.len) return ParseError.MissingOptionValue;
+                output = rest[index];
+            } else if (std.mem.startsWith(u8, arg, "--")) {
+                return ParseError.UnknownOption;
+            } else if (name == null) {
+                name = arg;
+            } else {
+                return ParseError.UnknownOption;
+            }
+        }
+        if (clear == (name != null)) {
+            // Exactly one of a profile name or --clear.
+            return ParseError.MissingCommand;
+        }
+        return .{ .set_profile = .{ .name = name, .duration = duration, .output = output } };
+    }
+    if (std.mem.eql(u8, command, "bundle")) {
+        return parseBundle(args[2..]);
+    }
+    if (std.mem.eql(u8, command, "import")) {
+        const rest = args[2..];
+        if (rest.len == 0) return ParseError.MissingCommand;
+        const tool = std.meta.stringToEnum(ImportTool, rest[0]) orelse
+            return ParseError.UnknownCommand;
+        if (rest.len > 2) return ParseError.MissingOptionValue;
+        return .{ .config_import = .{
+            .tool = tool,
+            .source = if (rest.len == 2) rest[1] else null,
+        } };
+    }
+    return ParseError.UnknownCommand;
+}
+
+fn parseBundle(args: []const []const u8) ParseError!Command {
+    if (args.len == 0) return ParseError.MissingCommand;
+    if (std.mem.eql(u8, args[0], "export")) {
+        if (args.len != 3) return ParseError.MissingOptionValue;
+        return .{ .bundle_export = .{ .profile = args[1], .out_path = args[2] } };
+    }
+    if (std.mem.eql(u8, args[0], "import")) {
+        if (args.len != 2) return ParseError.MissingOptionValue;
+        return .{ .bundle_import = .{ .bundle_path = args[1] } };
+    }
+    return ParseError.UnknownCommand;
+}
+
+fn parseGui(args: []const []const u8) ParseError!gui.Options {
+    var options: gui.Options = .{};
+
+    var i: usize = 0;
+    while (i < args.len) : (i += 1) {
+        const arg = args[i];
+        if (std.mem.eql(u8, arg, "--target")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            options.target = args[i];
+        } else if (std.mem.eql(u8, arg, "--metrics-file")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            options.metrics_file = args[i];
+        } else {
+            return ParseError.UnknownOption;
+        }
+    }
+
+    return options;
+}
+
+fn parsePlay(allocator: std.mem.Allocator, args: []const []const u8) Error!player.Options {
+    var videos: std.ArrayList([]const u8) = .empty;
+    errdefer videos.deinit(allocator);
+    var loop = true;
+    var target: []const u8 = "default";
+    var control_socket = false;
+    var metrics_listen: ?[]const u8 = null;
+    var metrics_stream = false;
+    var metrics_history_s: ?u32 = null;
+    var otlp_endpoint: ?[]const u8 = null;
+    var dbus = false;
+    var hdr = false;
+    var dump_dot_dir: ?[]const u8 = null;
+    var icc_profile: ?[]const u8 = null;
+    var max_players: u32 = supervisor.default_max_players;
+    var audio = false;
+    var volume: f64 = 1.0;
+    var audio_sink: ?[]const u8 = null;
+    var blend_to: ?[]const u8 = null;
+    var blend_window: ?blend.Window = null;
+    var pipeline_override: ?[]const u8 = null;
+    var decode_at_output = true;
+    var adaptive_min_fps: ?f64 = null;
+    var allow_yuv = true;
+    var gpu_postproc = true;
+    var net_buffer_bytes: ?u32 = null;
+    var net_timeout_s: u32 = 10;
+    var reconnect = true;
+    var max_retries: u32 = 3;
+    var order: playlist.Order = .sequential;
+    var repeat: playlist.Repeat = .all;
+    var slide_duration_s: u32 = 60;
+    var embed_sink = false;
+    var fade_s: f64 = 1.0;
+    var transition_fade_ms: u32 = 0;
+    var frame_step_s: ?u32 = null;
+    var on_battery: power.Policy = .@"continue";
+    var buffer_depth: u32 = swapchain.default_depth;
+    var buffer_mode: pathprobe.Mode = .auto;
+    var scale_mode: layout.ScaleMode = .fit;
+    var mem_cap_mb: ?u32 = null;
+    var cpu_budget_pct: ?u32 = null;
+
+    var i: usize = 0;
+    while (i < args.len) : (i += 1) {
+        const arg = args[i];
+        if (std.mem.eql(u8, arg, "--no-loop")) {
+            loop = false;
+        } else if (std.mem.eql(u8, arg, "--control-socket")) {
+            control_socket = true;
+        } else if (std.mem.eql(u8, arg, "--metrics-listen")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            metrics_listen = args[i];
+        } else if (std.mem.eql(u8, arg, "--metrics-stream")) {
+            metrics_stream = true;
+        } else if (std.mem.eql(u8, arg, "--metrics-history")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            metrics_history_s = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+            if (metrics_history_s.? == 0) return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--otlp-endpoint")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            otlp_endpoint = args[i];
+        } else if (std.mem.eql(u8, arg, "--dbus")) {
+            dbus = true;
+        } else if (std.mem.eql(u8, arg, "--hdr")) {
+            hdr = true;
+        } else if (std.mem.eql(u8, arg, "--dump-dot")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            dump_dot_dir = args[i];
+        } else if (std.mem.eql(u8, arg, "--icc-profile")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            icc_profile = args[i];
+        } else if (std.mem.eql(u8, arg, "--audio")) {
+            audio = true;
+        } else if (std.mem.eql(u8, arg, "--audio-sink")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            audio_sink = args[i];
+            audio = true;
+        } else if (std.mem.eql(u8, arg, "--volume")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            volume = std.fmt.parseFloat(f64, args[i]) catch
+                return ParseError.InvalidOptionValue;
+            if (volume < 0 or volume > 1) return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--full-decode")) {
+            decode_at_output = false;
+        } else if (std.mem.eql(u8, arg, "--force-rgba")) {
+            allow_yuv = false;
+        } else if (std.mem.eql(u8, arg, "--no-gpu-postproc")) {
+            gpu_postproc = false;
+        } else if (std.mem.eql(u8, arg, "--net-buffer")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            net_buffer_bytes = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--net-timeout")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            net_timeout_s = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--no-reconnect")) {
+            reconnect = false;
+        } else if (std.mem.eql(u8, arg, "--max-retries")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            max_retries = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--order")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            order = std.meta.stringToEnum(playlist.Order, args[i]) orelse
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--repeat")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            repeat = std.meta.stringToEnum(playlist.Repeat, args[i]) orelse
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--waylandsink")) {
+            embed_sink = true;
+        } else if (std.mem.eql(u8, arg, "--slide-duration")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            slide_duration_s = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--fade")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            fade_s = std.fmt.parseFloat(f64, args[i]) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--transition-fade")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            transition_fade_ms = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--mem-cap")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            mem_cap_mb = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--cpu-budget")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            cpu_budget_pct = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+            if (cpu_budget_pct.? == 0 or cpu_budget_pct.? > 100)
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--buffer-mode")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            buffer_mode = std.meta.stringToEnum(pathprobe.Mode, args[i]) orelse
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--scale-mode")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            scale_mode = std.meta.stringToEnum(layout.ScaleMode, args[i]) orelse
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--buffers")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            buffer_depth = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+            if (buffer_depth < swapchain.min_depth or buffer_depth > swapchain.max_depth)
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--frame-step")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            frame_step_s = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+            if (frame_step_s.? == 0) return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--on-battery")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            on_battery = std.meta.stringToEnum(power.Policy, args[i]) orelse
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--adaptive-fps")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            adaptive_min_fps = std.fmt.parseFloat(f64, args[i]) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--pipeline")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            pipeline_override = args[i];
+        } else if (std.mem.eql(u8, arg, "--blend-to")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            blend_to = args[i];
+        } else if (std.mem.eql(u8, arg, "--blend-window")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            blend_window = blend.parseWindow(args[i]) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--max-players")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            max_players = std.fmt.parseInt(u32, args[i], 10) catch
+                return ParseError.InvalidOptionValue;
+        } else if (std.mem.eql(u8, arg, "--target")) {
+            i += 1;
+            if (i >= args.len) return ParseError.MissingOptionValue;
+            target = args[i];
+        } else if (std.mem.startsWith(u8, arg, "--")) {
+            return ParseError.UnknownOption;
+        } else {
+            try videos.append(allocator, arg);
+        }
+    }
+
+    // A custom pipeline carries its own source; the positional becomes a
+    // label for metrics/status.
+    if (videos.items.len == 0 and pipeline_override != null) {
+        try videos.append(allocator, "custom-pipeline");
+    }
+    if (videos.items.len == 0) return ParseError.MissingVideo;
+    const entries = try videos.toOwnedSlice(allocator);
+
+    return .{
+        .video = entries[0],
+        .playlist = entries,
+        .loop = loop,
+        .target = target,
+        .control_socket = control_socket,
+        .metrics_listen = metrics_listen,
+        .metrics_stream = metrics_stream,
+        .metrics_history_s = metrics_history_s,
+        .otlp_endpoint = otlp_endpoint,
+        .dbus = dbus,
+        .hdr = hdr,
+        .dump_dot_dir = dump_dot_dir,
+        .icc_profile = icc_profile,
+        .max_players = max_players,
+        .audio = audio,
+        .volume = volume,
+        .audio_sink = audio_sink,
+        .blend_to = blend_to,
+        .blend_window = blend_window,
+        .pipeline_override = pipeline_override,
+        .decode_at_output = decode_at_output,
+        .adaptive_min_fps = adaptive_min_fps,
+        .allow_yuv = allow_yuv,
+        .gpu_postproc = gpu_postproc,
+        .net_buffer_bytes = net_buffer_bytes,
+        .net_timeout_s = net_timeout_s,
+        .reconnect = reconnect,
+        .max_retries = max_retries,
+        .order = order,
+        .repeat = repeat,
+        .slide_duration_s = slide_duration_s,
+        .fade_s = fade_s,
+        .transition_fade_ms = transition_fade_ms,
+        .embed_sink = embed_sink,
+        .frame_step_s = frame_step_s,
+        .on_battery = on_battery,
+        .buffer_depth = buffer_depth,
+        .buffer_mode = buffer_mode,
+        .scale_mode = scale_mode,
+        .mem_cap_mb = mem_cap_mb,
+        .cpu_budget_pct = cpu_budget_pct,
+    };
+}
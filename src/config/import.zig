@@ -0,0 +1,254 @@
+//! Converters from other wallpaper tools.
+//!
+//! `waystream import <tool>` reads an mpvpaper command line, a
+//! hyprpaper.conf, or the swww daemon's state directory and turns each
+//! wallpaper into a profile, so switching does not mean retyping a setup
+//! that already works. Converters only read foreign formats; registering
+//! the resulting profiles goes through `ProfilesConfig.addProfile`.
+
+const std = @import("std");
+const profiles_mod = @import("profiles.zig");
+const layout = @import("../render/layout.zig");
+
+/// Frees profiles returned by the converters in this module.
+pub fn freeProfiles(allocator: std.mem.Allocator, profiles: []profiles_mod.Profile) void {
+    for (profiles) |profile| {
+        allocator.free(profile.name);
+        allocator.free(profile.video);
+        for (profile.outputs) |output| allocator.free(output);
+        allocator.free(profile.outputs);
+    }
+    allocator.free(profiles);
+}
+
+/// Parses an mpvpaper invocation, e.g.
+/// `mpvpaper -o "no-audio --loop-playlist" DP-1 ~/Videos/wall.mp4`.
+/// Flags are dropped (looping and silence are our defaults anyway); the
+/// two positionals become the profile. `*` and `ALL` mean every output.
+/// Returns null when the command carries no output/video pair.
+pub fn fromMpvpaperCommand(
+    allocator: std.mem.Allocator,
+    command: []const u8,
+) !?profiles_mod.Profile {
+    var output: ?[]const u8 = null;
+    var video: ?[]const u8 = null;
+
+    var it = std.mem.tokenizeAny(u8, command, " \t");
+    var first = true;
+    var in_quoted_value = false;
+    var expect_value = false;
+    while (it.next()) |token| {
+        if (first) {
+            first = false;
+            if (std.mem.eql(u8, token, "mpvpaper")) continue;
+        }
+        if (in_quoted_value) {
+            if (std.mem.endsWith(u8, token, "\"")) in_quoted_value = false;
+            continue;
+        }
+        if (expect_value) {
+            expect_value = false;
+            if (std.mem.startsWith(u8, token, "\"") and !std.mem.endsWith(u8, token, "\""))
+                in_quoted_value = true;
+            continue;
+        }
+        if (std.mem.startsWith(u8, token, "-")) {
+            for ([_][]const u8{ "-o", "--mpv-options", "-l", "--layer" }) |takes_value| {
+                if (std.mem.eql(u8, token, takes_value)) {
+                    expect_value = true;
+                    break;
+                }
+            }
+            continue;
+        }
+        if (output == null) {
+            output = token;
+        } else if (video == null) {
+            video = token;
+        }
+    }
+
+    const video_text = video orelse return null;
+    const output_text = output.?;
+
+    const all_outputs = std.mem.eql(u8, output_text, "*") or
+        std.mem.eql(u8, output_text, "ALL");
+    const outputs = if (all_outputs)
+        try allocator.alloc([]const u8, 0)
+    else blk: {
+        const one = try allocator.alloc([]const u8, 1);
+        one[0] = try allocator.dupe(u8, output_text);
+        break :blk one;
+    };
+    errdefer {
+        for (outputs) |entry| allocator.free(entry);
+        allocator.free(outputs);
+    }
+
+    return .{
+        .name = try allocator.dupe(u8, "mpvpaper"),
+        .video = try allocator.dupe(u8, video_text),
+        .outputs = outputs,
+    };
+}
+
+/// Parses hyprpaper.conf `wallpaper = <output>,<path>` lines (one profile
+/// each, named after the output); `preload` lines and comments are
+/// skipped. A `contain:`/`cover:` path prefix maps to the matching scale
+/// mode. Caller frees with `freeProfiles`.
+pub fn fromHyprpaperConf(
+    allocator: std.mem.Allocator,
+    source: []const u8,
+) ![]profiles_mod.Profile {
+    var found: std.ArrayList(profiles_mod.Profile) = .empty;
+    errdefer freeProfilesList(allocator, &found);
+
+    var lines = std.mem.tokenizeScalar(u8, source, '\n');
+    while (lines.next()) |raw_line| {
+        const line = std.mem.trim(u8, raw_line, " \t\r");
+        if (line.len == 0 or line[0] == '#') continue;
+        const equals = std.mem.indexOfScalar(u8, line, '=') orelse continue;
+        const key = std.mem.trim(u8, line[0..equals], " \t");
+        if (!std.mem.eql(u8, key, "wallpaper")) continue;
+
+        const value = std.mem.trim(u8, line[equals + 1 ..], " \t");
+        const comma = std.mem.indexOfScalar(u8, value, ',') orelse continue;
+        const output = std.mem.trim(u8, value[0..comma], " \t");
+        var path = std.mem.trim(u8, value[comma + 1 ..], " \t");
+
+        var scale_mode: ?layout.ScaleMode = null;
+        if (std.mem.startsWith(u8, path, "contain:")) {
+            scale_mode = .fit;
+            path = path["contain:".len..];
+        } else if (std.mem.startsWith(u8, path, "cover:")) {
+            scale_mode = .fill;
+            path = path["cover:".len..];
+        }
+        if (path.len == 0) continue;
+
+        const outputs = if (output.len == 0)
+            try allocator.alloc([]const u8, 0)
+        else blk: {
+            const one = try allocator.alloc([]const u8, 1);
+            one[0] = try allocator.dupe(u8, output);
+            break :blk one;
+        };
+        errdefer {
+            for (outputs) |entry| allocator.free(entry);
+            allocator.free(outputs);
+        }
+
+        try found.append(allocator, .{
+            .name = try allocator.dupe(u8, if (output.len > 0) output else "hyprpaper"),
+            .video = try allocator.dupe(u8, path),
+            .outputs = outputs,
+            .scale_mode = scale_mode,
+        });
+    }
+    return found.toOwnedSlice(allocator);
+}
+
+/// Reads the swww daemon's cache directory (one file per output whose
+/// first line is the current image path) into per-output profiles.
+/// Caller frees with `freeProfiles`.
+pub fn fromSwwwState(
+    allocator: std.mem.Allocator,
+    cache_dir: []const u8,
+) ![]profiles_mod.Profile {
+    var found: std.ArrayList(profiles_mod.Profile) = .empty;
+    errdefer freeProfilesList(allocator, &found);
+
+    var dir = std.fs.cwd().openDir(cache_dir, .{ .iterate = true }) catch
+        return found.toOwnedSlice(allocator);
+    defer dir.close();
+
+    var it = dir.iterate();
+    while (try it.next()) |entry| {
+        if (entry.kind != .file) continue;
+
+        const content = dir.readFileAlloc(allocator, entry.name, 64 * 1024) catch continue;
+        defer allocator.free(content);
+        var content_lines = std.mem.tokenizeScalar(u8, content, '\n');
+        const path = std.mem.trim(u8, content_lines.next() orelse continue, " \t\r");
+        if (path.len == 0) continue;
+
+        const outputs = try allocator.alloc([]const u8, 1);
+        outputs[0] = try allocator.dupe(u8, entry.name);
+        errdefer {
+            allocator.free(outputs[0]);
+            allocator.free(outputs);
+        }
+
+        try found.append(allocator, .{
+            .name = try allocator.dupe(u8, entry.name),
+            .video = try allocator.dupe(u8, path),
+            .outputs = outputs,
+        });
+    }
+    return found.toOwnedSlice(allocator);
+}
+
+fn freeProfilesList(
+    allocator: std.mem.Allocator,
+    list: *std.ArrayList(profiles_mod.Profile),
+) void {
+    for (list.items) |profile| {
+        allocator.free(profile.name);
+        allocator.free(profile.video);
+        for (profile.outputs) |output| allocator.free(output);
+        allocator.free(profile.outputs);
+    }
+    list.deinit(allocator);
+}
+
+test "an mpvpaper command becomes a profile" {
+    const imported = (try fromMpvpaperCommand(
+        std.testing.allocator,
+        "mpvpaper -o \"no-audio --loop-playlist\" DP-1 /home/me/wall.mp4",
+    )).?;
+    var one = [_]profiles_mod.Profile{imported};
+    defer for (&one) |profile| {
+        std.testing.allocator.free(profile.name);
+        std.testing.allocator.free(profile.video);
+        for (profile.outputs) |output| std.testing.allocator.free(output);
+        std.testing.allocator.free(profile.outputs);
+    };
+
+    try std.testing.expectEqualStrings("/home/me/wall.mp4", imported.video);
+    try std.testing.expectEqualStrings("DP-1", imported.outputs[0]);
+}
+
+test "hyprpaper wallpaper lines map outputs and scale prefixes" {
+    const conf =
+        \\# comment
+        \\preload = ~/Pictures/wall.png
+        \\wallpaper = DP-1,contain:~/Pictures/wall.png
+        \\wallpaper = ,~/Pictures/other.png
+    ;
+    const imported = try fromHyprpaperConf(std.testing.allocator, conf);
+    defer freeProfiles(std.testing.allocator, imported);
+
+    try std.testing.expectEqual(@as(usize, 2), imported.len);
+    try std.testing.expectEqualStrings("DP-1", imported[0].name);
+    try std.testing.expectEqual(@as(usize, 1), imported[0].outputs.len);
+    try std.testing.expect(imported[0].scale_mode.? == .fit);
+    // An empty output means all outputs.
+    try std.testing.expectEqualStrings("hyprpaper", imported[1].name);
+    try std.testing.expectEqual(@as(usize, 0), imported[1].outputs.len);
+}
+
+test "swww state files become per-output profiles" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{ .sub_path = "DP-1", .data = "/home/me/wall.gif\n" });
+
+    const cache_dir = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(cache_dir);
+
+    const imported = try fromSwwwState(std.testing.allocator, cache_dir);
+    defer freeProfiles(std.testing.allocator, imported);
+
+    try std.testing.expectEqual(@as(usize, 1), imported.len);
+    try std.testing.expectEqualStrings("DP-1", imported[0].name);
+    try std.testing.expectEqualStrings("/home/me/wall.gif", imported[0].video);
+}
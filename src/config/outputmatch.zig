@@ -0,0 +1,98 @@
+//! Output selectors.
+//!
+//! Connector names move between docks (yesterday's DP-3 is today's DP-5),
+//! so a profile `outputs` entry can match stable identity fields instead
+//! of just the name:
+//!
+//!   - `DP-1`            exact connector name
+//!   - `DP-*`            glob on the connector name
+//!   - `desc:*U2720Q*`   glob on the compositor's output description
+//!   - `make:Dell*`      glob on the manufacturer
+//!   - `model:...`, `serial:...` likewise
+//!
+//! Globs use the same `*`/`?` syntax as video paths; carrying a regex
+//! engine for this would be overkill. Selectors resolve against the
+//! compositor's advertised output info at spawn time.
+
+const std = @import("std");
+const glob = @import("../playback/glob.zig");
+
+/// Identity of one output as the compositor advertises it. Fields a
+/// compositor does not provide stay empty and never match a keyed
+/// selector.
+pub const OutputInfo = struct {
+    name: []const u8,
+    description: []const u8 = "",
+    make: []const u8 = "",
+    model: []const u8 = "",
+    serial: []const u8 = "",
+};
+
+/// True when `selector` matches the output. An unknown prefix is not an
+/// error: the colon is taken as part of a (strange) connector name.
+pub fn matches(selector: []const u8, info: OutputInfo) bool {
+    if (std.mem.indexOfScalar(u8, selector, ':')) |colon| {
+        const key = selector[0..colon];
+        const pattern = selector[colon + 1 ..];
+        const field: ?[]const u8 = if (std.mem.eql(u8, key, "desc"))
+            info.description
+        else if (std.mem.eql(u8, key, "make"))
+            info.make
+        else if (std.mem.eql(u8, key, "model"))
+            info.model
+        else if (std.mem.eql(u8, key, "serial"))
+            info.serial
+        else
+            null;
+        if (field) |value| return value.len > 0 and globMatch(pattern, value);
+    }
+    return globMatch(selector, info.name);
+}
+
+/// True when any selector matches; an empty list means every output.
+pub fn matchesAny(selectors: []const []const u8, info: OutputInfo) bool {
+    if (selectors.len == 0) return true;
+    for (selectors) |selector| {
+        if (matches(selector, info)) return true;
+    }
+    return false;
+}
+
+fn globMatch(pattern: []const u8, value: []const u8) bool {
+    if (!glob.hasPattern(pattern)) return std.mem.eql(u8, pattern, value);
+    return glob.matches(pattern, value);
+}
+
+test "plain selectors match names exactly or by glob" {
+    const info = OutputInfo{ .name = "DP-3" };
+    try std.testing.expect(matches("DP-3", info));
+    try std.testing.expect(matches("DP-*", info));
+    try std.testing.expect(!matches("DP-1", info));
+    try std.testing.expect(!matches("HDMI-*", info));
+}
+
+test "keyed selectors match identity fields" {
+    const info = OutputInfo{
+        .name = "DP-5",
+        .description = "Dell Inc. DELL U2720Q 123ABC",
+        .make = "Dell Inc.",
+        .model = "DELL U2720Q",
+        .serial = "123ABC",
+    };
+    try std.testing.expect(matches("desc:*U2720Q*", info));
+    try std.testing.expect(matches("make:Dell*", info));
+    try std.testing.expect(matches("model:DELL U2720Q", info));
+    try std.testing.expect(matches("serial:123ABC", info));
+    try std.testing.expect(!matches("serial:XYZ", info));
+    // A missing field never matches, even against "*".
+    try std.testing.expect(!matches("serial:*", .{ .name = "DP-5" }));
+}
+
+test "an empty selector list matches every output" {
+    try std.testing.expect(matchesAny(&.{}, .{ .name = "DP-1" }));
+    try std.testing.expect(matchesAny(&.{ "HDMI-1", "desc:*Dell*" }, .{
+        .name = "DP-1",
+        .description = "Dell something",
+    }));
+    try std.testing.expect(!matchesAny(&.{"HDMI-1"}, .{ .name = "DP-1" }));
+}
@@ -0,0 +1,348 @@
+//! Manual override state.
+//!
+//! One small JSON document in the state dir holding everything set by
+//! hand at runtime: the active profile override (with optional expiry),
+//! a paused flag, and per-output overrides. The first cut of this file
+//! was a bare `{"profile": ...}` object; that shape is still read and
+//! upgraded transparently on load.
+
+const std = @import("std");
+
+pub const Override = struct {
+    profile: []const u8,
+    /// Unix seconds after which the override stops applying; null means
+    /// it holds until cleared.
+    expires_unix: ?i64 = null,
+};
+
+pub const OutputOverride = struct {
+    output: []const u8,
+    profile: []const u8,
+    /// Expiry, same semantics as the global override's.
+    expires_unix: ?i64 = null,
+};
+
+/// Default override file location, under the state dir.
+pub fn defaultPath(allocator: std.mem.Allocator) ![]u8 {
+    if (std.posix.getenv("XDG_STATE_HOME")) |state_home| {
+        return std.fmt.allocPrint(allocator, "{s}/waystream/override.json", .{state_home});
+    }
+    const home = std.posix.getenv("HOME") orelse "/";
+    return std.fmt.allocPrint(allocator, "{s}/.local/state/waystream/override.json", .{home});
+}
+
+pub const State = struct {
+    arena: std.heap.ArenaAllocator,
+    path: []const u8,
+    active: ?Override = null,
+    paused: bool = false,
+    outputs: []const OutputOverride = &.{},
+
+    pub fn deinit(self: *State) void {
+        self.arena.deinit();
+        self.* = undefined;
+    }
+
+    /// Name of the actively overriding profile, or null when none is
+    /// set or the override has expired.
+    pub fn activeProfile(self: *const State, now_unix: i64) ?[]const u8 {
+        const active = self.active orelse return null;
+        if (active.expires_unix) |unix| {
+            if (now_unix >= unix) return null;
+        }
+        return active.profile;
+    }
+
+    /// Unexpired override for one output by connector name, if any.
+    pub fn outputProfile(self: *const State, output: []const u8, now_unix: i64) ?[]const u8 {
+        for (self.outputs) |entry| {
+            if (!std.mem.eql(u8, entry.output, output)) continue;
+            if (entry.expires_unix) |unix| {
+                if (now_unix >= unix) return null;
+            }
+            return entry.profile;
+        }
+        return null;
+    }
+
+    /// Sets the active override, copying the name into the state.
+    pub fn setActive(self: *State, profile: []const u8, expires_unix: ?i64) !void {
+        self.active = .{
+            .profile = try self.arena.allocator().dupe(u8, profile),
+            .expires_unix = expires_unix,
+        };
+    }
+
+    /// Sets or replaces the override for one output.
+    pub fn setOutput(
+        self: *State,
+        output: []const u8,
+        profile: []const u8,
+        expires_unix: ?i64,
+    ) !void {
+        const arena_allocator = self.arena.allocator();
+        const copy = try arena_allocator.dupe(u8, profile);
+        var entries: std.ArrayList(OutputOverride) = .empty;
+        for (self.outputs) |entry| {
+            if (std.mem.eql(u8, entry.output, output)) continue;
+            try entries.append(arena_allocator, entry);
+        }
+        try entries.append(arena_allocator, .{
+            .output = try arena_allocator.dupe(u8, output),
+            .profile = copy,
+            .expires_unix = expires_unix,
+        });
+        self.outputs = try entries.toOwnedSlice(arena_allocator);
+    }
+
+    /// Drops the override for one output; missing is fine.
+    pub fn clearOutput(self: *State, output: []const u8) !void {
+        const arena_allocator = self.arena.allocator();
+        var entries: std.ArrayList(OutputOverride) = .empty;
+        for (self.outputs) |entry| {
+            if (std.mem.eql(u8, entry.output, output)) continue;
+            try entries.append(arena_allocator, entry);
+        }
+        self.outputs = try entries.toOwnedSlice(arena_allocator);
+    }
+
+    /// Writes the state back, or removes the file when nothing is set
+    /// so an empty document does not linger forever.
+    pub fn persist(self: *const State) !void {
+        if (self.active == null and !self.paused and self.outputs.len == 0) {
+            std.fs.cwd().deleteFile(self.path) catch {};
+            return;
+        }
+        const allocator = self.arena.child_allocator;
+
+        var text: std.ArrayList(u8) = .empty;
+        defer text.deinit(allocator);
+
+        try text.appendSlice(allocator, "{");
+        if (self.active) |active| {
+            const field = if (active.expires_unix) |expires|
+                try std.fmt.allocPrint(
+                    allocator,
+                    "\"active\":{{\"profile\":\"{s}\",\"expires_unix\":{d}}}",
+                    .{ active.profile, expires },
+                )
+            else
+                try std.fmt.allocPrint(allocator, "\"active\":{{\"profile\":\"{s}\"}}", .{
+                    active.profile,
+                });
+            defer allocator.free(field);
+            try text.appendSlice(allocator, field);
+        }
+        if (self.paused) {
+            if (text.items.len > 1) try text.appendSlice(allocator, ",");
+            try text.appendSlice(allocator, "\"paused\":true");
+        }
+        if (self.outputs.len > 0) {
+            if (text.items.len > 1) try text.appendSlice(allocator, ",");
+            try text.appendSlice(allocator, "\"outputs\":[");
+            for (self.outputs, 0..) |entry, index| {
+                if (index > 0) try text.appendSlice(allocator, ",");
+                const field = if (entry.expires_unix) |expires|
+                    try std.fmt.allocPrint(
+                        allocator,
+                        "{{\"output\":\"{s}\",\"profile\":\"{s}\",\"expires_unix\":{d}}}",
+                        .{ entry.output, entry.profile, expires },
+                    )
+                else
+                    try std.fmt.allocPrint(
+                        allocator,
+                        "{{\"output\":\"{s}\",\"profile\":\"{s}\"}}",
+                        .{ entry.output, entry.profile },
+                    );
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            try text.appendSlice(allocator, "]");
+        }
+        try text.appendSlice(allocator, "}\n");
+
+        if (std.fs.path.dirname(self.path)) |dir| {
+            std.fs.cwd().makePath(dir) catch {};
+        }
+        const file = try std.fs.cwd().createFile(self.path, .{});
+        defer file.close();
+        try file.writeAll(text.items);
+    }
+};
+
+/// Loads the state at `path` (the default location when null). Missing
+/// or malformed files read as an empty state; an expired active
+/// override is dropped here so nothing downstream sees it.
+pub fn load(allocator: std.mem.Allocator, path: ?[]const u8, now_unix: i64) !State {
+    var state = State{ .arena = std.heap.ArenaAllocator.init(allocator), .path = &.{} };
+    errdefer state.deinit();
+    const arena_allocator = state.arena.allocator();
+
+    state.path = if (path) |given|
+        try arena_allocator.dupe(u8, given)
+    else blk: {
+        const default = try defaultPath(allocator);
+        defer allocator.free(default);
+        break :blk try arena_allocator.dupe(u8, default);
+    };
+
+    parseInto(&state, arena_allocator) catch {};
+    if (state.active) |active| {
+        if (active.expires_unix) |unix| {
+            if (now_unix >= unix) state.active = null;
+        }
+    }
+    if (state.outputs.len > 0) {
+        var kept: std.ArrayList(OutputOverride) = .empty;
+        for (state.outputs) |entry| {
+            if (entry.expires_unix) |unix| {
+                if (now_unix >= unix) continue;
+            }
+            try kept.append(arena_allocator, entry);
+        }
+        state.outputs = try kept.toOwnedSlice(arena_allocator);
+    }
+    return state;
+}
+
+fn parseInto(state: *State, arena_allocator: std.mem.Allocator) !void {
+    const data = try std.fs.cwd().readFileAlloc(arena_allocator, state.path, 64 * 1024);
+    const parsed = try std.json.parseFromSliceLeaky(std.json.Value, arena_allocator, data, .{});
+    const root = switch (parsed) {
+        .object => |object| object,
+        else => return,
+    };
+
+    // The original format was the bare override object itself.
+    if (root.get("profile")) |_| {
+        state.active = parseOverride(root) orelse return;
+        return;
+    }
+
+    if (root.get("active")) |value| {
+        if (value == .object) state.active = parseOverride(value.object);
+    }
+    if (root.get("paused")) |value| {
+        if (value == .bool) state.paused = value.bool;
+    }
+    if (root.get("outputs")) |value| {
+        if (value != .array) return;
+        var entries: std.ArrayList(OutputOverride) = .empty;
+        for (value.array.items) |item| {
+            if (item != .object) continue;
+            const output = item.object.get("output") orelse continue;
+            const profile = item.object.get("profile") orelse continue;
+            if (output != .string or profile != .string) continue;
+            const expires: ?i64 = if (item.object.get("expires_unix")) |expiry| switch (expiry) {
+                .integer => |unix| unix,
+                else => null,
+            } else null;
+            try entries.append(arena_allocator, .{
+                .output = output.string,
+                .profile = profile.string,
+                .expires_unix = expires,
+            });
+        }
+        state.outputs = try entries.toOwnedSlice(arena_allocator);
+    }
+}
+
+fn parseOverride(object: std.json.ObjectMap) ?Override {
+    const profile = switch (object.get("profile") orelse return null) {
+        .string => |value| value,
+        else => return null,
+    };
+    const expires: ?i64 = if (object.get("expires_unix")) |value| switch (value) {
+        .integer => |unix| unix,
+        else => null,
+    } else null;
+    return .{ .profile = profile, .expires_unix = expires };
+}
+
+test "the state round-trips with expiry, paused flag, and outputs" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+    const path = try std.fmt.allocPrint(std.testing.allocator, "{s}/override.json", .{dir_path});
+    defer std.testing.allocator.free(path);
+
+    var state = try load(std.testing.allocator, path, 0);
+    try state.setActive("night", 1000);
+    state.paused = true;
+    try state.setOutput("DP-1", "day", null);
+    try state.setOutput("HDMI-A-1", "presentation", 800);
+    try state.persist();
+    state.deinit();
+
+    var reread = try load(std.testing.allocator, path, 500);
+    defer reread.deinit();
+    try std.testing.expectEqualStrings("night", reread.activeProfile(500).?);
+    try std.testing.expect(reread.paused);
+    try std.testing.expectEqualStrings("day", reread.outputProfile("DP-1", 500).?);
+    try std.testing.expectEqualStrings("presentation", reread.outputProfile("HDMI-A-1", 500).?);
+    try std.testing.expect(reread.outputProfile("HDMI-A-1", 900) == null);
+    try std.testing.expect(reread.outputProfile("eDP-1", 500) == null);
+
+    // An expired per-output entry is pruned at load like the global one.
+    var later = try load(std.testing.allocator, path, 900);
+    defer later.deinit();
+    try std.testing.expectEqual(@as(usize, 1), later.outputs.len);
+
+    try later.clearOutput("DP-1");
+    try std.testing.expect(later.outputProfile("DP-1", 900) == null);
+}
+
+test "an expired override reads as absent" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+    const path = try std.fmt.allocPrint(std.testing.allocator, "{s}/override.json", .{dir_path});
+    defer std.testing.allocator.free(path);
+
+    var state = try load(std.testing.allocator, path, 0);
+    try state.setActive("night", 1000);
+    try state.persist();
+    state.deinit();
+
+    var reread = try load(std.testing.allocator, path, 2000);
+    defer reread.deinit();
+    try std.testing.expect(reread.activeProfile(2000) == null);
+}
+
+test "the original bare override format migrates on load" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{
+        .sub_path = "override.json",
+        .data = "{\"profile\":\"night\",\"expires_unix\":1000}\n",
+    });
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+    const path = try std.fmt.allocPrint(std.testing.allocator, "{s}/override.json", .{dir_path});
+    defer std.testing.allocator.free(path);
+
+    var state = try load(std.testing.allocator, path, 500);
+    defer state.deinit();
+    try std.testing.expectEqualStrings("night", state.activeProfile(500).?);
+    try std.testing.expectEqual(@as(?i64, 1000), state.active.?.expires_unix);
+}
+
+test "persisting an empty state removes the file" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+    const path = try std.fmt.allocPrint(std.testing.allocator, "{s}/override.json", .{dir_path});
+    defer std.testing.allocator.free(path);
+
+    var state = try load(std.testing.allocator, path, 0);
+    try state.setActive("night", null);
+    try state.persist();
+    state.active = null;
+    try state.persist();
+    state.deinit();
+
+    try std.testing.expectError(error.FileNotFound, std.fs.cwd().access(path, .{}));
+}
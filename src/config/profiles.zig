@@ -0,0 +1,1717 @@
+//! Wallpaper profile configuration.
+//!
+//! Profiles live in a ZON document (default:
+//! `$XDG_CONFIG_HOME/waystream/profiles.zon`):
+//!
+//! ```zon
+//! .{
+//!     .version = 2,
+//!     .default_profile = "day",
+//!     .rotate_every = "30m",
+//!     .profiles = .{
+//!         .{ .name = "day", .video = "/home/me/Wallpapers/day.mp4" },
+//!         .{ .name = "night", .video = "/home/me/Wallpapers/night.mp4" },
+//!         .{ .name = "ambient", .videos = .{ "calm.mp4", "rain.mp4" } },
+//!     },
+//! }
+//! ```
+//!
+//! A `profiles.json` or `profiles.yaml` with the same shape is accepted
+//! for configs generated by home-manager/nix or scripts; see `Format` and
+//! `yaml.zig` for the YAML subset. A system-wide config at
+//! `/etc/waystream/profiles.zon` underlays the user file, so packages and
+//! admins can ship defaults that users refine; see `default_system_path`
+//! for the merge rules.
+
+const std = @import("std");
+const layout = @import("../render/layout.zig");
+const pathprobe = @import("../render/pathprobe.zig");
+const glob = @import("../playback/glob.zig");
+const blend = @import("../render/blend.zig");
+const schedule = @import("schedule.zig");
+const tz = @import("tz.zig");
+const yaml = @import("yaml.zig");
+const override_mod = @import("override.zig");
+const power = @import("../metrics/power.zig");
+
+/// Version written by this build. History:
+///
+///  - 1: singular `output` per profile
+///  - 2: `outputs` list (and everything since)
+///
+/// Older documents are upgraded in memory at load; `migrateAndRewrite`
+/// persists the upgrade.
+pub const current_config_version: u32 = 2;
+
+pub const Profile = struct {
+    name: []const u8,
+    /// Deprecated v1 spelling of `outputs`; migrated at load, never
+    /// written back.
+    output: []const u8 = "",
+    /// Name of a profile to inherit unset fields from, so common settings
+    /// (outputs, scale mode, mute) live once and variants only override
+    /// the video and schedule. Chains are resolved at load time.
+    extends: ?[]const u8 = null,
+    /// Single video; may stay empty when `videos` is set instead.
+    video: []const u8 = "",
+    /// Playlist entries, advanced on EOS; takes precedence over `video`.
+    /// One "ambient" profile can rotate through several clips this way.
+    videos: []const []const u8 = &.{},
+    /// Output selectors this profile drives: connector names, globs, or
+    /// `desc:`/`make:`/`model:`/`serial:` patterns (see
+    /// config/outputmatch.zig). Empty means all outputs.
+    outputs: []const []const u8 = &.{},
+    /// Override the global mute for this profile; null inherits it. Lets
+    /// one profile (a music visualizer, say) play sound while the rest
+    /// stay silent.
+    mute: ?bool = null,
+    /// Override the global audio volume (0.0-1.0); null inherits it.
+    volume: ?f64 = null,
+    /// Placement override (fit/fill/stretch); null inherits the global.
+    scale_mode: ?layout.ScaleMode = null,
+    /// Present through waylandsink instead of the appsink renderer.
+    backend: ?Backend = null,
+    /// Frame-path override (auto/shm/dmabuf); null inherits the global.
+    buffer_mode: ?pathprobe.Mode = null,
+    /// Time-of-day window ("HH:MM-HH:MM", local, wraps past midnight)
+    /// during which the scheduler activates this profile.
+    window: ?[]const u8 = null,
+    /// Calendar days ("MM-DD") the profile is limited to; empty means
+    /// every day. Holiday wallpapers live here instead of in cron.
+    dates: []const []const u8 = &.{},
+    /// Calendar days the profile never runs, for "not on the 1st" rules.
+    except_dates: []const []const u8 = &.{},
+    /// Timezone the window is evaluated in — an IANA name
+    /// ("Asia/Shanghai") or fixed offset ("UTC+8"); null means the
+    /// system-local clock. Keeps day/night switching anchored to the
+    /// home zone on laptops that roam (see config/tz.zig).
+    timezone: ?[]const u8 = null,
+    /// Runtime conditions ("vpn", "do-not-disturb") that must all hold
+    /// for the scheduler to consider this profile. Front-ends report
+    /// them through a schedule.ConditionProvider; without one the
+    /// profile stays out.
+    conditions: []const []const u8 = &.{},
+    /// Breaks overlapping-window ties: higher wins, default 0, equal
+    /// priorities fall back to file order (see config/schedule.zig).
+    priority: ?i32 = null,
+    /// What this profile does while the machine discharges
+    /// (continue/static/pause); null inherits the global policy.
+    on_battery: ?power.Policy = null,
+};
+
+pub const Backend = enum {
+    appsink,
+    waylandsink,
+};
+
+/// How a profile switch presents: a hard cut (the default) or fading the
+/// incoming source in. Part of the config model so session managers and
+/// the GUI all switch the same way instead of hardcoding kill-and-restart.
+pub const Transition = struct {
+    kind: Kind = .cut,
+    duration_ms: u32 = 400,
+
+    pub const Kind = enum { cut, fade };
+};
+
+/// Appends the positional video arguments a player spawn should use: the
+/// playlist when one is set, otherwise the single video. Entries that are
+/// glob patterns or directories expand to their matching files (sorted by
+/// name), re-evaluated on every spawn so a reload picks up new files. The
+/// player treats several positionals as a playlist advanced on EOS, so
+/// this is all the expansion a playlist profile needs.
+///
+/// Expanded paths are allocated with `allocator`; callers already free
+/// their spawn argv entries, and literal entries stay borrowed from the
+/// config arena, so ownership is unchanged.
+pub fn appendVideos(
+    profile: Profile,
+    allocator: std.mem.Allocator,
+    args: *std.ArrayList([]const u8),
+) !void {
+    if (profile.videos.len > 0) {
+        for (profile.videos) |entry| try appendExpanded(entry, allocator, args);
+    } else if (profile.video.len > 0) {
+        try appendExpanded(profile.video, allocator, args);
+    }
+}
+
+fn appendExpanded(
+    entry: []const u8,
+    allocator: std.mem.Allocator,
+    args: *std.ArrayList([]const u8),
+) !void {
+    if (!glob.hasPattern(entry) and !std.mem.startsWith(u8, entry, "~/")) {
+        if (std.fs.cwd().statFile(entry)) |stat| {
+            if (stat.kind != .directory) return args.append(allocator, entry);
+        } else |_| {
+            return args.append(allocator, entry);
+        }
+    }
+    const expanded = try glob.expand(allocator, entry, .name);
+    defer allocator.free(expanded);
+    try args.appendSlice(allocator, expanded);
+}
+
+/// Appends the play flags for a profile's renderer overrides, for callers
+/// that spawn a player per profile (the GUI restart path, session
+/// managers). Flag strings are static; enum tags are comptime constants,
+/// so nothing needs freeing.
+pub fn appendRendererFlags(
+    profile: Profile,
+    allocator: std.mem.Allocator,
+    args: *std.ArrayList([]const u8),
+) !void {
+    if (profile.scale_mode) |mode| {
+        try args.append(allocator, "--scale-mode");
+        try args.append(allocator, @tagName(mode));
+    }
+    if (profile.buffer_mode) |mode| {
+        try args.append(allocator, "--buffer-mode");
+        try args.append(allocator, @tagName(mode));
+    }
+    if (profile.backend) |backend| {
+        if (backend == .waylandsink) try args.append(allocator, "--waylandsink");
+    }
+    if (profile.on_battery) |policy| {
+        try args.append(allocator, "--on-battery");
+        try args.append(allocator, @tagName(policy));
+    }
+}
+
+/// Audio settings a player (re)start should use. A profile switch that
+/// changes these must restart the pipeline — the volume element is baked
+/// into the launch description — so they are part of the restart
+/// signature, not a live property.
+pub const AudioSettings = struct {
+    audio: bool,
+    volume: f64,
+};
+
+/// Resolves a profile's audio overrides against the global settings.
+pub fn audioSettings(profile: Profile, global_audio: bool, global_volume: f64) AudioSettings {
+    return .{
+        .audio = if (profile.mute) |mute| !mute else global_audio,
+        .volume = std.math.clamp(profile.volume orelse global_volume, 0, 1),
+    };
+}
+
+const Document = struct {
+    /// Schema version of the file. Absent means 1, the pre-`outputs`
+    /// layout, so old configs keep loading.
+    version: u32 = 1,
+    default_profile: ?[]const u8 = null,
+    /// Extra profile files to merge in, as paths or glob patterns relative
+    /// to this file (`.include = .{ "profiles.d/*.zon" }`). Included files
+    /// hold the same document shape; only their profiles are taken, in
+    /// include order after this file's own, and includes do not nest. On a
+    /// duplicate name the earlier definition wins and the shadowed file is
+    /// named in a warning.
+    include: []const []const u8 = &.{},
+    /// Rotate to a random eligible profile this often ("30m", "2h"; see
+    /// config/schedule.zig for the parser and picker). Null disables
+    /// rotation.
+    rotate_every: ?[]const u8 = null,
+    /// Names eligible for rotation; empty means every profile.
+    rotate_among: []const []const u8 = &.{},
+    /// How profile switches present (`.transition = .{ .kind = .fade }`).
+    transition: Transition = .{},
+    profiles: []const Profile = &.{},
+};
+
+pub const LoadError = error{
+    ReadFailed,
+    ParseFailed,
+    UnknownBase,
+    ExtendsCycle,
+    UnsupportedVersion,
+} || std.mem.Allocator.Error;
+
+/// Upgrades an older document in memory and returns the version it was
+/// read at. A document newer than this build is refused rather than
+/// half-understood.
+fn migrateDocument(
+    allocator: std.mem.Allocator,
+    document: *Document,
+    config_path: []const u8,
+) LoadError!u32 {
+    const from = document.version;
+    if (from > current_config_version) {
+        std.log.err("{s}: config version {d} is newer than this build understands ({d})", .{
+            config_path,
+            from,
+            current_config_version,
+        });
+        return LoadError.UnsupportedVersion;
+    }
+    if (from < current_config_version) {
+        if (from < 2) {
+            // v1 named a single `output` per profile.
+            const upgraded = try allocator.dupe(Profile, document.profiles);
+            for (upgraded) |*profile| {
+                if (profile.outputs.len == 0 and profile.output.len > 0) {
+                    const outputs = try allocator.alloc([]const u8, 1);
+                    outputs[0] = profile.output;
+                    profile.outputs = outputs;
+                }
+                profile.output = "";
+            }
+            document.profiles = upgraded;
+        }
+        std.log.warn("{s}: version {d} config upgraded to {d} in memory; " ++
+            "run `waystream migrate` to rewrite it", .{
+            config_path,
+            from,
+            current_config_version,
+        });
+        document.version = current_config_version;
+    }
+    return from;
+}
+
+/// Copies every field the child left unset from `base`. `extends` itself
+/// is deliberately not inherited; chain walking follows the originals.
+fn inheritFrom(child: *Profile, base: Profile) void {
+    if (child.video.len == 0) child.video = base.video;
+    if (child.videos.len == 0) child.videos = base.videos;
+    if (child.outputs.len == 0) child.outputs = base.outputs;
+    if (child.mute == null) child.mute = base.mute;
+    if (child.volume == null) child.volume = base.volume;
+    if (child.scale_mode == null) child.scale_mode = base.scale_mode;
+    if (child.backend == null) child.backend = base.backend;
+    if (child.buffer_mode == null) child.buffer_mode = base.buffer_mode;
+    if (child.window == null) child.window = base.window;
+    if (child.dates.len == 0) child.dates = base.dates;
+    if (child.except_dates.len == 0) child.except_dates = base.except_dates;
+    if (child.timezone == null) child.timezone = base.timezone;
+    if (child.conditions.len == 0) child.conditions = base.conditions;
+    if (child.priority == null) child.priority = base.priority;
+    if (child.on_battery == null) child.on_battery = base.on_battery;
+}
+
+/// Resolves `extends` chains in place. Nearer bases win because a merge
+/// only fills fields still unset. A chain longer than the profile count
+/// can only mean a cycle.
+pub fn resolveExtends(profiles: []Profile) error{ UnknownBase, ExtendsCycle }!void {
+    for (profiles) |*profile| {
+        var hops: usize = 0;
+        var base_name = profile.extends;
+        while (base_name) |name| {
+            if (hops >= profiles.len) return error.ExtendsCycle;
+            hops += 1;
+            const base = for (profiles) |candidate| {
+                if (std.mem.eql(u8, candidate.name, name)) break candidate;
+            } else return error.UnknownBase;
+            inheritFrom(profile, base);
+            base_name = base.extends;
+        }
+    }
+}
+
+/// One validation finding. `err` findings describe configs that cannot
+/// work as written; `warn` findings describe likely mistakes that still
+/// run.
+pub const Diagnostic = struct {
+    severity: enum { warn, err },
+    /// Name of the profile concerned (borrowed from the config), empty
+    /// for document-level findings.
+    profile: []const u8,
+    message: []const u8,
+};
+
+pub const Diagnostics = struct {
+    allocator: std.mem.Allocator,
+    items: []Diagnostic,
+
+    pub fn deinit(self: *Diagnostics) void {
+        for (self.items) |diagnostic| self.allocator.free(diagnostic.message);
+        self.allocator.free(self.items);
+        self.* = undefined;
+    }
+
+    pub fn errorCount(self: *const Diagnostics) usize {
+        var count: usize = 0;
+        for (self.items) |diagnostic| {
+            if (diagnostic.severity == .err) count += 1;
+        }
+        return count;
+    }
+};
+
+/// Merges the profiles of every file matched by the document's include
+/// patterns after the document's own, earlier definitions shadowing later
+/// ones by name. Everything is allocated in the config arena.
+fn mergeIncludes(
+    allocator: std.mem.Allocator,
+    config_path: []const u8,
+    document: Document,
+) LoadError![]const Profile {
+    var merged: std.ArrayList(Profile) = .empty;
+    try merged.appendSlice(allocator, document.profiles);
+
+    const config_dir = std.fs.path.dirname(config_path) orelse ".";
+    for (document.include) |pattern| {
+        const anchored = if (std.fs.path.isAbsolute(pattern))
+            try allocator.dupe(u8, pattern)
+        else
+            std.fs.path.join(allocator, &.{ config_dir, pattern }) catch
+                return LoadError.ReadFailed;
+
+        // A pattern whose directory does not exist simply matches nothing;
+        // a literal path that is missing fails the read below by name.
+        const files = glob.expand(allocator, anchored, .name) catch continue;
+        for (files) |file_path| {
+            const source = std.fs.cwd().readFileAllocOptions(
+                allocator,
+                file_path,
+                1024 * 1024,
+                null,
+                .@"1",
+                0,
+            ) catch {
+                std.log.err("include {s}: read failed", .{file_path});
+                return LoadError.ReadFailed;
+            };
+            var fragment = parseDocument(allocator, source, formatForPath(file_path)) catch {
+                std.log.err("include {s}: parse failed", .{file_path});
+                return LoadError.ParseFailed;
+            };
+            _ = try migrateDocument(allocator, &fragment, file_path);
+            for (fragment.profiles) |profile| {
+                const shadowed = for (merged.items) |existing| {
+                    if (std.mem.eql(u8, existing.name, profile.name)) break true;
+                } else false;
+                if (shadowed) {
+                    std.log.warn("profile \"{s}\" from {s} shadowed by an earlier definition", .{
+                        profile.name,
+                        file_path,
+                    });
+                    continue;
+                }
+                try merged.append(allocator, profile);
+            }
+        }
+    }
+    return merged.toOwnedSlice(allocator);
+}
+
+/// On-disk config format, picked by file extension. ZON is the native
+/// format and the only one `save` writes; JSON exists for configs
+/// generated by home-manager/nix or scripts.
+pub const Format = enum { zon, json, yaml };
+
+pub fn formatForPath(path: []const u8) Format {
+    const extension = std.fs.path.extension(path);
+    if (std.ascii.eqlIgnoreCase(extension, ".json")) return .json;
+    if (std.ascii.eqlIgnoreCase(extension, ".yaml") or
+        std.ascii.eqlIgnoreCase(extension, ".yml")) return .yaml;
+    return .zon;
+}
+
+/// Parses one document in the format its path implies. YAML goes through
+/// the minimal block-style reader in `yaml.zig` and then the JSON field
+/// mapping, so all three formats share one document model.
+fn parseDocument(
+    arena_allocator: std.mem.Allocator,
+    source: [:0]const u8,
+    format: Format,
+) LoadError!Document {
+    return switch (format) {
+        .zon => std.zon.parse.fromSlice(
+            Document,
+            arena_allocator,
+            source,
+            null,
+            .{ .ignore_unknown_fields = true },
+        ) catch LoadError.ParseFailed,
+        .json => std.json.parseFromSliceLeaky(
+            Document,
+            arena_allocator,
+            source,
+            .{ .ignore_unknown_fields = true },
+        ) catch LoadError.ParseFailed,
+        .yaml => {
+            const value = yaml.parse(arena_allocator, source) catch |err| switch (err) {
+                error.OutOfMemory => return LoadError.OutOfMemory,
+                error.Invalid => return LoadError.ParseFailed,
+            };
+            return std.json.parseFromValueLeaky(
+                Document,
+                arena_allocator,
+                value,
+                .{ .ignore_unknown_fields = true },
+            ) catch LoadError.ParseFailed;
+        },
+    };
+}
+
+/// System-wide config consulted when loading from the default location.
+/// Merge semantics: document fields (`default_profile`, `rotate_every`,
+/// `rotate_among`, `transition`) apply only where the user file leaves
+/// them unset, and system profiles append after the user's own and
+/// includes, so a user profile shadows a system one of the same name.
+/// `save` never writes system profiles back.
+pub const default_system_path = "/etc/waystream/profiles.zon";
+
+pub const ProfilesConfig = struct {
+    arena: std.heap.ArenaAllocator,
+    document: Document,
+    /// Where the config was loaded from (owned by the arena).
+    path: []const u8,
+    format: Format = .zon,
+    /// How many leading profiles belong to the main file itself. The rest
+    /// were merged from includes and are never written back by `save`.
+    own_count: usize = 0,
+    /// The merged profiles as written, `extends` unresolved. The editing
+    /// API mutates this list and `save` writes its leading `own_count`
+    /// entries, so inheritance stays live on disk; `document.profiles` is
+    /// the resolved view rebuilt from it after every edit.
+    raw_profiles: []const Profile = &.{},
+    /// Version the file carried on disk (the in-memory document is always
+    /// at `current_config_version` after load).
+    loaded_version: u32 = current_config_version,
+
+    pub fn deinit(self: *ProfilesConfig) void {
+        self.arena.deinit();
+        self.* = undefined;
+    }
+
+    /// Default config file location (ZON).
+    pub fn defaultPath(allocator: std.mem.Allocator) ![]u8 {
+        return defaultPathWithExtension(allocator, "zon");
+    }
+
+    fn defaultPathWithExtension(allocator: std.mem.Allocator, extension: []const u8) ![]u8 {
+        if (std.posix.getenv("XDG_CONFIG_HOME")) |config_home| {
+            return std.fmt.allocPrint(allocator, "{s}/waystream/profiles.{s}", .{
+                config_home,
+                extension,
+            });
+        }
+        const home = std.posix.getenv("HOME") orelse "/";
+        return std.fmt.allocPrint(allocator, "{s}/.config/waystream/profiles.{s}", .{
+            home,
+            extension,
+        });
+    }
+
+    /// Loads the config at `path` (or the default location when null;
+    /// `profiles.json` is picked up there when no `profiles.zon` exists).
+    /// A missing file yields an empty config, not an error.
+    pub fn load(allocator: std.mem.Allocator, path: ?[]const u8) LoadError!ProfilesConfig {
+        var arena = std.heap.ArenaAllocator.init(allocator);
+        errdefer arena.deinit();
+        const arena_allocator = arena.allocator();
+
+        var resolved_path = if (path) |p|
+            try arena_allocator.dupe(u8, p)
+        else
+            defaultPath(arena_allocator) catch return LoadError.ReadFailed;
+
+        const source = readConfigFile(arena_allocator, resolved_path) catch |err| switch (err) {
+            error.FileNotFound => blk: {
+                if (path == null) {
+                    // Nix/home-manager setups generate JSON; look for it
+                    // before falling back to an empty config.
+                    const json_path = defaultPathWithExtension(arena_allocator, "json") catch
+                        return LoadError.ReadFailed;
+                    if (readConfigFile(arena_allocator, json_path)) |json_source| {
+                        resolved_path = json_path;
+                        break :blk json_source;
+                    } else |_| {}
+                }
+                var document: Document = .{};
+                var raw_profiles: []const Profile = &.{};
+                if (path == null) {
+                    overlaySystem(arena_allocator, &document, default_system_path) catch {};
+                    raw_profiles = document.profiles;
+                    const resolved = try arena_allocator.dupe(Profile, document.profiles);
+                    try resolveExtends(resolved);
+                    document.profiles = resolved;
+                }
+                return .{
+                    .arena = arena,
+                    .document = document,
+                    .path = resolved_path,
+                    .raw_profiles = raw_profiles,
+                };
+            },
+            else => return LoadError.ReadFailed,
+        };
+
+        const format = formatForPath(resolved_path);
+        var document = try parseDocument(arena_allocator, source, format);
+        const loaded_version = try migrateDocument(arena_allocator, &document, resolved_path);
+
+        const own_count = document.profiles.len;
+        if (document.include.len > 0) {
+            document.profiles = try mergeIncludes(arena_allocator, resolved_path, document);
+        }
+        if (path == null) {
+            // Only the default location layers the system config; an
+            // explicit path means "exactly this file".
+            overlaySystem(arena_allocator, &document, default_system_path) catch {};
+        }
+
+        // Resolve inheritance once here so every consumer sees flattened
+        // profiles and never has to chase `extends` itself. Includes merge
+        // first, so a base profile may live in an included file. The
+        // unresolved originals are kept for the editing API and `save`.
+        const raw_profiles = document.profiles;
+        const resolved = try arena_allocator.dupe(Profile, raw_profiles);
+        try resolveExtends(resolved);
+        document.profiles = resolved;
+
+        return .{
+            .arena = arena,
+            .document = document,
+            .path = resolved_path,
+            .format = format,
+            .own_count = own_count,
+            .raw_profiles = raw_profiles,
+            .loaded_version = loaded_version,
+        };
+    }
+
+    /// Persists an in-memory upgrade so the warning stops: rewrites the
+    /// file at the current version and returns true, or returns false
+    /// when it already was current.
+    pub fn migrateAndRewrite(self: *ProfilesConfig) !bool {
+        if (self.loaded_version == current_config_version) return false;
+        try self.save();
+        self.loaded_version = current_config_version;
+        return true;
+    }
+
+    /// Merges the system config under `document` per the rules on
+    /// `default_system_path`. A missing system file is the common case
+    /// and a no-op; an unreadable one only warns, since the user config
+    /// alone must keep working on a broken package.
+    fn overlaySystem(
+        arena_allocator: std.mem.Allocator,
+        document: *Document,
+        system_path: []const u8,
+    ) !void {
+        const source = readConfigFile(arena_allocator, system_path) catch return;
+        var system = parseDocument(arena_allocator, source, formatForPath(system_path)) catch {
+            std.log.warn("system config {s}: parse failed; ignoring", .{system_path});
+            return;
+        };
+        _ = migrateDocument(arena_allocator, &system, system_path) catch return;
+
+        if (document.default_profile == null) document.default_profile = system.default_profile;
+        if (document.rotate_every == null) document.rotate_every = system.rotate_every;
+        if (document.rotate_among.len == 0) document.rotate_among = system.rotate_among;
+        if (document.transition.kind == .cut) document.transition = system.transition;
+
+        var merged: std.ArrayList(Profile) = .empty;
+        try merged.appendSlice(arena_allocator, document.profiles);
+        for (system.profiles) |profile| {
+            const shadowed = for (merged.items) |existing| {
+                if (std.mem.eql(u8, existing.name, profile.name)) break true;
+            } else false;
+            if (shadowed) continue;
+            try merged.append(arena_allocator, profile);
+        }
+        document.profiles = try merged.toOwnedSlice(arena_allocator);
+    }
+
+    fn readConfigFile(allocator: std.mem.Allocator, file_path: []const u8) ![:0]u8 {
+        return std.fs.cwd().readFileAllocOptions(allocator, file_path, 1024 * 1024, null, .@"1", 0);
+    }
+
+    /// Which profile would run right now and why: consults the override
+    /// store, the (zoned, calendar-aware) schedule, `default_profile`,
+    /// and finally file order. `status` and the GUI print the reason.
+    pub fn pickProfileExplained(
+        self: *const ProfilesConfig,
+        allocator: std.mem.Allocator,
+        conditions: ?schedule.ConditionProvider,
+    ) schedule.Selection {
+        const now_unix = std.time.timestamp();
+        const local_minutes: u16 = @intFromFloat(blend.localMinutesNow());
+        const date: ?schedule.Date = if (blend.localMonthDayNow()) |month_day|
+            .{ .month = month_day[0], .day = month_day[1] }
+        else
+            null;
+
+        var state: ?override_mod.State = override_mod.load(allocator, null, now_unix) catch null;
+        defer if (state) |*loaded| loaded.deinit();
+        const override_name = if (state) |loaded| loaded.activeProfile(now_unix) else null;
+
+        return schedule.select(
+            allocator,
+            self.document.profiles,
+            self.document.default_profile,
+            override_name,
+            now_unix,
+            local_minutes,
+            date,
+            conditions,
+        );
+    }
+
+    pub fn findProfile(self: *const ProfilesConfig, name: []const u8) ?Profile {
+        for (self.document.profiles) |profile| {
+            if (std.mem.eql(u8, profile.name, name)) return profile;
+        }
+        return null;
+    }
+
+    /// Appends the play flag carrying the configured profile-switch
+    /// transition, for callers that spawn a player per profile. The
+    /// duration string is allocated with `allocator` and owned by the
+    /// caller's argv.
+    pub fn appendTransitionFlags(
+        self: *const ProfilesConfig,
+        allocator: std.mem.Allocator,
+        args: *std.ArrayList([]const u8),
+    ) !void {
+        if (self.document.transition.kind != .fade) return;
+        try args.append(allocator, "--transition-fade");
+        const value = try std.fmt.allocPrint(allocator, "{d}", .{
+            self.document.transition.duration_ms,
+        });
+        errdefer allocator.free(value);
+        try args.append(allocator, value);
+    }
+
+    /// Checks the loaded config for semantic problems the parser cannot
+    /// catch: missing video files, unparsable windows, duplicate names,
+    /// an unknown `default_profile`, and overlapping windows at equal
+    /// priority (where which profile runs is effectively arbitrary).
+    /// Caller deinits; `errorCount` says whether anything is fatal.
+    pub fn validate(self: *const ProfilesConfig, allocator: std.mem.Allocator) !Diagnostics {
+        var findings: std.ArrayList(Diagnostic) = .empty;
+        errdefer {
+            for (findings.items) |diagnostic| allocator.free(diagnostic.message);
+            findings.deinit(allocator);
+        }
+
+        const profiles = self.document.profiles;
+
+        if (self.document.default_profile) |default| {
+            if (self.findProfile(default) == null) {
+                try findings.append(allocator, .{
+                    .severity = .err,
+                    .profile = "",
+                    .message = try std.fmt.allocPrint(
+                        allocator,
+                        "default_profile \"{s}\" does not name a profile",
+                        .{default},
+                    ),
+                });
+            }
+        }
+
+        for (profiles, 0..) |profile, index| {
+            for (profiles[index + 1 ..]) |later| {
+                if (!std.mem.eql(u8, profile.name, later.name)) continue;
+                try findings.append(allocator, .{
+                    .severity = .err,
+                    .profile = profile.name,
+                    .message = try allocator.dupe(u8, "duplicate profile name"),
+                });
+            }
+
+            try checkVideo(allocator, profile.name, profile.video, &findings);
+            for (profile.videos) |entry| {
+                try checkVideo(allocator, profile.name, entry, &findings);
+            }
+
+            const window = if (profile.window) |text|
+                blend.parseWindow(text) catch blk: {
+                    try findings.append(allocator, .{
+                        .severity = .err,
+                        .profile = profile.name,
+                        .message = try std.fmt.allocPrint(
+                            allocator,
+                            "window \"{s}\" is not HH:MM-HH:MM",
+                            .{text},
+                        ),
+                    });
+                    break :blk null;
+                }
+            else
+                null;
+
+            if (profile.timezone) |zone| {
+                _ = tz.offsetSeconds(allocator, zone, std.time.timestamp()) catch {
+                    try findings.append(allocator, .{
+                        .severity = .err,
+                        .profile = profile.name,
+                        .message = try std.fmt.allocPrint(
+                            allocator,
+                            "unknown timezone \"{s}\"",
+                            .{zone},
+                        ),
+                    });
+                };
+            }
+
+            for ([_][]const []const u8{ profile.dates, profile.except_dates }) |list| {
+                for (list) |text| {
+                    _ = schedule.parseDate(text) catch {
+                        try findings.append(allocator, .{
+                            .severity = .err,
+                            .profile = profile.name,
+                            .message = try std.fmt.allocPrint(
+                                allocator,
+                                "date \"{s}\" is not MM-DD",
+                                .{text},
+                            ),
+                        });
+                    };
+                }
+            }
+
+            if (window) |own| {
+                for (profiles[index + 1 ..]) |later| {
+                    const other_text = later.window orelse continue;
+                    const other = blend.parseWindow(other_text) catch continue;
+                    if (!schedule.windowsOverlap(own, other)) continue;
+                    if ((profile.priority orelse 0) != (later.priority orelse 0)) continue;
+                    try findings.append(allocator, .{
+                        .severity = .warn,
+                        .profile = profile.name,
+                        .message = try std.fmt.allocPrint(
+                            allocator,
+                            "window overlaps \"{s}\" at equal priority; file order decides",
+                            .{later.name},
+                        ),
+                    });
+                }
+            }
+        }
+
+        return .{
+            .allocator = allocator,
+            .items = try findings.toOwnedSlice(allocator),
+        };
+    }
+
+    /// Flags a local video path that does not exist. URLs, glob patterns
+    /// and empty entries are skipped — they resolve at play time.
+    fn checkVideo(
+        allocator: std.mem.Allocator,
+        name: []const u8,
+        video: []const u8,
+        findings: *std.ArrayList(Diagnostic),
+    ) !void {
+        if (video.len == 0) return;
+        if (std.mem.indexOf(u8, video, "://") != null) return;
+        if (glob.hasPattern(video)) return;
+
+        const resolved = if (std.mem.startsWith(u8, video, "~/"))
+            if (std.posix.getenv("HOME")) |home|
+                try std.fmt.allocPrint(allocator, "{s}/{s}", .{ home, video[2..] })
+            else
+                try allocator.dupe(u8, video)
+        else
+            try allocator.dupe(u8, video);
+        defer allocator.free(resolved);
+
+        std.fs.cwd().access(resolved, .{}) catch {
+            try findings.append(allocator, .{
+                .severity = .err,
+                .profile = name,
+                .message = try std.fmt.allocPrint(allocator, "video not found: {s}", .{video}),
+            });
+        };
+    }
+
+    /// Appends a profile and persists the whole document. The profile
+    /// goes after the main file's own entries but before any included
+    /// ones, so it lands in the file `save` writes. Rejects duplicate
+    /// names and unparsable windows up front — the GUI and editing
+    /// commands rely on this instead of re-validating themselves.
+    pub fn addProfile(self: *ProfilesConfig, profile: Profile) !void {
+        if (self.findProfile(profile.name) != null) return error.DuplicateName;
+        try checkWindow(profile);
+        const arena_allocator = self.arena.allocator();
+
+        var profiles: std.ArrayList(Profile) = .empty;
+        try profiles.appendSlice(arena_allocator, self.raw_profiles);
+        try profiles.insert(arena_allocator, self.own_count, .{
+            .name = try arena_allocator.dupe(u8, profile.name),
+            .extends = profile.extends,
+            .video = try arena_allocator.dupe(u8, profile.video),
+            .videos = profile.videos,
+            .outputs = profile.outputs,
+            .mute = profile.mute,
+            .volume = profile.volume,
+            .scale_mode = profile.scale_mode,
+            .backend = profile.backend,
+            .buffer_mode = profile.buffer_mode,
+            .window = profile.window,
+            .dates = profile.dates,
+            .except_dates = profile.except_dates,
+            .timezone = profile.timezone,
+            .conditions = profile.conditions,
+            .priority = profile.priority,
+            .on_battery = profile.on_battery,
+        });
+        self.raw_profiles = try profiles.toOwnedSlice(arena_allocator);
+        self.own_count += 1;
+        try self.reresolve();
+
+        try self.save();
+    }
+
+    /// Removes a profile by name and persists. A `default_profile`
+    /// pointing at it is cleared rather than left dangling.
+    pub fn removeProfile(self: *ProfilesConfig, name: []const u8) !void {
+        const index = try self.ownIndex(name);
+        const arena_allocator = self.arena.allocator();
+
+        var profiles: std.ArrayList(Profile) = .empty;
+        try profiles.appendSlice(arena_allocator, self.raw_profiles);
+        _ = profiles.orderedRemove(index);
+        self.raw_profiles = try profiles.toOwnedSlice(arena_allocator);
+        self.own_count -= 1;
+        try self.reresolve();
+
+        if (self.document.default_profile) |default| {
+            if (std.mem.eql(u8, default, name)) self.document.default_profile = null;
+        }
+
+        try self.save();
+    }
+
+    /// Renames a profile and persists, following the references other
+    /// parts of the document hold: `default_profile`, `rotate_among`,
+    /// and `extends` in the main file's own profiles.
+    pub fn renameProfile(self: *ProfilesConfig, old_name: []const u8, new_name: []const u8) !void {
+        if (self.findProfile(new_name) != null) return error.DuplicateName;
+        const index = try self.ownIndex(old_name);
+        const arena_allocator = self.arena.allocator();
+        const copy = try arena_allocator.dupe(u8, new_name);
+
+        var profiles: std.ArrayList(Profile) = .empty;
+        try profiles.appendSlice(arena_allocator, self.raw_profiles);
+        profiles.items[index].name = copy;
+        for (profiles.items[0..self.own_count]) |*profile| {
+            if (profile.extends) |base| {
+                if (std.mem.eql(u8, base, old_name)) profile.extends = copy;
+            }
+        }
+        self.raw_profiles = try profiles.toOwnedSlice(arena_allocator);
+
+        if (self.document.default_profile) |default| {
+            if (std.mem.eql(u8, default, old_name)) self.document.default_profile = copy;
+        }
+        if (self.document.rotate_among.len > 0) {
+            const among = try arena_allocator.dupe([]const u8, self.document.rotate_among);
+            for (among) |*entry| {
+                if (std.mem.eql(u8, entry.*, old_name)) entry.* = copy;
+            }
+            self.document.rotate_among = among;
+        }
+        try self.reresolve();
+
+        try self.save();
+    }
+
+    /// Replaces a profile wholesale and persists; `profile.name` may
+    /// differ from `name` as long as it stays unique.
+    pub fn updateProfile(self: *ProfilesConfig, name: []const u8, profile: Profile) !void {
+        const index = try self.ownIndex(name);
+        if (!std.mem.eql(u8, name, profile.name) and self.findProfile(profile.name) != null)
+            return error.DuplicateName;
+        try checkWindow(profile);
+        const arena_allocator = self.arena.allocator();
+
+        var profiles: std.ArrayList(Profile) = .empty;
+        try profiles.appendSlice(arena_allocator, self.raw_profiles);
+        profiles.items[index] = profile;
+        profiles.items[index].name = try arena_allocator.dupe(u8, profile.name);
+        profiles.items[index].video = try arena_allocator.dupe(u8, profile.video);
+        self.raw_profiles = try profiles.toOwnedSlice(arena_allocator);
+        try self.reresolve();
+
+        try self.save();
+    }
+
+    /// Index of one of the main file's own profiles. Profiles from
+    /// includes exist in the merged view but belong to other files, so
+    /// the editing API refuses to touch them.
+    fn ownIndex(self: *const ProfilesConfig, name: []const u8) !usize {
+        for (self.document.profiles, 0..) |profile, index| {
+            if (!std.mem.eql(u8, profile.name, name)) continue;
+            if (index >= self.own_count) {
+                std.log.err("\"{s}\" comes from an include; edit that file instead", .{name});
+                return error.IncludedProfile;
+            }
+            return index;
+        }
+        return error.UnknownProfile;
+    }
+
+    fn checkWindow(profile: Profile) !void {
+        const window = profile.window orelse return;
+        _ = blend.parseWindow(window) catch return error.InvalidWindow;
+    }
+
+    /// Rebuilds the resolved view in `document.profiles` from the edited
+    /// raw list, so consumers keep seeing flattened profiles while the
+    /// file keeps its `extends` chains live.
+    fn reresolve(self: *ProfilesConfig) !void {
+        const arena_allocator = self.arena.allocator();
+        const resolved = try arena_allocator.dupe(Profile, self.raw_profiles);
+        try resolveExtends(resolved);
+        self.document.profiles = resolved;
+    }
+
+    /// Writes the document back to its path. Only the main file's own
+    /// profiles are written; included files stay untouched. Only ZON
+    /// configs can be written back — JSON configs are generated by
+    /// external tooling and treated as read-only.
+    pub fn save(self: *const ProfilesConfig) !void {
+        if (self.format != .zon) {
+            std.log.err("{s} is generated externally; edit its source instead", .{self.path});
+            return error.ReadOnlyConfig;
+        }
+        const allocator = self.arena.child_allocator;
+
+        var text: std.ArrayList(u8) = .empty;
+        defer text.deinit(allocator);
+
+        try text.appendSlice(allocator, ".{\n");
+        {
+            const line = try std.fmt.allocPrint(allocator, "    .version = {d},\n", .{
+                current_config_version,
+            });
+            defer allocator.free(line);
+            try text.appendSlice(allocator, line);
+        }
+        if (self.document.default_profile) |default| {
+            const line = try std.fmt.allocPrint(allocator, "    .default_profile = \"{s}\",\n", .{default});
+            defer allocator.free(line);
+            try text.appendSlice(allocator, line);
+        }
+        if (self.document.rotate_every) |every| {
+            const line = try std.fmt.allocPrint(allocator, "    .rotate_every = \"{s}\",\n", .{every});
+            defer allocator.free(line);
+            try text.appendSlice(allocator, line);
+        }
+        if (self.document.rotate_among.len > 0) {
+            try text.appendSlice(allocator, "    .rotate_among = .{ ");
+            for (self.document.rotate_among, 0..) |name, index| {
+                const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                    if (index > 0) ", " else "",
+                    name,
+                });
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            try text.appendSlice(allocator, " },\n");
+        }
+        if (self.document.transition.kind != .cut) {
+            const line = try std.fmt.allocPrint(
+                allocator,
+                "    .transition = .{{ .kind = .{s}, .duration_ms = {d} }},\n",
+                .{ @tagName(self.document.transition.kind), self.document.transition.duration_ms },
+            );
+            defer allocator.free(line);
+            try text.appendSlice(allocator, line);
+        }
+        if (self.document.include.len > 0) {
+            try text.appendSlice(allocator, "    .include = .{ ");
+            for (self.document.include, 0..) |pattern, index| {
+                const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                    if (index > 0) ", " else "",
+                    pattern,
+                });
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            try text.appendSlice(allocator, " },\n");
+        }
+        try text.appendSlice(allocator, "    .profiles = .{\n");
+        for (self.raw_profiles[0..self.own_count]) |profile| {
+            const line = try std.fmt.allocPrint(
+                allocator,
+                "        .{{ .name = \"{s}\", .video = \"{s}\"",
+                .{ profile.name, profile.video },
+            );
+            defer allocator.free(line);
+            try text.appendSlice(allocator, line);
+            if (profile.extends) |base| {
+                const field = try std.fmt.allocPrint(allocator, ", .extends = \"{s}\"", .{base});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.videos.len > 0) {
+                try text.appendSlice(allocator, ", .videos = .{ ");
+                for (profile.videos, 0..) |entry, index| {
+                    const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                        if (index > 0) ", " else "",
+                        entry,
+                    });
+                    defer allocator.free(field);
+                    try text.appendSlice(allocator, field);
+                }
+                try text.appendSlice(allocator, " }");
+            }
+            if (profile.outputs.len > 0) {
+                try text.appendSlice(allocator, ", .outputs = .{ ");
+                for (profile.outputs, 0..) |entry, index| {
+                    const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                        if (index > 0) ", " else "",
+                        entry,
+                    });
+                    defer allocator.free(field);
+                    try text.appendSlice(allocator, field);
+                }
+                try text.appendSlice(allocator, " }");
+            }
+            if (profile.mute) |mute| {
+                const field = try std.fmt.allocPrint(allocator, ", .mute = {}", .{mute});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.volume) |volume| {
+                const field = try std.fmt.allocPrint(allocator, ", .volume = {d:.2}", .{volume});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.scale_mode) |mode| {
+                const field = try std.fmt.allocPrint(allocator, ", .scale_mode = .{s}", .{@tagName(mode)});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.backend) |backend| {
+                const field = try std.fmt.allocPrint(allocator, ", .backend = .{s}", .{@tagName(backend)});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.buffer_mode) |mode| {
+                const field = try std.fmt.allocPrint(allocator, ", .buffer_mode = .{s}", .{@tagName(mode)});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.window) |window| {
+                const field = try std.fmt.allocPrint(allocator, ", .window = \"{s}\"", .{window});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.dates.len > 0) {
+                try text.appendSlice(allocator, ", .dates = .{ ");
+                for (profile.dates, 0..) |entry, index| {
+                    const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                        if (index > 0) ", " else "",
+                        entry,
+                    });
+                    defer allocator.free(field);
+                    try text.appendSlice(allocator, field);
+                }
+                try text.appendSlice(allocator, " }");
+            }
+            if (profile.except_dates.len > 0) {
+                try text.appendSlice(allocator, ", .except_dates = .{ ");
+                for (profile.except_dates, 0..) |entry, index| {
+                    const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                        if (index > 0) ", " else "",
+                        entry,
+                    });
+                    defer allocator.free(field);
+                    try text.appendSlice(allocator, field);
+                }
+                try text.appendSlice(allocator, " }");
+            }
+            if (profile.timezone) |zone| {
+                const field = try std.fmt.allocPrint(allocator, ", .timezone = \"{s}\"", .{zone});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.conditions.len > 0) {
+                try text.appendSlice(allocator, ", .conditions = .{ ");
+                for (profile.conditions, 0..) |entry, index| {
+                    const field = try std.fmt.allocPrint(allocator, "{s}\"{s}\"", .{
+                        if (index > 0) ", " else "",
+                        entry,
+                    });
+                    defer allocator.free(field);
+                    try text.appendSlice(allocator, field);
+                }
+                try text.appendSlice(allocator, " }");
+            }
+            if (profile.priority) |priority| {
+                const field = try std.fmt.allocPrint(allocator, ", .priority = {d}", .{priority});
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            if (profile.on_battery) |policy| {
+                // Quoted so the `continue` tag stays a valid ZON literal.
+                const field = try std.fmt.allocPrint(allocator, ", .on_battery = .@\"{s}\"", .{
+                    @tagName(policy),
+                });
+                defer allocator.free(field);
+                try text.appendSlice(allocator, field);
+            }
+            try text.appendSlice(allocator, " },\n");
+        }
+        try text.appendSlice(allocator, "    },\n}\n");
+
+        if (std.fs.path.dirname(self.path)) |dir| {
+            std.fs.cwd().makePath(dir) catch {};
+        }
+        const file = try std.fs.cwd().createFile(self.path, .{});
+        defer file.close();
+        try file.writeAll(text.items);
+    }
+};
+
+/// A single field change applied to the config source text. `save`
+/// regenerates the whole file and so destroys user comments and
+/// ordering; runtime toggles (the mute control, volume nudges) go
+/// through `saveEdit` instead, which patches only the value.
+pub const Edit = struct {
+    profile: []const u8,
+    field: Field,
+    /// Rendered ZON literal, e.g. "true", "0.35", or "\"08:00-20:00\"".
+    value: []const u8,
+
+    pub const Field = enum { mute, volume, video, window };
+};
+
+/// Applies one edit to the config file at `path`, leaving everything
+/// else — comments included — byte-for-byte untouched. A field the
+/// profile does not have yet is inserted after its `.name`.
+pub fn saveEdit(allocator: std.mem.Allocator, path: []const u8, edit: Edit) !void {
+    if (formatForPath(path) != .zon) {
+        std.log.err("{s} is generated externally; edit its source instead", .{path});
+        return error.ReadOnlyConfig;
+    }
+    const source = try std.fs.cwd().readFileAlloc(allocator, path, 1024 * 1024);
+    defer allocator.free(source);
+
+    const patched = try applyEdit(allocator, source, edit);
+    defer allocator.free(patched);
+
+    const file = try std.fs.cwd().createFile(path, .{});
+    defer file.close();
+    try file.writeAll(patched);
+}
+
+fn applyEdit(allocator: std.mem.Allocator, source: []const u8, edit: Edit) ![]u8 {
+    const loc = findProfileLoc(source, edit.profile) orelse return error.UnknownProfile;
+    const field_name = @tagName(edit.field);
+
+    if (fieldValueSpan(source[loc.block_start..loc.block_end], field_name)) |span| {
+        return std.mem.concat(allocator, u8, &.{
+            source[0 .. loc.block_start + span.start],
+            edit.value,
+            source[loc.block_start + span.end ..],
+        });
+    }
+    const inserted = try std.fmt.allocPrint(allocator, ", .{s} = {s}", .{ field_name, edit.value });
+    defer allocator.free(inserted);
+    return std.mem.concat(allocator, u8, &.{
+        source[0..loc.name_end],
+        inserted,
+        source[loc.name_end..],
+    });
+}
+
+const ProfileLoc = struct {
+    block_start: usize,
+    block_end: usize,
+    /// One past the closing quote of the `.name` string, the safe spot
+    /// to insert a new field (never inside a comment).
+    name_end: usize,
+};
+
+/// Finds the struct literal containing `.name = "<name>"`, skipping
+/// string literals and `//` comments while tracking brace depth.
+fn findProfileLoc(source: []const u8, name: []const u8) ?ProfileLoc {
+    var stack: [32]usize = undefined;
+    var depth: usize = 0;
+    var target_depth: ?usize = null;
+    var name_end: usize = 0;
+
+    var i: usize = 0;
+    while (i < source.len) : (i += 1) {
+        const c = source[i];
+        if (c == '/' and i + 1 < source.len and source[i + 1] == '/') {
+            while (i < source.len and source[i] != '\n') i += 1;
+            continue;
+        }
+        if (c == '"') {
+            const content_start = i + 1;
+            i += 1;
+            while (i < source.len and source[i] != '"') {
+                if (source[i] == '\\') i += 1;
+                i += 1;
+            }
+            if (i >= source.len) return null;
+            if (target_depth == null and depth > 0 and
+                std.mem.eql(u8, source[content_start..i], name) and
+                isNameAssignment(source[0 .. content_start - 1]))
+            {
+                target_depth = depth;
+                name_end = i + 1;
+            }
+            continue;
+        }
+        if (c == '{') {
+            if (depth >= stack.len) return null;
+            stack[depth] = i;
+            depth += 1;
+            continue;
+        }
+        if (c == '}') {
+            if (depth == 0) return null;
+            depth -= 1;
+            if (target_depth) |target| {
+                if (depth == target - 1) {
+                    return .{
+                        .block_start = stack[depth],
+                        .block_end = i,
+                        .name_end = name_end,
+                    };
+                }
+            }
+        }
+    }
+    return null;
+}
+
+fn isNameAssignment(prefix: []const u8) bool {
+    var rest = std.mem.trimRight(u8, prefix, " \t\r\n");
+    if (rest.len == 0 or rest[rest.len - 1] != '=') return false;
+    rest = std.mem.trimRight(u8, rest[0 .. rest.len - 1], " \t\r\n");
+    return std.mem.endsWith(u8, rest, ".name");
+}
+
+const ValueSpan = struct { start: usize, end: usize };
+
+/// Span of the value assigned to `.field` within one profile block,
+/// offsets relative to the block.
+fn fieldValueSpan(block: []const u8, field: []const u8) ?ValueSpan {
+    var i: usize = 0;
+    while (i < block.len) : (i += 1) {
+        const c = block[i];
+        if (c == '/' and i + 1 < block.len and block[i + 1] == '/') {
+            while (i < block.len and block[i] != '\n') i += 1;
+            continue;
+        }
+        if (c == '"') {
+            i += 1;
+            while (i < block.len and block[i] != '"') {
+                if (block[i] == '\\') i += 1;
+                i += 1;
+            }
+            continue;
+        }
+        if (c != '.') continue;
+        if (!std.mem.startsWith(u8, block[i + 1 ..], field)) continue;
+        var j = i + 1 + field.len;
+        // A non-identifier character must follow, so `.mute` never
+        // matches inside `.muted`.
+        if (j < block.len and (std.ascii.isAlphanumeric(block[j]) or block[j] == '_')) continue;
+        while (j < block.len and std.ascii.isWhitespace(block[j])) j += 1;
+        if (j >= block.len or block[j] != '=') continue;
+        j += 1;
+        while (j < block.len and std.ascii.isWhitespace(block[j])) j += 1;
+        const value_start = j;
+        if (j < block.len and block[j] == '"') {
+            j += 1;
+            while (j < block.len and block[j] != '"') {
+                if (block[j] == '\\') j += 1;
+                j += 1;
+            }
+            if (j < block.len) j += 1;
+        } else {
+            while (j < block.len and block[j] != ',' and block[j] != '}' and
+                block[j] != '\n' and block[j] != '/') j += 1;
+            while (j > value_start and std.ascii.isWhitespace(block[j - 1])) j -= 1;
+        }
+        if (j == value_start) return null;
+        return .{ .start = value_start, .end = j };
+    }
+    return null;
+}
+
+test "includes merge after the main file and shadow by name" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    .include = .{ "profiles.d/*.zon" },
+        \\    .profiles = .{
+        \\        .{ .name = "day", .video = "main.mp4" },
+        \\    },
+        \\}
+        ,
+    });
+    try tmp.dir.makePath("profiles.d");
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.d/extra.zon",
+        .data =
+        \\.{
+        \\    .profiles = .{
+        \\        .{ .name = "day", .video = "shadowed.mp4" },
+        \\        .{ .name = "night", .video = "night.mp4" },
+        \\    },
+        \\}
+        ,
+    });
+
+    const config_path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(config_path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, config_path);
+    defer config.deinit();
+
+    try std.testing.expectEqual(@as(usize, 2), config.document.profiles.len);
+    // The main file's definition wins over the include's.
+    try std.testing.expectEqualStrings("main.mp4", config.findProfile("day").?.video);
+    try std.testing.expectEqualStrings("night.mp4", config.findProfile("night").?.video);
+    // Only the main file's own profile would be written back.
+    try std.testing.expectEqual(@as(usize, 1), config.own_count);
+}
+
+test "v1 configs migrate output to outputs and rewrite once" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    .profiles = .{
+        \\        .{ .name = "day", .video = "day.mp4", .output = "DP-1" },
+        \\    },
+        \\}
+        ,
+    });
+    const config_path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(config_path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, config_path);
+    defer config.deinit();
+
+    try std.testing.expectEqual(@as(u32, 1), config.loaded_version);
+    try std.testing.expectEqualStrings("DP-1", config.findProfile("day").?.outputs[0]);
+
+    try std.testing.expect(try config.migrateAndRewrite());
+    try std.testing.expect(!try config.migrateAndRewrite());
+
+    var reloaded = try ProfilesConfig.load(std.testing.allocator, config_path);
+    defer reloaded.deinit();
+    try std.testing.expectEqual(current_config_version, reloaded.loaded_version);
+    try std.testing.expectEqualStrings("DP-1", reloaded.findProfile("day").?.outputs[0]);
+}
+
+test "a JSON config loads by extension and is read-only" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.json",
+        .data =
+        \\{"default_profile": "day",
+        \\ "profiles": [{"name": "day", "video": "day.mp4", "scale_mode": "fill"}]}
+        ,
+    });
+    const config_path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.json");
+    defer std.testing.allocator.free(config_path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, config_path);
+    defer config.deinit();
+
+    try std.testing.expectEqual(Format.json, config.format);
+    try std.testing.expectEqualStrings("day.mp4", config.findProfile("day").?.video);
+    try std.testing.expectEqual(layout.ScaleMode.fill, config.findProfile("day").?.scale_mode.?);
+    try std.testing.expectError(error.ReadOnlyConfig, config.save());
+}
+
+test "a YAML config loads by extension and is read-only" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.yaml",
+        .data =
+        \\default_profile: day
+        \\transition:
+        \\  kind: fade
+        \\  duration_ms: 250
+        \\profiles:
+        \\  - name: day
+        \\    video: day.mp4
+        \\    scale_mode: fill
+        \\    outputs: [DP-1, HDMI-A-1]
+        \\
+        ,
+    });
+    const config_path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.yaml");
+    defer std.testing.allocator.free(config_path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, config_path);
+    defer config.deinit();
+
+    try std.testing.expectEqual(Format.yaml, config.format);
+    try std.testing.expectEqualStrings("day.mp4", config.findProfile("day").?.video);
+    try std.testing.expectEqual(layout.ScaleMode.fill, config.findProfile("day").?.scale_mode.?);
+    try std.testing.expectEqual(Transition.Kind.fade, config.document.transition.kind);
+    try std.testing.expectEqualStrings("HDMI-A-1", config.findProfile("day").?.outputs[1]);
+    try std.testing.expectError(error.ReadOnlyConfig, config.save());
+}
+
+test "validate reports semantic problems with severities" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    .default_profile = "nope",
+        \\    .profiles = .{
+        \\        .{ .name = "a", .video = "/definitely/missing.mp4", .window = "nonsense" },
+        \\        .{ .name = "a", .video = "" },
+        \\        .{ .name = "b", .video = "", .window = "08:00-12:00" },
+        \\        .{ .name = "c", .video = "", .window = "10:00-14:00" },
+        \\    },
+        \\}
+        ,
+    });
+    const config_path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(config_path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, config_path);
+    defer config.deinit();
+    var findings = try config.validate(std.testing.allocator);
+    defer findings.deinit();
+
+    // Unknown default, duplicate name, missing video, bad window.
+    try std.testing.expectEqual(@as(usize, 4), findings.errorCount());
+    // The b/c window overlap at equal priority is a warning on top.
+    try std.testing.expectEqual(@as(usize, 5), findings.items.len);
+}
+
+test "extends fills unset fields and keeps overrides" {
+    var profiles = [_]Profile{
+        .{ .name = "base", .outputs = &.{"DP-1"}, .scale_mode = .fill, .mute = true },
+        .{ .name = "night", .extends = "base", .video = "night.mp4", .mute = false },
+    };
+    try resolveExtends(&profiles);
+
+    try std.testing.expectEqual(layout.ScaleMode.fill, profiles[1].scale_mode.?);
+    try std.testing.expectEqualStrings("DP-1", profiles[1].outputs[0]);
+    // The child's own override survives the merge.
+    try std.testing.expectEqual(false, profiles[1].mute.?);
+}
+
+test "extends chains resolve through intermediate bases" {
+    var profiles = [_]Profile{
+        .{ .name = "root", .scale_mode = .stretch, .priority = 5 },
+        .{ .name = "mid", .extends = "root", .priority = 1 },
+        .{ .name = "leaf", .extends = "mid", .video = "leaf.mp4" },
+    };
+    try resolveExtends(&profiles);
+
+    try std.testing.expectEqual(layout.ScaleMode.stretch, profiles[2].scale_mode.?);
+    // The nearer base wins.
+    try std.testing.expectEqual(@as(i32, 1), profiles[2].priority.?);
+}
+
+test "extends cycles and unknown bases are load errors" {
+    var cycle = [_]Profile{
+        .{ .name = "a", .extends = "b" },
+        .{ .name = "b", .extends = "a" },
+    };
+    try std.testing.expectError(error.ExtendsCycle, resolveExtends(&cycle));
+
+    var dangling = [_]Profile{
+        .{ .name = "a", .extends = "missing" },
+    };
+    try std.testing.expectError(error.UnknownBase, resolveExtends(&dangling));
+}
+
+test "saveEdit patches a value without disturbing comments" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    // hand-tuned; do not reorder
+        \\    .profiles = .{
+        \\        .{ .name = "day", .video = "a.mp4", .mute = false }, // loud one
+        \\        .{ .name = "night", .video = "b.mp4" },
+        \\    },
+        \\}
+        ,
+    });
+    const path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(path);
+
+    try saveEdit(std.testing.allocator, path, .{
+        .profile = "day",
+        .field = .mute,
+        .value = "true",
+    });
+
+    const rewritten = try std.fs.cwd().readFileAlloc(std.testing.allocator, path, 1024 * 1024);
+    defer std.testing.allocator.free(rewritten);
+    try std.testing.expect(std.mem.indexOf(u8, rewritten, "// hand-tuned; do not reorder") != null);
+    try std.testing.expect(std.mem.indexOf(u8, rewritten, "// loud one") != null);
+    try std.testing.expect(std.mem.indexOf(u8, rewritten, ".mute = true") != null);
+    try std.testing.expect(std.mem.indexOf(u8, rewritten, ".mute = false") == null);
+
+    // The patched file still parses.
+    var config = try ProfilesConfig.load(std.testing.allocator, path);
+    defer config.deinit();
+    try std.testing.expectEqual(@as(?bool, true), config.findProfile("day").?.mute);
+}
+
+test "saveEdit inserts a missing field after the profile name" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    .profiles = .{
+        \\        .{ .name = "day", .video = "a.mp4" },
+        \\    },
+        \\}
+        ,
+    });
+    const path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(path);
+
+    try saveEdit(std.testing.allocator, path, .{
+        .profile = "day",
+        .field = .volume,
+        .value = "0.25",
+    });
+    const rewritten = try std.fs.cwd().readFileAlloc(std.testing.allocator, path, 1024 * 1024);
+    defer std.testing.allocator.free(rewritten);
+    try std.testing.expect(
+        std.mem.indexOf(u8, rewritten, ".name = \"day\", .volume = 0.25") != null,
+    );
+
+    try std.testing.expectError(error.UnknownProfile, saveEdit(std.testing.allocator, path, .{
+        .profile = "missing",
+        .field = .mute,
+        .value = "true",
+    }));
+}
+
+test "profile edits validate names and follow references" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    .default_profile = "day",
+        \\    .rotate_among = .{ "day", "night" },
+        \\    .profiles = .{
+        \\        .{ .name = "day", .video = "a.mp4" },
+        \\        .{ .name = "night", .video = "b.mp4", .extends = "day" },
+        \\    },
+        \\}
+        ,
+    });
+    const path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, path);
+    defer config.deinit();
+
+    try std.testing.expectError(error.DuplicateName, config.addProfile(.{
+        .name = "day",
+        .video = "x.mp4",
+    }));
+    try std.testing.expectError(error.InvalidWindow, config.addProfile(.{
+        .name = "odd",
+        .video = "x.mp4",
+        .window = "not-a-window",
+    }));
+    try std.testing.expectError(error.UnknownProfile, config.removeProfile("missing"));
+
+    try config.renameProfile("day", "daylight");
+    try config.updateProfile("night", .{ .name = "night", .video = "c.mp4" });
+    try config.removeProfile("daylight");
+
+    var reloaded = try ProfilesConfig.load(std.testing.allocator, path);
+    defer reloaded.deinit();
+    try std.testing.expectEqual(@as(usize, 1), reloaded.document.profiles.len);
+    try std.testing.expectEqualStrings("c.mp4", reloaded.findProfile("night").?.video);
+    // The rename followed rotate_among; the removal cleared the default.
+    try std.testing.expect(reloaded.document.default_profile == null);
+    try std.testing.expectEqualStrings("daylight", reloaded.document.rotate_among[0]);
+}
+
+test "edits keep extends chains live instead of materializing them" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{
+        .sub_path = "profiles.zon",
+        .data =
+        \\.{
+        \\    .profiles = .{
+        \\        .{ .name = "base", .video = "base.mp4", .volume = 0.5, .mute = true },
+        \\        .{ .name = "kid", .video = "kid.mp4", .extends = "base" },
+        \\    },
+        \\}
+        ,
+    });
+    const path = try tmp.dir.realpathAlloc(std.testing.allocator, "profiles.zon");
+    defer std.testing.allocator.free(path);
+
+    var config = try ProfilesConfig.load(std.testing.allocator, path);
+    try config.addProfile(.{ .name = "extra", .video = "extra.mp4" });
+    // The save above must not have frozen base's fields into "kid".
+    try config.updateProfile("base", .{ .name = "base", .video = "base.mp4", .volume = 0.25 });
+    config.deinit();
+
+    var reloaded = try ProfilesConfig.load(std.testing.allocator, path);
+    defer reloaded.deinit();
+    const kid = reloaded.findProfile("kid").?;
+    try std.testing.expectEqual(@as(f64, 0.25), kid.volume.?);
+    // updateProfile dropped base's mute, so the child no longer sees one.
+    try std.testing.expectEqual(@as(?bool, null), kid.mute);
+}
+
+test "the system config underlays the user document" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{
+        .sub_path = "system.zon",
+        .data =
+        \\.{
+        \\    .version = 2,
+        \\    .default_profile = "corp",
+        \\    .profiles = .{
+        \\        .{ .name = "corp", .video = "/usr/share/branding.mp4" },
+        \\        .{ .name = "day", .video = "/usr/share/day.mp4" },
+        \\    },
+        \\}
+        ,
+    });
+    const system_path = try tmp.dir.realpathAlloc(std.testing.allocator, "system.zon");
+    defer std.testing.allocator.free(system_path);
+
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    var document = Document{
+        .profiles = &.{.{ .name = "day", .video = "mine.mp4" }},
+    };
+    try ProfilesConfig.overlaySystem(arena.allocator(), &document, system_path);
+
+    // The unset default came from the system file; the user's "day"
+    // shadows the system one; "corp" was appended.
+    try std.testing.expectEqualStrings("corp", document.default_profile.?);
+    try std.testing.expectEqual(@as(usize, 2), document.profiles.len);
+    try std.testing.expectEqualStrings("mine.mp4", document.profiles[0].video);
+    try std.testing.expectEqualStrings("corp", document.profiles[1].name);
+}
@@ -0,0 +1,633 @@
+//! Profile scheduling.
+//!
+//! A profile can carry a time-of-day `window` ("HH:MM-HH:MM", local time,
+//! wrapping past midnight like blend windows); at any moment the active
+//! profile is picked from the windows containing "now". Overlaps used to
+//! resolve by file order silently — now an optional `priority` decides
+//! (higher wins), file order only breaks exact ties, and `pickExplained`
+//! spells the decision out per profile so "why is the wrong wallpaper up"
+//! is answerable without reading this file.
+
+const std = @import("std");
+const profiles_mod = @import("profiles.zig");
+const blend = @import("../render/blend.zig");
+const tz = @import("tz.zig");
+
+const minutes_per_day: u16 = 24 * 60;
+
+/// True when `now_minutes` (minutes since local midnight) falls inside
+/// the window; end-before-start windows wrap past midnight.
+pub fn windowContains(window: blend.Window, now_minutes: u16) bool {
+    const now = now_minutes % minutes_per_day;
+    if (window.end_minutes <= window.start_minutes) {
+        return now >= window.start_minutes or now < window.end_minutes;
+    }
+    return now >= window.start_minutes and now < window.end_minutes;
+}
+
+/// True when the two windows share at least one minute, accounting for
+/// midnight wrapping on either side.
+pub fn windowsOverlap(a: blend.Window, b: blend.Window) bool {
+    return windowContains(a, b.start_minutes) or windowContains(b, a.start_minutes);
+}
+
+pub const DateError = error{InvalidDate};
+
+/// A calendar day, month and day-of-month.
+pub const Date = struct { month: u8, day: u8 };
+
+/// Parses "MM-DD" (also accepting single digits, "1-1").
+pub fn parseDate(text: []const u8) DateError!Date {
+    const dash = std.mem.indexOfScalar(u8, text, '-') orelse return DateError.InvalidDate;
+    const month = std.fmt.parseInt(u8, text[0..dash], 10) catch return DateError.InvalidDate;
+    const day = std.fmt.parseInt(u8, text[dash + 1 ..], 10) catch return DateError.InvalidDate;
+    if (month < 1 or month > 12 or day < 1 or day > 31) return DateError.InvalidDate;
+    return .{ .month = month, .day = day };
+}
+
+/// True when the profile may run on `date` per its `dates` and
+/// `except_dates` lists. A null date (a caller without a calendar)
+/// keeps date-restricted profiles out rather than guessing; unparsable
+/// entries never match — `validate` reports them.
+pub fn dateAllowed(profile: profiles_mod.Profile, date: ?Date) bool {
+    if (date) |today| {
+        for (profile.except_dates) |text| {
+            const entry = parseDate(text) catch continue;
+            if (entry.month == today.month and entry.day == today.day) return false;
+        }
+    }
+    if (profile.dates.len == 0) return true;
+    const today = date orelse return false;
+    for (profile.dates) |text| {
+        const entry = parseDate(text) catch continue;
+        if (entry.month == today.month and entry.day == today.day) return true;
+    }
+    return false;
+}
+
+/// Pluggable source of runtime conditions ("vpn", "workspace:web",
+/// "do-not-disturb") supplied by front-ends, so profiles can be gated on
+/// state this process cannot know itself. Same shape as power.Provider:
+/// a context pointer and a probe function.
+pub const ConditionProvider = struct {
+    context: ?*anyopaque = null,
+    holdsFn: *const fn (context: ?*anyopaque, condition: []const u8) bool,
+
+    pub fn holds(self: ConditionProvider, condition: []const u8) bool {
+        return self.holdsFn(self.context, condition);
+    }
+};
+
+/// True when every condition the profile names holds. Without a
+/// provider, condition-gated profiles stay out — a VPN wallpaper must
+/// not show because nobody could check the VPN.
+pub fn conditionsMet(profile: profiles_mod.Profile, conditions: ?ConditionProvider) bool {
+    if (profile.conditions.len == 0) return true;
+    const provider = conditions orelse return false;
+    for (profile.conditions) |condition| {
+        if (!provider.holds(condition)) return false;
+    }
+    return true;
+}
+
+/// Index of the profile to run at `now_minutes`, or null when no
+/// scheduled window matches. Profiles without a window never compete
+/// here; they are explicit choices, not scheduled ones.
+pub fn pick(profiles: []const profiles_mod.Profile, now_minutes: u16) ?usize {
+    return pickOn(profiles, now_minutes, null);
+}
+
+/// Like `pick`, but calendar-aware: a profile with `dates` only competes
+/// on one of them, and never on one of its `except_dates`.
+pub fn pickOn(profiles: []const profiles_mod.Profile, now_minutes: u16, date: ?Date) ?usize {
+    var best: ?usize = null;
+    for (profiles, 0..) |profile, index| {
+        if (!dateAllowed(profile, date)) continue;
+        const window_text = profile.window orelse continue;
+        const window = blend.parseWindow(window_text) catch continue;
+        if (!windowContains(window, now_minutes)) continue;
+        if (best) |current| {
+            // Higher priority wins; an exact tie keeps the earlier
+            // profile, so file order stays the deterministic fallback.
+            if ((profile.priority orelse 0) <= (profiles[current].priority orelse 0)) continue;
+        }
+        best = index;
+    }
+    return best;
+}
+
+/// Minutes since midnight on the profile's own clock: its `timezone`
+/// when set, otherwise the caller's system-local `local_minutes`. An
+/// unresolvable zone falls back to local time — a wrong wallpaper beats
+/// none, and `validate` reports the bad name.
+pub fn profileMinutes(
+    allocator: std.mem.Allocator,
+    profile: profiles_mod.Profile,
+    now_unix: i64,
+    local_minutes: u16,
+) u16 {
+    const zone = profile.timezone orelse return local_minutes;
+    const offset = tz.offsetSeconds(allocator, zone, now_unix) catch return local_minutes;
+    const seconds_of_day = @mod(now_unix + offset, std.time.s_per_day);
+    return @intCast(@divTrunc(seconds_of_day, std.time.s_per_min));
+}
+
+/// Like `pickOn`, but each profile's window is tested against the wall
+/// clock of its own timezone, and runtime conditions are consulted.
+/// Dates stay on the system-local calendar.
+pub fn pickZoned(
+    allocator: std.mem.Allocator,
+    profiles: []const profiles_mod.Profile,
+    now_unix: i64,
+    local_minutes: u16,
+    date: ?Date,
+    conditions: ?ConditionProvider,
+) ?usize {
+    var best: ?usize = null;
+    for (profiles, 0..) |profile, index| {
+        if (!dateAllowed(profile, date)) continue;
+        if (!conditionsMet(profile, conditions)) continue;
+        const window_text = profile.window orelse continue;
+        const window = blend.parseWindow(window_text) catch continue;
+        const minutes = profileMinutes(allocator, profile, now_unix, local_minutes);
+        if (!windowContains(window, minutes)) continue;
+        if (best) |current| {
+            if ((profile.priority orelse 0) <= (profiles[current].priority orelse 0)) continue;
+        }
+        best = index;
+    }
+    return best;
+}
+
+/// Why a profile was selected, in precedence order.
+pub const Reason = enum {
+    override,
+    schedule,
+    default,
+    first,
+    none,
+
+    pub fn describe(self: Reason) []const u8 {
+        return switch (self) {
+            .override => "manual override",
+            .schedule => "matched schedule window",
+            .default => "default_profile",
+            .first => "first in file order",
+            .none => "no profiles configured",
+        };
+    }
+};
+
+pub const Selection = struct {
+    chosen: ?usize,
+    reason: Reason,
+};
+
+/// The whole "which profile runs now" decision in one place: a manual
+/// override wins, then the zoned, calendar-aware schedule, then
+/// `default_profile`, then plain file order. Returning the reason
+/// alongside the index lets `status` and the GUI answer "why is my
+/// night wallpaper up at noon" instead of just restating the pick.
+pub fn select(
+    allocator: std.mem.Allocator,
+    profiles: []const profiles_mod.Profile,
+    default_profile: ?[]const u8,
+    override_name: ?[]const u8,
+    now_unix: i64,
+    local_minutes: u16,
+    date: ?Date,
+    conditions: ?ConditionProvider,
+) Selection {
+    if (override_name) |name| {
+        for (profiles, 0..) |profile, index| {
+            if (std.mem.eql(u8, profile.name, name))
+                return .{ .chosen = index, .reason = .override };
+        }
+    }
+    if (pickZoned(allocator, profiles, now_unix, local_minutes, date, conditions)) |index|
+        return .{ .chosen = index, .reason = .schedule };
+    if (default_profile) |name| {
+        for (profiles, 0..) |profile, index| {
+            if (std.mem.eql(u8, profile.name, name))
+                return .{ .chosen = index, .reason = .default };
+        }
+    }
+    if (profiles.len > 0) return .{ .chosen = 0, .reason = .first };
+    return .{ .chosen = null, .reason = .none };
+}
+
+/// Per-output selection: an override for this specific output outranks
+/// the global override, which outranks everything else (see `select`).
+/// This is what lets one monitor run "presentation" while the rest keep
+/// following the schedule.
+pub fn selectForOutput(
+    allocator: std.mem.Allocator,
+    profiles: []const profiles_mod.Profile,
+    default_profile: ?[]const u8,
+    output_override: ?[]const u8,
+    global_override: ?[]const u8,
+    now_unix: i64,
+    local_minutes: u16,
+    date: ?Date,
+    conditions: ?ConditionProvider,
+) Selection {
+    if (output_override) |name| {
+        for (profiles, 0..) |profile, index| {
+            if (std.mem.eql(u8, profile.name, name))
+                return .{ .chosen = index, .reason = .override };
+        }
+    }
+    return select(
+        allocator,
+        profiles,
+        default_profile,
+        global_override,
+        now_unix,
+        local_minutes,
+        date,
+        conditions,
+    );
+}
+
+/// Like `pick`, but an unexpired manual override (see override.zig)
+/// wins over every window. An override naming an unknown profile falls
+/// through to the schedule rather than blanking the wallpaper.
+pub fn pickOverridden(
+    profiles: []const profiles_mod.Profile,
+    now_minutes: u16,
+    override_name: ?[]const u8,
+) ?usize {
+    if (override_name) |name| {
+        for (profiles, 0..) |profile, index| {
+            if (std.mem.eql(u8, profile.name, name)) return index;
+        }
+    }
+    return pick(profiles, now_minutes);
+}
+
+pub const Explained = struct {
+    allocator: std.mem.Allocator,
+    /// Index of the winning profile, null when nothing matches.
+    chosen: ?usize,
+    /// One line per profile, in file order, saying why it won or lost.
+    lines: [][]u8,
+
+    pub fn deinit(self: *Explained) void {
+        for (self.lines) |line| self.allocator.free(line);
+        self.allocator.free(self.lines);
+        self.* = undefined;
+    }
+};
+
+/// Like `pick`, but records the reasoning per profile. Caller deinits.
+pub fn pickExplained(
+    allocator: std.mem.Allocator,
+    profiles: []const profiles_mod.Profile,
+    now_minutes: u16,
+) !Explained {
+    const chosen = pick(profiles, now_minutes);
+
+    const lines = try allocator.alloc([]u8, profiles.len);
+    var filled: usize = 0;
+    errdefer {
+        for (lines[0..filled]) |line| allocator.free(line);
+        allocator.free(lines);
+    }
+    for (profiles, 0..) |profile, index| {
+        const reason: []const u8 = blk: {
+            const window_text = profile.window orelse break :blk "no window, not scheduled";
+            const window = blend.parseWindow(window_text) catch break :blk "window unparsable";
+            if (!windowContains(window, now_minutes)) break :blk "window not active";
+            if (chosen == index) break :blk "active window, highest priority";
+            break :blk "active window, outranked";
+        };
+        lines[index] = try std.fmt.allocPrint(allocator, "{s}: priority {d}, {s}", .{
+            profile.name,
+            profile.priority orelse 0,
+            reason,
+        });
+        filled += 1;
+    }
+
+    return .{ .allocator = allocator, .chosen = chosen, .lines = lines };
+}
+
+pub const DurationError = error{InvalidDuration};
+
+/// Parses "90s"/"30m"/"2h" into seconds.
+pub fn parseDuration(text: []const u8) DurationError!u64 {
+    if (text.len < 2) return DurationError.InvalidDuration;
+    const value = std.fmt.parseInt(u64, text[0 .. text.len - 1], 10) catch
+        return DurationError.InvalidDuration;
+    return switch (text[text.len - 1]) {
+        's' => value,
+        'm' => value * std.time.s_per_min,
+        'h' => value * std.time.s_per_hour,
+        else => DurationError.InvalidDuration,
+    };
+}
+
+/// Indices of the profiles eligible for rotation: the ones named in
+/// `among`, or every profile when `among` is empty. Caller frees.
+pub fn rotationPool(
+    allocator: std.mem.Allocator,
+    profiles: []const profiles_mod.Profile,
+    among: []const []const u8,
+) ![]usize {
+    var pool: std.ArrayList(usize) = .empty;
+    errdefer pool.deinit(allocator);
+    for (profiles, 0..) |profile, index| {
+        if (among.len > 0) {
+            for (among) |name| {
+                if (std.mem.eql(u8, name, profile.name)) break;
+            } else continue;
+        }
+        try pool.append(allocator, index);
+    }
+    return pool.toOwnedSlice(allocator);
+}
+
+/// Timed random rotation: every `period_s` the tick yields a different
+/// pool entry, for users who want variety rather than a schedule.
+pub const Rotation = struct {
+    period_s: u64,
+    prng: std.Random.DefaultPrng,
+    last_tick_s: i64 = 0,
+    current: ?usize = null,
+
+    pub fn init(period_s: u64, seed: u64) Rotation {
+        return .{ .period_s = period_s, .prng = std.Random.DefaultPrng.init(seed) };
+    }
+
+    /// Returns an index into the pool when a rotation is due (the first
+    /// call always is), never the same entry twice in a row while the
+    /// pool has more than one.
+    pub fn tick(self: *Rotation, now_s: i64, pool_len: usize) ?usize {
+        if (pool_len == 0) return null;
+        if (self.last_tick_s != 0 and now_s - self.last_tick_s < @as(i64, @intCast(self.period_s)))
+            return null;
+        self.last_tick_s = now_s;
+
+        var next = self.prng.random().uintLessThan(usize, pool_len);
+        if (self.current) |current| {
+            if (pool_len > 1 and next == current) next = (next + 1) % pool_len;
+        }
+        self.current = next;
+        return next;
+    }
+};
+
+test "priority beats file order on overlap" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "day", .video = "a", .window = "08:00-20:00" },
+        .{ .name = "focus", .video = "b", .window = "09:00-17:00", .priority = 10 },
+    };
+    try std.testing.expectEqual(@as(?usize, 1), pick(&profiles, 12 * 60));
+    // Outside the higher-priority window the broad one is back.
+    try std.testing.expectEqual(@as(?usize, 0), pick(&profiles, 19 * 60));
+}
+
+test "equal priority keeps the first profile in file order" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "a", .video = "a", .window = "00:00-23:59" },
+        .{ .name = "b", .video = "b", .window = "00:00-23:59" },
+    };
+    try std.testing.expectEqual(@as(?usize, 0), pick(&profiles, 600));
+}
+
+test "wrapping windows cover midnight" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "night", .video = "n", .window = "22:00-06:00" },
+    };
+    try std.testing.expectEqual(@as(?usize, 0), pick(&profiles, 23 * 60));
+    try std.testing.expectEqual(@as(?usize, 0), pick(&profiles, 3 * 60));
+    try std.testing.expectEqual(@as(?usize, null), pick(&profiles, 12 * 60));
+}
+
+test "overlap detection wraps past midnight" {
+    const night = try blend.parseWindow("22:00-06:00");
+    const morning = try blend.parseWindow("05:00-09:00");
+    const afternoon = try blend.parseWindow("12:00-18:00");
+    try std.testing.expect(windowsOverlap(night, morning));
+    try std.testing.expect(!windowsOverlap(night, afternoon));
+}
+
+test "durations parse with s/m/h suffixes" {
+    try std.testing.expectEqual(@as(u64, 90), try parseDuration("90s"));
+    try std.testing.expectEqual(@as(u64, 1800), try parseDuration("30m"));
+    try std.testing.expectEqual(@as(u64, 7200), try parseDuration("2h"));
+    try std.testing.expectError(DurationError.InvalidDuration, parseDuration("30"));
+}
+
+test "rotation honors the period and never repeats back to back" {
+    var rotation = Rotation.init(1800, 42);
+    const first = rotation.tick(0, 4) orelse return error.TestExpectedRotation;
+    // Not due yet.
+    try std.testing.expectEqual(@as(?usize, null), rotation.tick(900, 4));
+    var previous = first;
+    var now: i64 = 1800;
+    for (0..16) |_| {
+        const next = rotation.tick(now, 4) orelse return error.TestExpectedRotation;
+        try std.testing.expect(next != previous);
+        previous = next;
+        now += 1800;
+    }
+}
+
+test "the rotation pool filters by name" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "a", .video = "a" },
+        .{ .name = "b", .video = "b" },
+        .{ .name = "c", .video = "c" },
+    };
+    const pool = try rotationPool(std.testing.allocator, &profiles, &.{ "a", "c" });
+    defer std.testing.allocator.free(pool);
+    try std.testing.expectEqualSlices(usize, &.{ 0, 2 }, pool);
+
+    const all = try rotationPool(std.testing.allocator, &profiles, &.{});
+    defer std.testing.allocator.free(all);
+    try std.testing.expectEqual(@as(usize, 3), all.len);
+}
+
+test "an override outranks the schedule until it names nobody" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "day", .video = "a", .window = "08:00-20:00" },
+        .{ .name = "night", .video = "b" },
+    };
+    try std.testing.expectEqual(@as(?usize, 1), pickOverridden(&profiles, 12 * 60, "night"));
+    // Unknown overrides fall through to the schedule.
+    try std.testing.expectEqual(@as(?usize, 0), pickOverridden(&profiles, 12 * 60, "gone"));
+    try std.testing.expectEqual(@as(?usize, 0), pickOverridden(&profiles, 12 * 60, null));
+}
+
+test "the explanation names winner and losers" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "day", .video = "a", .window = "08:00-20:00" },
+        .{ .name = "focus", .video = "b", .window = "09:00-17:00", .priority = 10 },
+        .{ .name = "manual", .video = "c" },
+    };
+    var explained = try pickExplained(std.testing.allocator, &profiles, 12 * 60);
+    defer explained.deinit();
+
+    try std.testing.expectEqual(@as(?usize, 1), explained.chosen);
+    try std.testing.expect(std.mem.indexOf(u8, explained.lines[0], "outranked") != null);
+    try std.testing.expect(std.mem.indexOf(u8, explained.lines[1], "highest priority") != null);
+    try std.testing.expect(std.mem.indexOf(u8, explained.lines[2], "not scheduled") != null);
+}
+
+test "dates and except_dates gate the schedule by calendar day" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "everyday", .video = "a", .window = "00:00-23:59" },
+        .{
+            .name = "holiday",
+            .video = "b",
+            .window = "00:00-23:59",
+            .priority = 10,
+            .dates = &.{ "12-24", "12-25" },
+        },
+    };
+    try std.testing.expectEqual(@as(?usize, 1), pickOn(&profiles, 600, .{ .month = 12, .day = 25 }));
+    try std.testing.expectEqual(@as(?usize, 0), pickOn(&profiles, 600, .{ .month = 6, .day = 1 }));
+    // Without a calendar the date-restricted profile stays out.
+    try std.testing.expectEqual(@as(?usize, 0), pickOn(&profiles, 600, null));
+
+    const skipping = [_]profiles_mod.Profile{
+        .{
+            .name = "weekdays",
+            .video = "c",
+            .window = "00:00-23:59",
+            .except_dates = &.{"1-1"},
+        },
+    };
+    try std.testing.expectEqual(@as(?usize, null), pickOn(&skipping, 600, .{ .month = 1, .day = 1 }));
+    try std.testing.expectEqual(@as(?usize, 0), pickOn(&skipping, 600, .{ .month = 1, .day = 2 }));
+
+    try std.testing.expectError(DateError.InvalidDate, parseDate("13-01"));
+    try std.testing.expectError(DateError.InvalidDate, parseDate("12/24"));
+}
+
+test "a profile's timezone shifts its window evaluation" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "home-night", .video = "a", .window = "20:00-06:00", .timezone = "UTC+8" },
+    };
+    // 13:00 UTC, local clock also 13:00: 21:00 in the home zone, active.
+    const noonish_unix: i64 = 13 * std.time.s_per_hour;
+    try std.testing.expectEqual(
+        @as(?usize, 0),
+        pickZoned(std.testing.allocator, &profiles, noonish_unix, 13 * 60, null, null),
+    );
+    // 10:00 UTC is 18:00 at home: outside the window even though a
+    // plain local pick at 22:00 would have matched.
+    const morning_unix: i64 = 10 * std.time.s_per_hour;
+    try std.testing.expectEqual(
+        @as(?usize, null),
+        pickZoned(std.testing.allocator, &profiles, morning_unix, 22 * 60, null, null),
+    );
+}
+
+test "selection precedence: override, schedule, default, first" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "fallback", .video = "a" },
+        .{ .name = "day", .video = "b", .window = "08:00-20:00" },
+        .{ .name = "night", .video = "c" },
+    };
+    const noon: u16 = 12 * 60;
+
+    const overridden = select(std.testing.allocator, &profiles, "fallback", "night", 0, noon, null, null);
+    try std.testing.expectEqual(@as(?usize, 2), overridden.chosen);
+    try std.testing.expectEqual(Reason.override, overridden.reason);
+
+    const scheduled = select(std.testing.allocator, &profiles, "fallback", null, 0, noon, null, null);
+    try std.testing.expectEqual(@as(?usize, 1), scheduled.chosen);
+    try std.testing.expectEqual(Reason.schedule, scheduled.reason);
+
+    const defaulted = select(std.testing.allocator, &profiles, "night", null, 0, 22 * 60, null, null);
+    try std.testing.expectEqual(@as(?usize, 2), defaulted.chosen);
+    try std.testing.expectEqual(Reason.default, defaulted.reason);
+
+    const first = select(std.testing.allocator, &profiles, null, null, 0, 22 * 60, null, null);
+    try std.testing.expectEqual(@as(?usize, 0), first.chosen);
+    try std.testing.expectEqual(Reason.first, first.reason);
+
+    const empty = select(std.testing.allocator, &.{}, null, null, 0, noon, null, null);
+    try std.testing.expectEqual(Reason.none, empty.reason);
+}
+
+test "an output-specific override outranks the global one" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "day", .video = "a", .window = "08:00-20:00" },
+        .{ .name = "presentation", .video = "b" },
+        .{ .name = "night", .video = "c" },
+    };
+    const noon: u16 = 12 * 60;
+
+    const hdmi = selectForOutput(
+        std.testing.allocator,
+        &profiles,
+        null,
+        "presentation",
+        "night",
+        0,
+        noon,
+        null,
+        null,
+    );
+    try std.testing.expectEqual(@as(?usize, 1), hdmi.chosen);
+    try std.testing.expectEqual(Reason.override, hdmi.reason);
+
+    // An output without its own override follows the global decision.
+    const other = selectForOutput(std.testing.allocator, &profiles, null, null, "night", 0, noon, null, null);
+    try std.testing.expectEqual(@as(?usize, 2), other.chosen);
+    try std.testing.expectEqual(Reason.override, other.reason);
+
+    const plain = selectForOutput(std.testing.allocator, &profiles, null, null, null, 0, noon, null, null);
+    try std.testing.expectEqual(@as(?usize, 0), plain.chosen);
+    try std.testing.expectEqual(Reason.schedule, plain.reason);
+}
+
+test "condition-gated profiles need a provider that vouches for them" {
+    const profiles = [_]profiles_mod.Profile{
+        .{ .name = "plain", .video = "a", .window = "00:00-23:59" },
+        .{
+            .name = "vpn-work",
+            .video = "b",
+            .window = "00:00-23:59",
+            .priority = 10,
+            .conditions = &.{"vpn"},
+        },
+        .{
+            .name = "focused",
+            .video = "c",
+            .window = "00:00-23:59",
+            .priority = 20,
+            .conditions = &.{ "vpn", "do-not-disturb" },
+        },
+    };
+    const noon: u16 = 12 * 60;
+
+    // No provider at all: only the unconditional profile competes.
+    try std.testing.expectEqual(
+        @as(?usize, 0),
+        pickZoned(std.testing.allocator, &profiles, 0, noon, null, null),
+    );
+
+    const Probe = struct {
+        fn vpnOnly(_: ?*anyopaque, condition: []const u8) bool {
+            return std.mem.eql(u8, condition, "vpn");
+        }
+        fn everything(_: ?*anyopaque, _: []const u8) bool {
+            return true;
+        }
+    };
+
+    // "focused" also wants do-not-disturb, so the vpn-only provider
+    // admits just "vpn-work" despite the lower priority.
+    try std.testing.expectEqual(
+        @as(?usize, 1),
+        pickZoned(std.testing.allocator, &profiles, 0, noon, null, .{ .holdsFn = Probe.vpnOnly }),
+    );
+    try std.testing.expectEqual(
+        @as(?usize, 2),
+        pickZoned(std.testing.allocator, &profiles, 0, noon, null, .{ .holdsFn = Probe.everything }),
+    );
+}
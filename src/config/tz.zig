@@ -0,0 +1,235 @@
+//! Minimal timezone offsets.
+//!
+//! Schedule windows normally use the system-local clock, which is wrong
+//! for a laptop that roams: the "night" profile should follow the home
+//! zone, not wherever the machine woke up. A profile can therefore name
+//! a timezone, either a fixed offset ("UTC+8", "+05:30") or an IANA
+//! name ("Asia/Shanghai") resolved against the system tz database. TZif
+//! v2+ files are read through their 64-bit section (the v1 block they
+//! carry for compatibility stops at 2038); plain v1 files fall back to
+//! the 32-bit block. Either way only the UTC offset in force at a given
+//! moment is extracted, which is all a wallpaper needs.
+
+const std = @import("std");
+
+pub const default_zoneinfo_root = "/usr/share/zoneinfo";
+
+pub const TzError = error{ UnknownTimezone, InvalidTimezone };
+
+/// UTC offset in seconds for `name` at `now_unix`, using the system tz
+/// database for IANA names.
+pub fn offsetSeconds(allocator: std.mem.Allocator, name: []const u8, now_unix: i64) !i32 {
+    return offsetSecondsIn(allocator, default_zoneinfo_root, name, now_unix);
+}
+
+/// Same, against an explicit zoneinfo root (for tests).
+pub fn offsetSecondsIn(
+    allocator: std.mem.Allocator,
+    root: []const u8,
+    name: []const u8,
+    now_unix: i64,
+) !i32 {
+    if (parseFixed(name)) |offset| return offset;
+    // Reject anything that could escape the database directory.
+    if (name.len == 0 or name[0] == '/' or std.mem.indexOf(u8, name, "..") != null)
+        return TzError.UnknownTimezone;
+
+    const path = try std.fs.path.join(allocator, &.{ root, name });
+    defer allocator.free(path);
+    const data = std.fs.cwd().readFileAlloc(allocator, path, 1024 * 1024) catch
+        return TzError.UnknownTimezone;
+    defer allocator.free(data);
+    return tzifOffset(data, now_unix);
+}
+
+/// Parses "UTC", "UTC+8", "+08:00", "-0530" style fixed offsets.
+fn parseFixed(name: []const u8) ?i32 {
+    var rest = name;
+    if (std.ascii.startsWithIgnoreCase(rest, "UTC")) rest = rest[3..];
+    if (rest.len == 0) {
+        return if (rest.ptr != name.ptr) 0 else null;
+    }
+    const negative = rest[0] == '-';
+    if (rest[0] != '+' and rest[0] != '-') return null;
+    rest = rest[1..];
+
+    var hours_text = rest;
+    var minutes_text: []const u8 = "";
+    if (std.mem.indexOfScalar(u8, rest, ':')) |colon| {
+        hours_text = rest[0..colon];
+        minutes_text = rest[colon + 1 ..];
+    } else if (rest.len == 4) {
+        hours_text = rest[0..2];
+        minutes_text = rest[2..];
+    }
+    const hours = std.fmt.parseInt(u32, hours_text, 10) catch return null;
+    const minutes = if (minutes_text.len > 0)
+        std.fmt.parseInt(u32, minutes_text, 10) catch return null
+    else
+        0;
+    if (hours > 14 or minutes > 59) return null;
+    const total: i32 = @intCast(hours * 3600 + minutes * 60);
+    return if (negative) -total else total;
+}
+
+/// The six record counts every TZif header carries at offsets 20..44.
+const TzifCounts = struct {
+    isutcnt: u32,
+    isstdcnt: u32,
+    leapcnt: u32,
+    timecnt: u32,
+    typecnt: u32,
+    charcnt: u32,
+
+    fn read(header: *const [44]u8) TzifCounts {
+        return .{
+            .isutcnt = std.mem.readInt(u32, header[20..24], .big),
+            .isstdcnt = std.mem.readInt(u32, header[24..28], .big),
+            .leapcnt = std.mem.readInt(u32, header[28..32], .big),
+            .timecnt = std.mem.readInt(u32, header[32..36], .big),
+            .typecnt = std.mem.readInt(u32, header[36..40], .big),
+            .charcnt = std.mem.readInt(u32, header[40..44], .big),
+        };
+    }
+
+    /// Bytes of the data block that follows the header, for transition
+    /// times (and leap-second occurrences) `time_size` bytes wide.
+    fn dataSize(self: TzifCounts, time_size: usize) usize {
+        return @as(usize, self.timecnt) * (time_size + 1) +
+            @as(usize, self.typecnt) * 6 +
+            self.charcnt +
+            @as(usize, self.leapcnt) * (time_size + 4) +
+            self.isstdcnt + self.isutcnt;
+    }
+};
+
+/// Offset in force at `now_unix` per a TZif blob. Version 2+ files carry
+/// a second header and a 64-bit data block after the legacy 32-bit one;
+/// read that so transitions past 2038 (and zones whose history predates
+/// 1901) resolve correctly. Plain v1 files use the 32-bit block.
+fn tzifOffset(data: []const u8, now_unix: i64) TzError!i32 {
+    if (data.len < 44 or !std.mem.eql(u8, data[0..4], "TZif")) return TzError.InvalidTimezone;
+    if (data[4] >= '2') {
+        const v1_counts = TzifCounts.read(data[0..44]);
+        const wide_at = 44 + v1_counts.dataSize(4);
+        if (data.len < wide_at + 44 or !std.mem.eql(u8, data[wide_at..][0..4], "TZif"))
+            return TzError.InvalidTimezone;
+        return blockOffset(data[wide_at..], now_unix, 8);
+    }
+    return blockOffset(data, now_unix, 4);
+}
+
+/// Resolves `now_unix` within one header-plus-data block, with transition
+/// times `time_size` (4 or 8) bytes wide: the type of the last transition
+/// at or before now, or the first type when none has happened yet.
+fn blockOffset(block: []const u8, now_unix: i64, comptime time_size: usize) TzError!i32 {
+    const counts = TzifCounts.read(block[0..44]);
+    if (counts.typecnt == 0) return TzError.InvalidTimezone;
+
+    const transitions_at: usize = 44;
+    const indices_at = transitions_at + time_size * @as(usize, counts.timecnt);
+    const types_at = indices_at + counts.timecnt;
+    if (block.len < types_at + 6 * @as(usize, counts.typecnt)) return TzError.InvalidTimezone;
+
+    var type_index: usize = 0;
+    for (0..counts.timecnt) |i| {
+        const raw = block[transitions_at + time_size * i ..][0..time_size];
+        const when: i64 = std.mem.readInt(
+            if (time_size == 8) i64 else i32,
+            raw,
+            .big,
+        );
+        if (now_unix < when) break;
+        type_index = block[indices_at + i];
+    }
+    if (type_index >= counts.typecnt) return TzError.InvalidTimezone;
+    const raw = block[types_at + 6 * type_index ..][0..4];
+    return std.mem.readInt(i32, raw, .big);
+}
+
+test "fixed offsets parse in both spellings" {
+    try std.testing.expectEqual(@as(?i32, 0), parseFixed("UTC"));
+    try std.testing.expectEqual(@as(?i32, 8 * 3600), parseFixed("UTC+8"));
+    try std.testing.expectEqual(@as(?i32, 5 * 3600 + 1800), parseFixed("+05:30"));
+    try std.testing.expectEqual(@as(?i32, -(5 * 3600 + 1800)), parseFixed("-0530"));
+    try std.testing.expectEqual(@as(?i32, null), parseFixed("Asia/Shanghai"));
+    try std.testing.expectEqual(@as(?i32, null), parseFixed("UTC+99"));
+}
+
+test "a TZif file yields the offset in force at the query time" {
+    // One transition at t=1000 from offset 3600 (type 0) to 7200 (type 1).
+    var data: std.ArrayList(u8) = .empty;
+    defer data.deinit(std.testing.allocator);
+    try data.appendSlice(std.testing.allocator, "TZif");
+    try data.appendNTimes(std.testing.allocator, 0, 16); // version + reserved
+    for ([_]u32{ 0, 0, 0, 1, 2, 0 }) |count| { // isut isstd leap time type char
+        var raw: [4]u8 = undefined;
+        std.mem.writeInt(u32, &raw, count, .big);
+        try data.appendSlice(std.testing.allocator, &raw);
+    }
+    var when: [4]u8 = undefined;
+    std.mem.writeInt(i32, &when, 1000, .big);
+    try data.appendSlice(std.testing.allocator, &when);
+    try data.append(std.testing.allocator, 1); // type index after transition
+    for ([_]i32{ 3600, 7200 }) |offset| {
+        var raw: [4]u8 = undefined;
+        std.mem.writeInt(i32, &raw, offset, .big);
+        try data.appendSlice(std.testing.allocator, &raw);
+        try data.appendSlice(std.testing.allocator, &.{ 0, 0 }); // isdst, desigidx
+    }
+
+    try std.testing.expectEqual(@as(i32, 3600), try tzifOffset(data.items, 500));
+    try std.testing.expectEqual(@as(i32, 7200), try tzifOffset(data.items, 2000));
+}
+
+test "a TZif v2 file resolves through the 64-bit block" {
+    var data: std.ArrayList(u8) = .empty;
+    defer data.deinit(std.testing.allocator);
+
+    // Legacy v1 section: no transitions, one type, skipped entirely.
+    try data.appendSlice(std.testing.allocator, "TZif2");
+    try data.appendNTimes(std.testing.allocator, 0, 15); // reserved
+    for ([_]u32{ 0, 0, 0, 0, 1, 0 }) |count| { // isut isstd leap time type char
+        var raw: [4]u8 = undefined;
+        std.mem.writeInt(u32, &raw, count, .big);
+        try data.appendSlice(std.testing.allocator, &raw);
+    }
+    try data.appendNTimes(std.testing.allocator, 0, 6); // one v1 type record
+
+    // 64-bit section: to 7200 at t=1000, back to 3600 past the i32 range.
+    const back_at: i64 = 1 << 32;
+    try data.appendSlice(std.testing.allocator, "TZif2");
+    try data.appendNTimes(std.testing.allocator, 0, 15);
+    for ([_]u32{ 0, 0, 0, 2, 2, 0 }) |count| {
+        var raw: [4]u8 = undefined;
+        std.mem.writeInt(u32, &raw, count, .big);
+        try data.appendSlice(std.testing.allocator, &raw);
+    }
+    for ([_]i64{ 1000, back_at }) |when| {
+        var raw: [8]u8 = undefined;
+        std.mem.writeInt(i64, &raw, when, .big);
+        try data.appendSlice(std.testing.allocator, &raw);
+    }
+    try data.appendSlice(std.testing.allocator, &.{ 1, 0 }); // type indices
+    for ([_]i32{ 3600, 7200 }) |offset| {
+        var raw: [4]u8 = undefined;
+        std.mem.writeInt(i32, &raw, offset, .big);
+        try data.appendSlice(std.testing.allocator, &raw);
+        try data.appendSlice(std.testing.allocator, &.{ 0, 0 }); // isdst, desigidx
+    }
+
+    try std.testing.expectEqual(@as(i32, 3600), try tzifOffset(data.items, 500));
+    try std.testing.expectEqual(@as(i32, 7200), try tzifOffset(data.items, 2000));
+    try std.testing.expectEqual(@as(i32, 3600), try tzifOffset(data.items, back_at + 1));
+}
+
+test "unknown names and escapes are rejected" {
+    try std.testing.expectError(
+        TzError.UnknownTimezone,
+        offsetSecondsIn(std.testing.allocator, "/nonexistent", "No/Such_Zone", 0),
+    );
+    try std.testing.expectError(
+        TzError.UnknownTimezone,
+        offsetSecondsIn(std.testing.allocator, "/nonexistent", "../etc/passwd", 0),
+    );
+}
@@ -0,0 +1,434 @@
+//! Minimal YAML reader for profile documents.
+//!
+//! Parses the block-style subset profile configs use — mappings nested by
+//! indentation, `-` sequences, plain/quoted scalars, flow `[...]`/`{...}`
+//! collections, comments — into a `std.json.Value`, so the document model
+//! reuses the JSON field mapping (`std.json.parseFromValueLeaky`) instead
+//! of growing a second reflection layer. Anchors, aliases, tags, block
+//! scalars, and multi-document streams are rejected; the home-manager and
+//! script generators that emit profile YAML produce none of them.
+//!
+//! Plain scalars reference `source` directly instead of copying; callers
+//! keep the source alive as long as the value (the config arena owns
+//! both).
+
+const std = @import("std");
+
+pub const Error = error{Invalid} || std.mem.Allocator.Error;
+
+/// One significant line; blanks, full-line comments, and document markers
+/// are dropped before parsing (inline comments are cut per value).
+const Line = struct {
+    indent: usize,
+    text: []const u8,
+};
+
+/// Parses one YAML document. All allocations come from `allocator`;
+/// callers hand in an arena, matching how the config loader owns parsed
+/// documents.
+pub fn parse(allocator: std.mem.Allocator, source: []const u8) Error!std.json.Value {
+    var lines: std.ArrayList(Line) = .empty;
+    defer lines.deinit(allocator);
+
+    var iterator = std.mem.splitScalar(u8, source, '\n');
+    while (iterator.next()) |raw| {
+        const line = std.mem.trimRight(u8, raw, "\r");
+        var indent: usize = 0;
+        while (indent < line.len and line[indent] == ' ') indent += 1;
+        // Tabs have no defined width in YAML indentation; reject instead
+        // of guessing.
+        if (indent < line.len and line[indent] == '\t') return Error.Invalid;
+        const text = line[indent..];
+        if (text.len == 0 or text[0] == '#') continue;
+        if (std.mem.eql(u8, text, "---")) continue;
+        if (std.mem.eql(u8, text, "...")) break;
+        try lines.append(allocator, .{ .indent = indent, .text = text });
+    }
+
+    if (lines.items.len == 0) {
+        return .{ .object = std.json.ObjectMap.init(allocator) };
+    }
+
+    var parser: Parser = .{ .allocator = allocator, .lines = lines.items };
+    const root = try parser.parseNode(lines.items[0].indent);
+    // Anything left over sits outside the root's indentation — a second
+    // document or a stray outdent.
+    if (parser.index != parser.lines.len) return Error.Invalid;
+    return root;
+}
+
+const Parser = struct {
+    allocator: std.mem.Allocator,
+    lines: []Line,
+    index: usize = 0,
+
+    fn parseNode(self: *Parser, indent: usize) Error!std.json.Value {
+        const line = self.lines[self.index];
+        if (isSequenceItem(line.text)) return self.parseSequence(indent);
+        return self.parseMapping(indent);
+    }
+
+    fn parseMapping(self: *Parser, indent: usize) Error!std.json.Value {
+        var map = std.json.ObjectMap.init(self.allocator);
+        while (self.index < self.lines.len) {
+            const line = self.lines[self.index];
+            if (line.indent < indent) break;
+            if (line.indent > indent or isSequenceItem(line.text)) return Error.Invalid;
+
+            const colon = keyColon(line.text) orelse return Error.Invalid;
+            const key = std.mem.trim(u8, line.text[0..colon], " ");
+            if (key.len == 0) return Error.Invalid;
+            const rest = stripComment(std.mem.trim(u8, line.text[colon + 1 ..], " "));
+            self.index += 1;
+
+            const value = if (rest.len > 0)
+                try self.parseScalarText(rest)
+            else if (self.index < self.lines.len and self.lines[self.index].indent > indent)
+                try self.parseNode(self.lines[self.index].indent)
+            else if (self.index < self.lines.len and
+                self.lines[self.index].indent == indent and
+                isSequenceItem(self.lines[self.index].text))
+                // YAML lets a block sequence sit at its key's own column.
+                try self.parseSequence(indent)
+            else
+                std.json.Value.null;
+            try map.put(key, value);
+        }
+        return .{ .object = map };
+    }
+
+    fn parseSequence(self: *Parser, indent: usize) Error!std.json.Value {
+        var list = std.json.Array.init(self.allocator);
+        while (self.index < self.lines.len) {
+            const line = self.lines[self.index];
+            if (line.indent != indent or !isSequenceItem(line.text)) break;
+
+            var rest_start: usize = 1;
+            while (rest_start < line.text.len and line.text[rest_start] == ' ') rest_start += 1;
+            const rest = stripComment(std.mem.trimRight(u8, line.text[rest_start..], " "));
+
+            if (rest.len == 0) {
+                // A bare dash: the item is the indented block below it.
+                self.index += 1;
+                if (self.index < self.lines.len and self.lines[self.index].indent > indent) {
+                    try list.append(try self.parseNode(self.lines[self.index].indent));
+                } else {
+                    try list.append(.null);
+                }
+                continue;
+            }
+
+            if (keyColon(rest) != null) {
+                // `- key: value` starts a mapping whose first entry sits on
+                // the dash line; re-enter at the key's own column so the
+                // item's remaining keys line up with it.
+                const virtual = line.indent + rest_start;
+                self.lines[self.index] = .{ .indent = virtual, .text = rest };
+                try list.append(try self.parseMapping(virtual));
+            } else {
+                self.index += 1;
+                try list.append(try self.parseScalarText(rest));
+            }
+        }
+        return .{ .array = list };
+    }
+
+    /// A trimmed, comment-free scalar or flow collection.
+    fn parseScalarText(self: *Parser, raw: []const u8) Error!std.json.Value {
+        return switch (raw[0]) {
+            '"', '\'' => {
+                var end: usize = 0;
+                const text = try self.parseQuoted(raw, &end);
+                if (std.mem.trim(u8, raw[end..], " ").len != 0) return Error.Invalid;
+                return .{ .string = text };
+            },
+            '[' => self.parseFlowSequence(raw),
+            '{' => self.parseFlowMapping(raw),
+            '&', '*', '!', '|', '>' => Error.Invalid,
+            else => parsePlain(raw),
+        };
+    }
+
+    /// Quoted string starting at `raw[0]`; sets `end` past the closing
+    /// quote. Double quotes support the escapes the configs need, single
+    /// quotes only the doubled-quote escape, per YAML.
+    fn parseQuoted(self: *Parser, raw: []const u8, end: *usize) Error![]const u8 {
+        const quote = raw[0];
+        var text: std.ArrayList(u8) = .empty;
+        defer text.deinit(self.allocator);
+        var i: usize = 1;
+        while (i < raw.len) : (i += 1) {
+            const char = raw[i];
+            if (char == quote) {
+                if (quote == '\'' and i + 1 < raw.len and raw[i + 1] == '\'') {
+                    try text.append(self.allocator, '\'');
+                    i += 1;
+                    continue;
+                }
+                end.* = i + 1;
+                return text.toOwnedSlice(self.allocator);
+            }
+            if (quote == '"' and char == '\\') {
+                i += 1;
+                if (i >= raw.len) return Error.Invalid;
+                try text.append(self.allocator, switch (raw[i]) {
+                    '"' => '"',
+                    '\\' => '\\',
+                    'n' => '\n',
+                    't' => '\t',
+                    else => return Error.Invalid,
+                });
+                continue;
+            }
+            try text.append(self.allocator, char);
+        }
+        return Error.Invalid;
+    }
+
+    fn parseFlowSequence(self: *Parser, raw: []const u8) Error!std.json.Value {
+        var list = std.json.Array.init(self.allocator);
+        var elements = try flowElements(raw);
+        while (elements.next()) |element| {
+            try list.append(try self.parseScalarText(element));
+        }
+        if (elements.failed) return Error.Invalid;
+        return .{ .array = list };
+    }
+
+    fn parseFlowMapping(self: *Parser, raw: []const u8) Error!std.json.Value {
+        var map = std.json.ObjectMap.init(self.allocator);
+        var elements = try flowElements(raw);
+        while (elements.next()) |element| {
+            const colon = keyColon(element) orelse return Error.Invalid;
+            const key = std.mem.trim(u8, element[0..colon], " ");
+            const value = std.mem.trim(u8, element[colon + 1 ..], " ");
+            if (key.len == 0 or value.len == 0) return Error.Invalid;
+            try map.put(key, try self.parseScalarText(value));
+        }
+        if (elements.failed) return Error.Invalid;
+        return .{ .object = map };
+    }
+};
+
+/// Splits the elements of a one-line flow collection body, respecting
+/// nested brackets and quotes. On a malformed element the iterator stops
+/// and raises `failed`, which callers check after draining it.
+const FlowElements = struct {
+    body: []const u8,
+    index: usize = 0,
+    done: bool = false,
+    failed: bool = false,
+
+    fn next(self: *FlowElements) ?[]const u8 {
+        if (self.done or self.failed) return null;
+        const start = self.index;
+        var depth: usize = 0;
+        var quote: u8 = 0;
+        var i = start;
+        while (i < self.body.len) : (i += 1) {
+            const char = self.body[i];
+            if (quote != 0) {
+                if (char == '\\' and quote == '"') i += 1;
+                if (char == quote) quote = 0;
+                continue;
+            }
+            switch (char) {
+                '"', '\'' => quote = char,
+                '[', '{' => depth += 1,
+                ']', '}' => {
+                    if (depth == 0) return self.fail();
+                    depth -= 1;
+                },
+                ',' => if (depth == 0) {
+                    self.index = i + 1;
+                    const element = std.mem.trim(u8, self.body[start..i], " ");
+                    if (element.len == 0) return self.fail();
+                    return element;
+                },
+                else => {},
+            }
+        }
+        if (depth != 0 or quote != 0) return self.fail();
+        self.done = true;
+        const last = std.mem.trim(u8, self.body[start..], " ");
+        // A trailing comma leaves an empty final element.
+        if (last.len == 0) return self.fail();
+        return last;
+    }
+
+    fn fail(self: *FlowElements) ?[]const u8 {
+        self.failed = true;
+        return null;
+    }
+};
+
+/// Iterator over the elements inside `[...]` or `{...}`; errors when the
+/// closer is missing or followed by anything but a comment.
+fn flowElements(raw: []const u8) Error!FlowElements {
+    const closer: u8 = if (raw[0] == '[') ']' else '}';
+    var depth: usize = 0;
+    var quote: u8 = 0;
+    var i: usize = 0;
+    while (i < raw.len) : (i += 1) {
+        const char = raw[i];
+        if (quote != 0) {
+            if (char == '\\' and quote == '"') i += 1;
+            if (char == quote) quote = 0;
+            continue;
+        }
+        switch (char) {
+            '"', '\'' => quote = char,
+            '[', '{' => depth += 1,
+            ']', '}' => {
+                depth -= 1;
+                if (depth == 0) {
+                    if (char != closer) return Error.Invalid;
+                    if (std.mem.trim(u8, raw[i + 1 ..], " ").len != 0) return Error.Invalid;
+                    const body = std.mem.trim(u8, raw[1..i], " ");
+                    return .{ .body = body, .done = body.len == 0 };
+                }
+            },
+            else => {},
+        }
+    }
+    return Error.Invalid;
+}
+
+/// True when the line is a block sequence entry (`-` or `- ...`).
+fn isSequenceItem(text: []const u8) bool {
+    return text[0] == '-' and (text.len == 1 or text[1] == ' ');
+}
+
+/// Index of the colon separating a mapping key from its value: the first
+/// `:` followed by a space or the end of the text.
+fn keyColon(text: []const u8) ?usize {
+    var i: usize = 0;
+    var quote: u8 = 0;
+    while (i < text.len) : (i += 1) {
+        const char = text[i];
+        if (quote != 0) {
+            if (char == quote) quote = 0;
+            continue;
+        }
+        switch (char) {
+            '"', '\'' => quote = char,
+            ':' => if (i + 1 == text.len or text[i + 1] == ' ') return i,
+            else => {},
+        }
+    }
+    return null;
+}
+
+/// Cuts an end-of-line comment: a `#` at the start of the value or
+/// preceded by a space, outside quotes.
+fn stripComment(text: []const u8) []const u8 {
+    var quote: u8 = 0;
+    for (text, 0..) |char, i| {
+        if (quote != 0) {
+            if (char == quote) quote = 0;
+            continue;
+        }
+        switch (char) {
+            '"', '\'' => quote = char,
+            '#' => if (i == 0 or text[i - 1] == ' ') {
+                return std.mem.trimRight(u8, text[0..i], " ");
+            },
+            else => {},
+        }
+    }
+    return text;
+}
+
+/// Plain (unquoted) scalar: null/bool/number, else the text itself.
+fn parsePlain(raw: []const u8) std.json.Value {
+    if (std.mem.eql(u8, raw, "~") or std.mem.eql(u8, raw, "null")) return .null;
+    if (std.mem.eql(u8, raw, "true")) return .{ .bool = true };
+    if (std.mem.eql(u8, raw, "false")) return .{ .bool = false };
+    if (std.fmt.parseInt(i64, raw, 10)) |integer| {
+        return .{ .integer = integer };
+    } else |_| {}
+    if (std.fmt.parseFloat(f64, raw)) |float| {
+        // parseFloat accepts words like "nan"; only treat it as a number
+        // when it looks like one.
+        if (raw[0] == '-' or raw[0] == '+' or std.ascii.isDigit(raw[0])) {
+            return .{ .float = float };
+        }
+    } else |_| {}
+    return .{ .string = raw };
+}
+
+test "block mappings, sequences, and scalars" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+
+    const value = try parse(arena.allocator(),
+        \\# profiles
+        \\version: 2
+        \\default_profile: day
+        \\include:
+        \\- extra.yaml
+        \\transition:
+        \\  kind: fade
+        \\  duration_ms: 250
+        \\profiles:
+        \\  - name: day
+        \\    video: day.mp4   # daytime clip
+        \\    volume: 0.5
+        \\    mute: false
+        \\  - name: night
+        \\    videos:
+        \\      - "a.mp4"
+        \\      - b.mp4
+        \\
+    );
+
+    const root = value.object;
+    try std.testing.expectEqual(@as(i64, 2), root.get("version").?.integer);
+    try std.testing.expectEqualStrings("day", root.get("default_profile").?.string);
+    try std.testing.expectEqualStrings("extra.yaml", root.get("include").?.array.items[0].string);
+    try std.testing.expectEqualStrings("fade", root.get("transition").?.object.get("kind").?.string);
+
+    const profiles = root.get("profiles").?.array.items;
+    try std.testing.expectEqual(@as(usize, 2), profiles.len);
+    try std.testing.expectEqualStrings("day.mp4", profiles[0].object.get("video").?.string);
+    try std.testing.expectEqual(@as(f64, 0.5), profiles[0].object.get("volume").?.float);
+    try std.testing.expectEqual(false, profiles[0].object.get("mute").?.bool);
+    const videos = profiles[1].object.get("videos").?.array.items;
+    try std.testing.expectEqualStrings("a.mp4", videos[0].string);
+    try std.testing.expectEqualStrings("b.mp4", videos[1].string);
+}
+
+test "flow collections and quoting" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+
+    const value = try parse(arena.allocator(),
+        \\outputs: [DP-1, "HDMI-A 1"]
+        \\transition: { kind: cut, duration_ms: 0 }
+        \\window: '08:00-18:00'
+        \\note: "a \"quoted\" word"
+        \\empty: []
+        \\
+    );
+
+    const root = value.object;
+    const outputs = root.get("outputs").?.array.items;
+    try std.testing.expectEqualStrings("DP-1", outputs[0].string);
+    try std.testing.expectEqualStrings("HDMI-A 1", outputs[1].string);
+    try std.testing.expectEqualStrings("cut", root.get("transition").?.object.get("kind").?.string);
+    try std.testing.expectEqual(@as(i64, 0), root.get("transition").?.object.get("duration_ms").?.integer);
+    try std.testing.expectEqualStrings("08:00-18:00", root.get("window").?.string);
+    try std.testing.expectEqualStrings("a \"quoted\" word", root.get("note").?.string);
+    try std.testing.expectEqual(@as(usize, 0), root.get("empty").?.array.items.len);
+}
+
+test "unsupported constructs are rejected, not misread" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+
+    try std.testing.expectError(Error.Invalid, parse(allocator, "key: &anchor x\n"));
+    try std.testing.expectError(Error.Invalid, parse(allocator, "key: |\n  block\n"));
+    try std.testing.expectError(Error.Invalid, parse(allocator, "\tkey: value\n"));
+    try std.testing.expectError(Error.Invalid, parse(allocator, "key: [a, b\n"));
+}
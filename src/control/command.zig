@@ -0,0 +1,75 @@
+//! Control commands accepted by a play process.
+//!
+//! Commands arrive as newline-delimited JSON objects on the control socket,
+//! e.g. `{"cmd":"seek","seconds":12.5}` or `{"cmd":"pause"}`.
+
+const std = @import("std");
+
+pub const Command = union(enum) {
+    pause,
+    resume_playback,
+    /// Absolute position in seconds.
+    seek: f64,
+    /// Playback rate multiplier (1.0 = normal).
+    set_rate: f64,
+    /// Switch to a different video; owned by the command, freed by the consumer.
+    set_video: []u8,
+    /// Dump a pipeline DOT graph (needs --dump-dot on the player).
+    dump_dot,
+    quit,
+
+    pub fn deinit(self: Command, allocator: std.mem.Allocator) void {
+        switch (self) {
+            .set_video => |video| allocator.free(video),
+            else => {},
+        }
+    }
+};
+
+pub const ParseError = error{MalformedCommand} || std.mem.Allocator.Error;
+
+/// Parses a single JSON command line. Strings inside the result are
+/// duplicated with `allocator` so they outlive the input buffer.
+pub fn parse(allocator: std.mem.Allocator, line: []const u8) ParseError!Command {
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+
+    const parsed = std.json.parseFromSliceLeaky(std.json.Value, arena.allocator(), line, .{}) catch
+        return ParseError.MalformedCommand;
+    const root = switch (parsed) {
+        .object => |object| object,
+        else => return ParseError.MalformedCommand,
+    };
+
+    const cmd = switch (root.get("cmd") orelse return ParseError.MalformedCommand) {
+        .string => |value| value,
+        else => return ParseError.MalformedCommand,
+    };
+
+    if (std.mem.eql(u8, cmd, "pause")) return .pause;
+    if (std.mem.eql(u8, cmd, "resume")) return .resume_playback;
+    if (std.mem.eql(u8, cmd, "quit")) return .quit;
+    if (std.mem.eql(u8, cmd, "dump-dot")) return .dump_dot;
+    if (std.mem.eql(u8, cmd, "seek")) {
+        return .{ .seek = getNumber(root, "seconds") orelse return ParseError.MalformedCommand };
+    }
+    if (std.mem.eql(u8, cmd, "set-rate")) {
+        return .{ .set_rate = getNumber(root, "rate") orelse return ParseError.MalformedCommand };
+    }
+    if (std.mem.eql(u8, cmd, "set-video")) {
+        const video = switch (root.get("video") orelse return ParseError.MalformedCommand) {
+            .string => |value| value,
+            else => return ParseError.MalformedCommand,
+        };
+        return .{ .set_video = try allocator.dupe(u8, video) };
+    }
+    return ParseError.MalformedCommand;
+}
+
+fn getNumber(object: std.json.ObjectMap, key: []const u8) ?f64 {
+    return switch (object.get(key) orelse return null) {
+        .float => |value| value,
+        .integer => |value| @floatFromInt(value),
+        else => null,
+    };
+}
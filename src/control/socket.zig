@@ -0,0 +1,120 @@
+//! Per-player control socket.
+//!
+//! Each play process can listen on a Unix socket (path derived from its
+//! target name) for JSON commands, so scripts can pause, seek, or swap the
+//! video without restarting the pipeline. An accept thread reads commands
+//! into a queue that the playback loop drains once per iteration.
+
+const std = @import("std");
+const command_mod = @import("command.zig");
+
+pub const Command = command_mod.Command;
+
+pub const Server = struct {
+    allocator: std.mem.Allocator,
+    path: []u8,
+    listener: std.net.Server,
+    thread: std.Thread,
+
+    mutex: std.Thread.Mutex = .{},
+    queue: std.ArrayList(Command) = .empty,
+    running: std.atomic.Value(bool) = std.atomic.Value(bool).init(true),
+
+    /// Socket path for a playback target, preferring XDG_RUNTIME_DIR.
+    pub fn socketPath(allocator: std.mem.Allocator, target: []const u8) ![]u8 {
+        const runtime_dir = std.posix.getenv("XDG_RUNTIME_DIR") orelse "/tmp";
+        return std.fmt.allocPrint(allocator, "{s}/waystream/control/{s}.sock", .{ runtime_dir, target });
+    }
+
+    pub fn start(allocator: std.mem.Allocator, target: []const u8) !*Server {
+        const path = try socketPath(allocator, target);
+        errdefer allocator.free(path);
+
+        if (std.fs.path.dirname(path)) |dir| {
+            std.fs.cwd().makePath(dir) catch {};
+        }
+        // A previous run may have left the socket behind.
+        std.fs.cwd().deleteFile(path) catch {};
+
+        const address = try std.net.Address.initUnix(path);
+        const listener = try address.listen(.{});
+
+        const server = try allocator.create(Server);
+        errdefer allocator.destroy(server);
+        server.* = .{
+            .allocator = allocator,
+            .path = path,
+            .listener = listener,
+            .thread = undefined,
+        };
+        server.thread = try std.Thread.spawn(.{}, acceptLoop, .{server});
+        return server;
+    }
+
+    pub fn stop(self: *Server) void {
+        self.running.store(false, .release);
+        // Closing the listener unblocks accept() in the thread.
+        self.listener.deinit();
+        self.thread.join();
+
+        self.mutex.lock();
+        for (self.queue.items) |cmd| cmd.deinit(self.allocator);
+        self.queue.deinit(self.allocator);
+        self.mutex.unlock();
+
+        std.fs.cwd().deleteFile(self.path) catch {};
+        const allocator = self.allocator;
+        allocator.free(self.path);
+        allocator.destroy(self);
+    }
+
+    /// Takes the next queued command, if any. The caller owns it.
+    pub fn poll(self: *Server) ?Command {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+        if (self.queue.items.len == 0) return null;
+        return self.queue.orderedRemove(0);
+    }
+
+    fn acceptLoop(self: *Server) void {
+        while (self.running.load(.acquire)) {
+            const connection = self.listener.accept() catch return;
+            self.handleConnection(connection);
+        }
+    }
+
+    fn handleConnection(self: *Server, connection: std.net.Server.Connection) void {
+        defer connection.stream.close();
+
+        var buffer: [4096]u8 = undefined;
+        var used: usize = 0;
+        while (true) {
+            const n = connection.stream.read(buffer[used..]) catch return;
+            if (n == 0) break;
+            used += n;
+
+            var start: usize = 0;
+            while (std.mem.indexOfScalarPos(u8, buffer[0..used], start, '\n')) |newline| {
+                self.enqueueLine(buffer[start..newline]);
+                start = newline + 1;
+            }
+            std.mem.copyForwards(u8, &buffer, buffer[start..used]);
+            used -= start;
+            if (used == buffer.len) return; // Oversized line; drop the client.
+        }
+        if (used > 0) self.enqueueLine(buffer[0..used]);
+    }
+
+    fn enqueueLine(self: *Server, line: []const u8) void {
+        const trimmed = std.mem.trim(u8, line, " \t\r");
+        if (trimmed.len == 0) return;
+
+        const cmd = command_mod.parse(self.allocator, trimmed) catch {
+            std.log.warn("ignoring malformed control command", .{});
+            return;
+        };
+        self.mutex.lock();
+        defer self.mutex.unlock();
+        self.queue.append(self.allocator, cmd) catch cmd.deinit(self.allocator);
+    }
+};
@@ -0,0 +1,95 @@
+//! Environment capability report.
+//!
+//! One structured snapshot of everything playback quality depends on —
+//! decoder availability, GPU postprocessing, Wayland protocol versions,
+//! dma-heap access — so front-ends and the `doctor` command can show
+//! actionable information instead of scattered log hints.
+
+const std = @import("std");
+const c = @import("gst/c.zig");
+const decoder = @import("gst/decoder.zig");
+const pipeline_mod = @import("playback/pipeline.zig");
+const wl_globals = @import("wayland/globals.zig");
+const gbm = @import("drm/gbm.zig");
+
+pub const DecoderStatus = struct {
+    name: []const u8,
+    available: bool,
+    rank: u32,
+};
+
+pub const Report = struct {
+    gst_major: c_uint,
+    gst_minor: c_uint,
+    gst_micro: c_uint,
+    /// One entry per hardware decoder candidate, in preference order.
+    decoders: [decoder.default_candidates.len]DecoderStatus,
+    /// GPU scale/convert element the pipeline would pick, if any.
+    postproc: ?[]const u8,
+    /// False when no Wayland compositor was reachable; the protocol
+    /// versions below are null in that case.
+    wayland_connected: bool,
+    viewporter_version: ?u32 = null,
+    linux_dmabuf_version: ?u32 = null,
+    layer_shell_version: ?u32 = null,
+    syncobj_version: ?u32 = null,
+    /// Whether a DRM render node exists, i.e. GBM dmabuf allocation works.
+    render_node: bool,
+    /// Whether /dev/dma_heap is present and accessible (legacy allocator;
+    /// usually root-only on desktops).
+    dma_heap: bool,
+};
+
+/// Probes the environment. Cheap enough to call on demand; no allocation.
+pub fn collect() Report {
+    pipeline_mod.Pipeline.initGst();
+
+    var major: c_uint = 0;
+    var minor: c_uint = 0;
+    var micro: c_uint = 0;
+    var nano: c_uint = 0;
+    c.gst_version(&major, &minor, &micro, &nano);
+
+    var decoders: [decoder.default_candidates.len]DecoderStatus = undefined;
+    const registry = c.gst_registry_get();
+    for (decoder.default_candidates, 0..) |name, i| {
+        if (c.gst_registry_lookup_feature(registry, name)) |feature| {
+            defer c.gst_object_unref(feature);
+            decoders[i] = .{
+                .name = name,
+                .available = true,
+                .rank = c.gst_plugin_feature_get_rank(feature),
+            };
+        } else {
+            decoders[i] = .{ .name = name, .available = false, .rank = 0 };
+        }
+    }
+
+    var report = Report{
+        .gst_major = major,
+        .gst_minor = minor,
+        .gst_micro = micro,
+        .decoders = decoders,
+        .postproc = decoder.availablePostproc(),
+        .wayland_connected = false,
+        .render_node = gbm.hasRenderNode(),
+        .dma_heap = hasDmaHeap(),
+    };
+
+    var connection: wl_globals.Connection = undefined;
+    if (connection.connect()) {
+        defer connection.disconnect();
+        report.wayland_connected = true;
+        report.viewporter_version = connection.tracker.version(.viewporter);
+        report.linux_dmabuf_version = connection.tracker.version(.linux_dmabuf);
+        report.layer_shell_version = connection.tracker.version(.layer_shell);
+        report.syncobj_version = connection.tracker.version(.syncobj);
+    } else |_| {}
+
+    return report;
+}
+
+fn hasDmaHeap() bool {
+    std.fs.accessAbsolute("/dev/dma_heap", .{}) catch return false;
+    return true;
+}
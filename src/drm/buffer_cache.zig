@@ -0,0 +1,151 @@
+//! Size-keyed cache of allocated dmabufs.
+//!
+//! Output reconfigurations (mode changes, moving between two monitors'
+//! sizes) used to drop and reallocate the whole buffer pool each time.
+//! Keeping the last couple of configurations alive makes toggling between
+//! them free instead of a burst of allocations; anything older is evicted
+//! least-recently-used so a long session does not pin every size it ever
+//! rendered at.
+
+const std = @import("std");
+const gbm = @import("gbm.zig");
+
+/// One buffer configuration; buffers are interchangeable within a key.
+pub const Key = struct {
+    width: u32,
+    height: u32,
+    /// DRM fourcc.
+    format: u32,
+
+    pub fn eql(self: Key, other: Key) bool {
+        return self.width == other.width and
+            self.height == other.height and
+            self.format == other.format;
+    }
+};
+
+/// Configurations kept alive besides the active one.
+pub const default_capacity = 2;
+
+/// Generic over the buffer type so the eviction logic is testable without
+/// a GPU; production code uses `DmabufCache`.
+pub fn BufferCache(comptime Buffer: type) type {
+    return struct {
+        const Self = @This();
+
+        const Entry = struct {
+            key: Key,
+            buffers: std.ArrayList(Buffer),
+            last_used: u64,
+        };
+
+        allocator: std.mem.Allocator,
+        entries: std.ArrayList(Entry) = .empty,
+        capacity: usize = default_capacity,
+        clock: u64 = 0,
+
+        pub fn init(allocator: std.mem.Allocator) Self {
+            return .{ .allocator = allocator };
+        }
+
+        pub fn deinit(self: *Self) void {
+            for (self.entries.items) |*entry| {
+                for (entry.buffers.items) |*buffer| buffer.deinit();
+                entry.buffers.deinit(self.allocator);
+            }
+            self.entries.deinit(self.allocator);
+            self.* = undefined;
+        }
+
+        /// Hands back a cached buffer for this configuration, or null when
+        /// the caller has to allocate.
+        pub fn take(self: *Self, key: Key) ?Buffer {
+            const entry = self.find(key) orelse return null;
+            self.clock += 1;
+            entry.last_used = self.clock;
+            return entry.buffers.pop();
+        }
+
+        /// Returns a buffer to the cache, evicting the least recently used
+        /// configuration when over capacity.
+        pub fn put(self: *Self, key: Key, buffer: Buffer) !void {
+            self.clock += 1;
+            if (self.find(key)) |entry| {
+                entry.last_used = self.clock;
+                try entry.buffers.append(self.allocator, buffer);
+                return;
+            }
+
+            var buffers: std.ArrayList(Buffer) = .empty;
+            errdefer buffers.deinit(self.allocator);
+            try buffers.append(self.allocator, buffer);
+            try self.entries.append(self.allocator, .{
+                .key = key,
+                .buffers = buffers,
+                .last_used = self.clock,
+            });
+
+            while (self.entries.items.len > self.capacity) self.evictOldest();
+        }
+
+        fn find(self: *Self, key: Key) ?*Entry {
+            for (self.entries.items) |*entry| {
+                if (entry.key.eql(key)) return entry;
+            }
+            return null;
+        }
+
+        fn evictOldest(self: *Self) void {
+            var oldest: usize = 0;
+            for (self.entries.items, 0..) |entry, i| {
+                if (entry.last_used < self.entries.items[oldest].last_used) oldest = i;
+            }
+            var entry = self.entries.swapRemove(oldest);
+            for (entry.buffers.items) |*buffer| buffer.deinit();
+            entry.buffers.deinit(self.allocator);
+        }
+    };
+}
+
+pub const DmabufCache = BufferCache(gbm.Buffer);
+
+const TestBuffer = struct {
+    freed: *u32,
+
+    fn deinit(self: *TestBuffer) void {
+        self.freed.* += 1;
+    }
+};
+
+test "buffers round-trip within a configuration" {
+    var freed: u32 = 0;
+    var cache = BufferCache(TestBuffer).init(std.testing.allocator);
+    defer cache.deinit();
+
+    const key: Key = .{ .width = 1920, .height = 1080, .format = 0 };
+    try std.testing.expectEqual(@as(?TestBuffer, null), cache.take(key));
+    try cache.put(key, .{ .freed = &freed });
+    try std.testing.expect(cache.take(key) != null);
+    try std.testing.expectEqual(@as(u32, 0), freed);
+}
+
+test "least recently used configuration is evicted" {
+    var freed: u32 = 0;
+    var cache = BufferCache(TestBuffer).init(std.testing.allocator);
+    defer cache.deinit();
+
+    const a: Key = .{ .width = 1920, .height = 1080, .format = 0 };
+    const b: Key = .{ .width = 2560, .height = 1440, .format = 0 };
+    const c: Key = .{ .width = 3840, .height = 2160, .format = 0 };
+
+    try cache.put(a, .{ .freed = &freed });
+    try cache.put(b, .{ .freed = &freed });
+    // Touch `a` so `b` becomes the eviction candidate.
+    try cache.put(a, .{ .freed = &freed });
+    try cache.put(c, .{ .freed = &freed });
+
+    try std.testing.expectEqual(@as(u32, 1), freed);
+    try std.testing.expect(cache.take(a) != null);
+    try std.testing.expect(cache.take(c) != null);
+    try std.testing.expectEqual(@as(?TestBuffer, null), cache.take(b));
+}
@@ -0,0 +1,99 @@
+//! Hand-written GBM (Generic Buffer Management) bindings.
+//!
+//! Only the allocation slice waystream uses is declared; buffer objects stay
+//! behind opaque pointers. Format codes are DRM fourccs, built with
+//! `fourcc` below instead of importing drm_fourcc.h.
+
+pub const gbm_device = opaque {};
+pub const gbm_bo = opaque {};
+
+pub const GBM_BO_USE_SCANOUT: u32 = 1 << 0;
+pub const GBM_BO_USE_RENDERING: u32 = 1 << 2;
+pub const GBM_BO_USE_LINEAR: u32 = 1 << 4;
+
+pub const GBM_BO_TRANSFER_WRITE: u32 = 1 << 1;
+
+/// DRM_FORMAT_MOD_INVALID: let the implementation pick (implicit modifier).
+pub const DRM_FORMAT_MOD_INVALID: u64 = 0x00ffffff_ffffffff;
+/// DRM_FORMAT_MOD_LINEAR: plain row-major layout.
+pub const DRM_FORMAT_MOD_LINEAR: u64 = 0;
+
+pub extern fn gbm_create_device(fd: c_int) ?*gbm_device;
+pub extern fn gbm_device_destroy(device: *gbm_device) void;
+
+pub extern fn gbm_bo_create(
+    device: *gbm_device,
+    width: u32,
+    height: u32,
+    format: u32,
+    flags: u32,
+) ?*gbm_bo;
+pub extern fn gbm_bo_create_with_modifiers2(
+    device: *gbm_device,
+    width: u32,
+    height: u32,
+    format: u32,
+    modifiers: [*]const u64,
+    count: c_uint,
+    flags: u32,
+) ?*gbm_bo;
+pub extern fn gbm_bo_destroy(bo: *gbm_bo) void;
+
+pub extern fn gbm_bo_get_plane_count(bo: *gbm_bo) c_int;
+pub extern fn gbm_bo_get_fd_for_plane(bo: *gbm_bo, plane: c_int) c_int;
+pub extern fn gbm_bo_get_stride_for_plane(bo: *gbm_bo, plane: c_int) u32;
+pub extern fn gbm_bo_get_offset(bo: *gbm_bo, plane: c_int) u32;
+pub extern fn gbm_bo_get_modifier(bo: *gbm_bo) u64;
+
+// CPU access; for tiled layouts the implementation detiles through a
+// staging copy, so writes only land once `gbm_bo_unmap` returns.
+pub extern fn gbm_bo_map(
+    bo: *gbm_bo,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    flags: u32,
+    stride: *u32,
+    map_data: *?*anyopaque,
+) ?*anyopaque;
+pub extern fn gbm_bo_unmap(bo: *gbm_bo, map_data: ?*anyopaque) void;
+
+/// Builds a DRM fourcc code from its four character tag.
+pub fn fourcc(tag: *const [4]u8) u32 {
+    return @as(u32, tag[0]) |
+        @as(u32, tag[1]) << 8 |
+        @as(u32, tag[2]) << 16 |
+        @as(u32, tag[3]) << 24;
+}
+
+/// Wait succeeds once the point signals even if no fence was attached
+/// yet; without it, waiting on an unmaterialized point errors instead of
+/// timing out.
+pub const DRM_SYNCOBJ_WAIT_FLAGS_WAIT_FOR_SUBMIT: u32 = 1 << 1;
+
+// Timeline syncobj entry points from libdrm, used for explicit sync.
+pub extern fn drmSyncobjCreate(fd: c_int, flags: u32, handle: *u32) c_int;
+pub extern fn drmSyncobjDestroy(fd: c_int, handle: u32) c_int;
+pub extern fn drmSyncobjHandleToFD(fd: c_int, handle: u32, obj_fd: *c_int) c_int;
+pub extern fn drmSyncobjTimelineSignal(
+    fd: c_int,
+    handles: [*]const u32,
+    points: [*]const u64,
+    count: u32,
+) c_int;
+pub extern fn drmSyncobjTimelineWait(
+    fd: c_int,
+    handles: [*]const u32,
+    points: [*]const u64,
+    count: u32,
+    timeout_nsec: i64,
+    flags: u32,
+    first_signaled: ?*u32,
+) c_int;
+
+pub const DRM_FORMAT_ARGB8888 = fourcc("AR24");
+pub const DRM_FORMAT_XRGB8888 = fourcc("XR24");
+pub const DRM_FORMAT_ABGR8888 = fourcc("AB24");
+pub const DRM_FORMAT_XBGR8888 = fourcc("XB24");
+pub const DRM_FORMAT_NV12 = fourcc("NV12");
@@ -0,0 +1,163 @@
+//! Dmabuf allocation through GBM on a render node.
+//!
+//! The dma-heap path needs root on most desktops (`/dev/dma_heap/*` is
+//! 0600), which silently forced the shm fallback. Render nodes are
+//! world-usable by the `render`/`video` group, so GBM is the primary
+//! allocator: it produces scanout-capable buffers with whatever modifier
+//! the driver prefers, which the compositor can then import zero-copy.
+
+const std = @import("std");
+const c = @import("c.zig");
+
+pub const max_planes = 4;
+
+pub const Plane = struct {
+    fd: std.posix.fd_t,
+    offset: u32,
+    stride: u32,
+};
+
+/// One allocated dmabuf, ready for zwp_linux_dmabuf import. The caller
+/// owns the plane fds and releases everything through `deinit`.
+pub const Buffer = struct {
+    width: u32,
+    height: u32,
+    /// DRM fourcc the buffer was allocated with.
+    format: u32,
+    modifier: u64,
+    planes: [max_planes]Plane,
+    plane_count: u32,
+
+    bo: *c.gbm_bo,
+
+    pub fn deinit(self: *Buffer) void {
+        for (self.planes[0..self.plane_count]) |plane| {
+            std.posix.close(plane.fd);
+        }
+        c.gbm_bo_destroy(self.bo);
+        self.* = undefined;
+    }
+};
+
+pub const AllocatorError = error{
+    NoRenderNode,
+    DeviceFailed,
+    AllocationFailed,
+};
+
+pub const GbmAllocator = struct {
+    fd: std.posix.fd_t,
+    device: *c.gbm_device,
+
+    /// Opens the first usable render node. Render nodes start at 128 and
+    /// one exists per GPU; probing a handful covers multi-GPU setups.
+    pub fn init() AllocatorError!GbmAllocator {
+        var node: u8 = 128;
+        while (node < 136) : (node += 1) {
+            var path_buffer: [32]u8 = undefined;
+            const path = renderNodePath(&path_buffer, node);
+            const fd = std.posix.open(path, .{ .ACCMODE = .RDWR, .CLOEXEC = true }, 0) catch
+                continue;
+            const device = c.gbm_create_device(fd) orelse {
+                std.posix.close(fd);
+                continue;
+            };
+            std.log.info("dmabuf allocator: gbm on {s}", .{path});
+            return .{ .fd = fd, .device = device };
+        }
+        return AllocatorError.NoRenderNode;
+    }
+
+    pub fn deinit(self: *GbmAllocator) void {
+        c.gbm_device_destroy(self.device);
+        std.posix.close(self.fd);
+        self.* = undefined;
+    }
+
+    /// Allocates a scanout-capable buffer. With a modifier list the driver
+    /// picks the best supported layout; an empty list (or driver refusal)
+    /// falls back to linear, which every compositor imports.
+    pub fn allocate(
+        self: *GbmAllocator,
+        width: u32,
+        height: u32,
+        format: u32,
+        modifiers: []const u64,
+    ) AllocatorError!Buffer {
+        const flags = c.GBM_BO_USE_SCANOUT | c.GBM_BO_USE_RENDERING;
+
+        const bo = blk: {
+            if (modifiers.len > 0) {
+                if (c.gbm_bo_create_with_modifiers2(
+                    self.device,
+                    width,
+                    height,
+                    format,
+                    modifiers.ptr,
+                    @intCast(modifiers.len),
+                    flags,
+                )) |bo| break :blk bo;
+            }
+            break :blk c.gbm_bo_create(
+                self.device,
+                width,
+                height,
+                format,
+                flags | c.GBM_BO_USE_LINEAR,
+            ) orelse return AllocatorError.AllocationFailed;
+        };
+        errdefer c.gbm_bo_destroy(bo);
+
+        const plane_count: u32 = @intCast(@max(c.gbm_bo_get_plane_count(bo), 1));
+        var buffer = Buffer{
+            .width = width,
+            .height = height,
+            .format = format,
+            .modifier = c.gbm_bo_get_modifier(bo),
+            .planes = undefined,
+            .plane_count = plane_count,
+            .bo = bo,
+        };
+        for (0..plane_count) |i| {
+            const plane: c_int = @intCast(i);
+            const fd = c.gbm_bo_get_fd_for_plane(bo, plane);
+            if (fd < 0) {
+                for (buffer.planes[0..i]) |prev| std.posix.close(prev.fd);
+                return AllocatorError.AllocationFailed;
+            }
+            buffer.planes[i] = .{
+                .fd = fd,
+                .offset = c.gbm_bo_get_offset(bo, plane),
+                .stride = c.gbm_bo_get_stride_for_plane(bo, plane),
+            };
+        }
+        return buffer;
+    }
+};
+
+/// Formats `/dev/dri/renderD<n>` into `buffer`.
+fn renderNodePath(buffer: *[32]u8, node: u8) [:0]const u8 {
+    return std.fmt.bufPrintZ(buffer, "/dev/dri/renderD{d}", .{node}) catch unreachable;
+}
+
+/// True when a render node exists, i.e. GBM allocation can work at all.
+pub fn hasRenderNode() bool {
+    var node: u8 = 128;
+    while (node < 136) : (node += 1) {
+        var path_buffer: [32]u8 = undefined;
+        std.fs.accessAbsoluteZ(renderNodePath(&path_buffer, node), .{}) catch continue;
+        return true;
+    }
+    return false;
+}
+
+test "render node paths are well-formed" {
+    var buffer: [32]u8 = undefined;
+    try std.testing.expectEqualStrings("/dev/dri/renderD128", renderNodePath(&buffer, 128));
+    try std.testing.expectEqualStrings("/dev/dri/renderD135", renderNodePath(&buffer, 135));
+}
+
+test "fourcc codes match the DRM encoding" {
+    try std.testing.expectEqual(@as(u32, 0x34325241), c.DRM_FORMAT_ARGB8888);
+    try std.testing.expectEqual(@as(u32, 0x3231564e), c.DRM_FORMAT_NV12);
+}
@@ -0,0 +1,99 @@
+//! Stable error taxonomy for the entry points.
+//!
+//! Each module already names its failures in its own error set, but the
+//! binary used to exit with the same code for all of them, so scripts
+//! and the GUI could not tell "config not found" from "pipeline died"
+//! from "compositor unreachable". This maps every public error value to
+//! a coarse category with a stable exit code; new error values may be
+//! added, but a value never moves between categories.
+
+const std = @import("std");
+
+pub const Category = enum {
+    /// Bad command line; the usage text is the fix.
+    usage,
+    /// The profiles config (or a value inside it) is wrong.
+    config,
+    /// GStreamer could not build or run the pipeline.
+    playback,
+    /// No usable Wayland compositor or protocol.
+    display,
+    /// A file or path the user named is missing or unreadable.
+    io,
+    /// Everything else; a bug report is the right response.
+    internal,
+
+    pub fn exitCode(self: Category) u8 {
+        return switch (self) {
+            .internal => 1,
+            .usage => 2,
+            .config => 3,
+            .playback => 4,
+            .display => 5,
+            .io => 6,
+        };
+    }
+};
+
+/// Buckets an error value by the stable contract above. Unknown values
+/// land in `.internal` rather than guessing.
+pub fn categorize(err: anyerror) Category {
+    return switch (err) {
+        error.MissingCommand,
+        error.UnknownCommand,
+        error.MissingVideo,
+        error.UnknownOption,
+        error.MissingOptionValue,
+        error.InvalidOptionValue,
+        => .usage,
+
+        error.ReadFailed,
+        error.ParseFailed,
+        error.UnknownBase,
+        error.ExtendsCycle,
+        error.UnsupportedVersion,
+        error.ReadOnlyConfig,
+        error.InvalidWindow,
+        error.InvalidDuration,
+        error.DuplicateName,
+        error.UnknownProfile,
+        error.IncludedProfile,
+        => .config,
+
+        error.InvalidUri,
+        error.PipelineParseFailed,
+        error.MissingAppsink,
+        error.StateChangeFailed,
+        error.NoImages,
+        => .playback,
+
+        error.ConnectFailed => .display,
+
+        error.FileNotFound,
+        error.AccessDenied,
+        error.InvalidPath,
+        error.IsDir,
+        error.NotDir,
+        => .io,
+
+        else => .internal,
+    };
+}
+
+test "error values map to their stable categories" {
+    try std.testing.expectEqual(Category.usage, categorize(error.UnknownOption));
+    try std.testing.expectEqual(Category.config, categorize(error.ExtendsCycle));
+    try std.testing.expectEqual(Category.playback, categorize(error.PipelineParseFailed));
+    try std.testing.expectEqual(Category.display, categorize(error.ConnectFailed));
+    try std.testing.expectEqual(Category.io, categorize(error.FileNotFound));
+    try std.testing.expectEqual(Category.internal, categorize(error.OutOfMemory));
+}
+
+test "exit codes are distinct per category" {
+    var seen = [_]bool{false} ** 7;
+    inline for (std.meta.fields(Category)) |field| {
+        const code = @as(Category, @enumFromInt(field.value)).exitCode();
+        try std.testing.expect(!seen[code]);
+        seen[code] = true;
+    }
+}
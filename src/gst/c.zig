@@ -0,0 +1,205 @@
+//! Hand-written GStreamer C bindings.
+//!
+//! Only the small slice of the API that waystream actually calls is declared
+//! here; everything else stays behind opaque pointers so the compiler keeps
+//! us honest about ownership.
+
+pub const GstElement = opaque {};
+pub const GstBin = opaque {};
+pub const GstBus = opaque {};
+pub const GstMessage = opaque {};
+pub const GstSample = opaque {};
+pub const GstRegistry = opaque {};
+pub const GstPluginFeature = opaque {};
+pub const GstElementFactory = opaque {};
+pub const GstBuffer = opaque {};
+pub const GstCaps = opaque {};
+pub const GstStructure = opaque {};
+
+pub const GError = extern struct {
+    domain: u32,
+    code: c_int,
+    message: [*:0]u8,
+};
+
+pub const GstState = enum(c_int) {
+    void_pending = 0,
+    null = 1,
+    ready = 2,
+    paused = 3,
+    playing = 4,
+};
+
+pub const GstStateChangeReturn = enum(c_int) {
+    failure = 0,
+    success = 1,
+    async_change = 2,
+    no_preroll = 3,
+};
+
+pub const GST_MESSAGE_EOS: c_uint = 1 << 0;
+pub const GST_MESSAGE_ERROR: c_uint = 1 << 1;
+
+pub const GST_SEEK_FLAG_FLUSH: c_int = 1 << 0;
+pub const GST_SEEK_FLAG_KEY_UNIT: c_int = 1 << 2;
+
+pub const GST_FORMAT_TIME: c_int = 3;
+
+pub const GST_CLOCK_TIME_NONE: u64 = ~@as(u64, 0);
+pub const GST_SECOND: u64 = 1_000_000_000;
+
+pub const GstMapFlags = c_int;
+pub const GST_MAP_READ: GstMapFlags = 1 << 0;
+
+pub const GstMapInfo = extern struct {
+    memory: ?*anyopaque,
+    flags: GstMapFlags,
+    data: [*c]u8,
+    size: usize,
+    maxsize: usize,
+    user_data: [4]?*anyopaque,
+    _gst_reserved: [4]?*anyopaque,
+};
+
+pub extern fn gst_init(argc: ?*c_int, argv: ?*[*][*:0]u8) void;
+pub extern fn gst_parse_launch(pipeline_description: [*:0]const u8, err: *?*GError) ?*GstElement;
+pub extern fn gst_filename_to_uri(filename: [*:0]const u8, err: *?*GError) ?[*:0]u8;
+
+pub extern fn gst_element_set_state(element: *GstElement, state: GstState) GstStateChangeReturn;
+pub extern fn gst_element_get_state(
+    element: *GstElement,
+    state: ?*GstState,
+    pending: ?*GstState,
+    timeout: u64,
+) GstStateChangeReturn;
+pub const GST_SEEK_TYPE_NONE: c_int = 0;
+pub const GST_SEEK_TYPE_SET: c_int = 1;
+
+pub extern fn gst_element_seek(
+    element: *GstElement,
+    rate: f64,
+    format: c_int,
+    seek_flags: c_int,
+    start_type: c_int,
+    start: i64,
+    stop_type: c_int,
+    stop: i64,
+) c_int;
+pub extern fn gst_element_seek_simple(
+    element: *GstElement,
+    format: c_int,
+    seek_flags: c_int,
+    seek_pos: i64,
+) c_int;
+pub extern fn gst_element_get_bus(element: *GstElement) ?*GstBus;
+
+pub extern fn gst_bin_get_by_name(bin: *GstBin, name: [*:0]const u8) ?*GstElement;
+
+pub extern fn gst_bus_pop_filtered(bus: *GstBus, types: c_uint) ?*GstMessage;
+
+/// GLib's poll record; on Linux this is laid out like struct pollfd.
+pub const GPollFD = extern struct {
+    fd: c_int,
+    events: c_ushort,
+    revents: c_ushort,
+};
+
+pub extern fn gst_bus_get_pollfd(bus: *GstBus, fd: *GPollFD) void;
+pub extern fn gst_message_parse_error(message: *GstMessage, gerror: *?*GError, debug: *?[*:0]u8) void;
+pub extern fn gst_message_unref(message: *GstMessage) void;
+
+/// GstFlowReturn success value; "new-sample" handlers return this to keep
+/// the stream flowing.
+pub const GST_FLOW_OK: c_int = 0;
+
+pub extern fn gst_app_sink_try_pull_sample(appsink: *GstElement, timeout: u64) ?*GstSample;
+pub extern fn gst_app_sink_try_pull_preroll(appsink: *GstElement, timeout: u64) ?*GstSample;
+pub extern fn gst_app_sink_is_eos(appsink: *GstElement) c_int;
+
+pub extern fn gst_sample_get_buffer(sample: *GstSample) ?*GstBuffer;
+pub extern fn gst_sample_get_caps(sample: *GstSample) ?*GstCaps;
+pub extern fn gst_sample_unref(sample: *GstSample) void;
+
+pub extern fn gst_caps_get_structure(caps: *GstCaps, index: c_uint) ?*GstStructure;
+pub extern fn gst_structure_get_int(structure: *GstStructure, fieldname: [*:0]const u8, value: *c_int) c_int;
+pub extern fn gst_structure_get_string(structure: *GstStructure, fieldname: [*:0]const u8) ?[*:0]const u8;
+
+pub extern fn gst_buffer_map(buffer: *GstBuffer, info: *GstMapInfo, flags: GstMapFlags) c_int;
+pub extern fn gst_buffer_unmap(buffer: *GstBuffer, info: *GstMapInfo) void;
+
+/// GST_DEBUG_GRAPH_SHOW_ALL: media types, caps details, non-default
+/// params, and states.
+pub const GST_DEBUG_GRAPH_SHOW_ALL: c_int = (1 << 4) - 1;
+
+pub extern fn gst_debug_bin_to_dot_data(bin: *GstBin, details: c_int) ?[*:0]u8;
+
+pub const GST_RANK_NONE: c_int = 0;
+pub const GST_RANK_PRIMARY: c_int = 256;
+
+pub extern fn gst_version(major: *c_uint, minor: *c_uint, micro: *c_uint, nano: *c_uint) void;
+
+pub extern fn gst_registry_get() *GstRegistry;
+pub extern fn gst_registry_lookup_feature(registry: *GstRegistry, name: [*:0]const u8) ?*GstPluginFeature;
+pub extern fn gst_plugin_feature_get_rank(feature: *GstPluginFeature) c_uint;
+pub extern fn gst_plugin_feature_set_rank(feature: *GstPluginFeature, rank: c_uint) void;
+
+pub extern fn gst_element_get_factory(element: *GstElement) ?*GstElementFactory;
+pub extern fn gst_element_factory_get_metadata(
+    factory: *GstElementFactory,
+    key: [*:0]const u8,
+) ?[*:0]const u8;
+pub extern fn gst_object_get_name(object: *anyopaque) ?[*:0]u8;
+
+pub const GstPad = opaque {};
+pub extern fn gst_element_get_static_pad(element: *GstElement, name: [*:0]const u8) ?*GstPad;
+pub extern fn gst_pad_get_current_caps(pad: *GstPad) ?*GstCaps;
+pub extern fn gst_caps_unref(caps: *GstCaps) void;
+pub extern fn gst_structure_get_fraction(
+    structure: *GstStructure,
+    field: [*:0]const u8,
+    numerator: *c_int,
+    denominator: *c_int,
+) c_int;
+
+pub extern fn g_signal_connect_data(
+    instance: *anyopaque,
+    detailed_signal: [*:0]const u8,
+    c_handler: *const anyopaque,
+    data: ?*anyopaque,
+    destroy_data: ?*const anyopaque,
+    connect_flags: c_int,
+) c_ulong;
+
+pub extern fn g_object_set(object: *anyopaque, first_property_name: [*:0]const u8, ...) void;
+
+pub const GstContext = opaque {};
+pub extern fn gst_context_new(context_type: [*:0]const u8, persistent: c_int) *GstContext;
+pub extern fn gst_context_writable_structure(context: *GstContext) *GstStructure;
+pub extern fn gst_element_set_context(element: *GstElement, context: *GstContext) void;
+pub extern fn gst_context_unref(context: *GstContext) void;
+pub extern fn gst_structure_set(structure: *GstStructure, first_field: [*:0]const u8, ...) void;
+/// Fundamental G_TYPE_POINTER value (type number 17, shifted per GType ABI).
+pub const G_TYPE_POINTER: usize = 17 << 2;
+
+/// From gstvideo-1.0; the overlay interface pointer is the element itself.
+pub extern fn gst_video_overlay_set_window_handle(overlay: *anyopaque, handle: usize) void;
+
+pub const GstMemory = opaque {};
+
+pub extern fn gst_buffer_n_memory(buffer: *GstBuffer) c_uint;
+pub extern fn gst_buffer_peek_memory(buffer: *GstBuffer, idx: c_uint) ?*GstMemory;
+
+// From gstreamer-allocators-1.0; hardware decoders back their buffers with
+// dmabuf memory, which the presentation path can import without a copy.
+pub extern fn gst_is_dmabuf_memory(memory: *GstMemory) c_int;
+pub extern fn gst_dmabuf_memory_get_fd(memory: *GstMemory) c_int;
+
+pub extern fn gst_object_unref(object: *anyopaque) void;
+pub extern fn g_free(mem: ?*anyopaque) void;
+pub extern fn g_error_free(err: *GError) void;
+
+/// `gst_bin_get_by_name` wants a `GstBin*`; pipelines returned by
+/// `gst_parse_launch` always are one.
+pub fn asBin(element: *GstElement) *GstBin {
+    return @ptrCast(element);
+}
@@ -0,0 +1,132 @@
+//! Hardware decoder preference handling.
+//!
+//! Re-ranks decoder factories in the GStreamer registry before the pipeline
+//! is built so decodebin prefers hardware elements. Both lists are
+//! configurable instead of hardcoded:
+//!
+//! - WAYSTREAM_HW_DECODERS: comma-separated factory names to promote,
+//!   replacing the built-in candidate list.
+//! - WAYSTREAM_DISABLE_DECODERS: comma-separated factory names to rank to
+//!   NONE (e.g. a broken vaapih265dec), applied after promotion.
+//!
+//! Every change is logged, and the decoder actually picked by decodebin is
+//! reported via `Pipeline.selectedDecoder`.
+
+const std = @import("std");
+const c = @import("c.zig");
+
+pub const default_candidates = [_][:0]const u8{
+    "vah264dec",
+    "vah265dec",
+    "vaapih264dec",
+    "vaapih265dec",
+    "v4l2h264dec",
+    "v4l2h265dec",
+    "nvh264dec",
+    "nvh265dec",
+};
+
+/// Applies allow/deny preferences to the registry. Call after gst_init and
+/// before constructing pipelines.
+pub fn applyPreferences(allocator: std.mem.Allocator) void {
+    const registry = c.gst_registry_get();
+
+    if (std.posix.getenv("WAYSTREAM_HW_DECODERS")) |list| {
+        var it = std.mem.tokenizeScalar(u8, list, ',');
+        while (it.next()) |name| promote(allocator, registry, name);
+    } else {
+        for (default_candidates) |name| promoteZ(registry, name);
+    }
+
+    if (std.posix.getenv("WAYSTREAM_DISABLE_DECODERS")) |list| {
+        var it = std.mem.tokenizeScalar(u8, list, ',');
+        while (it.next()) |name| disable(allocator, registry, name);
+    }
+}
+
+fn promote(allocator: std.mem.Allocator, registry: *c.GstRegistry, name: []const u8) void {
+    const name_z = allocator.dupeZ(u8, std.mem.trim(u8, name, " ")) catch return;
+    defer allocator.free(name_z);
+    promoteZ(registry, name_z);
+}
+
+fn promoteZ(registry: *c.GstRegistry, name: [:0]const u8) void {
+    const feature = c.gst_registry_lookup_feature(registry, name) orelse return;
+    defer c.gst_object_unref(feature);
+    const boosted: c_uint = @intCast(c.GST_RANK_PRIMARY + 1);
+    if (c.gst_plugin_feature_get_rank(feature) < boosted) {
+        c.gst_plugin_feature_set_rank(feature, boosted);
+        std.log.info("decoder preference: promoted {s}", .{name});
+    }
+}
+
+fn disable(allocator: std.mem.Allocator, registry: *c.GstRegistry, name: []const u8) void {
+    const name_z = allocator.dupeZ(u8, std.mem.trim(u8, name, " ")) catch return;
+    defer allocator.free(name_z);
+    const feature = c.gst_registry_lookup_feature(registry, name_z) orelse {
+        std.log.warn("decoder preference: cannot disable unknown factory {s}", .{name_z});
+        return;
+    };
+    defer c.gst_object_unref(feature);
+    c.gst_plugin_feature_set_rank(feature, @intCast(c.GST_RANK_NONE));
+    std.log.info("decoder preference: disabled {s}", .{name_z});
+}
+
+/// GPU postprocessors that can take over scaling and colorspace conversion
+/// from videoconvert/videoscale, in preference order. vapostproc pairs with
+/// the va decoders, v4l2convert with stateful v4l2 ones.
+const postproc_candidates = [_][:0]const u8{
+    "vapostproc",
+    "v4l2convert",
+};
+
+/// First GPU postprocessor present in the registry, or null when scaling
+/// and conversion have to stay on the CPU. Call after gst_init.
+pub fn availablePostproc() ?[]const u8 {
+    const registry = c.gst_registry_get();
+    for (postproc_candidates) |name| {
+        const feature = c.gst_registry_lookup_feature(registry, name) orelse continue;
+        c.gst_object_unref(feature);
+        return name;
+    }
+    return null;
+}
+
+/// True when the factory metadata marks an element as a video decoder.
+pub fn isVideoDecoder(element: *c.GstElement) bool {
+    const factory = c.gst_element_get_factory(element) orelse return false;
+    const klass = c.gst_element_factory_get_metadata(factory, "klass") orelse return false;
+    const klass_str = std.mem.span(klass);
+    return std.mem.indexOf(u8, klass_str, "Decoder") != null and
+        std.mem.indexOf(u8, klass_str, "Video") != null;
+}
+
+/// True when the factory metadata marks an element as a demuxer.
+pub fn isDemuxer(element: *c.GstElement) bool {
+    const factory = c.gst_element_get_factory(element) orelse return false;
+    const klass = c.gst_element_factory_get_metadata(factory, "klass") orelse return false;
+    return std.mem.indexOf(u8, std.mem.span(klass), "Demuxer") != null;
+}
+
+/// True when the element decodes in hardware — either its klass says so or
+/// its factory is one of the known hardware candidates.
+pub fn isHardware(element: *c.GstElement) bool {
+    if (c.gst_element_get_factory(element)) |factory| {
+        if (c.gst_element_factory_get_metadata(factory, "klass")) |klass| {
+            if (std.mem.indexOf(u8, std.mem.span(klass), "Hardware") != null) return true;
+        }
+    }
+    const name = factoryName(element) orelse return false;
+    defer c.g_free(name);
+    const name_slice = std.mem.span(name);
+    for (default_candidates) |candidate| {
+        if (std.mem.eql(u8, name_slice, candidate)) return true;
+    }
+    return false;
+}
+
+/// Factory name of the element (caller frees with g_free), or null.
+pub fn factoryName(element: *c.GstElement) ?[*:0]u8 {
+    const factory = c.gst_element_get_factory(element) orelse return null;
+    return c.gst_object_get_name(@ptrCast(factory));
+}
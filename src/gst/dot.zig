@@ -0,0 +1,42 @@
+//! Pipeline graph dumps for debugging.
+//!
+//! Writes GStreamer DOT graphs into a user-chosen directory so decoder and
+//! caps negotiation problems can be diagnosed without rebuilding with
+//! GST_DEBUG knowledge. Unlike GST_DEBUG_DUMP_DOT_DIR we render the data
+//! ourselves, so no environment variable is needed.
+
+const std = @import("std");
+const c = @import("c.zig");
+
+/// Writes `<dir>/<unix_ms>-<label>.dot` describing `element`.
+/// Failures are logged, never fatal: a missing dump must not kill playback.
+pub fn dumpPipeline(
+    allocator: std.mem.Allocator,
+    element: *c.GstElement,
+    dir: []const u8,
+    label: []const u8,
+) void {
+    const data = c.gst_debug_bin_to_dot_data(c.asBin(element), c.GST_DEBUG_GRAPH_SHOW_ALL) orelse {
+        std.log.warn("dot dump: no graph data for {s}", .{label});
+        return;
+    };
+    defer c.g_free(data);
+
+    const path = std.fmt.allocPrint(allocator, "{s}/{d}-{s}.dot", .{
+        dir,
+        std.time.milliTimestamp(),
+        label,
+    }) catch return;
+    defer allocator.free(path);
+
+    std.fs.cwd().makePath(dir) catch {};
+    const file = std.fs.cwd().createFile(path, .{}) catch |err| {
+        std.log.warn("dot dump: cannot create {s}: {s}", .{ path, @errorName(err) });
+        return;
+    };
+    defer file.close();
+    file.writeAll(std.mem.span(data)) catch |err| {
+        std.log.warn("dot dump: write to {s} failed: {s}", .{ path, @errorName(err) });
+    };
+    std.log.info("dot dump: wrote {s}", .{path});
+}
@@ -0,0 +1,169 @@
+//! The `gui` command: a small raylib window visualizing playback metrics
+//! written by a play process.
+
+const std = @import("std");
+const rl = @import("raylib");
+const snapshot_mod = @import("metrics/snapshot.zig");
+const reader_mod = @import("metrics/reader.zig");
+
+pub const Options = struct {
+    /// Playback target whose metrics to display.
+    target: []const u8 = "default",
+    /// Explicit snapshot path; overrides the target-derived default.
+    metrics_file: ?[]const u8 = null,
+};
+
+/// A player writes once a second; three missed updates means it is gone.
+const stale_after_ms: i64 = 3000;
+
+pub fn run(allocator: std.mem.Allocator, options: Options) !void {
+    const path = if (options.metrics_file) |file|
+        try allocator.dupe(u8, file)
+    else
+        try snapshot_mod.defaultPath(allocator, options.target);
+    defer allocator.free(path);
+
+    rl.initWindow(480, 240, "waystream metrics");
+    defer rl.closeWindow();
+    rl.setTargetFPS(30);
+
+    var reader = try reader_mod.Reader.watch(allocator, path);
+    defer reader.deinit();
+    var loaded: ?snapshot_mod.LoadedSnapshot = null;
+    defer if (loaded) |*l| l.deinit();
+
+    while (!rl.windowShouldClose()) {
+        const now_ms = std.time.milliTimestamp();
+        if (reader.poll()) |new_loaded| {
+            if (loaded) |*old| old.deinit();
+            loaded = new_loaded;
+        }
+
+        rl.beginDrawing();
+        defer rl.endDrawing();
+        rl.clearBackground(.black);
+
+        if (loaded) |l| {
+            const stale = now_ms - l.snapshot.updated_unix_ms > stale_after_ms;
+            try drawSnapshot(allocator, l, stale);
+            if (stale and rl.isKeyPressed(.r)) {
+                restartPlayer(allocator, l.snapshot) catch |err| {
+                    std.log.err("restart failed: {s}", .{@errorName(err)});
+                };
+            }
+        } else {
+            rl.drawText("no metrics snapshot yet", 16, 16, 20, .light_gray);
+        }
+    }
+}
+
+/// Respawns a detached player for the snapshot's target and video.
+fn restartPlayer(allocator: std.mem.Allocator, snapshot: snapshot_mod.Snapshot) !void {
+    if (snapshot.video.len == 0) return error.NoVideoRecorded;
+
+    var self_exe_buffer: [std.fs.max_path_bytes]u8 = undefined;
+    const self_exe = try std.fs.selfExePath(&self_exe_buffer);
+
+    var child = std.process.Child.init(&.{
+        self_exe,
+        "play",
+        snapshot.video,
+        "--target",
+        snapshot.target,
+    }, allocator);
+    child.stdin_behavior = .Ignore;
+    child.stdout_behavior = .Ignore;
+    child.stderr_behavior = .Ignore;
+    try child.spawn();
+    // Deliberately not reaped; the player outlives the GUI.
+    std.log.info("restarted player for target {s}", .{snapshot.target});
+}
+
+fn drawSnapshot(allocator: std.mem.Allocator, loaded: snapshot_mod.LoadedSnapshot, stale: bool) !void {
+    const snapshot = loaded.snapshot;
+
+    if (stale) {
+        rl.drawRectangle(0, 140, 480, 48, .red);
+        rl.drawText("STALE (player not running?)", 16, 148, 22, .white);
+        rl.drawText("press R to restart the target", 16, 172, 14, .white);
+    }
+
+    const header = try std.fmt.allocPrintSentinel(
+        allocator,
+        "{s}  {s}",
+        .{ snapshot.target, if (snapshot.paused) "(paused)" else "" },
+        0,
+    );
+    defer allocator.free(header);
+    rl.drawText(header, 16, 16, 20, .white);
+
+    const stats = try std.fmt.allocPrintSentinel(
+        allocator,
+        "fps: {d:.1}  p95 {d:.1}ms  p99 {d:.1}ms  jitter {d:.1}ms\n" ++
+            "frames: {d}  dropped: {d}  skipped: {d}  late: {d}",
+        .{
+            snapshot.fps,
+            snapshot.frame_p95_ms,
+            snapshot.frame_p99_ms,
+            snapshot.frame_jitter_ms,
+            snapshot.frames_rendered,
+            snapshot.frames_dropped,
+            snapshot.frames_skipped,
+            snapshot.frames_late,
+        },
+        0,
+    );
+    defer allocator.free(stats);
+    rl.drawText(stats, 16, 48, 20, .light_gray);
+
+    if (snapshot.src_width > 0) {
+        const stream = if (snapshot.gpu_busy_pct >= 0)
+            try std.fmt.allocPrintSentinel(
+                allocator,
+                "{d}x{d} @ {d:.2}  {s}/{s}  {s}  gpu {d}%",
+                .{
+                    snapshot.src_width,
+                    snapshot.src_height,
+                    snapshot.src_fps,
+                    if (snapshot.container.len > 0) snapshot.container else "?",
+                    if (snapshot.decoder.len > 0) snapshot.decoder else "?",
+                    if (snapshot.hw_decode) "hw" else "sw",
+                    snapshot.gpu_busy_pct,
+                },
+                0,
+            )
+        else
+            try std.fmt.allocPrintSentinel(
+                allocator,
+                "{d}x{d} @ {d:.2}  {s}/{s}  {s}",
+                .{
+                    snapshot.src_width,
+                    snapshot.src_height,
+                    snapshot.src_fps,
+                    if (snapshot.container.len > 0) snapshot.container else "?",
+                    if (snapshot.decoder.len > 0) snapshot.decoder else "?",
+                    if (snapshot.hw_decode) "hw" else "sw",
+                },
+                0,
+            );
+        defer allocator.free(stream);
+        rl.drawText(stream, 16, 92, 16, .light_gray);
+    }
+
+    if (snapshot.notes.len > 0) {
+        const notes = try std.fmt.allocPrintSentinel(allocator, "{s}", .{snapshot.notes}, 0);
+        defer allocator.free(notes);
+        rl.drawText(notes, 16, 112, 16, .sky_blue);
+    }
+
+    // Mixed-version installs keep working with the fields both sides know;
+    // make the skew visible instead of failing the load.
+    if (loaded.compat.describe()) |notice| {
+        const banner = try std.fmt.allocPrintSentinel(allocator, "schema v{d}: {s}", .{
+            snapshot.schema_version,
+            notice,
+        }, 0);
+        defer allocator.free(banner);
+        rl.drawText(banner, 16, 200, 18, .yellow);
+    }
+}
@@ -1,34 +1,319 @@
-// raylib-zig (c) Nikolas Wipper 2023
-
-const rl = @import("raylib");
+const std = @import("std");
+const cli = @import("cli.zig");
+const player = @import("player.zig");
+const gui = @import("gui.zig");
+const supervisor = @import("supervisor.zig");
+const bundle = @import("bundle.zig");
+const diagnostics = @import("diagnostics.zig");
+const errors = @import("errors.zig");
+const profiles = @import("config/profiles.zig");
+const import = @import("config/import.zig");
+const override = @import("config/override.zig");
+const schedule = @import("config/schedule.zig");
 
 pub fn main() anyerror!void {
-    // Initialization
-    //--------------------------------------------------------------------------------------
-    const screenWidth = 800;
-    const screenHeight = 450;
+    var gpa: std.heap.GeneralPurposeAllocator(.{}) = .init;
+    defer _ = gpa.deinit();
+    const allocator = gpa.allocator();
+
+    const args = try std.process.argsAlloc(allocator);
+    defer std.process.argsFree(allocator, args);
+
+    const command = cli.parse(allocator, args) catch |err| {
+        std.log.err("{s}", .{@errorName(err)});
+        std.debug.print("{s}", .{cli.usage});
+        std.process.exit(2);
+    };
+
+    runCommand(allocator, command) catch |err| {
+        const category = errors.categorize(err);
+        std.log.err("{s} ({s})", .{ @errorName(err), @tagName(category) });
+        std.process.exit(category.exitCode());
+    };
+}
+
+fn runCommand(allocator: std.mem.Allocator, command: cli.Command) !void {
+    switch (command) {
+        .help => std.debug.print("{s}", .{cli.usage}),
+        .play => |options| {
+            defer allocator.free(options.playlist);
+            try player.run(allocator, options);
+        },
+        .gui => |options| try gui.run(allocator, options),
+        .status => try printStatus(allocator),
+        .doctor => try printDoctor(allocator),
+        .migrate => try runMigrate(allocator),
+        .set_profile => |options| try runSetProfile(
+            allocator,
+            options.name,
+            options.duration,
+            options.output,
+        ),
+        .bundle_export => |options| try bundle.exportBundle(allocator, options.profile, options.out_path),
+        .bundle_import => |options| try bundle.importBundle(allocator, options.bundle_path),
+        .config_import => |options| try runImport(allocator, options.tool, options.source),
+    }
+}
+
+fn runImport(allocator: std.mem.Allocator, tool: cli.ImportTool, source: ?[]const u8) !void {
+    const imported = switch (tool) {
+        .mpvpaper => blk: {
+            const command = source orelse {
+                std.debug.print("import mpvpaper needs the command line as one argument\n", .{});
+                std.process.exit(2);
+            };
+            const profile = try import.fromMpvpaperCommand(allocator, command) orelse {
+                std.debug.print("no output/video pair found in that command\n", .{});
+                std.process.exit(2);
+            };
+            const one = try allocator.alloc(profiles.Profile, 1);
+            one[0] = profile;
+            break :blk one;
+        },
+        .hyprpaper => blk: {
+            const conf_path = source orelse
+                try defaultToolPath(allocator, "XDG_CONFIG_HOME", ".config", "hypr/hyprpaper.conf");
+            defer if (source == null) allocator.free(conf_path);
+            const conf = try std.fs.cwd().readFileAlloc(allocator, conf_path, 1024 * 1024);
+            defer allocator.free(conf);
+            break :blk try import.fromHyprpaperConf(allocator, conf);
+        },
+        .swww => blk: {
+            const cache_dir = source orelse
+                try defaultToolPath(allocator, "XDG_CACHE_HOME", ".cache", "swww");
+            defer if (source == null) allocator.free(cache_dir);
+            break :blk try import.fromSwwwState(allocator, cache_dir);
+        },
+    };
+    defer import.freeProfiles(allocator, imported);
+
+    var config = try profiles.ProfilesConfig.load(allocator, null);
+    defer config.deinit();
+
+    var added: usize = 0;
+    for (imported) |profile| {
+        if (config.findProfile(profile.name) != null) {
+            std.debug.print("skipping \"{s}\": a profile with that name exists\n", .{profile.name});
+            continue;
+        }
+        try config.addProfile(profile);
+        added += 1;
+    }
+    std.debug.print("imported {d} profile(s) into {s}\n", .{ added, config.path });
+}
+
+fn defaultToolPath(
+    allocator: std.mem.Allocator,
+    env_var: []const u8,
+    home_fallback: []const u8,
+    suffix: []const u8,
+) ![]u8 {
+    if (std.posix.getenv(env_var)) |root| {
+        return std.fmt.allocPrint(allocator, "{s}/{s}", .{ root, suffix });
+    }
+    const home = std.posix.getenv("HOME") orelse "/";
+    return std.fmt.allocPrint(allocator, "{s}/{s}/{s}", .{ home, home_fallback, suffix });
+}
 
-    rl.initWindow(screenWidth, screenHeight, "raylib-zig [core] example - basic window");
-    defer rl.closeWindow(); // Close window and OpenGL context
+fn runSetProfile(
+    allocator: std.mem.Allocator,
+    name: ?[]const u8,
+    duration: ?[]const u8,
+    output: ?[]const u8,
+) !void {
+    var state = try override.load(allocator, null, std.time.timestamp());
+    defer state.deinit();
 
-    rl.setTargetFPS(60); // Set our game to run at 60 frames-per-second
-    //--------------------------------------------------------------------------------------
+    const profile_name = name orelse {
+        if (output) |out| {
+            try state.clearOutput(out);
+            std.debug.print("override for {s} cleared\n", .{out});
+        } else {
+            state.active = null;
+            std.debug.print("override cleared\n", .{});
+        }
+        try state.persist();
+        return;
+    };
 
-    // Main game loop
-    while (!rl.windowShouldClose()) { // Detect window close button or ESC key
-        // Update
-        //----------------------------------------------------------------------------------
-        // TODO: Update your variables here
-        //----------------------------------------------------------------------------------
+    var config = try profiles.ProfilesConfig.load(allocator, null);
+    defer config.deinit();
+    if (config.findProfile(profile_name) == null) {
+        std.debug.print("no profile named \"{s}\" in {s}\n", .{ profile_name, config.path });
+        std.process.exit(2);
+    }
 
-        // Draw
-        //----------------------------------------------------------------------------------
-        rl.beginDrawing();
-        defer rl.endDrawing();
+    const expires_unix: ?i64 = if (duration) |text| blk: {
+        const seconds = schedule.parseDuration(text) catch {
+            std.debug.print("--for wants a duration like 90s/30m/2h, got \"{s}\"\n", .{text});
+            std.process.exit(2);
+        };
+        break :blk std.time.timestamp() + @as(i64, @intCast(seconds));
+    } else null;
+
+    if (output) |out| {
+        try state.setOutput(out, profile_name, expires_unix);
+    } else {
+        try state.setActive(profile_name, expires_unix);
+    }
+    try state.persist();
+
+    const scope = output orelse "all outputs";
+    if (duration) |text| {
+        std.debug.print("override: {s} on {s} for {s}\n", .{ profile_name, scope, text });
+    } else {
+        std.debug.print("override: {s} on {s} until cleared\n", .{ profile_name, scope });
+    }
+}
 
-        rl.clearBackground(.white);
+fn runMigrate(allocator: std.mem.Allocator) !void {
+    var config = try profiles.ProfilesConfig.load(allocator, null);
+    defer config.deinit();
 
-        rl.drawText("Congrats! You created your first window!", 190, 200, 20, .light_gray);
-        //----------------------------------------------------------------------------------
+    if (try config.migrateAndRewrite()) {
+        std.debug.print("rewrote {s} at version {d}\n", .{
+            config.path,
+            profiles.current_config_version,
+        });
+    } else {
+        std.debug.print("{s} already at version {d}\n", .{
+            config.path,
+            profiles.current_config_version,
+        });
     }
 }
+
+fn printStatus(allocator: std.mem.Allocator) !void {
+    const players = try supervisor.activePlayers(allocator);
+    defer supervisor.freePlayers(allocator, players);
+
+    if (players.len == 0) {
+        std.debug.print("no players running\n", .{});
+    } else {
+        for (players) |entry| {
+            std.debug.print("{s}\tpid {d}\t{s}\n", .{ entry.target, entry.pid, entry.video });
+        }
+    }
+
+    var config = profiles.ProfilesConfig.load(allocator, null) catch return;
+    defer config.deinit();
+    if (config.document.profiles.len == 0) return;
+    const selection = config.pickProfileExplained(allocator, null);
+    if (selection.chosen) |index| {
+        std.debug.print("profile: {s} ({s})\n", .{
+            config.document.profiles[index].name,
+            selection.reason.describe(),
+        });
+    }
+}
+
+fn printDoctor(allocator: std.mem.Allocator) !void {
+    const report = diagnostics.collect();
+
+    std.debug.print("gstreamer: {d}.{d}.{d}\n", .{
+        report.gst_major,
+        report.gst_minor,
+        report.gst_micro,
+    });
+
+    std.debug.print("hardware decoders:\n", .{});
+    for (report.decoders) |status| {
+        if (status.available) {
+            std.debug.print("  {s}\tavailable (rank {d})\n", .{ status.name, status.rank });
+        } else {
+            std.debug.print("  {s}\tmissing\n", .{status.name});
+        }
+    }
+    std.debug.print("gpu postproc: {s}\n", .{report.postproc orelse "none (CPU fallback)"});
+
+    if (report.wayland_connected) {
+        std.debug.print("wayland:\n", .{});
+        printProtocol("zwlr_layer_shell_v1", report.layer_shell_version);
+        printProtocol("zwp_linux_dmabuf_v1", report.linux_dmabuf_version);
+        printProtocol("wp_viewporter", report.viewporter_version);
+        printProtocol("wp_linux_drm_syncobj_manager_v1", report.syncobj_version);
+    } else {
+        std.debug.print("wayland: no compositor reachable\n", .{});
+    }
+    std.debug.print("render node: {s}\n", .{
+        if (report.render_node) "present (gbm dmabuf allocation)" else "absent",
+    });
+    std.debug.print("dma_heap: {s}\n", .{if (report.dma_heap) "present" else "absent"});
+
+    var config = profiles.ProfilesConfig.load(allocator, null) catch |err| {
+        std.debug.print("profiles config: failed to load ({s})\n", .{@errorName(err)});
+        return;
+    };
+    defer config.deinit();
+    var findings = try config.validate(allocator);
+    defer findings.deinit();
+
+    if (findings.items.len == 0) {
+        std.debug.print("profiles config: ok ({d} profiles)\n", .{config.document.profiles.len});
+    } else {
+        std.debug.print("profiles config:\n", .{});
+        for (findings.items) |diagnostic| {
+            std.debug.print("  [{s}] {s}: {s}\n", .{
+                @tagName(diagnostic.severity),
+                if (diagnostic.profile.len > 0) diagnostic.profile else "config",
+                diagnostic.message,
+            });
+        }
+    }
+}
+
+fn printProtocol(name: []const u8, version: ?u32) void {
+    if (version) |v| {
+        std.debug.print("  {s}\tv{d}\n", .{ name, v });
+    } else {
+        std.debug.print("  {s}\tmissing\n", .{name});
+    }
+}
+
+test {
+    _ = @import("testing/virtual_outputs.zig");
+    _ = @import("render/color.zig");
+    _ = @import("wayland/globals.zig");
+    _ = @import("render/blend.zig");
+    _ = @import("playback/adaptive.zig");
+    _ = @import("render/yuv.zig");
+    _ = @import("playback/resolver.zig");
+    _ = @import("playback/playlist.zig");
+    _ = @import("playback/slideshow.zig");
+    _ = @import("playback/filewatch.zig");
+    _ = @import("playback/glob.zig");
+    _ = @import("drm/gbm.zig");
+    _ = @import("wayland/dmabuf_feedback.zig");
+    _ = @import("wayland/dmabuf_import.zig");
+    _ = @import("render/swapchain.zig");
+    _ = @import("wayland/syncobj.zig");
+    _ = @import("drm/buffer_cache.zig");
+    _ = @import("render/pathprobe.zig");
+    _ = @import("render/scale.zig");
+    _ = @import("render/worker.zig");
+    _ = @import("wayland/import_cache.zig");
+    _ = @import("playback/budget.zig");
+    _ = @import("wayland/commit_batch.zig");
+    _ = @import("wayland/color_management.zig");
+    _ = @import("metrics/prometheus.zig");
+    _ = @import("metrics/stream.zig");
+    _ = @import("metrics/gpu.zig");
+    _ = @import("metrics/frametime.zig");
+    _ = @import("metrics/latency.zig");
+    _ = @import("metrics/history.zig");
+    _ = @import("metrics/retention.zig");
+    _ = @import("metrics/otlp.zig");
+    _ = @import("metrics/power.zig");
+    _ = @import("metrics/reader.zig");
+    _ = @import("metrics/events.zig");
+    _ = @import("metrics/dbus.zig");
+    _ = @import("config/schedule.zig");
+    _ = @import("config/tz.zig");
+    _ = @import("config/yaml.zig");
+    _ = @import("config/profiles.zig");
+    _ = @import("config/import.zig");
+    _ = @import("config/outputmatch.zig");
+    _ = @import("config/override.zig");
+    _ = @import("errors.zig");
+    _ = @import("metrics/memory.zig");
+}
@@ -0,0 +1,124 @@
+//! D-Bus metrics signal.
+//!
+//! Status bars (Waybar and friends) want wallpaper FPS without polling a
+//! file at their refresh rate. With `--dbus` each player emits a
+//! `MetricsUpdated` signal on the session bus once per metrics interval,
+//! carrying the summary fields; bars subscribe with a match rule and
+//! never touch the filesystem. The bindings below are the usual
+//! hand-written slice — GDBus lives in gio, which ships with the GLib
+//! stack already linked — and only broadcast signals are used, so there
+//! is no bus name to own and no method table to serve.
+
+const std = @import("std");
+const c = @import("../gst/c.zig");
+const snapshot_mod = @import("snapshot.zig");
+
+pub const interface_name = "org.waystream.Player1";
+pub const signal_name = "MetricsUpdated";
+
+pub const GDBusConnection = opaque {};
+pub const GVariant = opaque {};
+
+const G_BUS_TYPE_SESSION: c_int = 2;
+
+extern fn g_bus_get_sync(
+    bus_type: c_int,
+    cancellable: ?*anyopaque,
+    err: ?*?*c.GError,
+) ?*GDBusConnection;
+extern fn g_dbus_connection_emit_signal(
+    connection: *GDBusConnection,
+    destination: ?[*:0]const u8,
+    object_path: [*:0]const u8,
+    interface: [*:0]const u8,
+    signal: [*:0]const u8,
+    parameters: ?*GVariant,
+    err: ?*?*c.GError,
+) c_int;
+extern fn g_variant_new(format: [*:0]const u8, ...) *GVariant;
+extern fn g_object_unref(object: *anyopaque) void;
+
+/// Object path for a playback target. D-Bus paths allow only
+/// `[A-Za-z0-9_]` per element, so anything else in the target name maps
+/// to '_'. Caller frees the result.
+pub fn objectPath(allocator: std.mem.Allocator, target: []const u8) ![:0]u8 {
+    const path = try std.fmt.allocPrintSentinel(allocator, "/org/waystream/player/{s}", .{target}, 0);
+    for (path["/org/waystream/player/".len..]) |*byte| {
+        if (!std.ascii.isAlphanumeric(byte.*) and byte.* != '_') byte.* = '_';
+    }
+    return path;
+}
+
+pub const Publisher = struct {
+    allocator: std.mem.Allocator,
+    connection: *GDBusConnection,
+    path: [:0]u8,
+
+    /// Connects to the session bus; fails when there is none (headless
+    /// boots), which the caller treats as "run without the signal".
+    pub fn start(allocator: std.mem.Allocator, target: []const u8) !*Publisher {
+        var bus_err: ?*c.GError = null;
+        const connection = g_bus_get_sync(G_BUS_TYPE_SESSION, null, &bus_err) orelse {
+            if (bus_err) |err| {
+                std.log.warn("session bus unavailable: {s}", .{err.message});
+                c.g_error_free(err);
+            }
+            return error.NoSessionBus;
+        };
+        errdefer g_object_unref(connection);
+
+        const publisher = try allocator.create(Publisher);
+        errdefer allocator.destroy(publisher);
+        publisher.* = .{
+            .allocator = allocator,
+            .connection = connection,
+            .path = try objectPath(allocator, target),
+        };
+        return publisher;
+    }
+
+    pub fn stop(self: *Publisher) void {
+        g_object_unref(self.connection);
+        self.allocator.free(self.path);
+        const allocator = self.allocator;
+        allocator.destroy(self);
+    }
+
+    /// Emits one MetricsUpdated(s target, d fps, t rendered, t dropped,
+    /// b paused) broadcast. g_variant_new sinks the floating ref.
+    pub fn publish(self: *Publisher, snapshot: snapshot_mod.Snapshot) !void {
+        const target = try std.fmt.allocPrintSentinel(self.allocator, "{s}", .{snapshot.target}, 0);
+        defer self.allocator.free(target);
+
+        const parameters = g_variant_new(
+            "(sdttb)",
+            target.ptr,
+            snapshot.fps,
+            snapshot.frames_rendered,
+            snapshot.frames_dropped,
+            @as(c_int, @intFromBool(snapshot.paused)),
+        );
+        var emit_err: ?*c.GError = null;
+        if (g_dbus_connection_emit_signal(
+            self.connection,
+            null,
+            self.path,
+            interface_name,
+            signal_name,
+            parameters,
+            &emit_err,
+        ) == 0) {
+            if (emit_err) |err| {
+                std.log.warn("dbus emit failed: {s}", .{err.message});
+                c.g_error_free(err);
+            }
+            return error.EmitFailed;
+        }
+    }
+};
+
+test "object paths stay within the D-Bus character set" {
+    const path = try objectPath(std.testing.allocator, "eDP-1.main");
+    defer std.testing.allocator.free(path);
+    try std.testing.expectEqualStrings("/org/waystream/player/eDP_1_main", path);
+}
@@ -0,0 +1,80 @@
+//! Bounded pipeline event log.
+//!
+//! A snapshot says what playback looks like right now; "why did it
+//! stutter at 14:03" needs what happened. Significant events — state
+//! changes, loop seeks, rebuilds, recoveries — land in a small ring with
+//! their timestamps and ride along in every snapshot, so the last minute
+//! of pipeline history is readable from the file alone. The ring is
+//! bounded: a looping two-second clip seeking every cycle cannot grow the
+//! snapshot without limit.
+
+const std = @import("std");
+
+/// Events kept; older ones are overwritten.
+pub const capacity = 16;
+
+pub const Log = struct {
+    allocator: std.mem.Allocator,
+    entries: [capacity]?[]u8 = @splat(null),
+    next: usize = 0,
+    len: usize = 0,
+
+    pub fn init(allocator: std.mem.Allocator) Log {
+        return .{ .allocator = allocator };
+    }
+
+    pub fn deinit(self: *Log) void {
+        for (&self.entries) |*entry| {
+            if (entry.*) |text| self.allocator.free(text);
+        }
+        self.* = undefined;
+    }
+
+    /// Records one timestamped event; allocation failure drops it, an
+    /// event log must never take playback down.
+    pub fn add(self: *Log, unix_ms: i64, comptime fmt: []const u8, args: anytype) void {
+        const text = std.fmt.allocPrint(self.allocator, "{d} " ++ fmt, .{unix_ms} ++ args) catch
+            return;
+        if (self.entries[self.next]) |old| self.allocator.free(old);
+        self.entries[self.next] = text;
+        self.next = (self.next + 1) % capacity;
+        if (self.len < capacity) self.len += 1;
+    }
+
+    /// Joins the ring oldest-first with '|' for the snapshot. Caller
+    /// frees the result.
+    pub fn render(self: *const Log, allocator: std.mem.Allocator) ![]u8 {
+        var out: std.ArrayList(u8) = .empty;
+        errdefer out.deinit(allocator);
+
+        const start = (self.next + capacity - self.len) % capacity;
+        for (0..self.len) |offset| {
+            const text = self.entries[(start + offset) % capacity] orelse continue;
+            if (out.items.len > 0) try out.append(allocator, '|');
+            try out.appendSlice(allocator, text);
+        }
+        return out.toOwnedSlice(allocator);
+    }
+};
+
+test "events render oldest first with timestamps" {
+    var log = Log.init(std.testing.allocator);
+    defer log.deinit();
+    log.add(100, "playing", .{});
+    log.add(200, "seek to start ({s})", .{"loop"});
+
+    const text = try log.render(std.testing.allocator);
+    defer std.testing.allocator.free(text);
+    try std.testing.expectEqualStrings("100 playing|200 seek to start (loop)", text);
+}
+
+test "the ring keeps only the newest events" {
+    var log = Log.init(std.testing.allocator);
+    defer log.deinit();
+    for (0..capacity + 2) |index| log.add(@intCast(index), "e{d}", .{index});
+
+    const text = try log.render(std.testing.allocator);
+    defer std.testing.allocator.free(text);
+    try std.testing.expect(std.mem.startsWith(u8, text, "2 e2|"));
+    try std.testing.expect(std.mem.endsWith(u8, text, "17 e17"));
+}
@@ -0,0 +1,151 @@
+//! Frame-time distribution tracking.
+//!
+//! Average FPS hides stutter: a stream can present 59 frames on time and
+//! one 150ms late and still report a healthy rate. Recording individual
+//! frame-to-frame times in a ring and reducing them to p95/p99, max, and
+//! jitter (standard deviation) once per metrics interval makes those
+//! hitches visible in the snapshot and the GUI.
+
+const std = @import("std");
+
+/// Samples kept; at 60fps this is a bit over four seconds of history.
+pub const window = 256;
+
+/// Upper bucket bounds for the cumulative frame-time histogram, in
+/// milliseconds. Chosen around common refresh intervals (le17 ≈ 60fps,
+/// le34 ≈ 30fps) so downstream tooling can compute percentiles from the
+/// snapshot without us shipping raw samples.
+pub const bounds_ms = [_]u64{ 8, 12, 17, 20, 25, 34, 50 };
+
+pub const Stats = struct {
+    p95_ms: f64 = 0,
+    p99_ms: f64 = 0,
+    max_ms: f64 = 0,
+    /// Standard deviation of the frame time.
+    jitter_ms: f64 = 0,
+};
+
+pub const Recorder = struct {
+    samples: [window]f64 = undefined,
+    len: usize = 0,
+    next: usize = 0,
+    /// Cumulative bucket counts (one per bound plus overflow). Unlike the
+    /// ring these survive reset(): they are monotonic counters in the
+    /// Prometheus sense, and discontinuities never reach record() anyway.
+    counts: [bounds_ms.len + 1]u64 = @splat(0),
+
+    /// Records one frame-to-frame time in milliseconds.
+    pub fn record(self: *Recorder, frame_ms: f64) void {
+        self.samples[self.next] = frame_ms;
+        self.next = (self.next + 1) % window;
+        if (self.len < window) self.len += 1;
+
+        for (bounds_ms, 0..) |bound, index| {
+            if (frame_ms <= @as(f64, @floatFromInt(bound))) {
+                self.counts[index] += 1;
+                break;
+            }
+        } else self.counts[bounds_ms.len] += 1;
+    }
+
+    /// Ring resets on discontinuities (seeks, pauses, source swaps) so a
+    /// deliberate gap is not reported as a hitch.
+    pub fn reset(self: *Recorder) void {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// Reduces the current window; zeroes with fewer than two samples.
+    pub fn stats(self: *const Recorder) Stats {
+        if (self.len < 2) return .{};
+
+        var sorted: [window]f64 = undefined;
+        @memcpy(sorted[0..self.len], self.samples[0..self.len]);
+        std.mem.sort(f64, sorted[0..self.len], {}, std.sort.asc(f64));
+
+        var sum: f64 = 0;
+        for (sorted[0..self.len]) |sample| sum += sample;
+        const mean = sum / @as(f64, @floatFromInt(self.len));
+
+        var variance: f64 = 0;
+        for (sorted[0..self.len]) |sample| {
+            const deviation = sample - mean;
+            variance += deviation * deviation;
+        }
+        variance /= @as(f64, @floatFromInt(self.len));
+
+        return .{
+            .p95_ms = sorted[percentileIndex(self.len, 95)],
+            .p99_ms = sorted[percentileIndex(self.len, 99)],
+            .max_ms = sorted[self.len - 1],
+            .jitter_ms = @sqrt(variance),
+        };
+    }
+
+    /// Nearest-rank index into a sorted sample slice.
+    fn percentileIndex(len: usize, percentile: usize) usize {
+        const rank = (len * percentile + 99) / 100;
+        return @min(rank, len) - 1;
+    }
+
+    /// Compact histogram for the snapshot, e.g. "le8:0,le12:4,...,inf:0".
+    /// Caller frees the result.
+    pub fn renderHist(self: *const Recorder, allocator: std.mem.Allocator) ![]u8 {
+        var buffer: [256]u8 = undefined;
+        var used: usize = 0;
+        for (bounds_ms, 0..) |bound, index| {
+            const part = try std.fmt.bufPrint(
+                buffer[used..],
+                "le{d}:{d},",
+                .{ bound, self.counts[index] },
+            );
+            used += part.len;
+        }
+        const tail = try std.fmt.bufPrint(buffer[used..], "inf:{d}", .{
+            self.counts[bounds_ms.len],
+        });
+        used += tail.len;
+        return allocator.dupe(u8, buffer[0..used]);
+    }
+};
+
+test "percentiles pick out the slow tail" {
+    var recorder: Recorder = .{};
+    // 98 smooth frames and two hitches.
+    for (0..98) |_| recorder.record(16.7);
+    recorder.record(50);
+    recorder.record(150);
+
+    const stats = recorder.stats();
+    try std.testing.expectEqual(@as(f64, 150), stats.max_ms);
+    try std.testing.expect(stats.p99_ms >= 50);
+    try std.testing.expectApproxEqAbs(@as(f64, 16.7), stats.p95_ms, 0.1);
+    try std.testing.expect(stats.jitter_ms > 0);
+}
+
+test "uniform frame times have zero jitter" {
+    var recorder: Recorder = .{};
+    for (0..10) |_| recorder.record(16.7);
+    const stats = recorder.stats();
+    try std.testing.expectApproxEqAbs(@as(f64, 0), stats.jitter_ms, 0.0001);
+    try std.testing.expectApproxEqAbs(@as(f64, 16.7), stats.p95_ms, 0.0001);
+}
+
+test "bucket counts accumulate across ring resets" {
+    var recorder: Recorder = .{};
+    recorder.record(16);
+    recorder.reset();
+    recorder.record(16.9);
+    recorder.record(40);
+
+    const text = try recorder.renderHist(std.testing.allocator);
+    defer std.testing.allocator.free(text);
+    try std.testing.expectEqualStrings("le8:0,le12:0,le17:2,le20:0,le25:0,le34:0,le50:1,inf:0", text);
+}
+
+test "the ring forgets samples older than the window" {
+    var recorder: Recorder = .{};
+    recorder.record(500);
+    for (0..window) |_| recorder.record(16.7);
+    try std.testing.expectApproxEqAbs(@as(f64, 16.7), recorder.stats().max_ms, 0.0001);
+}
@@ -0,0 +1,113 @@
+//! Append-only metrics history.
+//!
+//! The snapshot file always shows the last interval and nothing else;
+//! questions like "does the wallpaper stutter every day at backup time"
+//! need a record. With `--metrics-history <s>` the player appends one
+//! summarized snapshot line every N seconds to a JSONL file under the
+//! state directory (it must survive reboots, unlike the runtime-dir
+//! snapshot). Growth is bounded: when the file passes the size limit it is
+//! rotated to `<path>.1`, replacing the previous generation, so the log
+//! holds at most two generations' worth of history.
+
+const std = @import("std");
+const snapshot_mod = @import("snapshot.zig");
+
+/// Rotation threshold; two generations of this bound the disk cost.
+pub const default_max_bytes: u64 = 8 * 1024 * 1024;
+
+/// History file location for a playback target, preferring XDG_STATE_HOME.
+pub fn defaultPath(allocator: std.mem.Allocator, target: []const u8) ![]u8 {
+    if (std.posix.getenv("XDG_STATE_HOME")) |state| {
+        return std.fmt.allocPrint(allocator, "{s}/waystream/history/{s}.jsonl", .{ state, target });
+    }
+    const home = std.posix.getenv("HOME") orelse "/tmp";
+    return std.fmt.allocPrint(
+        allocator,
+        "{s}/.local/state/waystream/history/{s}.jsonl",
+        .{ home, target },
+    );
+}
+
+pub const History = struct {
+    allocator: std.mem.Allocator,
+    path: []u8,
+    max_bytes: u64 = default_max_bytes,
+
+    pub fn init(allocator: std.mem.Allocator, path: []u8) History {
+        return .{ .allocator = allocator, .path = path };
+    }
+
+    pub fn deinit(self: *History) void {
+        self.allocator.free(self.path);
+        self.* = undefined;
+    }
+
+    /// Appends one record, rotating first when the file is past the limit.
+    pub fn append(self: *History, snapshot: snapshot_mod.Snapshot) !void {
+        const line = try snapshot_mod.renderLine(self.allocator, snapshot);
+        defer self.allocator.free(line);
+
+        if (std.fs.path.dirname(self.path)) |dir| {
+            std.fs.cwd().makePath(dir) catch {};
+        }
+        try self.rotateIfNeeded();
+
+        const file = try std.fs.cwd().createFile(self.path, .{ .truncate = false });
+        defer file.close();
+        try file.seekFromEnd(0);
+        try file.writeAll(line);
+    }
+
+    fn rotateIfNeeded(self: *History) !void {
+        const stat = std.fs.cwd().statFile(self.path) catch return;
+        if (stat.size < self.max_bytes) return;
+
+        const rotated = try std.fmt.allocPrint(self.allocator, "{s}.1", .{self.path});
+        defer self.allocator.free(rotated);
+        try std.fs.cwd().rename(self.path, rotated);
+    }
+};
+
+test "records append as JSONL lines" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+
+    var history = History.init(
+        std.testing.allocator,
+        try std.fmt.allocPrint(std.testing.allocator, "{s}/t.jsonl", .{root}),
+    );
+    defer history.deinit();
+
+    try history.append(.{ .target = "a", .fps = 30 });
+    try history.append(.{ .target = "a", .fps = 31 });
+
+    const data = try tmp.dir.readFileAlloc(std.testing.allocator, "t.jsonl", 1 << 16);
+    defer std.testing.allocator.free(data);
+    try std.testing.expectEqual(@as(usize, 2), std.mem.count(u8, data, "\n"));
+}
+
+test "the file rotates once it passes the size limit" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+
+    var history = History.init(
+        std.testing.allocator,
+        try std.fmt.allocPrint(std.testing.allocator, "{s}/t.jsonl", .{root}),
+    );
+    defer history.deinit();
+    history.max_bytes = 1;
+
+    try history.append(.{ .target = "a" });
+    try history.append(.{ .target = "a" });
+
+    // The first record moved to the .1 generation; the live file holds
+    // only the second.
+    const live = try tmp.dir.readFileAlloc(std.testing.allocator, "t.jsonl", 1 << 16);
+    defer std.testing.allocator.free(live);
+    try std.testing.expectEqual(@as(usize, 1), std.mem.count(u8, live, "\n"));
+    try tmp.dir.access("t.jsonl.1", .{});
+}
@@ -0,0 +1,86 @@
+//! Decode-to-present latency histogram.
+//!
+//! "The wallpaper lags behind the audio" reports need a number: how long a
+//! frame sits between appsink handing it over and the moment it reaches
+//! the screen. Each frame carries its delivery timestamp through the
+//! compose worker; the time to upload is recorded into fixed buckets whose
+//! bounds roughly double, so one glance separates "a frame of queueing"
+//! from "hundreds of milliseconds stuck in compose".
+
+const std = @import("std");
+
+/// Upper bucket bounds in milliseconds; everything beyond the last bound
+/// lands in the overflow bucket.
+pub const bounds_ms = [_]u64{ 2, 4, 8, 16, 33, 66, 132 };
+
+pub const Histogram = struct {
+    /// One count per bound plus the overflow bucket.
+    counts: [bounds_ms.len + 1]u64 = @splat(0),
+    total: u64 = 0,
+    sum_ms: f64 = 0,
+    max_ms: f64 = 0,
+
+    pub fn record(self: *Histogram, latency_ms: f64) void {
+        const clamped = @max(latency_ms, 0);
+        for (bounds_ms, 0..) |bound, index| {
+            if (clamped <= @as(f64, @floatFromInt(bound))) {
+                self.counts[index] += 1;
+                break;
+            }
+        } else self.counts[bounds_ms.len] += 1;
+
+        self.total += 1;
+        self.sum_ms += clamped;
+        self.max_ms = @max(self.max_ms, clamped);
+    }
+
+    pub fn meanMs(self: *const Histogram) f64 {
+        if (self.total == 0) return 0;
+        return self.sum_ms / @as(f64, @floatFromInt(self.total));
+    }
+
+    /// Compact form for the snapshot, e.g. "le2:0,le4:12,...,inf:1".
+    /// Caller frees the result.
+    pub fn render(self: *const Histogram, allocator: std.mem.Allocator) ![]u8 {
+        var buffer: [256]u8 = undefined;
+        var used: usize = 0;
+        for (bounds_ms, 0..) |bound, index| {
+            const part = try std.fmt.bufPrint(
+                buffer[used..],
+                "le{d}:{d},",
+                .{ bound, self.counts[index] },
+            );
+            used += part.len;
+        }
+        const tail = try std.fmt.bufPrint(buffer[used..], "inf:{d}", .{
+            self.counts[bounds_ms.len],
+        });
+        used += tail.len;
+        return allocator.dupe(u8, buffer[0..used]);
+    }
+};
+
+test "latencies land in doubling buckets" {
+    var histogram: Histogram = .{};
+    histogram.record(1);
+    histogram.record(8);
+    histogram.record(9);
+    histogram.record(500);
+
+    try std.testing.expectEqual(@as(u64, 1), histogram.counts[0]);
+    try std.testing.expectEqual(@as(u64, 1), histogram.counts[2]);
+    try std.testing.expectEqual(@as(u64, 1), histogram.counts[3]);
+    try std.testing.expectEqual(@as(u64, 1), histogram.counts[bounds_ms.len]);
+    try std.testing.expectEqual(@as(f64, 500), histogram.max_ms);
+    try std.testing.expectApproxEqAbs(@as(f64, 129.5), histogram.meanMs(), 0.001);
+}
+
+test "render is the compact snapshot form" {
+    var histogram: Histogram = .{};
+    histogram.record(3);
+    histogram.record(3);
+
+    const text = try histogram.render(std.testing.allocator);
+    defer std.testing.allocator.free(text);
+    try std.testing.expectEqualStrings("le2:0,le4:2,le8:0,le16:0,le33:0,le66:0,le132:0,inf:0", text);
+}
@@ -0,0 +1,64 @@
+//! Buffer memory accounting.
+//!
+//! A single 4K RGBA frame is 33 MB; with conversion scratch, presentation
+//! buffers, and several outputs the total climbs fast and invisibly. Each
+//! holder reports what it currently has; the totals go into the metrics
+//! snapshot, and an optional cap lets the player trade resolution for
+//! memory instead of ballooning past what the user expects.
+
+const std = @import("std");
+
+pub const Category = enum {
+    /// Decoded/converted frame scratch buffers.
+    frame_store,
+    /// CPU-side presentation buffers (shm path, textures).
+    shm_pool,
+    /// GBM-allocated dmabufs.
+    dmabuf_pool,
+    /// Decoder dmabufs currently imported and held.
+    imported,
+};
+
+pub const Accounting = struct {
+    bytes: std.EnumArray(Category, u64) = .initFill(0),
+    /// Total budget; null disables enforcement.
+    cap_bytes: ?u64 = null,
+
+    /// Replaces the recorded size for a category; holders report absolute
+    /// usage rather than deltas so a missed release cannot drift the total.
+    pub fn set(self: *Accounting, category: Category, size: u64) void {
+        self.bytes.set(category, size);
+    }
+
+    pub fn total(self: *const Accounting) u64 {
+        var sum: u64 = 0;
+        for (self.bytes.values) |size| sum += size;
+        return sum;
+    }
+
+    pub fn overCap(self: *const Accounting) bool {
+        const cap = self.cap_bytes orelse return false;
+        return self.total() > cap;
+    }
+};
+
+test "totals and cap enforcement" {
+    var accounting: Accounting = .{ .cap_bytes = 100 };
+    accounting.set(.frame_store, 60);
+    accounting.set(.shm_pool, 30);
+    try std.testing.expectEqual(@as(u64, 90), accounting.total());
+    try std.testing.expect(!accounting.overCap());
+
+    accounting.set(.imported, 20);
+    try std.testing.expect(accounting.overCap());
+
+    // Absolute reporting: re-reporting a smaller size recovers.
+    accounting.set(.frame_store, 10);
+    try std.testing.expect(!accounting.overCap());
+}
+
+test "no cap means never over" {
+    var accounting: Accounting = .{};
+    accounting.set(.dmabuf_pool, std.math.maxInt(u32));
+    try std.testing.expect(!accounting.overCap());
+}
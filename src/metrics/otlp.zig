@@ -0,0 +1,121 @@
+//! OTLP export of playback metrics.
+//!
+//! Fleet setups (kiosks, digital signage) already run an OpenTelemetry
+//! collector per site; for them a Prometheus scrape per screen is the
+//! wrong direction. With `-Dotlp` compiled in, `--otlp-endpoint` (or the
+//! standard `OTEL_EXPORTER_OTLP_ENDPOINT` variable) pushes one
+//! OTLP/HTTP JSON metrics request per metrics interval to the collector's
+//! `/v1/metrics`. The payload is built by hand — the shape is small and
+//! stable enough that pulling in a protobuf stack buys nothing. Plain
+//! HTTP only; fleets terminate TLS at the collector sidecar.
+
+const std = @import("std");
+const snapshot_mod = @import("snapshot.zig");
+const build_options = @import("build_options");
+
+/// Whether this binary was compiled with the exporter (`-Dotlp`). The
+/// code always builds; the flag only decides if an endpoint is honored.
+pub const enabled = build_options.otlp_export;
+
+/// Renders one ExportMetricsServiceRequest in OTLP/JSON form. Caller
+/// frees the result.
+pub fn renderRequest(
+    allocator: std.mem.Allocator,
+    snapshot: snapshot_mod.Snapshot,
+    time_unix_nano: i128,
+) ![]u8 {
+    return std.fmt.allocPrint(
+        allocator,
+        "{{\"resourceMetrics\":[{{\"resource\":{{\"attributes\":[" ++
+            "{{\"key\":\"service.name\",\"value\":{{\"stringValue\":\"waystream\"}}}}," ++
+            "{{\"key\":\"waystream.target\",\"value\":{{\"stringValue\":\"{s}\"}}}}]}}," ++
+            "\"scopeMetrics\":[{{\"scope\":{{\"name\":\"waystream\"}},\"metrics\":[" ++
+            "{{\"name\":\"waystream.fps\",\"gauge\":{{\"dataPoints\":[" ++
+            "{{\"timeUnixNano\":\"{d}\",\"asDouble\":{d:.2}}}]}}}}," ++
+            "{{\"name\":\"waystream.frames.rendered\",\"sum\":{{\"isMonotonic\":true," ++
+            "\"aggregationTemporality\":2,\"dataPoints\":[" ++
+            "{{\"timeUnixNano\":\"{d}\",\"asInt\":\"{d}\"}}]}}}}," ++
+            "{{\"name\":\"waystream.frames.dropped\",\"sum\":{{\"isMonotonic\":true," ++
+            "\"aggregationTemporality\":2,\"dataPoints\":[" ++
+            "{{\"timeUnixNano\":\"{d}\",\"asInt\":\"{d}\"}}]}}}}," ++
+            "{{\"name\":\"waystream.memory.bytes\",\"gauge\":{{\"dataPoints\":[" ++
+            "{{\"timeUnixNano\":\"{d}\",\"asInt\":\"{d}\"}}]}}}}]}}]}}]}}",
+        .{
+            snapshot.target,
+            time_unix_nano, snapshot.fps,
+            time_unix_nano, snapshot.frames_rendered,
+            time_unix_nano, snapshot.frames_dropped,
+            time_unix_nano, snapshot.mem_bytes,
+        },
+    );
+}
+
+pub const Exporter = struct {
+    allocator: std.mem.Allocator,
+    host: []u8,
+    port: u16,
+
+    /// Parses `host:port` (an optional `http://` prefix is tolerated) and
+    /// returns a heap exporter; nothing is contacted until publish().
+    pub fn start(allocator: std.mem.Allocator, endpoint: []const u8) !*Exporter {
+        var rest = endpoint;
+        if (std.mem.startsWith(u8, rest, "http://")) rest = rest["http://".len..];
+        const colon = std.mem.lastIndexOfScalar(u8, rest, ':') orelse
+            return error.InvalidAddress;
+        const port = std.fmt.parseInt(u16, rest[colon + 1 ..], 10) catch
+            return error.InvalidAddress;
+
+        const exporter = try allocator.create(Exporter);
+        errdefer allocator.destroy(exporter);
+        exporter.* = .{
+            .allocator = allocator,
+            .host = try allocator.dupe(u8, rest[0..colon]),
+            .port = port,
+        };
+        return exporter;
+    }
+
+    pub fn stop(self: *Exporter) void {
+        self.allocator.free(self.host);
+        const allocator = self.allocator;
+        allocator.destroy(self);
+    }
+
+    /// Pushes one metrics request. A down collector costs one failed
+    /// connect per metrics interval; the caller logs and moves on.
+    pub fn publish(self: *Exporter, snapshot: snapshot_mod.Snapshot) !void {
+        const body = try renderRequest(self.allocator, snapshot, std.time.nanoTimestamp());
+        defer self.allocator.free(body);
+
+        const stream = try std.net.tcpConnectToHost(self.allocator, self.host, self.port);
+        defer stream.close();
+
+        const head = try std.fmt.allocPrint(
+            self.allocator,
+            "POST /v1/metrics HTTP/1.1\r\n" ++
+                "Host: {s}\r\n" ++
+                "Content-Type: application/json\r\n" ++
+                "Content-Length: {d}\r\n" ++
+                "Connection: close\r\n\r\n",
+            .{ self.host, body.len },
+        );
+        defer self.allocator.free(head);
+
+        try stream.writeAll(head);
+        try stream.writeAll(body);
+    }
+};
+
+test "the request is OTLP/JSON with the target as a resource attribute" {
+    const body = try renderRequest(std.testing.allocator, .{
+        .target = "hdmi",
+        .fps = 59.94,
+        .frames_rendered = 1000,
+    }, 5);
+    defer std.testing.allocator.free(body);
+
+    try std.testing.expect(std.mem.indexOf(u8, body, "\"resourceMetrics\"") != null);
+    try std.testing.expect(std.mem.indexOf(u8, body, "\"stringValue\":\"hdmi\"") != null);
+    try std.testing.expect(std.mem.indexOf(u8, body, "\"asDouble\":59.94") != null);
+    try std.testing.expect(std.mem.indexOf(u8, body, "\"timeUnixNano\":\"5\"") != null);
+}
@@ -0,0 +1,124 @@
+//! Power-source probing.
+//!
+//! "It stutters on battery" is a different bug than "it stutters": TLP,
+//! GPU clock caps, and compositor frame limiting all key off the power
+//! source. Recording AC/battery in every snapshot saves that round trip
+//! in bug reports. Read from sysfs: a Mains supply reporting online wins,
+//! otherwise any battery present means we are discharging.
+
+const std = @import("std");
+
+pub const default_sysfs_root = "/sys/class/power_supply";
+
+pub const State = enum {
+    unknown,
+    ac,
+    battery,
+};
+
+/// What playback does while on battery. `continue` is the default and
+/// changes nothing; `static` drops to quasi-static frame stepping;
+/// `pause` halts the pipeline until AC returns.
+pub const Policy = enum { @"continue", static, pause };
+
+/// Pluggable source of the power state, so tests and setups where sysfs
+/// lies (some VMs, exotic firmware) can substitute the probe.
+pub const Provider = struct {
+    context: ?*anyopaque = null,
+    probeFn: *const fn (context: ?*anyopaque, allocator: std.mem.Allocator) State,
+
+    pub fn probe(self: Provider, allocator: std.mem.Allocator) State {
+        return self.probeFn(self.context, allocator);
+    }
+
+    /// Default provider backed by /sys/class/power_supply.
+    pub const sysfs: Provider = .{ .probeFn = probeSysfs };
+};
+
+fn probeSysfs(_: ?*anyopaque, allocator: std.mem.Allocator) State {
+    return probe(allocator, default_sysfs_root);
+}
+
+/// Probes the power source under `root`; desktops without any supply
+/// entries (and sandboxes) report `.unknown`.
+pub fn probe(allocator: std.mem.Allocator, root: []const u8) State {
+    var dir = std.fs.cwd().openDir(root, .{ .iterate = true }) catch return .unknown;
+    defer dir.close();
+
+    var saw_battery = false;
+    var it = dir.iterate();
+    while (it.next() catch return .unknown) |entry| {
+        const kind = readTrimmed(allocator, dir, entry.name, "type") orelse continue;
+        defer allocator.free(kind);
+
+        if (std.mem.eql(u8, kind, "Mains")) {
+            const online = readTrimmed(allocator, dir, entry.name, "online") orelse continue;
+            defer allocator.free(online);
+            if (std.mem.eql(u8, online, "1")) return .ac;
+        } else if (std.mem.eql(u8, kind, "Battery")) {
+            saw_battery = true;
+        }
+    }
+    return if (saw_battery) .battery else .unknown;
+}
+
+fn readTrimmed(
+    allocator: std.mem.Allocator,
+    dir: std.fs.Dir,
+    supply: []const u8,
+    file: []const u8,
+) ?[]u8 {
+    var path_buffer: [std.fs.max_path_bytes]u8 = undefined;
+    const path = std.fmt.bufPrint(&path_buffer, "{s}/{s}", .{ supply, file }) catch return null;
+    const raw = dir.readFileAlloc(allocator, path, 64) catch return null;
+    defer allocator.free(raw);
+    const trimmed = std.mem.trim(u8, raw, " \t\r\n");
+    return allocator.dupe(u8, trimmed) catch null;
+}
+
+test "an online mains supply reports ac" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.makePath("AC");
+    try tmp.dir.writeFile(.{ .sub_path = "AC/type", .data = "Mains\n" });
+    try tmp.dir.writeFile(.{ .sub_path = "AC/online", .data = "1\n" });
+    try tmp.dir.makePath("BAT0");
+    try tmp.dir.writeFile(.{ .sub_path = "BAT0/type", .data = "Battery\n" });
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+
+    try std.testing.expectEqual(State.ac, probe(std.testing.allocator, root));
+}
+
+test "an offline mains supply with a battery reports battery" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.makePath("AC");
+    try tmp.dir.writeFile(.{ .sub_path = "AC/type", .data = "Mains\n" });
+    try tmp.dir.writeFile(.{ .sub_path = "AC/online", .data = "0\n" });
+    try tmp.dir.makePath("BAT0");
+    try tmp.dir.writeFile(.{ .sub_path = "BAT0/type", .data = "Battery\n" });
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+
+    try std.testing.expectEqual(State.battery, probe(std.testing.allocator, root));
+}
+
+test "a custom provider substitutes the sysfs probe" {
+    const fake = struct {
+        fn probeFake(_: ?*anyopaque, _: std.mem.Allocator) State {
+            return .battery;
+        }
+    };
+    const provider = Provider{ .probeFn = fake.probeFake };
+    try std.testing.expectEqual(State.battery, provider.probe(std.testing.allocator));
+}
+
+test "no supplies reports unknown" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+
+    try std.testing.expectEqual(State.unknown, probe(std.testing.allocator, root));
+}
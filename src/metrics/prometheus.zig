@@ -0,0 +1,152 @@
+//! Prometheus text exposition of playback metrics.
+//!
+//! `--metrics-listen <addr:port>` starts a minimal HTTP endpoint serving
+//! the latest snapshot in Prometheus text format, so wallpaper health can
+//! sit in Grafana next to everything else. The exporter keeps its own copy
+//! of the snapshot (updated once per metrics interval) and answers every
+//! request from that, so scrapes never touch the playback loop.
+
+const std = @import("std");
+const snapshot_mod = @import("snapshot.zig");
+
+/// Renders a snapshot as Prometheus text format. Caller frees the result.
+pub fn render(allocator: std.mem.Allocator, snapshot: snapshot_mod.Snapshot) ![]u8 {
+    return std.fmt.allocPrint(
+        allocator,
+        "# HELP waystream_fps Frames presented per second.\n" ++
+            "# TYPE waystream_fps gauge\n" ++
+            "waystream_fps{{target=\"{s}\"}} {d:.2}\n" ++
+            "# HELP waystream_frames_rendered_total Frames presented since start.\n" ++
+            "# TYPE waystream_frames_rendered_total counter\n" ++
+            "waystream_frames_rendered_total{{target=\"{s}\"}} {d}\n" ++
+            "# HELP waystream_frames_dropped_total Stale frames skipped by keep-latest queueing.\n" ++
+            "# TYPE waystream_frames_dropped_total counter\n" ++
+            "waystream_frames_dropped_total{{target=\"{s}\"}} {d}\n" ++
+            "# HELP waystream_paused Whether playback is paused.\n" ++
+            "# TYPE waystream_paused gauge\n" ++
+            "waystream_paused{{target=\"{s}\"}} {d}\n" ++
+            "# HELP waystream_memory_bytes Bytes held in frame stores and buffer pools.\n" ++
+            "# TYPE waystream_memory_bytes gauge\n" ++
+            "waystream_memory_bytes{{target=\"{s}\"}} {d}\n" ++
+            "# HELP waystream_first_frame_ms Cold-start time to the first presented frame.\n" ++
+            "# TYPE waystream_first_frame_ms gauge\n" ++
+            "waystream_first_frame_ms{{target=\"{s}\"}} {d:.0}\n" ++
+            "# HELP waystream_backend Constant 1 carrying decode/present labels.\n" ++
+            "# TYPE waystream_backend gauge\n" ++
+            "waystream_backend{{target=\"{s}\",decoder=\"{s}\",hw=\"{}\",buffer_path=\"{s}\"}} 1\n",
+        .{
+            snapshot.target,     snapshot.fps,
+            snapshot.target,     snapshot.frames_rendered,
+            snapshot.target,     snapshot.frames_dropped,
+            snapshot.target,     @intFromBool(snapshot.paused),
+            snapshot.target,     snapshot.mem_bytes,
+            snapshot.target,     snapshot.first_frame_ms,
+            snapshot.target,     snapshot.decoder,
+            snapshot.hw_decode,  snapshot.buffer_path,
+        },
+    );
+}
+
+pub const Exporter = struct {
+    allocator: std.mem.Allocator,
+    listener: std.net.Server,
+    thread: std.Thread,
+
+    mutex: std.Thread.Mutex = .{},
+    /// Latest rendered exposition; swapped whole on publish.
+    body: []u8 = &.{},
+    running: std.atomic.Value(bool) = std.atomic.Value(bool).init(true),
+
+    /// Binds `addr:port` and starts answering scrapes.
+    pub fn start(allocator: std.mem.Allocator, listen: []const u8) !*Exporter {
+        const colon = std.mem.lastIndexOfScalar(u8, listen, ':') orelse
+            return error.InvalidAddress;
+        const port = std.fmt.parseInt(u16, listen[colon + 1 ..], 10) catch
+            return error.InvalidAddress;
+        const address = try std.net.Address.parseIp(listen[0..colon], port);
+        const listener = try address.listen(.{ .reuse_address = true });
+
+        const exporter = try allocator.create(Exporter);
+        errdefer allocator.destroy(exporter);
+        exporter.* = .{
+            .allocator = allocator,
+            .listener = listener,
+            .thread = undefined,
+        };
+        exporter.thread = try std.Thread.spawn(.{}, serveLoop, .{exporter});
+        return exporter;
+    }
+
+    pub fn stop(self: *Exporter) void {
+        self.running.store(false, .release);
+        // Closing the listener unblocks accept() in the thread.
+        self.listener.deinit();
+        self.thread.join();
+
+        self.allocator.free(self.body);
+        const allocator = self.allocator;
+        allocator.destroy(self);
+    }
+
+    /// Publishes a new snapshot; called once per metrics interval.
+    pub fn publish(self: *Exporter, snapshot: snapshot_mod.Snapshot) !void {
+        const body = try render(self.allocator, snapshot);
+        self.mutex.lock();
+        defer self.mutex.unlock();
+        self.allocator.free(self.body);
+        self.body = body;
+    }
+
+    fn serveLoop(self: *Exporter) void {
+        while (self.running.load(.acquire)) {
+            const connection = self.listener.accept() catch return;
+            self.handleScrape(connection);
+        }
+    }
+
+    fn handleScrape(self: *Exporter, connection: std.net.Server.Connection) void {
+        defer connection.stream.close();
+
+        // Read and discard the request line and headers; any GET gets the
+        // metrics, which is all a scraper ever sends.
+        var request: [2048]u8 = undefined;
+        _ = connection.stream.read(&request) catch return;
+
+        self.mutex.lock();
+        const body = self.allocator.dupe(u8, self.body) catch {
+            self.mutex.unlock();
+            return;
+        };
+        self.mutex.unlock();
+        defer self.allocator.free(body);
+
+        const header = std.fmt.allocPrint(
+            self.allocator,
+            "HTTP/1.1 200 OK\r\n" ++
+                "Content-Type: text/plain; version=0.0.4\r\n" ++
+                "Content-Length: {d}\r\n" ++
+                "Connection: close\r\n\r\n",
+            .{body.len},
+        ) catch return;
+        defer self.allocator.free(header);
+
+        connection.stream.writeAll(header) catch return;
+        connection.stream.writeAll(body) catch return;
+    }
+};
+
+test "render emits the fps gauge with the target label" {
+    const body = try render(std.testing.allocator, .{
+        .target = "eDP-1",
+        .fps = 59.94,
+        .frames_rendered = 1234,
+        .decoder = "vah264dec",
+        .hw_decode = true,
+        .buffer_path = "dmabuf",
+    });
+    defer std.testing.allocator.free(body);
+
+    try std.testing.expect(std.mem.indexOf(u8, body, "waystream_fps{target=\"eDP-1\"} 59.94\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, body, "waystream_frames_rendered_total{target=\"eDP-1\"} 1234\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, body, "decoder=\"vah264dec\",hw=\"true\",buffer_path=\"dmabuf\"") != null);
+}
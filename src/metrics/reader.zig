@@ -0,0 +1,90 @@
+//! Change-driven snapshot reading.
+//!
+//! The GUI used to reload the snapshot file on a timer, and every other
+//! consumer (CLI status, scripts) grew its own variant of the same loop.
+//! `Reader.watch` is the one shared implementation: it rides the inotify
+//! watcher from the video hot-reload path — snapshots are replaced by
+//! rename, which is exactly the MOVED_TO case it filters for — and hands
+//! back a typed snapshot only when the file actually changed. Where
+//! inotify is unavailable it degrades to interval polling.
+
+const std = @import("std");
+const snapshot_mod = @import("snapshot.zig");
+const filewatch = @import("../playback/filewatch.zig");
+
+/// Reload interval when inotify could not be set up.
+pub const fallback_interval_ms: i64 = 500;
+
+pub const Reader = struct {
+    allocator: std.mem.Allocator,
+    path: []u8,
+    watcher: ?filewatch.FileWatcher,
+    /// True once a load has succeeded; until then poll() keeps trying so
+    /// a snapshot that appears after watch() is still picked up.
+    primed: bool = false,
+    last_poll_ms: i64 = 0,
+
+    /// Starts watching `path`. The file need not exist yet; the watch is
+    /// on its directory.
+    pub fn watch(allocator: std.mem.Allocator, path: []const u8) !Reader {
+        const owned = try allocator.dupe(u8, path);
+        errdefer allocator.free(owned);
+        return .{
+            .allocator = allocator,
+            .path = owned,
+            .watcher = filewatch.FileWatcher.init(allocator, owned) catch null,
+        };
+    }
+
+    pub fn deinit(self: *Reader) void {
+        if (self.watcher) |*watcher| watcher.deinit();
+        self.allocator.free(self.path);
+        self.* = undefined;
+    }
+
+    /// Returns a fresh snapshot when the file changed since the last call
+    /// (and unconditionally for the first successful load); null when
+    /// nothing changed or the file is not readable yet. Caller deinits
+    /// the returned snapshot.
+    pub fn poll(self: *Reader) ?snapshot_mod.LoadedSnapshot {
+        const now_ms = std.time.milliTimestamp();
+        const changed = if (self.watcher) |*watcher|
+            watcher.changed()
+        else
+            now_ms - self.last_poll_ms >= fallback_interval_ms;
+        if (self.primed and !changed) return null;
+
+        self.last_poll_ms = now_ms;
+        const loaded = snapshot_mod.load(self.allocator, self.path) catch return null;
+        self.primed = true;
+        return loaded;
+    }
+};
+
+test "poll returns a snapshot only when the file changes" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+    const path = try std.fs.path.join(std.testing.allocator, &.{ root, "t.json" });
+    defer std.testing.allocator.free(path);
+
+    var reader = try Reader.watch(std.testing.allocator, path);
+    defer reader.deinit();
+
+    // Nothing to read yet.
+    try std.testing.expect(reader.poll() == null);
+
+    try snapshot_mod.save(std.testing.allocator, path, .{ .target = "a", .fps = 30 });
+    var first = reader.poll() orelse return error.TestExpectedSnapshot;
+    defer first.deinit();
+    try std.testing.expectEqual(@as(f64, 30), first.snapshot.fps);
+
+    // Unchanged file: no reload.
+    try std.testing.expect(reader.poll() == null);
+
+    try snapshot_mod.save(std.testing.allocator, path, .{ .target = "a", .fps = 60 });
+    var second = reader.poll() orelse return error.TestExpectedSnapshot;
+    defer second.deinit();
+    try std.testing.expectEqual(@as(f64, 60), second.snapshot.fps);
+}
@@ -0,0 +1,145 @@
+//! Metrics directory retention.
+//!
+//! Every playback target leaves a snapshot file behind under the metrics
+//! directory, and nothing ever removed them: a machine that cycles through
+//! target names (per-output targets, test runs) accumulates files forever.
+//! Each player sweeps the directory once at startup against a retention
+//! policy — files past the age limit go first, then the oldest files until
+//! the count and total-size limits hold. A running player rewrites its
+//! file every metrics interval, so live targets always look fresh and are
+//! never swept.
+
+const std = @import("std");
+
+pub const Policy = struct {
+    /// Most snapshot files kept, newest first.
+    max_files: usize = 32,
+    /// Files untouched longer than this are removed regardless of count.
+    max_age_s: i64 = 7 * std.time.s_per_day,
+    /// Oldest files go until the directory fits under this many bytes.
+    max_total_bytes: u64 = 16 * 1024 * 1024,
+};
+
+pub const Entry = struct {
+    name: []const u8,
+    mtime_s: i64,
+    size: u64,
+};
+
+/// Indices into `entries` that the policy says to delete, oldest first.
+/// Caller frees the result.
+pub fn selectVictims(
+    allocator: std.mem.Allocator,
+    entries: []const Entry,
+    policy: Policy,
+    now_s: i64,
+) ![]usize {
+    // Oldest-first order so count/size trimming removes the right files.
+    const order = try allocator.alloc(usize, entries.len);
+    defer allocator.free(order);
+    for (order, 0..) |*slot, i| slot.* = i;
+    std.mem.sort(usize, order, entries, entryOlder);
+
+    var victims: std.ArrayList(usize) = .empty;
+    errdefer victims.deinit(allocator);
+
+    var kept = entries.len;
+    var total: u64 = 0;
+    for (entries) |entry| total += entry.size;
+
+    for (order) |index| {
+        const entry = entries[index];
+        const expired = now_s - entry.mtime_s > policy.max_age_s;
+        const over_count = kept > policy.max_files;
+        const over_size = total > policy.max_total_bytes;
+        if (!expired and !over_count and !over_size) break;
+        try victims.append(allocator, index);
+        kept -= 1;
+        total -= entry.size;
+    }
+
+    return victims.toOwnedSlice(allocator);
+}
+
+fn entryOlder(entries: []const Entry, a: usize, b: usize) bool {
+    return entries[a].mtime_s < entries[b].mtime_s;
+}
+
+/// Applies the policy to every regular file in `dir_path`; missing
+/// directories are fine (nothing has written metrics yet). Returns how
+/// many files were removed.
+pub fn sweepDir(allocator: std.mem.Allocator, dir_path: []const u8, policy: Policy) !usize {
+    var dir = std.fs.cwd().openDir(dir_path, .{ .iterate = true }) catch return 0;
+    defer dir.close();
+
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const arena_allocator = arena.allocator();
+
+    var entries: std.ArrayList(Entry) = .empty;
+    var it = dir.iterate();
+    while (try it.next()) |dirent| {
+        if (dirent.kind != .file) continue;
+        const stat = dir.statFile(dirent.name) catch continue;
+        try entries.append(arena_allocator, .{
+            .name = try arena_allocator.dupe(u8, dirent.name),
+            .mtime_s = @intCast(@divTrunc(stat.mtime, std.time.ns_per_s)),
+            .size = stat.size,
+        });
+    }
+
+    const victims = try selectVictims(arena_allocator, entries.items, policy, std.time.timestamp());
+    for (victims) |index| {
+        dir.deleteFile(entries.items[index].name) catch continue;
+    }
+    return victims.len;
+}
+
+test "fresh files under every limit are kept" {
+    const entries = [_]Entry{
+        .{ .name = "a.json", .mtime_s = 990, .size = 100 },
+        .{ .name = "b.json", .mtime_s = 995, .size = 100 },
+    };
+    const victims = try selectVictims(std.testing.allocator, &entries, .{}, 1000);
+    defer std.testing.allocator.free(victims);
+    try std.testing.expectEqual(@as(usize, 0), victims.len);
+}
+
+test "expired files go even when the count is fine" {
+    const entries = [_]Entry{
+        .{ .name = "old.json", .mtime_s = 0, .size = 100 },
+        .{ .name = "new.json", .mtime_s = 999, .size = 100 },
+    };
+    const policy: Policy = .{ .max_age_s = 100 };
+    const victims = try selectVictims(std.testing.allocator, &entries, policy, 1000);
+    defer std.testing.allocator.free(victims);
+    try std.testing.expectEqual(@as(usize, 1), victims.len);
+    try std.testing.expectEqual(@as(usize, 0), victims[0]);
+}
+
+test "oldest files trim first for count and size limits" {
+    const entries = [_]Entry{
+        .{ .name = "c.json", .mtime_s = 30, .size = 10 },
+        .{ .name = "a.json", .mtime_s = 10, .size = 10 },
+        .{ .name = "b.json", .mtime_s = 20, .size = 10 },
+    };
+    const policy: Policy = .{ .max_files = 1, .max_age_s = 1000 };
+    const victims = try selectVictims(std.testing.allocator, &entries, policy, 40);
+    defer std.testing.allocator.free(victims);
+    // a (oldest) then b; c survives as the single kept file.
+    try std.testing.expectEqual(@as(usize, 2), victims.len);
+    try std.testing.expectEqual(@as(usize, 1), victims[0]);
+    try std.testing.expectEqual(@as(usize, 2), victims[1]);
+}
+
+test "sweepDir removes files over the count limit" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{ .sub_path = "a.json", .data = "{}" });
+    try tmp.dir.writeFile(.{ .sub_path = "b.json", .data = "{}" });
+    const root = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(root);
+
+    const removed = try sweepDir(std.testing.allocator, root, .{ .max_files = 1 });
+    try std.testing.expectEqual(@as(usize, 1), removed);
+}
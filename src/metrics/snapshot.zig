@@ -0,0 +1,380 @@
+//! Playback metrics snapshots.
+//!
+//! The play process periodically writes a small JSON document describing the
+//! current playback state; the GUI and other tooling read it. Parsing is
+//! forward compatible: unknown fields are ignored and a snapshot written by a
+//! newer (or older) player still yields the fields we know about, along with
+//! a `SchemaCompat` so front-ends can tell the user about the skew.
+
+const std = @import("std");
+
+/// Schema version this build reads and writes natively. v2 added the
+/// fixed-bucket frame-time histogram (`frame_hist`) and the environment
+/// context fields (`power` through `output_scale`); everything else is
+/// unchanged, so v1 readers keep working minus those fields.
+pub const supported_schema_version: u32 = 2;
+
+/// How the loaded snapshot's schema relates to ours.
+pub const SchemaCompat = enum {
+    exact,
+    /// Snapshot was written by a newer player than this reader.
+    player_newer,
+    /// Snapshot was written by an older player than this reader.
+    player_older,
+
+    pub fn describe(self: SchemaCompat) ?[]const u8 {
+        return switch (self) {
+            .exact => null,
+            .player_newer => "player is newer than this GUI",
+            .player_older => "player is older than this GUI",
+        };
+    }
+};
+
+pub const Snapshot = struct {
+    schema_version: u32 = supported_schema_version,
+    updated_unix_ms: i64 = 0,
+    target: []const u8 = "",
+    /// Output (monitor) this snapshot describes. Today one process drives
+    /// one surface and leaves this empty; once single-process multi-output
+    /// lands, each surface writes its own snapshot keyed by output name
+    /// (see `outputPath`) instead of blending them into one average.
+    output: []const u8 = "",
+    video: []const u8 = "",
+    fps: f64 = 0,
+    frames_rendered: u64 = 0,
+    /// Stale frames skipped by keep-latest queueing when rendering lags.
+    frames_dropped: u64 = 0,
+    /// Frames the renderer chose not to compose (CPU budget, stale
+    /// compose jobs replaced before the worker got to them).
+    frames_skipped: u64 = 0,
+    /// Frames presented more than half a frame interval behind schedule.
+    frames_late: u64 = 0,
+    paused: bool = false,
+    /// Free-form status notes (e.g. adaptive-quality decisions).
+    notes: []const u8 = "",
+    /// Negotiated source resolution; 0 until the pipeline prerolls.
+    src_width: u32 = 0,
+    src_height: u32 = 0,
+    /// Source framerate from the caps; 0 for variable/live streams.
+    src_fps: f64 = 0,
+    /// Negotiated pixel format ("rgba8", "nv12", ...), "" when unknown.
+    src_format: []const u8 = "",
+    /// Demuxer factory name identifying the container, "" when unknown.
+    container: []const u8 = "",
+    /// Video decoder factory name, "" until decodebin picks one.
+    decoder: []const u8 = "",
+    /// True when the decoder runs in hardware.
+    hw_decode: bool = false,
+    /// Presentation path chosen at startup ("shm"/"dmabuf"), "" if unset.
+    buffer_path: []const u8 = "",
+    /// Measured cost of one shm frame copy at startup.
+    shm_copy_ms: f64 = 0,
+    /// Bytes currently held in frame stores and buffer pools.
+    mem_bytes: u64 = 0,
+    /// Cold-start time from process start to the first presented frame.
+    first_frame_ms: f64 = 0,
+    /// GPU/video-engine utilization in percent; -1 when the driver does
+    /// not expose it.
+    gpu_busy_pct: i32 = -1,
+    /// Frame-time distribution over the recent window.
+    frame_p95_ms: f64 = 0,
+    frame_p99_ms: f64 = 0,
+    frame_max_ms: f64 = 0,
+    /// Frame-time standard deviation; the stutter signal.
+    frame_jitter_ms: f64 = 0,
+    /// Cumulative frame-time histogram with fixed bounds, e.g.
+    /// "le8:0,le12:4,...,inf:0"; lets tooling compute percentiles without
+    /// us shipping raw samples.
+    frame_hist: []const u8 = "",
+    /// Appsink-delivery-to-present latency summary.
+    latency_avg_ms: f64 = 0,
+    latency_max_ms: f64 = 0,
+    /// Compact latency histogram, e.g. "le2:0,le4:12,...,inf:1".
+    latency_hist: []const u8 = "",
+    /// Power source at write time ("ac"/"battery"/"unknown"); battery
+    /// machines throttle in ways worth knowing about in a bug report.
+    power: []const u8 = "",
+    /// Compositor/desktop from XDG_CURRENT_DESKTOP, "" when unset.
+    compositor: []const u8 = "",
+    /// How the video is placed on the output ("fit"/"cover").
+    scale_mode: []const u8 = "",
+    /// Integer output scale factor the buffer is sized for.
+    output_scale: u32 = 1,
+    /// Recent pipeline events, oldest first, as "unix_ms text" entries
+    /// joined with '|'; bounded by the event ring's capacity.
+    events: []const u8 = "",
+};
+
+pub const LoadedSnapshot = struct {
+    arena: std.heap.ArenaAllocator,
+    snapshot: Snapshot,
+    compat: SchemaCompat,
+
+    pub fn deinit(self: *LoadedSnapshot) void {
+        self.arena.deinit();
+        self.* = undefined;
+    }
+};
+
+pub const LoadError = error{ ReadFailed, MalformedSnapshot } || std.mem.Allocator.Error;
+
+/// Reads a snapshot file, tolerating unknown fields and schema skew.
+pub fn load(allocator: std.mem.Allocator, path: []const u8) LoadError!LoadedSnapshot {
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    errdefer arena.deinit();
+    const arena_allocator = arena.allocator();
+
+    const data = std.fs.cwd().readFileAlloc(arena_allocator, path, 1024 * 1024) catch
+        return LoadError.ReadFailed;
+
+    const parsed = std.json.parseFromSliceLeaky(std.json.Value, arena_allocator, data, .{}) catch
+        return LoadError.MalformedSnapshot;
+    const root = switch (parsed) {
+        .object => |object| object,
+        else => return LoadError.MalformedSnapshot,
+    };
+
+    var snapshot: Snapshot = .{};
+    snapshot.schema_version = getU32(root, "schema_version") orelse
+        return LoadError.MalformedSnapshot;
+    snapshot.updated_unix_ms = getI64(root, "updated_unix_ms") orelse 0;
+    snapshot.target = getString(root, "target") orelse "";
+    snapshot.output = getString(root, "output") orelse "";
+    snapshot.video = getString(root, "video") orelse "";
+    snapshot.fps = getF64(root, "fps") orelse 0;
+    snapshot.frames_rendered = getU64(root, "frames_rendered") orelse 0;
+    snapshot.frames_dropped = getU64(root, "frames_dropped") orelse 0;
+    snapshot.frames_skipped = getU64(root, "frames_skipped") orelse 0;
+    snapshot.frames_late = getU64(root, "frames_late") orelse 0;
+    snapshot.paused = getBool(root, "paused") orelse false;
+    snapshot.notes = getString(root, "notes") orelse "";
+    snapshot.src_width = getU32(root, "src_width") orelse 0;
+    snapshot.src_height = getU32(root, "src_height") orelse 0;
+    snapshot.src_fps = getF64(root, "src_fps") orelse 0;
+    snapshot.src_format = getString(root, "src_format") orelse "";
+    snapshot.container = getString(root, "container") orelse "";
+    snapshot.decoder = getString(root, "decoder") orelse "";
+    snapshot.hw_decode = getBool(root, "hw_decode") orelse false;
+    snapshot.buffer_path = getString(root, "buffer_path") orelse "";
+    snapshot.shm_copy_ms = getF64(root, "shm_copy_ms") orelse 0;
+    snapshot.mem_bytes = getU64(root, "mem_bytes") orelse 0;
+    snapshot.first_frame_ms = getF64(root, "first_frame_ms") orelse 0;
+    snapshot.gpu_busy_pct = getI32(root, "gpu_busy_pct") orelse -1;
+    snapshot.frame_p95_ms = getF64(root, "frame_p95_ms") orelse 0;
+    snapshot.frame_p99_ms = getF64(root, "frame_p99_ms") orelse 0;
+    snapshot.frame_max_ms = getF64(root, "frame_max_ms") orelse 0;
+    snapshot.frame_jitter_ms = getF64(root, "frame_jitter_ms") orelse 0;
+    snapshot.frame_hist = getString(root, "frame_hist") orelse "";
+    snapshot.latency_avg_ms = getF64(root, "latency_avg_ms") orelse 0;
+    snapshot.latency_max_ms = getF64(root, "latency_max_ms") orelse 0;
+    snapshot.latency_hist = getString(root, "latency_hist") orelse "";
+    snapshot.power = getString(root, "power") orelse "";
+    snapshot.compositor = getString(root, "compositor") orelse "";
+    snapshot.scale_mode = getString(root, "scale_mode") orelse "";
+    snapshot.output_scale = getU32(root, "output_scale") orelse 1;
+    snapshot.events = getString(root, "events") orelse "";
+
+    const compat: SchemaCompat = if (snapshot.schema_version == supported_schema_version)
+        .exact
+    else if (snapshot.schema_version > supported_schema_version)
+        .player_newer
+    else
+        .player_older;
+
+    return .{
+        .arena = arena,
+        .snapshot = snapshot,
+        .compat = compat,
+    };
+}
+
+/// Serializes a snapshot as one newline-terminated JSON line; the same
+/// shape lands in the snapshot file and on the metrics stream socket.
+/// Caller frees the result.
+pub fn renderLine(allocator: std.mem.Allocator, snapshot: Snapshot) ![]u8 {
+    // String fields (video paths, notes, event text) can carry quotes and
+    // backslashes; escape them through a scratch arena so each field does
+    // not need an individual free.
+    var scratch_arena = std.heap.ArenaAllocator.init(allocator);
+    defer scratch_arena.deinit();
+    const scratch = scratch_arena.allocator();
+
+    return std.fmt.allocPrint(
+        allocator,
+        "{{\"schema_version\":{d},\"updated_unix_ms\":{d},\"target\":\"{s}\"," ++
+            "\"output\":\"{s}\"," ++
+            "\"video\":\"{s}\",\"fps\":{d:.2},\"frames_rendered\":{d}," ++
+            "\"frames_dropped\":{d},\"frames_skipped\":{d},\"frames_late\":{d}," ++
+            "\"paused\":{}," ++
+            "\"notes\":\"{s}\",\"src_width\":{d},\"src_height\":{d},\"src_fps\":{d:.3}," ++
+            "\"src_format\":\"{s}\"," ++
+            "\"container\":\"{s}\",\"decoder\":\"{s}\",\"hw_decode\":{}," ++
+            "\"buffer_path\":\"{s}\",\"shm_copy_ms\":{d:.3},\"mem_bytes\":{d}," ++
+            "\"first_frame_ms\":{d:.0},\"gpu_busy_pct\":{d}," ++
+            "\"frame_p95_ms\":{d:.2},\"frame_p99_ms\":{d:.2}," ++
+            "\"frame_max_ms\":{d:.2},\"frame_jitter_ms\":{d:.2}," ++
+            "\"frame_hist\":\"{s}\"," ++
+            "\"latency_avg_ms\":{d:.2},\"latency_max_ms\":{d:.2}," ++
+            "\"latency_hist\":\"{s}\"," ++
+            "\"power\":\"{s}\",\"compositor\":\"{s}\"," ++
+            "\"scale_mode\":\"{s}\",\"output_scale\":{d}," ++
+            "\"events\":\"{s}\"}}\n",
+        .{
+            snapshot.schema_version,
+            snapshot.updated_unix_ms,
+            try escapeJson(scratch, snapshot.target),
+            try escapeJson(scratch, snapshot.output),
+            try escapeJson(scratch, snapshot.video),
+            snapshot.fps,
+            snapshot.frames_rendered,
+            snapshot.frames_dropped,
+            snapshot.frames_skipped,
+            snapshot.frames_late,
+            snapshot.paused,
+            try escapeJson(scratch, snapshot.notes),
+            snapshot.src_width,
+            snapshot.src_height,
+            snapshot.src_fps,
+            try escapeJson(scratch, snapshot.src_format),
+            try escapeJson(scratch, snapshot.container),
+            try escapeJson(scratch, snapshot.decoder),
+            snapshot.hw_decode,
+            try escapeJson(scratch, snapshot.buffer_path),
+            snapshot.shm_copy_ms,
+            snapshot.mem_bytes,
+            snapshot.first_frame_ms,
+            snapshot.gpu_busy_pct,
+            snapshot.frame_p95_ms,
+            snapshot.frame_p99_ms,
+            snapshot.frame_max_ms,
+            snapshot.frame_jitter_ms,
+            try escapeJson(scratch, snapshot.frame_hist),
+            snapshot.latency_avg_ms,
+            snapshot.latency_max_ms,
+            try escapeJson(scratch, snapshot.latency_hist),
+            try escapeJson(scratch, snapshot.power),
+            try escapeJson(scratch, snapshot.compositor),
+            try escapeJson(scratch, snapshot.scale_mode),
+            snapshot.output_scale,
+            try escapeJson(scratch, snapshot.events),
+        },
+    );
+}
+
+/// Returns `text` unchanged when it is already a valid JSON string body,
+/// otherwise an escaped copy allocated from `allocator`.
+fn escapeJson(allocator: std.mem.Allocator, text: []const u8) ![]const u8 {
+    const needs_escaping = for (text) |char| {
+        if (char == '"' or char == '\\' or char < 0x20) break true;
+    } else false;
+    if (!needs_escaping) return text;
+
+    var out: std.ArrayList(u8) = .empty;
+    defer out.deinit(allocator);
+    for (text) |char| switch (char) {
+        '"' => try out.appendSlice(allocator, "\\\""),
+        '\\' => try out.appendSlice(allocator, "\\\\"),
+        '\n' => try out.appendSlice(allocator, "\\n"),
+        '\r' => try out.appendSlice(allocator, "\\r"),
+        '\t' => try out.appendSlice(allocator, "\\t"),
+        else => if (char < 0x20) {
+            var buffer: [6]u8 = undefined;
+            const escaped = std.fmt.bufPrint(&buffer, "\\u{x:0>4}", .{char}) catch unreachable;
+            try out.appendSlice(allocator, escaped);
+        } else {
+            try out.append(allocator, char);
+        },
+    };
+    return out.toOwnedSlice(allocator);
+}
+
+/// Atomically rewrites the snapshot file (write-then-rename).
+pub fn save(allocator: std.mem.Allocator, path: []const u8, snapshot: Snapshot) !void {
+    const json = try renderLine(allocator, snapshot);
+    defer allocator.free(json);
+
+    const tmp_path = try std.fmt.allocPrint(allocator, "{s}.tmp", .{path});
+    defer allocator.free(tmp_path);
+
+    if (std.fs.path.dirname(path)) |dir| {
+        std.fs.cwd().makePath(dir) catch {};
+    }
+    const file = try std.fs.cwd().createFile(tmp_path, .{});
+    {
+        defer file.close();
+        try file.writeAll(json);
+    }
+    try std.fs.cwd().rename(tmp_path, path);
+}
+
+/// Default snapshot location for a playback target, preferring
+/// XDG_RUNTIME_DIR and falling back to /tmp.
+pub fn defaultPath(allocator: std.mem.Allocator, target: []const u8) ![]u8 {
+    const runtime_dir = std.posix.getenv("XDG_RUNTIME_DIR") orelse "/tmp";
+    return std.fmt.allocPrint(allocator, "{s}/waystream/metrics/{s}.json", .{ runtime_dir, target });
+}
+
+/// Snapshot location for one surface of a multi-output target, keyed as
+/// `<target>@<output>`. An empty output name degrades to `defaultPath`,
+/// so single-surface callers need not branch.
+pub fn outputPath(
+    allocator: std.mem.Allocator,
+    target: []const u8,
+    output: []const u8,
+) ![]u8 {
+    if (output.len == 0) return defaultPath(allocator, target);
+    const runtime_dir = std.posix.getenv("XDG_RUNTIME_DIR") orelse "/tmp";
+    return std.fmt.allocPrint(
+        allocator,
+        "{s}/waystream/metrics/{s}@{s}.json",
+        .{ runtime_dir, target, output },
+    );
+}
+
+fn getU32(object: std.json.ObjectMap, key: []const u8) ?u32 {
+    const value = getI64(object, key) orelse return null;
+    if (value < 0 or value > std.math.maxInt(u32)) return null;
+    return @intCast(value);
+}
+
+fn getU64(object: std.json.ObjectMap, key: []const u8) ?u64 {
+    const value = getI64(object, key) orelse return null;
+    if (value < 0) return null;
+    return @intCast(value);
+}
+
+fn getI32(object: std.json.ObjectMap, key: []const u8) ?i32 {
+    const value = getI64(object, key) orelse return null;
+    if (value < std.math.minInt(i32) or value > std.math.maxInt(i32)) return null;
+    return @intCast(value);
+}
+
+fn getI64(object: std.json.ObjectMap, key: []const u8) ?i64 {
+    return switch (object.get(key) orelse return null) {
+        .integer => |value| value,
+        else => null,
+    };
+}
+
+fn getF64(object: std.json.ObjectMap, key: []const u8) ?f64 {
+    return switch (object.get(key) orelse return null) {
+        .float => |value| value,
+        .integer => |value| @floatFromInt(value),
+        else => null,
+    };
+}
+
+fn getString(object: std.json.ObjectMap, key: []const u8) ?[]const u8 {
+    return switch (object.get(key) orelse return null) {
+        .string => |value| value,
+        else => null,
+    };
+}
+
+fn getBool(object: std.json.ObjectMap, key: []const u8) ?bool {
+    return switch (object.get(key) orelse return null) {
+        .bool => |value| value,
+        else => null,
+    };
+}
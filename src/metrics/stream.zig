@@ -0,0 +1,147 @@
+//! Push metrics over a Unix socket.
+//!
+//! The snapshot file forces front-ends onto a poll timer and occasionally
+//! hands them a stale read. With `--metrics-stream` the play process also
+//! listens on a per-target socket and pushes every metrics update to all
+//! connected subscribers as newline-delimited JSON — the same lines the
+//! snapshot file holds, so a subscriber can `nc -U` the socket or parse it
+//! with the loader it already has. Slow or dead subscribers are dropped
+//! rather than allowed to stall the playback loop.
+
+const std = @import("std");
+const snapshot_mod = @import("snapshot.zig");
+
+pub const Publisher = struct {
+    allocator: std.mem.Allocator,
+    path: []u8,
+    listener: std.net.Server,
+    thread: std.Thread,
+
+    mutex: std.Thread.Mutex = .{},
+    subscribers: std.ArrayList(std.net.Stream) = .empty,
+    running: std.atomic.Value(bool) = std.atomic.Value(bool).init(true),
+
+    /// Stream socket path for a playback target.
+    pub fn socketPath(allocator: std.mem.Allocator, target: []const u8) ![]u8 {
+        const runtime_dir = std.posix.getenv("XDG_RUNTIME_DIR") orelse "/tmp";
+        return std.fmt.allocPrint(allocator, "{s}/waystream/metrics/{s}.sock", .{ runtime_dir, target });
+    }
+
+    pub fn start(allocator: std.mem.Allocator, target: []const u8) !*Publisher {
+        const path = try socketPath(allocator, target);
+        errdefer allocator.free(path);
+
+        if (std.fs.path.dirname(path)) |dir| {
+            std.fs.cwd().makePath(dir) catch {};
+        }
+        // A previous run may have left the socket behind.
+        std.fs.cwd().deleteFile(path) catch {};
+
+        const address = try std.net.Address.initUnix(path);
+        const listener = try address.listen(.{});
+
+        const publisher = try allocator.create(Publisher);
+        errdefer allocator.destroy(publisher);
+        publisher.* = .{
+            .allocator = allocator,
+            .path = path,
+            .listener = listener,
+            .thread = undefined,
+        };
+        publisher.thread = try std.Thread.spawn(.{}, acceptLoop, .{publisher});
+        return publisher;
+    }
+
+    pub fn stop(self: *Publisher) void {
+        self.running.store(false, .release);
+        // Closing the listener unblocks accept() in the thread.
+        self.listener.deinit();
+        self.thread.join();
+
+        self.mutex.lock();
+        for (self.subscribers.items) |stream| stream.close();
+        self.subscribers.deinit(self.allocator);
+        self.mutex.unlock();
+
+        std.fs.cwd().deleteFile(self.path) catch {};
+        const allocator = self.allocator;
+        allocator.free(self.path);
+        allocator.destroy(self);
+    }
+
+    /// Pushes one update to every subscriber; ones that cannot take the
+    /// write are closed and forgotten.
+    pub fn publish(self: *Publisher, snapshot: snapshot_mod.Snapshot) !void {
+        const line = try snapshot_mod.renderLine(self.allocator, snapshot);
+        defer self.allocator.free(line);
+
+        self.mutex.lock();
+        defer self.mutex.unlock();
+        var i: usize = 0;
+        while (i < self.subscribers.items.len) {
+            const stream = self.subscribers.items[i];
+            stream.writeAll(line) catch {
+                stream.close();
+                _ = self.subscribers.swapRemove(i);
+                continue;
+            };
+            i += 1;
+        }
+    }
+
+    fn acceptLoop(self: *Publisher) void {
+        while (self.running.load(.acquire)) {
+            const connection = self.listener.accept() catch return;
+            self.mutex.lock();
+            self.subscribers.append(self.allocator, connection.stream) catch {
+                connection.stream.close();
+            };
+            self.mutex.unlock();
+        }
+    }
+};
+
+test "subscribers receive published lines" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    // Build the socket inside the test's own directory to stay parallel-safe.
+    const path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(path);
+    const socket_path = try std.fmt.allocPrint(std.testing.allocator, "{s}/m.sock", .{path});
+    defer std.testing.allocator.free(socket_path);
+
+    const address = try std.net.Address.initUnix(socket_path);
+    var listener = try address.listen(.{});
+
+    const publisher = try std.testing.allocator.create(Publisher);
+    publisher.* = .{
+        .allocator = std.testing.allocator,
+        .path = try std.testing.allocator.dupe(u8, socket_path),
+        .listener = listener,
+        .thread = undefined,
+    };
+    publisher.thread = try std.Thread.spawn(.{}, Publisher.acceptLoop, .{publisher});
+    defer publisher.stop();
+
+    const client = try std.net.connectUnixSocket(socket_path);
+    defer client.close();
+
+    // The accept thread registers the subscriber asynchronously.
+    var waited_ms: u32 = 0;
+    while (waited_ms < 1000) : (waited_ms += 1) {
+        publisher.mutex.lock();
+        const ready = publisher.subscribers.items.len == 1;
+        publisher.mutex.unlock();
+        if (ready) break;
+        std.Thread.sleep(std.time.ns_per_ms);
+    }
+
+    try publisher.publish(.{ .target = "test", .fps = 30 });
+
+    var buffer: [512]u8 = undefined;
+    const len = try client.read(&buffer);
+    try std.testing.expect(len > 0);
+    try std.testing.expect(std.mem.indexOf(u8, buffer[0..len], "\"target\":\"test\"") != null);
+    try std.testing.expect(buffer[len - 1] == '\n');
+}
@@ -0,0 +1,101 @@
+//! Adaptive quality control.
+//!
+//! Watches the measured render FPS and, when it stays under the configured
+//! threshold, steps the decode resolution down (each step halves both axes)
+//! instead of letting playback stutter. Recovery steps back up once the
+//! rate holds comfortably above the threshold. Every decision is reported
+//! so it can be recorded in the metrics notes.
+
+const std = @import("std");
+const layout = @import("../render/layout.zig");
+
+pub const Policy = struct {
+    /// FPS below this triggers a downscale step.
+    min_fps: f64,
+    /// Consecutive low intervals required before stepping down.
+    sustain_intervals: u32 = 3,
+    /// Consecutive healthy intervals required before stepping back up.
+    recover_intervals: u32 = 10,
+    /// Margin (multiplier) above min_fps considered healthy.
+    recover_margin: f64 = 1.5,
+    /// Maximum downscale steps (2 = quarter resolution).
+    max_steps: u8 = 2,
+};
+
+pub const Decision = enum {
+    none,
+    downscaled,
+    upscaled,
+};
+
+pub const Adaptive = struct {
+    policy: Policy,
+    step: u8 = 0,
+    low_intervals: u32 = 0,
+    healthy_intervals: u32 = 0,
+
+    pub fn init(policy: Policy) Adaptive {
+        return .{ .policy = policy };
+    }
+
+    /// Feed one measurement interval's FPS; returns what changed.
+    pub fn observe(self: *Adaptive, fps: f64) Decision {
+        if (fps < self.policy.min_fps) {
+            self.healthy_intervals = 0;
+            self.low_intervals += 1;
+            if (self.low_intervals >= self.policy.sustain_intervals and
+                self.step < self.policy.max_steps)
+            {
+                self.low_intervals = 0;
+                self.step += 1;
+                return .downscaled;
+            }
+            return .none;
+        }
+
+        self.low_intervals = 0;
+        if (fps >= self.policy.min_fps * self.policy.recover_margin and self.step > 0) {
+            self.healthy_intervals += 1;
+            if (self.healthy_intervals >= self.policy.recover_intervals) {
+                self.healthy_intervals = 0;
+                self.step -= 1;
+                return .upscaled;
+            }
+        } else {
+            self.healthy_intervals = 0;
+        }
+        return .none;
+    }
+
+    /// Decode size for the current step.
+    pub fn scaledSize(self: *const Adaptive, full: layout.Size) layout.Size {
+        const shift: u5 = @intCast(self.step);
+        return .{
+            .width = @max(full.width >> shift, 1),
+            .height = @max(full.height >> shift, 1),
+        };
+    }
+};
+
+test "sustained low fps steps down, recovery steps back up" {
+    var adaptive = Adaptive.init(.{ .min_fps = 20, .sustain_intervals = 2, .recover_intervals = 2 });
+
+    try std.testing.expectEqual(Decision.none, adaptive.observe(10));
+    try std.testing.expectEqual(Decision.downscaled, adaptive.observe(10));
+    try std.testing.expectEqual(@as(u8, 1), adaptive.step);
+
+    const full: layout.Size = .{ .width = 1920, .height = 1080 };
+    try std.testing.expectEqual(@as(u32, 960), adaptive.scaledSize(full).width);
+
+    // One healthy interval is not enough; two are.
+    try std.testing.expectEqual(Decision.none, adaptive.observe(60));
+    try std.testing.expectEqual(Decision.upscaled, adaptive.observe(60));
+    try std.testing.expectEqual(@as(u8, 0), adaptive.step);
+}
+
+test "never exceeds max steps" {
+    var adaptive = Adaptive.init(.{ .min_fps = 20, .sustain_intervals = 1, .max_steps = 1 });
+    try std.testing.expectEqual(Decision.downscaled, adaptive.observe(5));
+    try std.testing.expectEqual(Decision.none, adaptive.observe(5));
+    try std.testing.expectEqual(@as(u8, 1), adaptive.step);
+}
@@ -0,0 +1,130 @@
+//! CPU budget control.
+//!
+//! `--cpu-budget <percent>` caps how much of one core the player may spend.
+//! Each metrics interval the player samples its own CPU time
+//! (CLOCK_PROCESS_CPUTIME_ID) and feeds the utilization here; sustained
+//! overruns first skip frames (present every 2nd, then every 4th) and then
+//! step the decode resolution down, mirroring the adaptive-quality
+//! escalation in the other direction. Every decision is reported so it can
+//! land in the metrics notes.
+
+const std = @import("std");
+
+pub const Policy = struct {
+    /// Budget as a fraction of one core (0.10 = 10%).
+    budget: f64,
+    /// Consecutive over-budget intervals required before throttling more.
+    sustain_intervals: u32 = 3,
+    /// Consecutive intervals comfortably under budget before relaxing.
+    recover_intervals: u32 = 10,
+    /// Fraction of the budget considered comfortable for recovery.
+    recover_margin: f64 = 0.7,
+};
+
+pub const Decision = enum {
+    none,
+    throttled,
+    relaxed,
+};
+
+/// Escalation ladder: each level trades more smoothness for less CPU.
+/// Levels 1–2 present every 2nd / 4th frame; 3–4 also halve / quarter the
+/// decode resolution.
+pub const max_level = 4;
+
+pub const Budget = struct {
+    policy: Policy,
+    level: u8 = 0,
+    over_intervals: u32 = 0,
+    under_intervals: u32 = 0,
+
+    pub fn init(policy: Policy) Budget {
+        return .{ .policy = policy };
+    }
+
+    /// Feed one interval's CPU utilization (fraction of one core).
+    pub fn observe(self: *Budget, utilization: f64) Decision {
+        if (utilization > self.policy.budget) {
+            self.under_intervals = 0;
+            self.over_intervals += 1;
+            if (self.over_intervals >= self.policy.sustain_intervals and
+                self.level < max_level)
+            {
+                self.over_intervals = 0;
+                self.level += 1;
+                return .throttled;
+            }
+            return .none;
+        }
+
+        self.over_intervals = 0;
+        if (utilization <= self.policy.budget * self.policy.recover_margin and self.level > 0) {
+            self.under_intervals += 1;
+            if (self.under_intervals >= self.policy.recover_intervals) {
+                self.under_intervals = 0;
+                self.level -= 1;
+                return .relaxed;
+            }
+        } else {
+            self.under_intervals = 0;
+        }
+        return .none;
+    }
+
+    /// Present every Nth decoded frame at the current level.
+    pub fn frameDivisor(self: *const Budget) u32 {
+        return switch (self.level) {
+            0 => 1,
+            1 => 2,
+            else => 4,
+        };
+    }
+
+    /// Decode downscale steps (halvings) at the current level.
+    pub fn downscaleSteps(self: *const Budget) u8 {
+        return switch (self.level) {
+            0, 1, 2 => 0,
+            3 => 1,
+            else => 2,
+        };
+    }
+};
+
+/// One process-CPU-time reading; subtract consecutive samples to get the
+/// CPU spent in an interval.
+pub fn processCpuNs() u64 {
+    var ts: std.posix.timespec = undefined;
+    std.posix.clock_gettime(.PROCESS_CPUTIME_ID, &ts) catch return 0;
+    return @as(u64, @intCast(ts.sec)) * std.time.ns_per_s + @as(u64, @intCast(ts.nsec));
+}
+
+test "sustained overruns climb the ladder, recovery descends it" {
+    var budget = Budget.init(.{ .budget = 0.1, .sustain_intervals = 2, .recover_intervals = 2 });
+
+    try std.testing.expectEqual(Decision.none, budget.observe(0.3));
+    try std.testing.expectEqual(Decision.throttled, budget.observe(0.3));
+    try std.testing.expectEqual(@as(u32, 2), budget.frameDivisor());
+    try std.testing.expectEqual(@as(u8, 0), budget.downscaleSteps());
+
+    try std.testing.expectEqual(Decision.none, budget.observe(0.05));
+    try std.testing.expectEqual(Decision.relaxed, budget.observe(0.05));
+    try std.testing.expectEqual(@as(u32, 1), budget.frameDivisor());
+}
+
+test "deep levels also downscale and the ladder tops out" {
+    var budget = Budget.init(.{ .budget = 0.1, .sustain_intervals = 1 });
+    for (0..max_level) |_| _ = budget.observe(0.5);
+    try std.testing.expectEqual(@as(u8, max_level), budget.level);
+    try std.testing.expectEqual(@as(u32, 4), budget.frameDivisor());
+    try std.testing.expectEqual(@as(u8, 2), budget.downscaleSteps());
+    // Already at the top; further overruns change nothing.
+    try std.testing.expectEqual(Decision.none, budget.observe(0.5));
+}
+
+test "slightly under budget holds the level instead of flapping" {
+    var budget = Budget.init(.{ .budget = 0.1, .sustain_intervals = 1, .recover_intervals = 1 });
+    _ = budget.observe(0.5);
+    // Under budget but above the recovery margin: stay throttled.
+    try std.testing.expectEqual(Decision.none, budget.observe(0.09));
+    try std.testing.expectEqual(@as(u8, 1), budget.level);
+}
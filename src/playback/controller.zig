@@ -0,0 +1,215 @@
+//! In-process playback sessions.
+//!
+//! `player.run` drives a pipeline from its own render loop; embedders (the
+//! GUI, a future daemon) instead want playback running on a background
+//! thread with a handle to poke it. `PlaybackSession.start` owns the
+//! pipeline and its service thread; `Controller` is the cheap, clonable
+//! handle offering pause/resume/seek/swapInput/stop.
+
+const std = @import("std");
+const pipeline_mod = @import("pipeline.zig");
+
+const Pipeline = pipeline_mod.Pipeline;
+
+pub const SessionOptions = struct {
+    video: []const u8,
+    loop: bool = true,
+    open_options: pipeline_mod.OpenOptions = .{},
+};
+
+const Request = union(enum) {
+    pause,
+    resume_playback,
+    seek: f64,
+    set_rate: f64,
+    /// Owned by the request; freed by the service thread.
+    swap_input: []u8,
+    stop,
+};
+
+pub const PlaybackSession = struct {
+    allocator: std.mem.Allocator,
+    pipeline: Pipeline,
+    loop: bool,
+    open_options: pipeline_mod.OpenOptions = .{},
+    thread: std.Thread,
+
+    mutex: std.Thread.Mutex = .{},
+    requests: std.ArrayList(Request) = .empty,
+    stopping: std.atomic.Value(bool) = std.atomic.Value(bool).init(false),
+    /// Self-pipe that wakes the service thread out of poll() on enqueue.
+    wake_pipe: [2]std.posix.fd_t,
+
+    /// Opens the pipeline, starts playback, and spawns the service thread.
+    pub fn start(allocator: std.mem.Allocator, options: SessionOptions) !*PlaybackSession {
+        const uri = try pipeline_mod.pathToUri(allocator, options.video);
+        defer allocator.free(uri);
+
+        var pipeline = try Pipeline.open(allocator, uri, options.open_options);
+        errdefer pipeline.deinit();
+        try pipeline.play();
+
+        const wake_pipe = try std.posix.pipe2(.{ .NONBLOCK = true });
+        errdefer for (wake_pipe) |fd| std.posix.close(fd);
+
+        const session = try allocator.create(PlaybackSession);
+        errdefer allocator.destroy(session);
+        session.* = .{
+            .allocator = allocator,
+            .pipeline = pipeline,
+            .loop = options.loop,
+            .open_options = options.open_options,
+            .thread = undefined,
+            .wake_pipe = wake_pipe,
+        };
+        session.pipeline.watchDecoderSelection();
+        session.thread = try std.Thread.spawn(.{}, serviceLoop, .{session});
+        return session;
+    }
+
+    pub fn controller(self: *PlaybackSession) Controller {
+        return .{ .session = self };
+    }
+
+    /// Pulls the next decoded frame; safe to call from the embedder's
+    /// render thread while the service thread runs.
+    pub fn pullFrame(self: *PlaybackSession, timeout_ns: u64) ?pipeline_mod.Frame {
+        return self.pipeline.pullFrame(timeout_ns);
+    }
+
+    pub fn isPaused(self: *PlaybackSession) bool {
+        return self.pipeline.paused;
+    }
+
+    /// Stops playback, joins the service thread, and frees the session.
+    pub fn stop(self: *PlaybackSession) void {
+        self.enqueue(.stop);
+        self.thread.join();
+
+        self.mutex.lock();
+        for (self.requests.items) |request| freeRequest(self.allocator, request);
+        self.requests.deinit(self.allocator);
+        self.mutex.unlock();
+
+        self.pipeline.deinit();
+        for (self.wake_pipe) |fd| std.posix.close(fd);
+        const allocator = self.allocator;
+        allocator.destroy(self);
+    }
+
+    fn enqueue(self: *PlaybackSession, request: Request) void {
+        self.mutex.lock();
+        self.requests.append(self.allocator, request) catch freeRequest(self.allocator, request);
+        self.mutex.unlock();
+        // Wake the service thread; a full pipe already guarantees a wakeup.
+        _ = std.posix.write(self.wake_pipe[1], &.{1}) catch {};
+    }
+
+    fn takeRequest(self: *PlaybackSession) ?Request {
+        self.mutex.lock();
+        defer self.mutex.unlock();
+        if (self.requests.items.len == 0) return null;
+        return self.requests.orderedRemove(0);
+    }
+
+    /// Sleeps in poll() on the bus fd and the wake pipe instead of ticking
+    /// every few milliseconds; the thread only runs when a bus message or
+    /// a request actually arrives.
+    fn serviceLoop(self: *PlaybackSession) void {
+        while (!self.stopping.load(.acquire)) {
+            while (self.takeRequest()) |request| self.applyRequest(request);
+            if (self.stopping.load(.acquire)) return;
+
+            if (self.pipeline.pollBus() != .running) {
+                if (self.loop) self.pipeline.seekToStart() else {
+                    self.stopping.store(true, .release);
+                    return;
+                }
+            }
+
+            var fds = [_]std.posix.pollfd{
+                .{ .fd = self.pipeline.busFd(), .events = std.posix.POLL.IN, .revents = 0 },
+                .{ .fd = self.wake_pipe[0], .events = std.posix.POLL.IN, .revents = 0 },
+            };
+            _ = std.posix.poll(&fds, -1) catch return;
+            if (fds[1].revents & std.posix.POLL.IN != 0) self.drainWakePipe();
+        }
+    }
+
+    fn drainWakePipe(self: *PlaybackSession) void {
+        var buffer: [16]u8 = undefined;
+        while (true) {
+            const len = std.posix.read(self.wake_pipe[0], &buffer) catch return;
+            if (len < buffer.len) return;
+        }
+    }
+
+    fn applyRequest(self: *PlaybackSession, request: Request) void {
+        switch (request) {
+            .pause => self.pipeline.pause() catch {},
+            .resume_playback => self.pipeline.play() catch {},
+            .seek => |seconds| self.pipeline.seekTo(seconds),
+            .set_rate => |rate| self.pipeline.setRate(rate),
+            .swap_input => |video| {
+                defer self.allocator.free(video);
+                self.swapInput(video) catch |err| {
+                    std.log.err("swap_input failed: {s}", .{@errorName(err)});
+                };
+            },
+            .stop => self.stopping.store(true, .release),
+        }
+    }
+
+    fn swapInput(self: *PlaybackSession, video: []const u8) !void {
+        const uri = try pipeline_mod.pathToUri(self.allocator, video);
+        defer self.allocator.free(uri);
+
+        var replacement = try Pipeline.open(self.allocator, uri, self.open_options);
+        errdefer replacement.deinit();
+        try replacement.play();
+
+        self.pipeline.deinit();
+        self.pipeline = replacement;
+        self.pipeline.watchDecoderSelection();
+    }
+
+    fn freeRequest(allocator: std.mem.Allocator, request: Request) void {
+        switch (request) {
+            .swap_input => |video| allocator.free(video),
+            else => {},
+        }
+    }
+};
+
+/// Handle for driving a `PlaybackSession` from any thread.
+pub const Controller = struct {
+    session: *PlaybackSession,
+
+    pub fn pause(self: Controller) void {
+        self.session.enqueue(.pause);
+    }
+
+    pub fn unpause(self: Controller) void {
+        self.session.enqueue(.resume_playback);
+    }
+
+    pub fn seek(self: Controller, seconds: f64) void {
+        self.session.enqueue(.{ .seek = seconds });
+    }
+
+    pub fn setRate(self: Controller, rate: f64) void {
+        self.session.enqueue(.{ .set_rate = rate });
+    }
+
+    /// Switches playback to a different video. `video` is copied.
+    pub fn swapInput(self: Controller, video: []const u8) !void {
+        const owned = try self.session.allocator.dupe(u8, video);
+        self.session.enqueue(.{ .swap_input = owned });
+    }
+
+    /// Requests the session to stop; `PlaybackSession.stop` still must be
+    /// called to join and free it.
+    pub fn requestStop(self: Controller) void {
+        self.session.enqueue(.stop);
+    }
+};
@@ -0,0 +1,178 @@
+//! waylandsink embedding.
+//!
+//! Instead of pulling frames through an appsink and blitting on the CPU,
+//! this mode hands the pipeline a `waylandsink` and shares our wl_display
+//! with it through a GstContext. The sink then imports and commits dmabuf
+//! buffers itself — zero copies on our side. The sink dispatches its own
+//! event queue, so the caller only has to watch the bus.
+
+const std = @import("std");
+const c = @import("../gst/c.zig");
+const pipeline_mod = @import("pipeline.zig");
+
+pub const sink_name = "waystream-embed";
+pub const probe_name = "waystream-probe";
+
+const wayland_display_context_type = "GstWaylandDisplayHandleContextType";
+
+/// Hands `display` to every wayland-aware element in the pipeline, so the
+/// sink joins our connection instead of opening its own.
+pub fn shareDisplay(element: *c.GstElement, display: *anyopaque) void {
+    const context = c.gst_context_new(wayland_display_context_type, 1);
+    const structure = c.gst_context_writable_structure(context);
+    c.gst_structure_set(
+        structure,
+        "display",
+        c.G_TYPE_POINTER,
+        display,
+        @as(?[*:0]const u8, null),
+    );
+    c.gst_element_set_context(element, context);
+    c.gst_context_unref(context);
+}
+
+/// Points the sink at an existing wl_surface (it attaches a subsurface)
+/// instead of creating its own toplevel. The surface must belong to the
+/// display shared via `shareDisplay`.
+pub fn attachSurface(sink: *c.GstElement, surface: *anyopaque) void {
+    c.gst_video_overlay_set_window_handle(sink, @intFromPtr(surface));
+}
+
+pub const EmbeddedPlayer = struct {
+    element: *c.GstElement,
+    sink: *c.GstElement,
+    bus: *c.GstBus,
+    paused: bool = false,
+    allocator: std.mem.Allocator,
+    /// Frames the sink-side probe has seen; bumped from the streaming
+    /// thread, so heap-allocated to give the handoff callback a stable
+    /// address across copies of this struct.
+    frames: *std.atomic.Value(u64),
+
+    pub fn open(
+        allocator: std.mem.Allocator,
+        uri: [:0]const u8,
+        display: *anyopaque,
+    ) !EmbeddedPlayer {
+        pipeline_mod.Pipeline.initGst();
+
+        // The identity probe in front of the sink is how this backend
+        // counts presented frames: the sink commits buffers itself, so
+        // nothing ever comes back through an appsink to count.
+        const description = try std.fmt.allocPrintSentinel(
+            allocator,
+            "uridecodebin name=waystream-dec uri={s} " ++
+                "waystream-dec. ! videoconvert ! " ++
+                "identity name={s} signal-handoffs=true ! waylandsink name={s}",
+            .{ uri, probe_name, sink_name },
+            0,
+        );
+        defer allocator.free(description);
+
+        var parse_err: ?*c.GError = null;
+        const element = c.gst_parse_launch(description, &parse_err) orelse {
+            if (parse_err) |err| {
+                std.log.err("embedded pipeline parse failed: {s}", .{err.message});
+                c.g_error_free(err);
+            }
+            return pipeline_mod.PipelineError.PipelineParseFailed;
+        };
+        errdefer c.gst_object_unref(element);
+
+        const sink = c.gst_bin_get_by_name(c.asBin(element), sink_name) orelse
+            return pipeline_mod.PipelineError.MissingAppsink;
+        const bus = c.gst_element_get_bus(element) orelse
+            return pipeline_mod.PipelineError.PipelineParseFailed;
+
+        shareDisplay(element, display);
+
+        const frames = try allocator.create(std.atomic.Value(u64));
+        errdefer allocator.destroy(frames);
+        frames.* = std.atomic.Value(u64).init(0);
+
+        if (c.gst_bin_get_by_name(c.asBin(element), probe_name)) |probe| {
+            defer c.gst_object_unref(probe);
+            _ = c.g_signal_connect_data(probe, "handoff", @ptrCast(&onHandoff), frames, null, 0);
+        }
+
+        if (c.gst_element_set_state(element, .paused) == .failure) {
+            return pipeline_mod.PipelineError.StateChangeFailed;
+        }
+
+        return .{
+            .element = element,
+            .sink = sink,
+            .bus = bus,
+            .allocator = allocator,
+            .frames = frames,
+        };
+    }
+
+    pub fn deinit(self: *EmbeddedPlayer) void {
+        _ = c.gst_element_set_state(self.element, .null);
+        c.gst_object_unref(self.bus);
+        c.gst_object_unref(self.sink);
+        c.gst_object_unref(self.element);
+        self.allocator.destroy(self.frames);
+        self.* = undefined;
+    }
+
+    /// Frames that have passed the probe on their way into the sink.
+    pub fn framesRendered(self: *const EmbeddedPlayer) u64 {
+        return self.frames.load(.monotonic);
+    }
+
+    fn onHandoff(
+        identity: *c.GstElement,
+        buffer: *c.GstBuffer,
+        user_data: ?*anyopaque,
+    ) callconv(.c) void {
+        _ = identity;
+        _ = buffer;
+        const frames: *std.atomic.Value(u64) = @ptrCast(@alignCast(user_data.?));
+        _ = frames.fetchAdd(1, .monotonic);
+    }
+
+    pub fn play(self: *EmbeddedPlayer) !void {
+        if (c.gst_element_set_state(self.element, .playing) == .failure) {
+            return pipeline_mod.PipelineError.StateChangeFailed;
+        }
+        self.paused = false;
+    }
+
+    pub fn pause(self: *EmbeddedPlayer) !void {
+        if (c.gst_element_set_state(self.element, .paused) == .failure) {
+            return pipeline_mod.PipelineError.StateChangeFailed;
+        }
+        self.paused = true;
+    }
+
+    pub fn seekToStart(self: *EmbeddedPlayer) void {
+        _ = c.gst_element_seek_simple(
+            self.element,
+            c.GST_FORMAT_TIME,
+            c.GST_SEEK_FLAG_FLUSH | c.GST_SEEK_FLAG_KEY_UNIT,
+            0,
+        );
+    }
+
+    pub fn pollBus(self: *EmbeddedPlayer) pipeline_mod.Pipeline.BusStatus {
+        if (c.gst_bus_pop_filtered(self.bus, c.GST_MESSAGE_ERROR)) |message| {
+            defer c.gst_message_unref(message);
+            var gerror: ?*c.GError = null;
+            var debug: ?[*:0]u8 = null;
+            c.gst_message_parse_error(message, &gerror, &debug);
+            if (gerror) |err| {
+                std.log.err("embedded pipeline error: {s}", .{err.message});
+                c.g_error_free(err);
+            }
+            if (debug) |d| c.g_free(d);
+            return .failed;
+        }
+        if (c.gst_bus_pop_filtered(self.bus, c.GST_MESSAGE_EOS)) |message| {
+            c.gst_message_unref(message);
+            return .eos;
+        }
+        return .running;
+    }
+};
@@ -0,0 +1,90 @@
+//! Inotify watch for the playing video file.
+//!
+//! Wallpaper generators typically re-render into the same path, replacing
+//! the file. The pipeline keeps reading the old (deleted) inode, so the
+//! on-screen video never updates. Watching the parent directory — rather
+//! than the file itself, whose watch would die with the inode — catches
+//! both in-place rewrites (CLOSE_WRITE) and atomic replaces (MOVED_TO).
+
+const std = @import("std");
+
+pub const FileWatcher = struct {
+    allocator: std.mem.Allocator,
+    fd: std.posix.fd_t,
+    /// Basename the events are filtered against.
+    name: []const u8,
+
+    /// Starts watching the directory containing `path`. Fails on paths
+    /// without a parent directory or when inotify is unavailable.
+    pub fn init(allocator: std.mem.Allocator, path: []const u8) !FileWatcher {
+        const dir = std.fs.path.dirname(path) orelse ".";
+        const base = std.fs.path.basename(path);
+        if (base.len == 0) return error.InvalidPath;
+
+        const fd = try std.posix.inotify_init1(std.os.linux.IN.NONBLOCK);
+        errdefer std.posix.close(fd);
+
+        _ = try std.posix.inotify_add_watch(
+            fd,
+            dir,
+            std.os.linux.IN.CLOSE_WRITE | std.os.linux.IN.MOVED_TO,
+        );
+
+        const name = try allocator.dupe(u8, base);
+        return .{ .allocator = allocator, .fd = fd, .name = name };
+    }
+
+    pub fn deinit(self: *FileWatcher) void {
+        std.posix.close(self.fd);
+        self.allocator.free(self.name);
+        self.* = undefined;
+    }
+
+    /// Drains pending events without blocking; true when the watched file
+    /// was rewritten or replaced since the last call.
+    pub fn changed(self: *FileWatcher) bool {
+        var hit = false;
+        var buffer: [4096]u8 align(@alignOf(std.os.linux.inotify_event)) = undefined;
+        while (true) {
+            const len = std.posix.read(self.fd, &buffer) catch |err| switch (err) {
+                error.WouldBlock => return hit,
+                else => return hit,
+            };
+            if (len == 0) return hit;
+
+            var offset: usize = 0;
+            while (offset + @sizeOf(std.os.linux.inotify_event) <= len) {
+                const event: *const std.os.linux.inotify_event =
+                    @ptrCast(@alignCast(&buffer[offset]));
+                offset += @sizeOf(std.os.linux.inotify_event) + event.len;
+                const event_name = event.getName() orelse continue;
+                if (std.mem.eql(u8, event_name, self.name)) hit = true;
+            }
+        }
+    }
+};
+
+test "watcher reports replaced file" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+    const video_path = try std.fs.path.join(std.testing.allocator, &.{ dir_path, "wall.mp4" });
+    defer std.testing.allocator.free(video_path);
+
+    try tmp.dir.writeFile(.{ .sub_path = "wall.mp4", .data = "v1" });
+
+    var watcher = try FileWatcher.init(std.testing.allocator, video_path);
+    defer watcher.deinit();
+    try std.testing.expect(!watcher.changed());
+
+    // Atomic replace: write a sibling, rename over the original.
+    try tmp.dir.writeFile(.{ .sub_path = "wall.mp4.tmp", .data = "v2" });
+    try tmp.dir.rename("wall.mp4.tmp", "wall.mp4");
+    try std.testing.expect(watcher.changed());
+
+    // Unrelated files do not trigger.
+    try tmp.dir.writeFile(.{ .sub_path = "other.mp4", .data = "x" });
+    try std.testing.expect(!watcher.changed());
+}
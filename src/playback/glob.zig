@@ -0,0 +1,210 @@
+//! Glob and directory expansion for video paths.
+//!
+//! `~/Wallpapers/nature/*.mp4` in a profile (or on the command line, when
+//! the shell did not already expand it) resolves to a playlist of matching
+//! files. Expansion happens when the entry list is built — every reload
+//! re-evaluates the pattern, so new files in the directory join the
+//! rotation. Patterns may use `*` and `?` in the final path component.
+
+const std = @import("std");
+
+const video_extensions = [_][]const u8{
+    ".mp4", ".mkv", ".webm", ".mov", ".avi", ".m4v",
+};
+
+pub fn isVideoFile(name: []const u8) bool {
+    const dot = std.mem.lastIndexOfScalar(u8, name, '.') orelse return false;
+    const extension = name[dot..];
+    for (video_extensions) |candidate| {
+        if (std.ascii.eqlIgnoreCase(extension, candidate)) return true;
+    }
+    return false;
+}
+
+/// True when `path` contains glob metacharacters.
+pub fn hasPattern(path: []const u8) bool {
+    return std.mem.indexOfAny(u8, path, "*?") != null;
+}
+
+/// Matches `name` against a pattern of literals, `?` (any one byte) and
+/// `*` (any run, including empty). Iterative with single-star backtracking.
+pub fn matches(pattern: []const u8, name: []const u8) bool {
+    var p: usize = 0;
+    var n: usize = 0;
+    var star: ?usize = null;
+    var star_n: usize = 0;
+    while (n < name.len) {
+        if (p < pattern.len and (pattern[p] == '?' or pattern[p] == name[n])) {
+            p += 1;
+            n += 1;
+        } else if (p < pattern.len and pattern[p] == '*') {
+            star = p;
+            star_n = n;
+            p += 1;
+        } else if (star) |last_star| {
+            p = last_star + 1;
+            star_n += 1;
+            n = star_n;
+        } else {
+            return false;
+        }
+    }
+    while (p < pattern.len and pattern[p] == '*') p += 1;
+    return p == pattern.len;
+}
+
+/// How expanded entries are ordered. Both are deterministic, so the
+/// sequential playlist order is stable across restarts.
+pub const Sort = enum { name, mtime };
+
+/// Expands a path into playable entries, each allocated with `allocator`:
+///
+///  - a glob pattern yields every matching file, sorted;
+///  - a directory yields every video file inside it, sorted;
+///  - anything else passes through as a single entry.
+///
+/// A pattern or directory that matches nothing yields an empty slice, not
+/// an error; the caller decides whether that is fatal. Caller frees with
+/// `free`.
+pub fn expand(allocator: std.mem.Allocator, path: []const u8, sort: Sort) ![][]const u8 {
+    const resolved = try expandHome(allocator, path);
+    defer allocator.free(resolved);
+
+    if (hasPattern(resolved)) {
+        const dir_path = std.fs.path.dirname(resolved) orelse ".";
+        return scan(allocator, dir_path, std.fs.path.basename(resolved), sort);
+    }
+
+    if (std.fs.cwd().statFile(resolved)) |stat| {
+        if (stat.kind == .directory) return scan(allocator, resolved, null, sort);
+    } else |_| {}
+
+    const single = try allocator.alloc([]const u8, 1);
+    errdefer allocator.free(single);
+    single[0] = try allocator.dupe(u8, resolved);
+    return single;
+}
+
+pub fn free(allocator: std.mem.Allocator, entries: [][]const u8) void {
+    for (entries) |entry| allocator.free(entry);
+    allocator.free(entries);
+}
+
+/// Replaces a leading `~/` with $HOME so profile entries can stay
+/// machine-independent. Caller frees.
+fn expandHome(allocator: std.mem.Allocator, path: []const u8) ![]u8 {
+    if (std.mem.startsWith(u8, path, "~/")) {
+        if (std.posix.getenv("HOME")) |home| {
+            return std.fmt.allocPrint(allocator, "{s}/{s}", .{ home, path[2..] });
+        }
+    }
+    return allocator.dupe(u8, path);
+}
+
+/// Files in `dir_path` matching `pattern` (or any video file when null),
+/// sorted per `sort`.
+fn scan(
+    allocator: std.mem.Allocator,
+    dir_path: []const u8,
+    pattern: ?[]const u8,
+    sort: Sort,
+) ![][]const u8 {
+    const Entry = struct {
+        path: []const u8,
+        mtime_ns: i128,
+    };
+
+    var dir = try std.fs.cwd().openDir(dir_path, .{ .iterate = true });
+    defer dir.close();
+
+    var found: std.ArrayList(Entry) = .empty;
+    defer found.deinit(allocator);
+    errdefer for (found.items) |entry| allocator.free(entry.path);
+
+    var it = dir.iterate();
+    while (try it.next()) |entry| {
+        if (entry.kind != .file and entry.kind != .sym_link) continue;
+        if (pattern) |p| {
+            if (!matches(p, entry.name)) continue;
+        } else if (!isVideoFile(entry.name)) continue;
+
+        const mtime_ns: i128 = if (sort == .mtime)
+            if (dir.statFile(entry.name)) |stat| stat.mtime else |_| 0
+        else
+            0;
+        const path = try std.fs.path.join(allocator, &.{ dir_path, entry.name });
+        errdefer allocator.free(path);
+        try found.append(allocator, .{ .path = path, .mtime_ns = mtime_ns });
+    }
+
+    switch (sort) {
+        .name => std.mem.sort(Entry, found.items, {}, struct {
+            fn lessThan(_: void, a: Entry, b: Entry) bool {
+                return std.mem.lessThan(u8, a.path, b.path);
+            }
+        }.lessThan),
+        .mtime => std.mem.sort(Entry, found.items, {}, struct {
+            fn lessThan(_: void, a: Entry, b: Entry) bool {
+                return a.mtime_ns < b.mtime_ns;
+            }
+        }.lessThan),
+    }
+
+    const entries = try allocator.alloc([]const u8, found.items.len);
+    for (entries, found.items) |*out, entry| out.* = entry.path;
+    return entries;
+}
+
+test "matches handles stars and question marks" {
+    try std.testing.expect(matches("*.mp4", "sunset.mp4"));
+    try std.testing.expect(matches("wall-??.mp4", "wall-01.mp4"));
+    try std.testing.expect(matches("*", "anything"));
+    try std.testing.expect(!matches("*.mp4", "sunset.mkv"));
+    try std.testing.expect(!matches("wall-??.mp4", "wall-1.mp4"));
+}
+
+test "a pattern expands to sorted matching files" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{ .sub_path = "b.mp4", .data = "" });
+    try tmp.dir.writeFile(.{ .sub_path = "a.mp4", .data = "" });
+    try tmp.dir.writeFile(.{ .sub_path = "notes.txt", .data = "" });
+
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+    const pattern = try std.fmt.allocPrint(std.testing.allocator, "{s}/*.mp4", .{dir_path});
+    defer std.testing.allocator.free(pattern);
+
+    const entries = try expand(std.testing.allocator, pattern, .name);
+    defer free(std.testing.allocator, entries);
+
+    try std.testing.expectEqual(@as(usize, 2), entries.len);
+    try std.testing.expect(std.mem.endsWith(u8, entries[0], "a.mp4"));
+    try std.testing.expect(std.mem.endsWith(u8, entries[1], "b.mp4"));
+}
+
+test "a bare directory expands to its video files" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{ .sub_path = "clip.webm", .data = "" });
+    try tmp.dir.writeFile(.{ .sub_path = "wall.png", .data = "" });
+
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+
+    const entries = try expand(std.testing.allocator, dir_path, .name);
+    defer free(std.testing.allocator, entries);
+
+    try std.testing.expectEqual(@as(usize, 1), entries.len);
+    try std.testing.expect(std.mem.endsWith(u8, entries[0], "clip.webm"));
+}
+
+test "a plain file passes through unchanged" {
+    const entries = try expand(std.testing.allocator, "/nonexistent/clip.mp4", .name);
+    defer free(std.testing.allocator, entries);
+
+    try std.testing.expectEqual(@as(usize, 1), entries.len);
+    try std.testing.expectEqualStrings("/nonexistent/clip.mp4", entries[0]);
+}
@@ -0,0 +1,722 @@
+//! GStreamer pipeline wrapper for video playback.
+//!
+//! Builds a decode pipeline that delivers RGBA frames through an appsink,
+//! which the renderer uploads to the screen. State changes and seeking go
+//! through this type so callers never touch raw GStreamer handles.
+
+const std = @import("std");
+const c = @import("../gst/c.zig");
+const color = @import("../render/color.zig");
+const dot = @import("../gst/dot.zig");
+const decoder = @import("../gst/decoder.zig");
+const layout = @import("../render/layout.zig");
+
+pub const appsink_name = "waystream-sink";
+
+pub const OpenOptions = struct {
+    /// Negotiate 16-bit RGBA and keep HDR colorimetry instead of forcing
+    /// everything through 8-bit SDR conversion.
+    hdr: bool = false,
+    /// Directory for pipeline DOT graph dumps; null disables dumping.
+    dump_dot_dir: ?[]const u8 = null,
+    /// Decode and play the audio track (wallpapers default to silent).
+    audio: bool = false,
+    /// Linear audio volume (1.0 = unity) applied ahead of the sink.
+    volume: f64 = 1.0,
+    /// PulseAudio/PipeWire sink name for audio output, e.g. a null sink so
+    /// wallpaper sound never hijacks the default device. Null uses the
+    /// default sink.
+    audio_sink: ?[]const u8 = null,
+    /// Full gst-launch-style description replacing the built-in pipeline.
+    /// Must contain an appsink named `waystream-sink`; the uri is ignored.
+    pipeline_override: ?[]const u8 = null,
+    /// Ask the pipeline to deliver frames already scaled to this size, so a
+    /// 4K source on a 1080p output is downscaled by videoscale instead of
+    /// full-resolution frames being converted and resized on the CPU later.
+    target_size: ?layout.Size = null,
+    /// Accept NV12/I420 straight from the decoder so videoconvert can pass
+    /// buffers through; conversion then happens at blit time instead.
+    allow_yuv: bool = true,
+    /// Use vapostproc/v4l2convert for scaling and colorspace conversion
+    /// when the registry has one, keeping both off the CPU.
+    gpu_postproc: bool = true,
+    /// Download buffer size in bytes for network sources; null keeps the
+    /// element default.
+    net_buffer_bytes: ?u32 = null,
+    /// Connection/read timeout applied to network sources.
+    net_timeout_s: u32 = 10,
+};
+
+/// Pixel layout of frames delivered by the appsink.
+pub const PixelFormat = enum {
+    rgba8,
+    /// RGBA64_LE: 16 bits per channel, used for HDR passthrough.
+    rgba16,
+    /// Planar YUV 4:2:0, interleaved UV plane.
+    nv12,
+    /// Planar YUV 4:2:0, separate U and V planes.
+    i420,
+    /// 10-bit NV12 layout (16-bit LE samples, MSB-aligned).
+    p010,
+};
+
+pub const PipelineError = error{
+    InvalidUri,
+    // Named apart from the config LoadError.ParseFailed so the two stay
+    // distinguishable in the error taxonomy (errors.zig).
+    PipelineParseFailed,
+    MissingAppsink,
+    StateChangeFailed,
+};
+
+/// One decoded frame, valid until `Frame.unref` is called.
+pub const Frame = struct {
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    colorimetry: color.Colorimetry,
+    /// Tightly packed pixel data laid out according to `format`.
+    pixels: []const u8,
+    /// Fd of the dmabuf backing the frame when the decoder delivered all
+    /// planes in one dmabuf memory block; null for system memory. Owned by
+    /// the buffer and valid until `unref`.
+    dmabuf_fd: ?std.posix.fd_t = null,
+
+    buffer: *c.GstBuffer,
+    sample: *c.GstSample,
+    map_info: c.GstMapInfo,
+
+    pub fn unref(self: *Frame) void {
+        c.gst_buffer_unmap(self.buffer, &self.map_info);
+        c.gst_sample_unref(self.sample);
+        self.* = undefined;
+    }
+};
+
+pub const Pipeline = struct {
+    allocator: std.mem.Allocator,
+    element: *c.GstElement,
+    appsink: *c.GstElement,
+    bus: *c.GstBus,
+    /// Self-pipe poked by the appsink's new-sample signal; `waitEvent`
+    /// polls the read end alongside the bus fd so play loops sleep until
+    /// there is actual work instead of ticking on an interval.
+    sample_pipe: [2]std.posix.fd_t,
+    paused: bool = false,
+    rate: f64 = 1.0,
+    dump_dot_dir: ?[]const u8 = null,
+    /// Factory name of the video decoder decodebin actually plugged, filled
+    /// in asynchronously once decoding starts.
+    selected_decoder: [64]u8 = @splat(0),
+    selected_decoder_len: usize = 0,
+    /// Whether the plugged decoder runs in hardware.
+    selected_decoder_hw: bool = false,
+    /// Factory name of the demuxer, identifying the container format.
+    selected_demuxer: [64]u8 = @splat(0),
+    selected_demuxer_len: usize = 0,
+
+    var gst_initialized = false;
+
+    pub fn initGst() void {
+        if (!gst_initialized) {
+            c.gst_init(null, null);
+            gst_initialized = true;
+        }
+    }
+
+    /// Builds a playback pipeline for `uri` and prerolls it to paused.
+    pub fn open(allocator: std.mem.Allocator, uri: [:0]const u8, options: OpenOptions) !Pipeline {
+        initGst();
+        decoder.applyPreferences(allocator);
+
+        const description = if (options.pipeline_override) |override|
+            try allocator.dupeZ(u8, override)
+        else
+            try buildDescription(allocator, uri, options);
+        defer allocator.free(description);
+
+        var parse_err: ?*c.GError = null;
+        const element = c.gst_parse_launch(description, &parse_err) orelse {
+            if (parse_err) |err| {
+                std.log.err("pipeline parse failed: {s}", .{err.message});
+                c.g_error_free(err);
+            }
+            return PipelineError.PipelineParseFailed;
+        };
+        errdefer c.gst_object_unref(element);
+
+        const appsink = c.gst_bin_get_by_name(c.asBin(element), appsink_name) orelse {
+            if (options.pipeline_override != null) {
+                std.log.err("custom pipeline must contain an appsink named {s}", .{appsink_name});
+            }
+            return PipelineError.MissingAppsink;
+        };
+        const bus = c.gst_element_get_bus(element) orelse
+            return PipelineError.PipelineParseFailed;
+
+        // The new-sample handler runs on the streaming thread and writes a
+        // byte here for every decoded sample. The write end is smuggled
+        // through the callback data pointer, same as the source-setup
+        // timeout below.
+        const sample_pipe = try std.posix.pipe2(.{ .NONBLOCK = true });
+        errdefer for (sample_pipe) |fd| std.posix.close(fd);
+        c.g_object_set(appsink, "emit-signals", @as(c_int, 1), @as(?[*:0]const u8, null));
+        _ = c.g_signal_connect_data(
+            appsink,
+            "new-sample",
+            @ptrCast(&onNewSample),
+            @ptrFromInt(@as(usize, @intCast(sample_pipe[1]))),
+            null,
+            0,
+        );
+
+        // Configure timeouts on the source element once uridecodebin creates
+        // it. The timeout is smuggled through the callback data pointer so no
+        // allocation has to outlive the signal connection.
+        if (isNetworkUri(uri) and options.pipeline_override == null) {
+            _ = c.g_signal_connect_data(
+                element,
+                "source-setup",
+                @ptrCast(&onSourceSetup),
+                @ptrFromInt(@as(usize, options.net_timeout_s)),
+                null,
+                0,
+            );
+        }
+
+        if (c.gst_element_set_state(element, .paused) == .failure) {
+            return PipelineError.StateChangeFailed;
+        }
+
+        return .{
+            .allocator = allocator,
+            .element = element,
+            .appsink = appsink,
+            .bus = bus,
+            .sample_pipe = sample_pipe,
+            .dump_dot_dir = options.dump_dot_dir,
+        };
+    }
+
+    /// Starts recording which video decoder decodebin plugs. Must be called
+    /// on the pipeline's final storage location (the callback keeps `self`).
+    pub fn watchDecoderSelection(self: *Pipeline) void {
+        _ = c.g_signal_connect_data(
+            self.element,
+            "deep-element-added",
+            @ptrCast(&onDeepElementAdded),
+            self,
+            null,
+            0,
+        );
+    }
+
+    /// Factory name of the selected video decoder, once known.
+    pub fn selectedDecoder(self: *const Pipeline) ?[]const u8 {
+        if (self.selected_decoder_len == 0) return null;
+        return self.selected_decoder[0..self.selected_decoder_len];
+    }
+
+    /// Factory name of the demuxer (container format), once known.
+    pub fn selectedDemuxer(self: *const Pipeline) ?[]const u8 {
+        if (self.selected_demuxer_len == 0) return null;
+        return self.selected_demuxer[0..self.selected_demuxer_len];
+    }
+
+    /// What the pipeline actually negotiated, as opposed to what was asked
+    /// for. Valid once prerolled.
+    pub const StreamInfo = struct {
+        width: u32,
+        height: u32,
+        /// Source framerate from the caps; 0 for variable/live streams.
+        fps: f64,
+        format: PixelFormat,
+        /// Demuxer factory name identifying the container, when one exists
+        /// (elementary streams and live sources have none).
+        container: ?[]const u8,
+        /// Decoder factory name, once decodebin has plugged one.
+        decoder: ?[]const u8,
+        /// True when the selected decoder runs in hardware.
+        hardware_decode: bool,
+    };
+
+    /// Reads the negotiated caps off the appsink's sink pad. Returns null
+    /// until caps are negotiated, i.e. before preroll completes.
+    pub fn streamInfo(self: *const Pipeline) ?StreamInfo {
+        const pad = c.gst_element_get_static_pad(self.appsink, "sink") orelse return null;
+        defer c.gst_object_unref(pad);
+        const caps = c.gst_pad_get_current_caps(pad) orelse return null;
+        defer c.gst_caps_unref(caps);
+        const structure = c.gst_caps_get_structure(caps, 0) orelse return null;
+
+        var width: c_int = 0;
+        var height: c_int = 0;
+        if (c.gst_structure_get_int(structure, "width", &width) == 0 or
+            c.gst_structure_get_int(structure, "height", &height) == 0)
+        {
+            return null;
+        }
+
+        var fps: f64 = 0;
+        var num: c_int = 0;
+        var den: c_int = 0;
+        if (c.gst_structure_get_fraction(structure, "framerate", &num, &den) != 0 and den > 0) {
+            fps = @as(f64, @floatFromInt(num)) / @as(f64, @floatFromInt(den));
+        }
+
+        const format: PixelFormat = blk: {
+            const name = c.gst_structure_get_string(structure, "format") orelse break :blk .rgba8;
+            const name_slice = std.mem.span(name);
+            if (std.mem.eql(u8, name_slice, "RGBA64_LE")) break :blk .rgba16;
+            if (std.mem.eql(u8, name_slice, "NV12")) break :blk .nv12;
+            if (std.mem.eql(u8, name_slice, "I420")) break :blk .i420;
+            if (std.mem.eql(u8, name_slice, "P010_10LE")) break :blk .p010;
+            break :blk .rgba8;
+        };
+
+        return .{
+            .width = @intCast(width),
+            .height = @intCast(height),
+            .fps = fps,
+            .format = format,
+            .container = self.selectedDemuxer(),
+            .decoder = self.selectedDecoder(),
+            .hardware_decode = self.selected_decoder_hw,
+        };
+    }
+
+    fn onSourceSetup(
+        bin: *c.GstElement,
+        source: *c.GstElement,
+        data: ?*anyopaque,
+    ) callconv(.c) void {
+        _ = bin;
+        const timeout_s: u32 = @intCast(@intFromPtr(data));
+        if (timeout_s == 0) return;
+
+        const name = decoder.factoryName(source) orelse return;
+        defer c.g_free(name);
+        const name_slice = std.mem.span(name);
+
+        // Property types differ per source, so match on the factory instead
+        // of setting blindly through varargs.
+        if (std.mem.eql(u8, name_slice, "souphttpsrc")) {
+            c.g_object_set(
+                source,
+                "timeout",
+                @as(c_uint, timeout_s),
+                "retries",
+                @as(c_int, 3),
+                @as(?[*:0]const u8, null),
+            );
+        } else if (std.mem.eql(u8, name_slice, "rtspsrc")) {
+            c.g_object_set(
+                source,
+                "tcp-timeout",
+                @as(u64, timeout_s) * std.time.us_per_s,
+                @as(?[*:0]const u8, null),
+            );
+        }
+    }
+
+    /// Runs on the streaming thread for every decoded sample. Only pokes
+    /// the wake pipe; the sample itself stays queued in the appsink until
+    /// the play loop pulls it. A full pipe already guarantees a wakeup.
+    fn onNewSample(sink: *c.GstElement, data: ?*anyopaque) callconv(.c) c_int {
+        _ = sink;
+        const fd: std.posix.fd_t = @intCast(@intFromPtr(data));
+        _ = std.posix.write(fd, &.{1}) catch {};
+        return c.GST_FLOW_OK;
+    }
+
+    fn onDeepElementAdded(
+        bin: *c.GstBin,
+        sub_bin: *c.GstBin,
+        element: *c.GstElement,
+        data: ?*anyopaque,
+    ) callconv(.c) void {
+        _ = bin;
+        _ = sub_bin;
+        const self: *Pipeline = @ptrCast(@alignCast(data.?));
+
+        if (decoder.isDemuxer(element)) {
+            const name = decoder.factoryName(element) orelse return;
+            defer c.g_free(name);
+            const name_slice = std.mem.span(name);
+            const len = @min(name_slice.len, self.selected_demuxer.len);
+            @memcpy(self.selected_demuxer[0..len], name_slice[0..len]);
+            self.selected_demuxer_len = len;
+            return;
+        }
+
+        if (!decoder.isVideoDecoder(element)) return;
+
+        const name = decoder.factoryName(element) orelse return;
+        defer c.g_free(name);
+        const name_slice = std.mem.span(name);
+        const len = @min(name_slice.len, self.selected_decoder.len);
+        @memcpy(self.selected_decoder[0..len], name_slice[0..len]);
+        self.selected_decoder_len = len;
+        self.selected_decoder_hw = decoder.isHardware(element);
+        std.log.info("video decoder selected: {s} ({s})", .{
+            name_slice,
+            if (self.selected_decoder_hw) "hardware" else "software",
+        });
+    }
+
+    /// Dumps a DOT graph of the pipeline when dumping is enabled.
+    pub fn dumpDot(self: *Pipeline, label: []const u8) void {
+        const dir = self.dump_dot_dir orelse return;
+        dot.dumpPipeline(self.allocator, self.element, dir, label);
+    }
+
+    pub fn deinit(self: *Pipeline) void {
+        // Tearing the state down first stops the streaming thread, so the
+        // new-sample handler cannot write into the pipe after it is closed.
+        _ = c.gst_element_set_state(self.element, .null);
+        for (self.sample_pipe) |fd| std.posix.close(fd);
+        c.gst_object_unref(self.bus);
+        c.gst_object_unref(self.appsink);
+        c.gst_object_unref(self.element);
+        self.* = undefined;
+    }
+
+    pub fn play(self: *Pipeline) PipelineError!void {
+        if (c.gst_element_set_state(self.element, .playing) == .failure) {
+            self.dumpDot("state-change-failed");
+            return PipelineError.StateChangeFailed;
+        }
+        self.paused = false;
+        self.dumpDot("playing");
+    }
+
+    pub fn pause(self: *Pipeline) PipelineError!void {
+        if (c.gst_element_set_state(self.element, .paused) == .failure) {
+            self.dumpDot("state-change-failed");
+            return PipelineError.StateChangeFailed;
+        }
+        self.paused = true;
+    }
+
+    pub fn seekToStart(self: *Pipeline) void {
+        self.seekTo(0);
+    }
+
+    /// Seeks to an absolute position in seconds, preserving the current rate.
+    pub fn seekTo(self: *Pipeline, seconds: f64) void {
+        const clamped = @max(seconds, 0);
+        const position: i64 = @intFromFloat(clamped * @as(f64, @floatFromInt(c.GST_SECOND)));
+        _ = c.gst_element_seek(
+            self.element,
+            self.rate,
+            c.GST_FORMAT_TIME,
+            c.GST_SEEK_FLAG_FLUSH | c.GST_SEEK_FLAG_KEY_UNIT,
+            c.GST_SEEK_TYPE_SET,
+            position,
+            c.GST_SEEK_TYPE_NONE,
+            -1,
+        );
+    }
+
+    /// Changes the playback rate via a non-repositioning seek.
+    pub fn setRate(self: *Pipeline, rate: f64) void {
+        if (rate <= 0) return;
+        self.rate = rate;
+        _ = c.gst_element_seek(
+            self.element,
+            rate,
+            c.GST_FORMAT_TIME,
+            c.GST_SEEK_FLAG_FLUSH,
+            c.GST_SEEK_TYPE_NONE,
+            -1,
+            c.GST_SEEK_TYPE_NONE,
+            -1,
+        );
+    }
+
+    /// Pollable fd that becomes readable when a bus message arrives, for
+    /// event-driven loops that want to sleep in poll() instead of ticking.
+    pub fn busFd(self: *Pipeline) std.posix.fd_t {
+        var pollfd: c.GPollFD = undefined;
+        c.gst_bus_get_pollfd(self.bus, &pollfd);
+        return pollfd.fd;
+    }
+
+    /// Sleeps until a decoded sample or a bus message is ready, or until
+    /// `timeout_ms` passes. Pairs with `pullFrame(0)`: the play loop parks
+    /// here in poll() and wakes on the appsink's new-sample signal instead
+    /// of polling the sink on a fixed tick.
+    pub fn waitEvent(self: *Pipeline, timeout_ms: i32) void {
+        var fds = [_]std.posix.pollfd{
+            .{ .fd = self.sample_pipe[0], .events = std.posix.POLL.IN, .revents = 0 },
+            .{ .fd = self.busFd(), .events = std.posix.POLL.IN, .revents = 0 },
+        };
+        _ = std.posix.poll(&fds, timeout_ms) catch return;
+        if (fds[0].revents & std.posix.POLL.IN != 0) self.drainSamplePipe();
+    }
+
+    fn drainSamplePipe(self: *Pipeline) void {
+        var buffer: [16]u8 = undefined;
+        while (true) {
+            const len = std.posix.read(self.sample_pipe[0], &buffer) catch return;
+            if (len < buffer.len) return;
+        }
+    }
+
+    pub const BusStatus = enum { running, eos, failed };
+
+    /// Polls the bus for terminal messages, distinguishing a clean end of
+    /// stream from a pipeline error so callers can decide whether to loop,
+    /// exit, or attempt a reconnect.
+    pub fn pollBus(self: *Pipeline) BusStatus {
+        if (c.gst_bus_pop_filtered(self.bus, c.GST_MESSAGE_ERROR)) |message| {
+            defer c.gst_message_unref(message);
+
+            var gerror: ?*c.GError = null;
+            var debug: ?[*:0]u8 = null;
+            c.gst_message_parse_error(message, &gerror, &debug);
+            if (gerror) |err| {
+                std.log.err("pipeline error: {s}", .{err.message});
+                c.g_error_free(err);
+            }
+            if (debug) |d| c.g_free(d);
+            self.dumpDot("error");
+            return .failed;
+        }
+
+        if (c.gst_bus_pop_filtered(self.bus, c.GST_MESSAGE_EOS)) |message| {
+            c.gst_message_unref(message);
+            return .eos;
+        }
+        return .running;
+    }
+
+    /// Pulls the next decoded frame, waiting at most `timeout_ns`.
+    pub fn pullFrame(self: *Pipeline, timeout_ns: u64) ?Frame {
+        const sample = c.gst_app_sink_try_pull_sample(self.appsink, timeout_ns) orelse
+            return null;
+        return frameFromSample(sample);
+    }
+
+    /// Pulls the preroll frame while the pipeline sits in PAUSED, so the
+    /// first commit already carries picture data instead of a black flash.
+    pub fn pullPreroll(self: *Pipeline, timeout_ns: u64) ?Frame {
+        const sample = c.gst_app_sink_try_pull_preroll(self.appsink, timeout_ns) orelse
+            return null;
+        return frameFromSample(sample);
+    }
+
+    fn frameFromSample(sample: *c.GstSample) ?Frame {
+        const buffer = c.gst_sample_get_buffer(sample) orelse {
+            c.gst_sample_unref(sample);
+            return null;
+        };
+        const caps = c.gst_sample_get_caps(sample) orelse {
+            c.gst_sample_unref(sample);
+            return null;
+        };
+        const structure = c.gst_caps_get_structure(caps, 0) orelse {
+            c.gst_sample_unref(sample);
+            return null;
+        };
+
+        var width: c_int = 0;
+        var height: c_int = 0;
+        if (c.gst_structure_get_int(structure, "width", &width) == 0 or
+            c.gst_structure_get_int(structure, "height", &height) == 0)
+        {
+            c.gst_sample_unref(sample);
+            return null;
+        }
+
+        const format: PixelFormat = blk: {
+            const name = c.gst_structure_get_string(structure, "format") orelse break :blk .rgba8;
+            const name_slice = std.mem.span(name);
+            if (std.mem.eql(u8, name_slice, "RGBA64_LE")) break :blk .rgba16;
+            if (std.mem.eql(u8, name_slice, "NV12")) break :blk .nv12;
+            if (std.mem.eql(u8, name_slice, "I420")) break :blk .i420;
+            if (std.mem.eql(u8, name_slice, "P010_10LE")) break :blk .p010;
+            break :blk .rgba8;
+        };
+        const colorimetry: color.Colorimetry = blk: {
+            const value = c.gst_structure_get_string(structure, "colorimetry") orelse
+                break :blk color.Colorimetry.sdr_default;
+            break :blk color.parseGstColorimetry(std.mem.span(value));
+        };
+
+        // A single dmabuf memory block means all planes share one fd in the
+        // contiguous layout; multi-block buffers (per-plane fds) stay on the
+        // mapped path.
+        const dmabuf_fd: ?std.posix.fd_t = blk: {
+            if (c.gst_buffer_n_memory(buffer) != 1) break :blk null;
+            const memory = c.gst_buffer_peek_memory(buffer, 0) orelse break :blk null;
+            if (c.gst_is_dmabuf_memory(memory) == 0) break :blk null;
+            break :blk c.gst_dmabuf_memory_get_fd(memory);
+        };
+
+        var map_info: c.GstMapInfo = undefined;
+        if (c.gst_buffer_map(buffer, &map_info, c.GST_MAP_READ) == 0) {
+            c.gst_sample_unref(sample);
+            return null;
+        }
+
+        return .{
+            .width = @intCast(width),
+            .height = @intCast(height),
+            .format = format,
+            .colorimetry = colorimetry,
+            .pixels = map_info.data[0..map_info.size],
+            .dmabuf_fd = dmabuf_fd,
+            .buffer = buffer,
+            .sample = sample,
+            .map_info = map_info,
+        };
+    }
+};
+
+/// Renders the gst-launch description for `uri` with the requested video
+/// caps and optional audio branch.
+fn buildDescription(allocator: std.mem.Allocator, uri: []const u8, options: OpenOptions) ![:0]u8 {
+    // With HDR enabled, let the decoder keep 16-bit output; otherwise prefer
+    // the decoder's native 4:2:0 layouts (converted at blit time) and fall
+    // back to 8-bit RGBA.
+    const formats: []const u8 = if (options.hdr)
+        "{ RGBA64_LE, P010_10LE, RGBA }"
+    else if (options.allow_yuv)
+        "{ NV12, I420, RGBA }"
+    else
+        "RGBA";
+
+    const audio_branch: []const u8 = if (!options.audio)
+        ""
+    else if (options.audio_sink) |sink|
+        try std.fmt.allocPrint(
+            allocator,
+            " waystream-dec. ! audioconvert ! audioresample ! " ++
+                "volume volume={d:.2} ! pulsesink device={s}",
+            .{ options.volume, sink },
+        )
+    else
+        try std.fmt.allocPrint(
+            allocator,
+            " waystream-dec. ! audioconvert ! audioresample ! " ++
+                "volume volume={d:.2} ! pulsesink",
+            .{options.volume},
+        );
+    defer if (options.audio) allocator.free(audio_branch);
+
+    // A GPU postprocessor handles scaling and conversion in one element;
+    // otherwise videoconvert plus videoscale do it on the CPU.
+    const postproc: ?[]const u8 = if (options.gpu_postproc) decoder.availablePostproc() else null;
+    if (postproc) |name| {
+        std.log.info("scaling/conversion offloaded to {s}", .{name});
+    }
+    const convert_stage: []const u8 = postproc orelse "videoconvert";
+
+    // Network sources get download buffering so short stalls play from the
+    // buffer instead of starving the sink.
+    const buffering: []const u8 = if (isNetworkUri(uri))
+        if (options.net_buffer_bytes) |bytes|
+            try std.fmt.allocPrint(allocator, " use-buffering=true buffer-size={d}", .{bytes})
+        else
+            " use-buffering=true"
+    else
+        "";
+    defer if (isNetworkUri(uri) and options.net_buffer_bytes != null) allocator.free(buffering);
+
+    // Scaling inside the pipeline keeps aspect ratio (no borders); the
+    // renderer still letterboxes the result onto the surface.
+    const scale_stage: []const u8 = if (options.target_size) |size|
+        try std.fmt.allocPrint(
+            allocator,
+            "{s}video/x-raw,width=[1,{d}],height=[1,{d}],pixel-aspect-ratio=1/1 ! ",
+            .{
+                if (postproc == null) "videoscale ! " else "",
+                size.width,
+                size.height,
+            },
+        )
+    else
+        "";
+    defer if (options.target_size != null) allocator.free(scale_stage);
+
+    // Cameras are live sources with no demuxer or audio track; v4l2src
+    // feeds the same convert/scale/appsink chain directly.
+    if (v4l2Device(uri)) |device| {
+        return std.fmt.allocPrintSentinel(
+            allocator,
+            "v4l2src device={s} ! {s} ! {s}video/x-raw,format={s} ! " ++
+                "appsink name={s} max-buffers=3 drop=true sync=true",
+            .{ device, convert_stage, scale_stage, formats, appsink_name },
+            0,
+        );
+    }
+
+    // Screen capture: the PipeWire node id comes from an
+    // xdg-desktop-portal screencast session negotiated out of band.
+    if (pipewireNode(uri)) |node| {
+        return std.fmt.allocPrintSentinel(
+            allocator,
+            "pipewiresrc path={s} ! {s} ! {s}video/x-raw,format={s} ! " ++
+                "appsink name={s} max-buffers=3 drop=true sync=true",
+            .{ node, convert_stage, scale_stage, formats, appsink_name },
+            0,
+        );
+    }
+
+    return std.fmt.allocPrintSentinel(
+        allocator,
+        "uridecodebin name=waystream-dec uri={s}{s} " ++
+            "waystream-dec. ! {s} ! {s}video/x-raw,format={s} ! " ++
+            "appsink name={s} max-buffers=3 drop=true sync=true{s}",
+        .{ uri, buffering, convert_stage, scale_stage, formats, appsink_name, audio_branch },
+        0,
+    );
+}
+
+/// Device path of a `v4l2:///dev/videoN` camera uri, or null.
+pub fn v4l2Device(uri: []const u8) ?[]const u8 {
+    const prefix = "v4l2://";
+    if (!std.mem.startsWith(u8, uri, prefix)) return null;
+    const device = uri[prefix.len..];
+    if (device.len == 0) return null;
+    return device;
+}
+
+/// PipeWire node id of a `pipewire://<node>` screen-capture uri, or null.
+/// The node id is obtained from an xdg-desktop-portal screencast session,
+/// e.g. via `xdp-screen-cast` or a compositor-specific helper.
+pub fn pipewireNode(uri: []const u8) ?[]const u8 {
+    const prefix = "pipewire://";
+    if (!std.mem.startsWith(u8, uri, prefix)) return null;
+    const node = uri[prefix.len..];
+    if (node.len == 0) return null;
+    return node;
+}
+
+/// True for sources that arrive over the network and can stall or drop.
+pub fn isNetworkUri(uri: []const u8) bool {
+    return std.mem.startsWith(u8, uri, "http://") or
+        std.mem.startsWith(u8, uri, "https://") or
+        std.mem.startsWith(u8, uri, "rtsp://") or
+        std.mem.startsWith(u8, uri, "rtmp://");
+}
+
+/// Converts a local path to a file:// URI; URIs are passed through untouched.
+pub fn pathToUri(allocator: std.mem.Allocator, path: []const u8) ![:0]u8 {
+    if (std.mem.indexOf(u8, path, "://") != null) {
+        return allocator.dupeZ(u8, path);
+    }
+
+    const path_z = try allocator.dupeZ(u8, path);
+    defer allocator.free(path_z);
+
+    var uri_err: ?*c.GError = null;
+    const uri = c.gst_filename_to_uri(path_z, &uri_err) orelse {
+        if (uri_err) |err| c.g_error_free(err);
+        return PipelineError.InvalidUri;
+    };
+    defer c.g_free(uri);
+
+    return allocator.dupeZ(u8, std.mem.span(uri));
+}
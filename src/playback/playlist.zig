@@ -0,0 +1,216 @@
+//! Sequential playback over multiple inputs.
+//!
+//! A playlist advances through its entries on EOS inside one pipeline and
+//! renderer instance. Order decides whether entries play as given, in a
+//! random permutation (reshuffled every cycle), or by weighted draw; repeat
+//! decides what happens after the last entry.
+//!
+//! Entries may carry a `:N` weight suffix (`sunset.mp4:3`) that makes them
+//! N times as likely under weighted order; a suffix that does not parse as
+//! a positive integer is treated as part of the path.
+
+const std = @import("std");
+
+pub const Order = enum { sequential, shuffle, weighted };
+
+pub const Repeat = enum {
+    /// Wrap around after the last entry.
+    all,
+    /// Restart the current entry forever.
+    one,
+    /// Stop after the last entry.
+    off,
+};
+
+/// What the player should do when the current entry hits EOS.
+pub const EosAction = union(enum) {
+    stop,
+    restart,
+    next: []const u8,
+};
+
+/// A playlist entry split into its path and draw weight.
+pub const WeightedEntry = struct {
+    path: []const u8,
+    weight: u32,
+};
+
+/// Splits an optional `:N` weight suffix off an entry. The path part is a
+/// subslice of `text`, so nothing needs freeing.
+pub fn parseWeightedEntry(text: []const u8) WeightedEntry {
+    if (std.mem.lastIndexOfScalar(u8, text, ':')) |colon| {
+        if (std.fmt.parseInt(u32, text[colon + 1 ..], 10)) |weight| {
+            if (weight > 0) return .{ .path = text[0..colon], .weight = weight };
+        } else |_| {}
+    }
+    return .{ .path = text, .weight = 1 };
+}
+
+/// Draws an index with probability proportional to its weight.
+pub fn pickWeighted(random: std.Random, weights: []const u32) usize {
+    var total: u64 = 0;
+    for (weights) |weight| total += weight;
+    var remaining = random.uintLessThan(u64, total);
+    for (weights, 0..) |weight, index| {
+        if (remaining < weight) return index;
+        remaining -= weight;
+    }
+    unreachable;
+}
+
+pub const Playlist = struct {
+    allocator: std.mem.Allocator,
+    /// Entry paths with any weight suffix stripped.
+    paths: [][]const u8,
+    weights: []u32,
+    order: Order,
+    repeat: Repeat,
+    /// Play order as indices into `paths`.
+    positions: []usize,
+    cursor: usize = 0,
+    rng: std.Random.DefaultPrng,
+
+    pub fn init(
+        allocator: std.mem.Allocator,
+        entries: []const []const u8,
+        order: Order,
+        repeat: Repeat,
+        seed: u64,
+    ) !Playlist {
+        const paths = try allocator.alloc([]const u8, entries.len);
+        errdefer allocator.free(paths);
+        const weights = try allocator.alloc(u32, entries.len);
+        errdefer allocator.free(weights);
+        for (entries, paths, weights) |entry, *path, *weight| {
+            const parsed = parseWeightedEntry(entry);
+            path.* = parsed.path;
+            weight.* = parsed.weight;
+        }
+
+        const positions = try allocator.alloc(usize, entries.len);
+        for (positions, 0..) |*position, i| position.* = i;
+
+        var self = Playlist{
+            .allocator = allocator,
+            .paths = paths,
+            .weights = weights,
+            .order = order,
+            .repeat = repeat,
+            .positions = positions,
+            .rng = std.Random.DefaultPrng.init(seed),
+        };
+        switch (order) {
+            .sequential => {},
+            .shuffle => self.rng.random().shuffle(usize, self.positions),
+            .weighted => self.positions[0] = pickWeighted(self.rng.random(), self.weights),
+        }
+        return self;
+    }
+
+    pub fn deinit(self: *Playlist) void {
+        self.allocator.free(self.positions);
+        self.allocator.free(self.weights);
+        self.allocator.free(self.paths);
+        self.* = undefined;
+    }
+
+    pub fn current(self: *const Playlist) []const u8 {
+        return self.paths[self.positions[self.cursor]];
+    }
+
+    /// Picks the follow-up for an EOS on the current entry.
+    pub fn onEos(self: *Playlist) EosAction {
+        if (self.repeat == .one) return .restart;
+        if (self.paths.len == 1) {
+            return if (self.repeat == .off) .stop else .restart;
+        }
+
+        if (self.cursor + 1 < self.positions.len) {
+            self.cursor += 1;
+            if (self.order == .weighted) {
+                self.positions[self.cursor] = pickWeighted(self.rng.random(), self.weights);
+            }
+            return .{ .next = self.current() };
+        }
+        if (self.repeat == .off) return .stop;
+
+        // New cycle: reshuffle (or redraw) so the order differs each time.
+        switch (self.order) {
+            .sequential => {},
+            .shuffle => self.rng.random().shuffle(usize, self.positions),
+            .weighted => self.positions[0] = pickWeighted(self.rng.random(), self.weights),
+        }
+        self.cursor = 0;
+        return .{ .next = self.current() };
+    }
+};
+
+test "sequential repeat-all wraps around" {
+    const entries = [_][]const u8{ "a.mp4", "b.mp4", "c.mp4" };
+    var playlist = try Playlist.init(std.testing.allocator, &entries, .sequential, .all, 0);
+    defer playlist.deinit();
+
+    try std.testing.expectEqualStrings("a.mp4", playlist.current());
+    try std.testing.expectEqualStrings("b.mp4", playlist.onEos().next);
+    try std.testing.expectEqualStrings("c.mp4", playlist.onEos().next);
+    try std.testing.expectEqualStrings("a.mp4", playlist.onEos().next);
+}
+
+test "repeat-off stops after the last entry" {
+    const entries = [_][]const u8{ "a.mp4", "b.mp4" };
+    var playlist = try Playlist.init(std.testing.allocator, &entries, .sequential, .off, 0);
+    defer playlist.deinit();
+
+    try std.testing.expectEqualStrings("b.mp4", playlist.onEos().next);
+    try std.testing.expectEqual(EosAction.stop, playlist.onEos());
+}
+
+test "repeat-one restarts without advancing" {
+    const entries = [_][]const u8{ "a.mp4", "b.mp4" };
+    var playlist = try Playlist.init(std.testing.allocator, &entries, .sequential, .one, 0);
+    defer playlist.deinit();
+
+    try std.testing.expectEqual(EosAction.restart, playlist.onEos());
+    try std.testing.expectEqualStrings("a.mp4", playlist.current());
+}
+
+test "weight suffixes parse and plain paths default to 1" {
+    const weighted = parseWeightedEntry("sunset.mp4:3");
+    try std.testing.expectEqualStrings("sunset.mp4", weighted.path);
+    try std.testing.expectEqual(@as(u32, 3), weighted.weight);
+
+    // A colon that is not a weight stays part of the path.
+    const plain = parseWeightedEntry("odd:name.mp4");
+    try std.testing.expectEqualStrings("odd:name.mp4", plain.path);
+    try std.testing.expectEqual(@as(u32, 1), plain.weight);
+}
+
+test "weighted order favors the heavier entry" {
+    const entries = [_][]const u8{ "rare.mp4:1", "favorite.mp4:9" };
+    var playlist = try Playlist.init(std.testing.allocator, &entries, .weighted, .all, 7);
+    defer playlist.deinit();
+
+    var favorite_plays: usize = 0;
+    var total: usize = 0;
+    for (0..200) |_| {
+        if (std.mem.eql(u8, playlist.current(), "favorite.mp4")) favorite_plays += 1;
+        total += 1;
+        _ = playlist.onEos();
+    }
+    // Expected ratio is 9:1; anything past a majority proves the bias.
+    try std.testing.expect(favorite_plays * 2 > total);
+}
+
+test "shuffle plays every entry once per cycle" {
+    const entries = [_][]const u8{ "a.mp4", "b.mp4", "c.mp4", "d.mp4" };
+    var playlist = try Playlist.init(std.testing.allocator, &entries, .shuffle, .all, 42);
+    defer playlist.deinit();
+
+    var seen = [_]bool{false} ** entries.len;
+    seen[playlist.positions[playlist.cursor]] = true;
+    for (0..entries.len - 1) |_| {
+        _ = playlist.onEos();
+        seen[playlist.positions[playlist.cursor]] = true;
+    }
+    for (seen) |was_played| try std.testing.expect(was_played);
+}
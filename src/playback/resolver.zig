@@ -0,0 +1,202 @@
+//! Online URL resolution via yt-dlp.
+//!
+//! Video-page URLs (YouTube, Vimeo, ...) cannot be fed to the pipeline
+//! directly; yt-dlp turns them into direct stream URLs. Resolved URLs are
+//! cached on disk and refreshed once they expire, so restarting the player
+//! does not shell out again for a still-valid link.
+
+const std = @import("std");
+
+/// Page hosts whose URLs need resolving before playback.
+const resolvable_hosts = [_][]const u8{
+    "youtube.com",
+    "youtu.be",
+    "vimeo.com",
+    "twitch.tv",
+};
+
+/// Fallback lifetime when the resolved URL carries no expiry of its own.
+const default_ttl_s: i64 = 3600;
+
+pub const ResolveError = error{
+    ResolverMissing,
+    ResolveFailed,
+} || std.mem.Allocator.Error;
+
+pub const ResolveOptions = struct {
+    /// Skip the cache and shell out even if a cached URL looks fresh.
+    force_refresh: bool = false,
+};
+
+/// True when `url` points at a video page rather than a direct stream.
+pub fn shouldResolve(url: []const u8) bool {
+    const scheme_end = std.mem.indexOf(u8, url, "://") orelse return false;
+    var host = url[scheme_end + 3 ..];
+    if (std.mem.indexOfScalar(u8, host, '/')) |slash| host = host[0..slash];
+    if (std.mem.startsWith(u8, host, "www.")) host = host[4..];
+
+    for (resolvable_hosts) |candidate| {
+        if (std.mem.eql(u8, host, candidate)) return true;
+    }
+    return false;
+}
+
+/// Resolves `url` to a direct stream URL, using the on-disk cache when the
+/// cached entry has not expired. Caller owns the returned slice.
+pub fn resolve(
+    allocator: std.mem.Allocator,
+    url: []const u8,
+    options: ResolveOptions,
+) ResolveError![]u8 {
+    const cache_path = try cachePath(allocator, url);
+    defer allocator.free(cache_path);
+
+    if (!options.force_refresh) {
+        if (readCache(allocator, cache_path, url)) |cached| return cached;
+    }
+
+    const resolved = try runYtDlp(allocator, url);
+    errdefer allocator.free(resolved);
+
+    const expires = parseExpiry(resolved) orelse std.time.timestamp() + default_ttl_s;
+    writeCache(allocator, cache_path, url, resolved, expires) catch |err| {
+        std.log.warn("resolver cache write failed: {s}", .{@errorName(err)});
+    };
+    std.log.info("resolved {s} via yt-dlp", .{url});
+    return resolved;
+}
+
+fn runYtDlp(allocator: std.mem.Allocator, url: []const u8) ResolveError![]u8 {
+    const result = std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = &.{ "yt-dlp", "--no-warnings", "--get-url", "--format", "best", url },
+        .max_output_bytes = 64 * 1024,
+    }) catch |err| {
+        if (err == error.FileNotFound) {
+            std.log.err("yt-dlp not found; install it to play {s}", .{url});
+            return ResolveError.ResolverMissing;
+        }
+        return ResolveError.ResolveFailed;
+    };
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+
+    if (result.term != .Exited or result.term.Exited != 0) {
+        std.log.err("yt-dlp failed for {s}: {s}", .{ url, std.mem.trim(u8, result.stderr, "\n ") });
+        return ResolveError.ResolveFailed;
+    }
+
+    // yt-dlp prints one URL per line; with a combined format the first line
+    // is the one we want.
+    var lines = std.mem.tokenizeScalar(u8, result.stdout, '\n');
+    const first = lines.next() orelse return ResolveError.ResolveFailed;
+    const trimmed = std.mem.trim(u8, first, " \r");
+    if (trimmed.len == 0) return ResolveError.ResolveFailed;
+    return allocator.dupe(u8, trimmed);
+}
+
+/// Expiry embedded in the resolved URL (YouTube CDN links carry an
+/// `expire=<unix>` query parameter), or null.
+pub fn parseExpiry(url: []const u8) ?i64 {
+    for ([_][]const u8{ "expire=", "expires=" }) |key| {
+        var search: usize = 0;
+        while (std.mem.indexOfPos(u8, url, search, key)) |start| {
+            search = start + key.len;
+            // Only match at a parameter boundary, not inside another key.
+            if (start > 0 and url[start - 1] != '?' and url[start - 1] != '&') continue;
+            const digits_start = start + key.len;
+            var end = digits_start;
+            while (end < url.len and std.ascii.isDigit(url[end])) end += 1;
+            if (end == digits_start) continue;
+            return std.fmt.parseInt(i64, url[digits_start..end], 10) catch continue;
+        }
+    }
+    return null;
+}
+
+fn cachePath(allocator: std.mem.Allocator, url: []const u8) ![]u8 {
+    const cache_root = std.posix.getenv("XDG_CACHE_HOME");
+    const home = std.posix.getenv("HOME");
+    const hash = std.hash.Wyhash.hash(0, url);
+    if (cache_root) |root| {
+        return std.fmt.allocPrint(allocator, "{s}/waystream/resolved/{x}.json", .{ root, hash });
+    }
+    if (home) |dir| {
+        return std.fmt.allocPrint(allocator, "{s}/.cache/waystream/resolved/{x}.json", .{ dir, hash });
+    }
+    return std.fmt.allocPrint(allocator, "/tmp/waystream/resolved/{x}.json", .{hash});
+}
+
+/// Returns the cached resolved URL when it matches `url` and is still
+/// fresh; null (never an error) otherwise.
+fn readCache(allocator: std.mem.Allocator, path: []const u8, url: []const u8) ?[]u8 {
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const arena_allocator = arena.allocator();
+
+    const data = std.fs.cwd().readFileAlloc(arena_allocator, path, 1024 * 1024) catch return null;
+    const parsed = std.json.parseFromSliceLeaky(std.json.Value, arena_allocator, data, .{}) catch
+        return null;
+    const root = switch (parsed) {
+        .object => |object| object,
+        else => return null,
+    };
+
+    const source = switch (root.get("source") orelse return null) {
+        .string => |value| value,
+        else => return null,
+    };
+    if (!std.mem.eql(u8, source, url)) return null;
+
+    const expires = switch (root.get("expires_unix") orelse return null) {
+        .integer => |value| value,
+        else => return null,
+    };
+    // Refresh a little early so playback never starts on a link about to die.
+    if (std.time.timestamp() >= expires - 60) return null;
+
+    const resolved = switch (root.get("resolved") orelse return null) {
+        .string => |value| value,
+        else => return null,
+    };
+    return allocator.dupe(u8, resolved) catch null;
+}
+
+fn writeCache(
+    allocator: std.mem.Allocator,
+    path: []const u8,
+    url: []const u8,
+    resolved: []const u8,
+    expires: i64,
+) !void {
+    const json = try std.fmt.allocPrint(
+        allocator,
+        "{{\"source\":\"{s}\",\"resolved\":\"{s}\",\"expires_unix\":{d}}}\n",
+        .{ url, resolved, expires },
+    );
+    defer allocator.free(json);
+
+    if (std.fs.path.dirname(path)) |dir| {
+        std.fs.cwd().makePath(dir) catch {};
+    }
+    const file = try std.fs.cwd().createFile(path, .{});
+    defer file.close();
+    try file.writeAll(json);
+}
+
+test "shouldResolve matches page hosts only" {
+    try std.testing.expect(shouldResolve("https://www.youtube.com/watch?v=abc"));
+    try std.testing.expect(shouldResolve("https://youtu.be/abc"));
+    try std.testing.expect(!shouldResolve("https://example.com/clip.mp4"));
+    try std.testing.expect(!shouldResolve("/home/user/clip.mp4"));
+}
+
+test "parseExpiry reads the expire query parameter" {
+    try std.testing.expectEqual(
+        @as(?i64, 1700000000),
+        parseExpiry("https://cdn.example/video?expire=1700000000&sig=x"),
+    );
+    try std.testing.expectEqual(@as(?i64, null), parseExpiry("https://cdn.example/video?sig=x"));
+    // `unexpired=` must not match `expire=`.
+    try std.testing.expectEqual(@as(?i64, null), parseExpiry("https://cdn.example/?unexpired=5"));
+}
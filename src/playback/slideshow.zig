@@ -0,0 +1,86 @@
+//! Image discovery for slideshow mode.
+//!
+//! Pointing `play` at a directory cycles through the images inside it
+//! entirely within the renderer — no video decoding involved. This module
+//! only finds and orders the images; timing and fading live in the player.
+
+const std = @import("std");
+
+const image_extensions = [_][]const u8{
+    ".png", ".jpg", ".jpeg", ".bmp", ".gif", ".qoi", ".tga",
+};
+
+pub fn isImageFile(name: []const u8) bool {
+    const dot = std.mem.lastIndexOfScalar(u8, name, '.') orelse return false;
+    const extension = name[dot..];
+    for (image_extensions) |candidate| {
+        if (std.ascii.eqlIgnoreCase(extension, candidate)) return true;
+    }
+    return false;
+}
+
+/// True when `path` is a directory (and therefore a slideshow source).
+pub fn isDirectory(path: []const u8) bool {
+    const stat = std.fs.cwd().statFile(path) catch return false;
+    return stat.kind == .directory;
+}
+
+/// Collects all image files in `dir_path`, sorted by name so the default
+/// sequential order is stable. Caller frees with `freeImages`.
+pub fn scanImages(allocator: std.mem.Allocator, dir_path: []const u8) ![][]const u8 {
+    var dir = try std.fs.cwd().openDir(dir_path, .{ .iterate = true });
+    defer dir.close();
+
+    var images: std.ArrayList([]const u8) = .empty;
+    errdefer {
+        for (images.items) |path| allocator.free(path);
+        images.deinit(allocator);
+    }
+
+    var it = dir.iterate();
+    while (try it.next()) |entry| {
+        if (entry.kind != .file and entry.kind != .sym_link) continue;
+        if (!isImageFile(entry.name)) continue;
+        const path = try std.fs.path.join(allocator, &.{ dir_path, entry.name });
+        try images.append(allocator, path);
+    }
+
+    const slice = try images.toOwnedSlice(allocator);
+    std.mem.sort([]const u8, slice, {}, struct {
+        fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+            return std.mem.lessThan(u8, a, b);
+        }
+    }.lessThan);
+    return slice;
+}
+
+pub fn freeImages(allocator: std.mem.Allocator, images: [][]const u8) void {
+    for (images) |path| allocator.free(path);
+    allocator.free(images);
+}
+
+test "isImageFile matches extensions case-insensitively" {
+    try std.testing.expect(isImageFile("wall.png"));
+    try std.testing.expect(isImageFile("wall.JPG"));
+    try std.testing.expect(!isImageFile("wall.mp4"));
+    try std.testing.expect(!isImageFile("no-extension"));
+}
+
+test "scanImages finds and sorts images" {
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    try tmp.dir.writeFile(.{ .sub_path = "b.png", .data = "" });
+    try tmp.dir.writeFile(.{ .sub_path = "a.jpg", .data = "" });
+    try tmp.dir.writeFile(.{ .sub_path = "notes.txt", .data = "" });
+
+    const dir_path = try tmp.dir.realpathAlloc(std.testing.allocator, ".");
+    defer std.testing.allocator.free(dir_path);
+
+    const images = try scanImages(std.testing.allocator, dir_path);
+    defer freeImages(std.testing.allocator, images);
+
+    try std.testing.expectEqual(@as(usize, 2), images.len);
+    try std.testing.expect(std.mem.endsWith(u8, images[0], "a.jpg"));
+    try std.testing.expect(std.mem.endsWith(u8, images[1], "b.png"));
+}
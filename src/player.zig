@@ -0,0 +1,1669 @@
+//! The `play` command: decodes a video with GStreamer and renders it as a
+//! looping background surface.
+
+const std = @import("std");
+const rl = @import("raylib");
+const pipeline_mod = @import("playback/pipeline.zig");
+const signals = @import("signals.zig");
+const snapshot_mod = @import("metrics/snapshot.zig");
+const control = @import("control/socket.zig");
+const icc = @import("render/icc.zig");
+const supervisor = @import("supervisor.zig");
+const blend = @import("render/blend.zig");
+const layout = @import("render/layout.zig");
+const adaptive_mod = @import("playback/adaptive.zig");
+const yuv = @import("render/yuv.zig");
+const resolver = @import("playback/resolver.zig");
+const playlist_mod = @import("playback/playlist.zig");
+const slideshow = @import("playback/slideshow.zig");
+const embed = @import("playback/embed.zig");
+const filewatch = @import("playback/filewatch.zig");
+const swapchain = @import("render/swapchain.zig");
+const pathprobe = @import("render/pathprobe.zig");
+const scale = @import("render/scale.zig");
+const worker = @import("render/worker.zig");
+const budget_mod = @import("playback/budget.zig");
+const prometheus = @import("metrics/prometheus.zig");
+const metrics_stream = @import("metrics/stream.zig");
+const history_mod = @import("metrics/history.zig");
+const retention = @import("metrics/retention.zig");
+const otlp = @import("metrics/otlp.zig");
+const power = @import("metrics/power.zig");
+const events_mod = @import("metrics/events.zig");
+const dbus = @import("metrics/dbus.zig");
+const gpu = @import("metrics/gpu.zig");
+const frametime = @import("metrics/frametime.zig");
+const latency = @import("metrics/latency.zig");
+const memory = @import("metrics/memory.zig");
+const wl_globals = @import("wayland/globals.zig");
+const color_management = @import("wayland/color_management.zig");
+const present = @import("wayland/present.zig");
+const dmabuf_import = @import("wayland/dmabuf_import.zig");
+
+const Pipeline = pipeline_mod.Pipeline;
+
+pub const Options = struct {
+    /// Path or URI of the video to play (first playlist entry).
+    video: []const u8,
+    /// All inputs for this target, advanced through on EOS. Empty means
+    /// just `video`.
+    playlist: []const []const u8 = &.{},
+    /// Playlist play order.
+    order: playlist_mod.Order = .sequential,
+    /// What happens after the last playlist entry (loop must be on).
+    repeat: playlist_mod.Repeat = .all,
+    /// Seconds each image stays up when the input is a directory of images.
+    slide_duration_s: u32 = 60,
+    /// Crossfade length between slides; 0 switches hard.
+    fade_s: f64 = 1.0,
+    /// Fade the incoming source in over this many milliseconds when the
+    /// video is swapped at runtime (set-video, playlist advance); 0 cuts
+    /// hard. This is the `transition` config setting on the wire.
+    transition_fade_ms: u32 = 0,
+    /// Low-power quasi-static mode: decode one frame every N seconds and
+    /// keep the pipeline paused in between.
+    frame_step_s: ?u32 = null,
+    /// What playback does while on battery (checked once per metrics
+    /// interval via `power_provider`).
+    on_battery: power.Policy = .@"continue",
+    /// Where the power state comes from; tests substitute this.
+    power_provider: power.Provider = .sysfs,
+    /// Presentation buffer depth (2 = double, 3 = triple buffering).
+    buffer_depth: u32 = swapchain.default_depth,
+    /// How frames reach the compositor; auto measures at startup.
+    buffer_mode: pathprobe.Mode = .auto,
+    /// How the video is placed on the surface (letterbox, crop, stretch).
+    scale_mode: layout.ScaleMode = .fit,
+    /// Cap on buffer memory in megabytes; decode resolution steps down
+    /// when exceeded. Null disables the cap.
+    mem_cap_mb: ?u32 = null,
+    /// CPU budget as a percentage of one core; frames are skipped and the
+    /// decode resolution stepped down to stay under it. Null disables.
+    cpu_budget_pct: ?u32 = null,
+    /// Present through waylandsink (zero-copy) instead of the appsink path.
+    embed_sink: bool = false,
+    /// Restart from the beginning on EOS.
+    loop: bool = true,
+    /// Name this playback runs under (metrics, control).
+    target: []const u8 = "default",
+    /// Listen for JSON commands on the target's control socket.
+    control_socket: bool = false,
+    /// HTTP address (addr:port) serving Prometheus metrics; null disables.
+    metrics_listen: ?[]const u8 = null,
+    /// Push metric updates to subscribers on the target's metrics socket.
+    metrics_stream: bool = false,
+    /// Append one snapshot every N seconds to the target's JSONL history
+    /// log (size-rotated). Null disables.
+    metrics_history_s: ?u32 = null,
+    /// OTLP collector (`host:port`) receiving metrics pushes; null falls
+    /// back to OTEL_EXPORTER_OTLP_ENDPOINT, and needs a `-Dotlp` build.
+    otlp_endpoint: ?[]const u8 = null,
+    /// Emit MetricsUpdated on the session bus once per metrics interval.
+    dbus: bool = false,
+    /// Negotiate HDR-capable formats and keep HDR colorimetry.
+    hdr: bool = false,
+    /// Directory for pipeline DOT graph dumps.
+    dump_dot_dir: ?[]const u8 = null,
+    /// ICC profile applied to decoded frames before display.
+    icc_profile: ?[]const u8 = null,
+    /// Soft limit on simultaneously running players.
+    max_players: u32 = supervisor.default_max_players,
+    /// Play the audio track.
+    audio: bool = false,
+    /// Linear audio volume (0.0-1.0, 1.0 = unity).
+    volume: f64 = 1.0,
+    /// PulseAudio/PipeWire sink name for audio output.
+    audio_sink: ?[]const u8 = null,
+    /// Second source blended in over the time window below.
+    blend_to: ?[]const u8 = null,
+    /// Local-time window over which the blend ramps from 0 to 1.
+    blend_window: ?blend.Window = null,
+    /// Custom gst-launch-style pipeline description.
+    pipeline_override: ?[]const u8 = null,
+    /// Scale frames to the output inside the pipeline instead of on the CPU.
+    decode_at_output: bool = true,
+    /// Step decode resolution down when FPS stays below this threshold.
+    adaptive_min_fps: ?f64 = null,
+    /// Accept NV12/I420 from the decoder and convert at blit time.
+    allow_yuv: bool = true,
+    /// Offload scaling/conversion to vapostproc or v4l2convert when present.
+    gpu_postproc: bool = true,
+    /// Download buffer size in bytes for network sources.
+    net_buffer_bytes: ?u32 = null,
+    /// Connection/read timeout for network sources.
+    net_timeout_s: u32 = 10,
+    /// Rebuild failed network streams with exponential backoff instead of
+    /// exiting; the last frame stays up while reconnecting.
+    reconnect: bool = true,
+    /// Recovery attempts for local pipeline errors before giving up.
+    /// Network streams retry indefinitely while `reconnect` is on.
+    max_retries: u32 = 3,
+};
+
+const metrics_interval_ms: i64 = 1000;
+
+/// Upper bound on one `Pipeline.waitEvent` park. The loop normally wakes
+/// early on the appsink's new-sample signal or a bus message; this only
+/// bounds how stale signal flags, control commands, and timers can get.
+const event_wait_ms: i32 = 50;
+
+/// How long to wait for the preroll frame before showing black anyway.
+const preroll_timeout_ns: u64 = 3 * std.time.ns_per_s;
+
+pub fn run(allocator: std.mem.Allocator, options: Options) !void {
+    signals.install();
+
+    try supervisor.register(allocator, options.target, options.video, options.max_players);
+    defer supervisor.unregister(allocator, options.target);
+
+    // A directory input means slideshow mode: cycle its images in the
+    // renderer without any video pipeline.
+    if (options.playlist.len <= 1 and slideshow.isDirectory(options.video)) {
+        return runSlideshow(allocator, options);
+    }
+
+    if (options.embed_sink) {
+        return runEmbedded(allocator, options);
+    }
+
+    // Explicit dmabuf mode means the real presentation path: layer-shell
+    // surfaces with zero-copy buffer import. Machines that cannot run it
+    // drop to the window path instead of refusing to start.
+    if (options.buffer_mode == .dmabuf) {
+        if (runLayerShell(allocator, options)) |_| {
+            return;
+        } else |err| switch (err) {
+            error.ConnectFailed,
+            error.MissingProtocol,
+            error.NoFeedback,
+            error.NoOutputs,
+            error.NoRenderNode,
+            error.DeviceFailed,
+            error.AllocationFailed,
+            => std.log.warn("dmabuf presentation unavailable ({s}); using the window path", .{
+                @errorName(err),
+            }),
+            else => return err,
+        }
+    }
+
+    // --no-loop trumps the repeat mode so a one-shot invocation still exits.
+    var playlist = try playlist_mod.Playlist.init(
+        allocator,
+        if (options.playlist.len > 0) options.playlist else &.{options.video},
+        options.order,
+        if (options.loop) options.repeat else .off,
+        @bitCast(std.time.milliTimestamp()),
+    );
+    defer playlist.deinit();
+
+    // Video-page URLs go through yt-dlp first; the playlist entry stays the
+    // user-facing label for metrics and status.
+    const resolver_used = resolver.shouldResolve(playlist.current());
+    var video_source: []const u8 = playlist.current();
+    if (resolver_used) {
+        video_source = try resolver.resolve(allocator, playlist.current(), .{});
+    }
+    defer if (resolver_used) allocator.free(video_source);
+
+    const uri = try pipeline_mod.pathToUri(allocator, video_source);
+    defer allocator.free(uri);
+
+    const start_ms = std.time.milliTimestamp();
+
+    var open_options: pipeline_mod.OpenOptions = .{
+        .hdr = options.hdr,
+        .dump_dot_dir = options.dump_dot_dir,
+        .audio = options.audio,
+        .volume = options.volume,
+        .audio_sink = options.audio_sink,
+        .pipeline_override = options.pipeline_override,
+        .allow_yuv = options.allow_yuv,
+        .gpu_postproc = options.gpu_postproc,
+        .net_buffer_bytes = options.net_buffer_bytes,
+        .net_timeout_s = options.net_timeout_s,
+    };
+
+    // Window bring-up and pipeline preroll are independent unless the caps
+    // must carry the output size; overlap them so cold start is bounded by
+    // the slower of the two instead of their sum.
+    var pre_opened: ?Pipeline = null;
+    var open_error: ?anyerror = null;
+    var open_thread: ?std.Thread = null;
+    if (!options.decode_at_output) {
+        open_thread = try std.Thread.spawn(
+            .{},
+            openPipelineTask,
+            .{ allocator, uri, open_options, &pre_opened, &open_error },
+        );
+    }
+
+    rl.initWindow(800, 450, "waystream");
+    defer rl.closeWindow();
+    rl.setTargetFPS(60);
+    var surface: layout.Size = .{
+        .width = @intCast(rl.getScreenWidth()),
+        .height = @intCast(rl.getScreenHeight()),
+    };
+    open_options.target_size = if (options.decode_at_output) surface else null;
+
+    var pipeline: Pipeline = undefined;
+    if (open_thread) |thread| {
+        thread.join();
+        pipeline = pre_opened orelse return open_error.?;
+    } else {
+        pipeline = try Pipeline.open(allocator, uri, open_options);
+    }
+    defer pipeline.deinit();
+    pipeline.watchDecoderSelection();
+
+    const control_server: ?*control.Server = if (options.control_socket)
+        try control.Server.start(allocator, options.target)
+    else
+        null;
+    defer if (control_server) |server| server.stop();
+
+    const metrics_exporter: ?*prometheus.Exporter = if (options.metrics_listen) |listen|
+        try prometheus.Exporter.start(allocator, listen)
+    else
+        null;
+    defer if (metrics_exporter) |exporter| exporter.stop();
+
+    const stream_publisher: ?*metrics_stream.Publisher = if (options.metrics_stream)
+        try metrics_stream.Publisher.start(allocator, options.target)
+    else
+        null;
+    defer if (stream_publisher) |publisher| publisher.stop();
+
+    const otlp_exporter: ?*otlp.Exporter = blk: {
+        const endpoint = options.otlp_endpoint orelse
+            std.posix.getenv("OTEL_EXPORTER_OTLP_ENDPOINT") orelse
+            break :blk null;
+        if (!otlp.enabled) {
+            std.log.warn("OTLP endpoint configured but this build lacks -Dotlp; ignoring", .{});
+            break :blk null;
+        }
+        break :blk try otlp.Exporter.start(allocator, endpoint);
+    };
+    defer if (otlp_exporter) |exporter| exporter.stop();
+
+    // Best effort: a missing session bus downgrades to running without
+    // the signal rather than refusing to start the wallpaper.
+    const dbus_publisher: ?*dbus.Publisher = if (options.dbus)
+        dbus.Publisher.start(allocator, options.target) catch null
+    else
+        null;
+    defer if (dbus_publisher) |publisher| publisher.stop();
+
+    var texture: ?rl.Texture2D = null;
+    defer if (texture) |tex| rl.unloadTexture(tex);
+
+    // Optional second source for time-of-day blending.
+    var blend_pipeline: ?Pipeline = null;
+    defer if (blend_pipeline) |*p| p.deinit();
+    var blend_texture: ?rl.Texture2D = null;
+    defer if (blend_texture) |tex| rl.unloadTexture(tex);
+    if (options.blend_to) |blend_video| {
+        const blend_uri = try pipeline_mod.pathToUri(allocator, blend_video);
+        defer allocator.free(blend_uri);
+        blend_pipeline = try Pipeline.open(allocator, blend_uri, open_options);
+        try blend_pipeline.?.play();
+    }
+
+    var icc_transform: ?icc.Transform = if (options.icc_profile) |profile|
+        try icc.Transform.open(allocator, profile)
+    else
+        null;
+    defer if (icc_transform) |*transform| transform.deinit();
+    var yuv_scratch: std.ArrayList(u8) = .empty;
+    defer yuv_scratch.deinit(allocator);
+    var blend_scratch: std.ArrayList(u8) = .empty;
+    defer blend_scratch.deinit(allocator);
+
+    // Per-surface compose worker: YUV conversion, ICC, and box filtering
+    // run on their own thread so several outputs convert in parallel and
+    // this loop only uploads finished buffers. Declared after the ICC
+    // transform so it stops (and drops any in-flight job) before the
+    // transform and pipeline go away.
+    const compose_worker = try ComposeWorker.start(allocator);
+    defer compose_worker.stop();
+
+    // The pipeline prerolled to PAUSED in open(); grab that first frame and
+    // upload it before starting playback, so the first presented frame is
+    // the wallpaper instead of a black flash.
+    var first_frame_ms: f64 = 0;
+    if (pipeline.pullPreroll(preroll_timeout_ns)) |frame| {
+        var first = frame;
+        defer first.unref();
+        const prepared = try prepareFrame(allocator, &yuv_scratch, first);
+        uploadFrame(&texture, first.width, first.height, prepared.format, prepared.pixels);
+        first_frame_ms = @floatFromInt(std.time.milliTimestamp() - start_ms);
+        std.log.info("first frame in {d:.0}ms", .{first_frame_ms});
+    }
+
+    try pipeline.play();
+
+    const metrics_path = try snapshot_mod.defaultPath(allocator, options.target);
+    defer allocator.free(metrics_path);
+    if (std.fs.path.dirname(metrics_path)) |metrics_dir| {
+        const swept = retention.sweepDir(allocator, metrics_dir, .{}) catch 0;
+        if (swept > 0) std.log.info("removed {d} stale metrics file(s)", .{swept});
+    }
+
+    var metrics_history: ?history_mod.History = if (options.metrics_history_s != null)
+        history_mod.History.init(allocator, try history_mod.defaultPath(allocator, options.target))
+    else
+        null;
+    defer if (metrics_history) |*hist| hist.deinit();
+    var last_history_ms: i64 = 0;
+
+    // One-off path measurement; the decision and numbers land in every
+    // snapshot so per-machine behaviour is explainable after the fact.
+    const path_probe = pathprobe.run(allocator, surface.width, surface.height);
+    const buffer_path = pathprobe.decide(options.buffer_mode, path_probe);
+    std.log.info("buffer path: {s} (shm copy {d:.2}ms, dmabuf {s})", .{
+        buffer_path.describe(),
+        path_probe.shm_copy_ms,
+        if (path_probe.dmabuf_ok) "ok" else "unavailable",
+    });
+
+    var frames_rendered: u64 = 0;
+    var frames_dropped: u64 = 0;
+    var frames_skipped: u64 = 0;
+    var frames_late: u64 = 0;
+    var last_present_ms: i64 = 0;
+    // Source frame interval from the negotiated caps; refreshed with the
+    // metrics so lateness is not judged against a stale rate.
+    var nominal_interval_ms: f64 = 0;
+    var frame_times: frametime.Recorder = .{};
+    var latency_histogram: latency.Histogram = .{};
+    var interval_frames: u64 = 0;
+    var last_metrics_ms = std.time.milliTimestamp();
+
+    // Adaptive quality needs in-pipeline scaling to have anything to adjust.
+    var adaptive: ?adaptive_mod.Adaptive = if (options.adaptive_min_fps) |min_fps|
+        if (options.decode_at_output)
+            adaptive_mod.Adaptive.init(.{ .min_fps = min_fps })
+        else
+            null
+    else
+        null;
+    var status_note: []const u8 = "";
+    defer if (status_note.len > 0) allocator.free(status_note);
+
+    // Timestamped pipeline history riding along in every snapshot, so
+    // "why did it stutter at 14:03" is answerable from the file.
+    var event_log = events_mod.Log.init(allocator);
+    defer event_log.deinit();
+    var decoder_logged = false;
+    var hdr_logged = false;
+    // When the last runtime source swap started, for the fade-in
+    // transition; 0 means none has happened yet.
+    var transition_started_ms: i64 = 0;
+    // Battery-policy state: whether this player paused or dropped to
+    // frame stepping because the machine is discharging.
+    var battery_paused = false;
+    var battery_static = false;
+    event_log.add(std.time.milliTimestamp(), "buffer path {s}", .{buffer_path.describe()});
+
+    var cpu_budget: ?budget_mod.Budget = if (options.cpu_budget_pct) |pct|
+        budget_mod.Budget.init(.{ .budget = @as(f64, @floatFromInt(pct)) / 100.0 })
+    else
+        null;
+    var last_cpu_ns = budget_mod.processCpuNs();
+    // Decoded frames seen, for the budget's every-Nth-frame skipping.
+    var budget_frame_count: u64 = 0;
+    // The high-resolution guardrail only needs to look at one frame.
+    var guard_checked = false;
+
+    // Confirms hardware decode is really engaged: a hw decoder with the
+    // engine flat at zero is worth noticing.
+    var gpu_sampler = gpu.Sampler.init(allocator);
+    defer gpu_sampler.deinit();
+
+    var accounting: memory.Accounting = .{
+        .cap_bytes = if (options.mem_cap_mb) |mb| @as(u64, mb) * 1024 * 1024 else null,
+    };
+    // Downscale steps already taken to satisfy the cap (halves each time).
+    var mem_step: u5 = 0;
+
+    // Recovery state: any pipeline error schedules a rebuild with backoff.
+    // Network streams retry forever (while reconnect is on); local sources
+    // get `max_retries` attempts before the error is fatal.
+    const max_backoff_ms: i64 = 30_000;
+    var reconnect_backoff_ms: i64 = 0;
+    var reconnect_at_ms: ?i64 = null;
+    var retries_used: u32 = 0;
+    var recovering = false;
+
+    // Rebuild the pipeline when the video file is replaced on disk, so
+    // wallpaper generators that re-render into the same path take effect
+    // without the stale deleted inode looping forever.
+    var file_watcher: ?filewatch.FileWatcher = if (options.playlist.len <= 1 and
+        !resolver_used and
+        options.pipeline_override == null and
+        std.mem.indexOf(u8, options.video, "://") == null)
+        filewatch.FileWatcher.init(allocator, options.video) catch |err| blk: {
+            std.log.warn("file watch unavailable: {s}", .{@errorName(err)});
+            break :blk null;
+        }
+    else
+        null;
+    defer if (file_watcher) |*watcher| watcher.deinit();
+
+    // In frame-step mode the pipeline pauses itself between frames, so the
+    // user's pause wish has to be tracked separately from pipeline.paused.
+    var user_paused = false;
+    var frame_step_due_ms: i64 = 0;
+
+    var quit_requested = false;
+    while (!rl.windowShouldClose() and !signals.quitRequested() and !quit_requested) {
+        if (signals.takeTogglePause()) {
+            user_paused = !user_paused;
+            event_log.add(std.time.milliTimestamp(), "{s} (SIGUSR1)", .{
+                if (user_paused) "paused" else "resumed",
+            });
+            if (user_paused) try pipeline.pause() else try pipeline.play();
+            if (blend_pipeline) |*second| {
+                if (user_paused) try second.pause() else try second.play();
+            }
+        }
+        var redraw_forced = signals.takeForceRedraw();
+
+        // A resize loses nothing: the last composed frame is still in
+        // `texture`, so pick up the new size and re-present it this very
+        // iteration instead of flashing black until the next decoded
+        // frame. The pipeline rebuild to the new decode size happens
+        // behind that repaint.
+        if (rl.isWindowResized()) {
+            surface = .{
+                .width = @intCast(rl.getScreenWidth()),
+                .height = @intCast(rl.getScreenHeight()),
+            };
+            redraw_forced = true;
+            std.log.info("surface resized to {d}x{d}", .{ surface.width, surface.height });
+            event_log.add(std.time.milliTimestamp(), "resized to {d}x{d}", .{
+                surface.width,
+                surface.height,
+            });
+            if (options.decode_at_output) {
+                open_options.target_size = surface;
+                swapVideo(allocator, &pipeline, playlist.current(), open_options) catch |err| {
+                    std.log.err("resize rebuild failed: {s}", .{@errorName(err)});
+                };
+            }
+        }
+
+        if (control_server) |server| {
+            while (server.poll()) |cmd| {
+                defer cmd.deinit(allocator);
+                switch (cmd) {
+                    .pause => {
+                        user_paused = true;
+                        try pipeline.pause();
+                        if (blend_pipeline) |*second| try second.pause();
+                    },
+                    .resume_playback => {
+                        user_paused = false;
+                        try pipeline.play();
+                        if (blend_pipeline) |*second| try second.play();
+                    },
+                    .seek => |seconds| {
+                        event_log.add(std.time.milliTimestamp(), "seek to {d:.1}s (control)", .{seconds});
+                        pipeline.seekTo(seconds);
+                        redraw_forced = true;
+                        // The jump is deliberate; keep it out of the
+                        // frame-time distribution.
+                        frame_times.reset();
+                        last_present_ms = 0;
+                    },
+                    .set_rate => |rate| pipeline.setRate(rate),
+                    .set_video => |video| {
+                        event_log.add(std.time.milliTimestamp(), "source swapped (control)", .{});
+                        swapVideo(allocator, &pipeline, video, open_options) catch |err| {
+                            std.log.err("set-video failed: {s}", .{@errorName(err)});
+                        };
+                        transition_started_ms = std.time.milliTimestamp();
+                        redraw_forced = true;
+                        frame_times.reset();
+                        last_present_ms = 0;
+                    },
+                    .dump_dot => pipeline.dumpDot("manual"),
+                    .quit => quit_requested = true,
+                }
+            }
+        }
+
+        switch (pipeline.pollBus()) {
+            .running => {},
+            .eos => switch (playlist.onEos()) {
+                .stop => break,
+                .restart => {
+                    event_log.add(std.time.milliTimestamp(), "loop seek", .{});
+                    pipeline.seekToStart();
+                },
+                .next => |next| {
+                    event_log.add(std.time.milliTimestamp(), "playlist advance", .{});
+                    swapToSource(allocator, &pipeline, next, open_options, .{}) catch |err| {
+                        std.log.err("playlist advance to {s} failed: {s}", .{ next, @errorName(err) });
+                    };
+                    transition_started_ms = std.time.milliTimestamp();
+                },
+            },
+            .failed => {
+                event_log.add(std.time.milliTimestamp(), "pipeline error", .{});
+                const network = pipeline_mod.isNetworkUri(playlist.current()) or
+                    resolver.shouldResolve(playlist.current());
+                const unlimited = network and options.reconnect;
+                if (!unlimited and retries_used >= options.max_retries) {
+                    std.log.err("pipeline failed after {d} recovery attempts", .{retries_used});
+                    break;
+                }
+                if (!unlimited) retries_used += 1;
+                reconnect_backoff_ms = if (reconnect_backoff_ms == 0)
+                    1000
+                else
+                    @min(reconnect_backoff_ms * 2, max_backoff_ms);
+                reconnect_at_ms = std.time.milliTimestamp() + reconnect_backoff_ms;
+                if (unlimited) {
+                    setNote(allocator, &status_note, "stream lost, reconnecting in {d}s", .{
+                        @divTrunc(reconnect_backoff_ms, 1000),
+                    });
+                } else {
+                    setNote(allocator, &status_note, "pipeline error, rebuilding in {d}s ({d}/{d})", .{
+                        @divTrunc(reconnect_backoff_ms, 1000),
+                        retries_used,
+                        options.max_retries,
+                    });
+                }
+            },
+        }
+
+        // Low-power stepping: wake the pipeline once the interval elapses,
+        // let one frame through, and pause again when it is uploaded.
+        if (effectiveFrameStep(options.frame_step_s, battery_static) != null and
+            pipeline.paused and !user_paused and
+            reconnect_at_ms == null and
+            std.time.milliTimestamp() >= frame_step_due_ms)
+        {
+            try pipeline.play();
+        }
+
+        if (file_watcher) |*watcher| {
+            if (watcher.changed()) {
+                std.log.info("video file replaced, reloading", .{});
+                swapToSource(allocator, &pipeline, options.video, open_options, .{}) catch |err| {
+                    std.log.err("reload after file change failed: {s}", .{@errorName(err)});
+                };
+                setNote(allocator, &status_note, "video file changed, reloaded", .{});
+                redraw_forced = true;
+            }
+        }
+
+        // The last uploaded frame stays on screen while we wait out the
+        // backoff and try to rebuild the pipeline.
+        if (reconnect_at_ms) |at| {
+            if (std.time.milliTimestamp() >= at) {
+                reconnect_at_ms = null;
+                // Expired stream links are the usual failure mode for
+                // resolved URLs; force_refresh fetches a fresh one.
+                if (swapToSource(allocator, &pipeline, playlist.current(), open_options, .{
+                    .force_refresh = true,
+                })) {
+                    reconnect_backoff_ms = 0;
+                    recovering = true;
+                    setNote(allocator, &status_note, "pipeline rebuilt", .{});
+                    event_log.add(std.time.milliTimestamp(), "pipeline rebuilt", .{});
+                } else |err| {
+                    std.log.warn("reconnect failed: {s}", .{@errorName(err)});
+                    reconnect_backoff_ms = @min(reconnect_backoff_ms * 2, max_backoff_ms);
+                    reconnect_at_ms = std.time.milliTimestamp() + reconnect_backoff_ms;
+                }
+            }
+        }
+
+        if (!pipeline.paused or redraw_forced) {
+            // Park until the appsink signals a decoded sample or the bus
+            // has news, then drain without blocking. A forced redraw while
+            // paused skips the wait: no new frame is coming.
+            if (!pipeline.paused) pipeline.waitEvent(event_wait_ms);
+            if (pipeline.pullFrame(0)) |frame| {
+                var current = frame;
+
+                // Keep-latest: when the renderer fell behind, anything
+                // still queued is stale; skip straight to the newest frame
+                // instead of replaying the backlog with growing latency.
+                while (pipeline.pullFrame(0)) |newer| {
+                    current.unref();
+                    current = newer;
+                    frames_dropped += 1;
+                }
+
+                // Record the wp-color-management image description HDR
+                // content would need, once; the window backend cannot carry
+                // it, so the snapshot is where "why does HDR look flat here"
+                // gets answered.
+                if (!hdr_logged and current.colorimetry.isHdr()) {
+                    hdr_logged = true;
+                    if (color_management.imageDescription(current.colorimetry)) |desc| {
+                        event_log.add(std.time.milliTimestamp(), "HDR needs image description {s}/{s}", .{
+                            @tagName(desc.primaries),
+                            @tagName(desc.transfer),
+                        });
+                    }
+                }
+
+                // Guardrail: a source vastly larger than the output would
+                // cost hundreds of MB of frame copies per second on the
+                // CPU path; rebuild with an in-pipeline downscale even
+                // though full decode was requested.
+                if (!guard_checked) {
+                    guard_checked = true;
+                    const video_size: layout.Size = .{
+                        .width = current.width,
+                        .height = current.height,
+                    };
+                    if (!options.decode_at_output and
+                        scale.needsDecodeGuard(video_size, surface))
+                    {
+                        std.log.warn(
+                            "source {d}x{d} vastly exceeds output {d}x{d}; scaling in the pipeline",
+                            .{ current.width, current.height, surface.width, surface.height },
+                        );
+                        current.unref();
+                        open_options.target_size = surface;
+                        swapVideo(allocator, &pipeline, playlist.current(), open_options) catch |err| {
+                            std.log.err("guardrail rebuild failed: {s}", .{@errorName(err)});
+                        };
+                        event_log.add(std.time.milliTimestamp(), "guardrail rebuild", .{});
+                        setNote(allocator, &status_note, "guardrail: {d}x{d} source decoded at {d}x{d}", .{
+                            video_size.width,
+                            video_size.height,
+                            surface.width,
+                            surface.height,
+                        });
+                        continue;
+                    }
+                }
+
+                // Under a CPU budget, only every Nth decoded frame is
+                // composed; the rest are released untouched.
+                budget_frame_count += 1;
+                const budget_skip = if (cpu_budget) |*b|
+                    budget_frame_count % b.frameDivisor() != 0
+                else
+                    false;
+                if (budget_skip) {
+                    current.unref();
+                    frames_skipped += 1;
+                } else {
+                    // The worker owns the frame from here; compose runs off
+                    // this thread and the finished buffer comes back below.
+                    compose_worker.submit(.{
+                        .frame = current,
+                        .surface = surface,
+                        .scale_mode = options.scale_mode,
+                        .icc_transform = if (icc_transform) |*transform| transform else null,
+                        .received_ms = std.time.milliTimestamp(),
+                    });
+                }
+            }
+        }
+
+        if (compose_worker.take()) |finished| {
+            var composed = finished;
+            defer composed.discard(allocator);
+
+            uploadFrame(&texture, composed.width, composed.height, composed.format, composed.pixels);
+            frames_rendered += 1;
+            interval_frames += 1;
+
+            // Lateness is judged against the source's own frame interval:
+            // a present more than 1.5 intervals after the previous one
+            // means at least one deadline slipped.
+            const present_ms = std.time.milliTimestamp();
+            latency_histogram.record(@floatFromInt(present_ms - composed.received_ms));
+            if (last_present_ms != 0 and nominal_interval_ms > 0 and
+                !pipeline.paused and
+                effectiveFrameStep(options.frame_step_s, battery_static) == null)
+            {
+                const gap: f64 = @floatFromInt(present_ms - last_present_ms);
+                if (gap > nominal_interval_ms * 1.5) frames_late += 1;
+                frame_times.record(gap);
+            }
+            last_present_ms = present_ms;
+
+            if (first_frame_ms == 0) {
+                first_frame_ms = @floatFromInt(std.time.milliTimestamp() - start_ms);
+                std.log.info("first frame in {d:.0}ms", .{first_frame_ms});
+            }
+
+            // Frames flowing again means the rebuild stuck; give the
+            // retry budget back.
+            if (recovering) {
+                recovering = false;
+                retries_used = 0;
+            }
+
+            if (effectiveFrameStep(options.frame_step_s, battery_static)) |step_s| {
+                try pipeline.pause();
+                frame_step_due_ms = std.time.milliTimestamp() +
+                    @as(i64, step_s) * std.time.ms_per_s;
+            }
+        }
+
+        if (blend_pipeline) |*second| {
+            if (second.pollBus() != .running) second.seekToStart();
+            if (!second.paused or redraw_forced) {
+                // The wait on the primary pipeline paces the loop; blend
+                // frames are taken opportunistically.
+                if (second.pullFrame(0)) |frame| {
+                    var current = frame;
+                    defer current.unref();
+                    const prepared = try prepareFrame(allocator, &blend_scratch, current);
+                    uploadFrame(&blend_texture, current.width, current.height, prepared.format, prepared.pixels);
+                }
+            }
+        }
+
+        const now_ms = std.time.milliTimestamp();
+        if (now_ms - last_metrics_ms >= metrics_interval_ms) {
+            const elapsed_s = @as(f64, @floatFromInt(now_ms - last_metrics_ms)) / std.time.ms_per_s;
+            const fps = @as(f64, @floatFromInt(interval_frames)) / elapsed_s;
+
+            if (adaptive) |*quality| {
+                if (!pipeline.paused and quality.observe(fps) != .none) {
+                    const scaled = quality.scaledSize(surface);
+                    open_options.target_size = scaled;
+                    swapVideo(allocator, &pipeline, playlist.current(), open_options) catch |err| {
+                        std.log.err("adaptive rebuild failed: {s}", .{@errorName(err)});
+                    };
+                    setNote(allocator, &status_note, "adaptive: decoding at {d}x{d} (step {d})", .{
+                        scaled.width,
+                        scaled.height,
+                        quality.step,
+                    });
+                }
+            }
+
+            if (cpu_budget) |*b| {
+                const cpu_now_ns = budget_mod.processCpuNs();
+                const interval_ns = @as(f64, @floatFromInt(now_ms - last_metrics_ms)) *
+                    std.time.ns_per_ms;
+                const utilization =
+                    @as(f64, @floatFromInt(cpu_now_ns - last_cpu_ns)) / interval_ns;
+                last_cpu_ns = cpu_now_ns;
+
+                if (b.observe(utilization) != .none) {
+                    if (options.decode_at_output) {
+                        const shift: u5 = @intCast(b.downscaleSteps());
+                        const scaled: layout.Size = .{
+                            .width = @max(surface.width >> shift, 1),
+                            .height = @max(surface.height >> shift, 1),
+                        };
+                        open_options.target_size = scaled;
+                        swapVideo(allocator, &pipeline, playlist.current(), open_options) catch |err| {
+                            std.log.err("budget rebuild failed: {s}", .{@errorName(err)});
+                        };
+                    }
+                    setNote(allocator, &status_note, "cpu budget: level {d} (cpu {d:.0}%, every {d}. frame)", .{
+                        b.level,
+                        utilization * 100,
+                        b.frameDivisor(),
+                    });
+                }
+            }
+
+            // Scratch buffers plus texture estimates; absolute values so
+            // the numbers stay truthful across rebuilds. Compose buffers
+            // are transient worker allocations and not tracked here.
+            accounting.set(.frame_store, yuv_scratch.capacity + blend_scratch.capacity);
+            accounting.set(.shm_pool, textureBytes(texture) + textureBytes(blend_texture));
+            if (accounting.overCap() and options.decode_at_output and mem_step < 2) {
+                mem_step += 1;
+                const scaled: layout.Size = .{
+                    .width = @max(surface.width >> mem_step, 1),
+                    .height = @max(surface.height >> mem_step, 1),
+                };
+                open_options.target_size = scaled;
+                swapVideo(allocator, &pipeline, playlist.current(), open_options) catch |err| {
+                    std.log.err("memory-cap rebuild failed: {s}", .{@errorName(err)});
+                };
+                setNote(allocator, &status_note, "memory cap: decoding at {d}x{d}", .{
+                    scaled.width,
+                    scaled.height,
+                });
+            }
+
+            if (!decoder_logged) {
+                if (pipeline.selectedDecoder()) |name| {
+                    decoder_logged = true;
+                    event_log.add(now_ms, "decoder {s} ({s})", .{
+                        name,
+                        if (pipeline.selected_decoder_hw) "hw" else "sw",
+                    });
+                }
+            }
+            const events_text = event_log.render(allocator) catch null;
+            defer if (events_text) |text| allocator.free(text);
+            const frame_stats = frame_times.stats();
+            const frame_hist = frame_times.renderHist(allocator) catch null;
+            defer if (frame_hist) |text| allocator.free(text);
+            const latency_hist = latency_histogram.render(allocator) catch null;
+            defer if (latency_hist) |text| allocator.free(text);
+            const stream = pipeline.streamInfo();
+            if (stream) |info| {
+                nominal_interval_ms = if (info.fps > 0) std.time.ms_per_s / info.fps else 0;
+            }
+            const power_state = options.power_provider.probe(allocator);
+            const on_battery = power_state == .battery;
+            switch (options.on_battery) {
+                .@"continue" => {},
+                .pause => if (on_battery and !battery_paused) {
+                    battery_paused = true;
+                    event_log.add(now_ms, "paused on battery", .{});
+                    if (!user_paused) pipeline.pause() catch |err|
+                        std.log.warn("battery pause failed: {s}", .{@errorName(err)});
+                } else if (!on_battery and battery_paused) {
+                    battery_paused = false;
+                    event_log.add(now_ms, "resumed on AC", .{});
+                    if (!user_paused) pipeline.play() catch |err|
+                        std.log.warn("battery resume failed: {s}", .{@errorName(err)});
+                },
+                .static => if (on_battery != battery_static) {
+                    battery_static = on_battery;
+                    event_log.add(
+                        now_ms,
+                        if (on_battery) "frame stepping on battery" else "animated on AC",
+                        .{},
+                    );
+                    // Entering static mode pauses itself after the next
+                    // frame; leaving it needs an explicit resume.
+                    if (!on_battery and !user_paused) pipeline.play() catch |err|
+                        std.log.warn("battery resume failed: {s}", .{@errorName(err)});
+                },
+            }
+
+            const snap: snapshot_mod.Snapshot = .{
+                .updated_unix_ms = now_ms,
+                .target = options.target,
+                .video = playlist.current(),
+                .fps = fps,
+                .frames_rendered = frames_rendered,
+                .frames_dropped = frames_dropped,
+                .frames_skipped = frames_skipped + compose_worker.droppedJobs(),
+                .frames_late = frames_late,
+                // Self-inflicted frame-step pauses are playback, not pauses.
+                .paused = if (effectiveFrameStep(options.frame_step_s, battery_static) != null)
+                    user_paused
+                else
+                    pipeline.paused,
+                .notes = status_note,
+                .src_width = if (stream) |info| info.width else 0,
+                .src_height = if (stream) |info| info.height else 0,
+                .src_fps = if (stream) |info| info.fps else 0,
+                .src_format = if (stream) |info| @tagName(info.format) else "",
+                .container = pipeline.selectedDemuxer() orelse "",
+                .decoder = pipeline.selectedDecoder() orelse "",
+                .hw_decode = pipeline.selected_decoder_hw,
+                .buffer_path = buffer_path.describe(),
+                .shm_copy_ms = path_probe.shm_copy_ms,
+                .mem_bytes = accounting.total(),
+                .first_frame_ms = first_frame_ms,
+                .gpu_busy_pct = if (pipeline.selected_decoder_hw)
+                    if (gpu_sampler.sample()) |pct| pct else -1
+                else
+                    -1,
+                .frame_p95_ms = frame_stats.p95_ms,
+                .frame_p99_ms = frame_stats.p99_ms,
+                .frame_max_ms = frame_stats.max_ms,
+                .frame_jitter_ms = frame_stats.jitter_ms,
+                .frame_hist = frame_hist orelse "",
+                .latency_avg_ms = latency_histogram.meanMs(),
+                .latency_max_ms = latency_histogram.max_ms,
+                .latency_hist = latency_hist orelse "",
+                .power = @tagName(power_state),
+                .compositor = std.posix.getenv("XDG_CURRENT_DESKTOP") orelse "",
+                .scale_mode = @tagName(options.scale_mode),
+                .output_scale = 1,
+                .events = events_text orelse "",
+            };
+            snapshot_mod.save(allocator, metrics_path, snap) catch |err|
+                std.log.warn("metrics write failed: {s}", .{@errorName(err)});
+            if (metrics_exporter) |exporter| {
+                exporter.publish(snap) catch |err|
+                    std.log.warn("metrics publish failed: {s}", .{@errorName(err)});
+            }
+            if (stream_publisher) |publisher| {
+                publisher.publish(snap) catch |err|
+                    std.log.warn("metrics stream failed: {s}", .{@errorName(err)});
+            }
+            if (otlp_exporter) |exporter| {
+                exporter.publish(snap) catch |err|
+                    std.log.warn("otlp push failed: {s}", .{@errorName(err)});
+            }
+            if (dbus_publisher) |publisher| {
+                publisher.publish(snap) catch |err|
+                    std.log.warn("dbus publish failed: {s}", .{@errorName(err)});
+            }
+            if (metrics_history) |*hist| {
+                const period_ms: i64 = @as(i64, options.metrics_history_s.?) * std.time.ms_per_s;
+                if (now_ms - last_history_ms >= period_ms) {
+                    hist.append(snap) catch |err|
+                        std.log.warn("metrics history write failed: {s}", .{@errorName(err)});
+                    last_history_ms = now_ms;
+                }
+            }
+            interval_frames = 0;
+            last_metrics_ms = now_ms;
+        }
+
+        // Paused or recovering sources change nothing frame to frame: the
+        // composed canvas is already cached in `texture`, so re-present it
+        // at a trickle instead of burning 60 fps on identical draws. The
+        // park is on the pipeline fds, so bus errors still wake us early.
+        if (pipeline.paused and !redraw_forced and blend_texture == null) {
+            pipeline.waitEvent(100);
+        }
+
+        rl.beginDrawing();
+        defer rl.endDrawing();
+        rl.clearBackground(.black);
+        if (texture) |tex| {
+            drawPlaced(tex, surface, options.scale_mode, transitionTint(
+                options.transition_fade_ms,
+                transition_started_ms,
+                now_ms,
+            ));
+        }
+        if (blend_texture) |tex| {
+            const weight: f32 = if (options.blend_window) |window|
+                blend.weightAt(window, blend.localMinutesNow())
+            else
+                1;
+            if (weight > 0) {
+                const alpha: u8 = @intFromFloat(@round(weight * 255));
+                drawPlaced(tex, surface, options.scale_mode, .{ .r = 255, .g = 255, .b = 255, .a = alpha });
+            }
+        }
+    }
+}
+
+/// RGBA bytes held by a texture, for memory accounting.
+fn textureBytes(texture: ?rl.Texture2D) u64 {
+    const tex = texture orelse return 0;
+    return @as(u64, @intCast(tex.width)) * @as(u64, @intCast(tex.height)) * 4;
+}
+
+/// Interval used when the static-on-battery policy kicks in without an
+/// explicit --frame-step.
+const battery_static_step_s: u32 = 60;
+
+/// Frame-step interval in effect: the explicit option wins, otherwise the
+/// battery fallback while the static policy is active.
+fn effectiveFrameStep(configured: ?u32, battery_static: bool) ?u32 {
+    if (configured) |step| return step;
+    return if (battery_static) battery_static_step_s else null;
+}
+
+/// Tint for the main texture while a swap transition runs: alpha ramps
+/// from transparent to opaque over the configured fade (the canvas behind
+/// it is black, so the new source fades in from black). White once the
+/// fade is over, disabled, or before any swap happened.
+fn transitionTint(fade_ms: u32, started_ms: i64, now_ms: i64) rl.Color {
+    if (fade_ms == 0 or started_ms == 0) return .white;
+    const elapsed = now_ms - started_ms;
+    if (elapsed >= fade_ms) return .white;
+    const t = @as(f32, @floatFromInt(elapsed)) / @as(f32, @floatFromInt(fade_ms));
+    const alpha: u8 = @intFromFloat(@round(std.math.clamp(t, 0, 1) * 255));
+    return .{ .r = 255, .g = 255, .b = 255, .a = alpha };
+}
+
+/// Draws a texture onto the surface per the scale mode (letterboxed,
+/// cropped, or stretched).
+fn drawPlaced(tex: rl.Texture2D, surface: layout.Size, mode: layout.ScaleMode, tint: rl.Color) void {
+    const placement = layout.placeVideo(
+        .{ .width = @intCast(tex.width), .height = @intCast(tex.height) },
+        surface,
+        mode,
+    );
+    rl.drawTexturePro(
+        tex,
+        .{ .x = 0, .y = 0, .width = @floatFromInt(tex.width), .height = @floatFromInt(tex.height) },
+        .{
+            .x = @floatFromInt(placement.x),
+            .y = @floatFromInt(placement.y),
+            .width = @floatFromInt(placement.width),
+            .height = @floatFromInt(placement.height),
+        },
+        .{ .x = 0, .y = 0 },
+        0,
+        tint,
+    );
+}
+
+/// Like `swapVideo`, but resolves video-page URLs first.
+fn swapToSource(
+    allocator: std.mem.Allocator,
+    pipeline: *Pipeline,
+    video: []const u8,
+    open_options: pipeline_mod.OpenOptions,
+    resolve_options: resolver.ResolveOptions,
+) !void {
+    if (resolver.shouldResolve(video)) {
+        const resolved = try resolver.resolve(allocator, video, resolve_options);
+        defer allocator.free(resolved);
+        return swapVideo(allocator, pipeline, resolved, open_options);
+    }
+    return swapVideo(allocator, pipeline, video, open_options);
+}
+
+/// Tears down the current pipeline and starts one for `video` in its place.
+fn swapVideo(
+    allocator: std.mem.Allocator,
+    pipeline: *Pipeline,
+    video: []const u8,
+    open_options: pipeline_mod.OpenOptions,
+) !void {
+    const uri = try pipeline_mod.pathToUri(allocator, video);
+    defer allocator.free(uri);
+
+    var replacement = try Pipeline.open(allocator, uri, open_options);
+    errdefer replacement.deinit();
+    try replacement.play();
+
+    pipeline.deinit();
+    pipeline.* = replacement;
+    pipeline.watchDecoderSelection();
+}
+
+/// Plays through waylandsink on a shared wl_display connection: the sink
+/// imports and commits buffers itself, so no frame data crosses our process
+/// on the CPU. The sink also dispatches its own event queue; this loop only
+/// watches signals and the bus.
+fn runEmbedded(allocator: std.mem.Allocator, options: Options) !void {
+    var connection: wl_globals.Connection = undefined;
+    try connection.connect();
+    defer connection.disconnect();
+
+    const uri = try pipeline_mod.pathToUri(allocator, options.video);
+    defer allocator.free(uri);
+
+    var embedded = try embed.EmbeddedPlayer.open(allocator, uri, connection.display);
+    defer embedded.deinit();
+    try embedded.play();
+
+    const metrics_path = try snapshot_mod.defaultPath(allocator, options.target);
+    defer allocator.free(metrics_path);
+    var last_metrics_ms = std.time.milliTimestamp();
+    var last_frames: u64 = 0;
+
+    while (!signals.quitRequested()) {
+        if (signals.takeTogglePause()) {
+            if (embedded.paused) try embedded.play() else try embedded.pause();
+        }
+
+        switch (embedded.pollBus()) {
+            .running => {},
+            .eos => {
+                if (!options.loop) break;
+                embedded.seekToStart();
+            },
+            .failed => break,
+        }
+
+        const now_ms = std.time.milliTimestamp();
+        if (now_ms - last_metrics_ms >= metrics_interval_ms) {
+            const frames = embedded.framesRendered();
+            const elapsed_s = @as(f64, @floatFromInt(now_ms - last_metrics_ms)) /
+                std.time.ms_per_s;
+            const fps = @as(f64, @floatFromInt(frames - last_frames)) / elapsed_s;
+            last_frames = frames;
+
+            snapshot_mod.save(allocator, metrics_path, .{
+                .updated_unix_ms = now_ms,
+                .target = options.target,
+                .video = options.video,
+                .fps = fps,
+                .frames_rendered = frames,
+                .paused = embedded.paused,
+                .notes = "waylandsink (zero-copy)",
+            }) catch |err| std.log.warn("metrics write failed: {s}", .{@errorName(err)});
+            last_metrics_ms = now_ms;
+        }
+
+        std.Thread.sleep(50 * std.time.ns_per_ms);
+    }
+}
+
+/// The `--buffer-mode dmabuf` presentation path: decoded frames are
+/// converted to RGBA, written into GBM buffers, and attached to one
+/// layer-shell background surface per output, scaled compositor-side.
+/// Playlists, blending, and the control socket stay on the window path
+/// for now; this loop covers looping single-source playback.
+fn runLayerShell(allocator: std.mem.Allocator, options: Options) !void {
+    const engine = try present.Engine.init(allocator, options.buffer_depth);
+    defer engine.deinit();
+
+    const uri = try pipeline_mod.pathToUri(allocator, options.video);
+    defer allocator.free(uri);
+
+    var open_options: pipeline_mod.OpenOptions = .{
+        .dump_dot_dir = options.dump_dot_dir,
+        .audio = options.audio,
+        .volume = options.volume,
+        .audio_sink = options.audio_sink,
+        .pipeline_override = options.pipeline_override,
+        .allow_yuv = options.allow_yuv,
+        .gpu_postproc = options.gpu_postproc,
+        .net_buffer_bytes = options.net_buffer_bytes,
+        .net_timeout_s = options.net_timeout_s,
+    };
+    if (options.hdr) {
+        std.log.warn("HDR is not wired into the dmabuf path yet; decoding SDR", .{});
+    }
+    // Decode at the largest output so the compositor-side viewport only
+    // ever scales down.
+    if (options.decode_at_output) {
+        var best: layout.Size = .{ .width = 0, .height = 0 };
+        for (engine.outputs.items) |output| {
+            if (output.width * output.height > best.width * best.height) {
+                best = .{ .width = output.width, .height = output.height };
+            }
+        }
+        if (best.width > 0) open_options.target_size = best;
+    }
+
+    const start_ms = std.time.milliTimestamp();
+    var pipeline = try Pipeline.open(allocator, uri, open_options);
+    defer pipeline.deinit();
+    pipeline.watchDecoderSelection();
+
+    var yuv_scratch: std.ArrayList(u8) = .empty;
+    defer yuv_scratch.deinit(allocator);
+
+    var first_frame_ms: f64 = 0;
+    if (pipeline.pullPreroll(preroll_timeout_ns)) |frame| {
+        _ = try presentLayerFrame(allocator, engine, &yuv_scratch, frame);
+        first_frame_ms = @floatFromInt(std.time.milliTimestamp() - start_ms);
+        std.log.info("first frame in {d:.0}ms", .{first_frame_ms});
+    }
+    try pipeline.play();
+
+    var last_metrics_ms = std.time.milliTimestamp();
+    var frames_rendered: u64 = 0;
+    var frames_dropped: u64 = 0;
+    var interval_frames: u64 = 0;
+
+    while (!signals.quitRequested()) {
+        if (signals.takeTogglePause()) {
+            if (pipeline.paused) try pipeline.play() else try pipeline.pause();
+        }
+        engine.pump();
+
+        switch (pipeline.pollBus()) {
+            .running => {},
+            .eos => {
+                if (!options.loop) break;
+                pipeline.seekToStart();
+            },
+            .failed => break,
+        }
+
+        if (!pipeline.paused) {
+            pipeline.waitEvent(event_wait_ms);
+            if (pipeline.pullFrame(0)) |frame| {
+                var current = frame;
+
+                // Keep-latest, same as the window path: stale queued frames
+                // only add latency.
+                while (pipeline.pullFrame(0)) |newer| {
+                    current.unref();
+                    current = newer;
+                    frames_dropped += 1;
+                }
+
+                if (try presentLayerFrame(allocator, engine, &yuv_scratch, current)) {
+                    frames_rendered += 1;
+                    interval_frames += 1;
+                }
+            }
+        } else {
+            // Nothing to present while paused; park on the pipeline fds so
+            // a bus message or SIGUSR1-driven resume is not far behind.
+            pipeline.waitEvent(100);
+        }
+
+        const now_ms = std.time.milliTimestamp();
+        if (now_ms - last_metrics_ms >= metrics_interval_ms) {
+            const elapsed_s = @as(f64, @floatFromInt(now_ms - last_metrics_ms)) / std.time.ms_per_s;
+            const stream = pipeline.streamInfo();
+            // One snapshot per surface, keyed `<target>@<output>`; frame
+            // counts are per output, the decode-side numbers are shared.
+            // Compositors that never sent a wl_output name degrade to the
+            // single default path.
+            for (engine.outputs.items) |output| {
+                if (output.closed) continue;
+                const path = snapshot_mod.outputPath(
+                    allocator,
+                    options.target,
+                    output.name(),
+                ) catch |err| {
+                    std.log.warn("metrics path failed: {s}", .{@errorName(err)});
+                    continue;
+                };
+                defer allocator.free(path);
+                snapshot_mod.save(allocator, path, .{
+                    .updated_unix_ms = now_ms,
+                    .target = options.target,
+                    .output = output.name(),
+                    .video = options.video,
+                    .fps = @as(f64, @floatFromInt(interval_frames)) / elapsed_s,
+                    .frames_rendered = output.frames_presented,
+                    .frames_dropped = frames_dropped,
+                    .paused = pipeline.paused,
+                    .src_width = if (stream) |info| info.width else 0,
+                    .src_height = if (stream) |info| info.height else 0,
+                    .src_fps = if (stream) |info| info.fps else 0,
+                    .decoder = pipeline.selectedDecoder() orelse "",
+                    .hw_decode = pipeline.selected_decoder_hw,
+                    .buffer_path = "dmabuf",
+                    .first_frame_ms = first_frame_ms,
+                    .notes = "layer-shell dmabuf",
+                }) catch |err| std.log.warn("metrics write failed: {s}", .{@errorName(err)});
+            }
+            interval_frames = 0;
+            last_metrics_ms = now_ms;
+        }
+    }
+}
+
+/// A decoded frame handed to the engine by dmabuf fd instead of by pixels;
+/// stays alive until the engine's single release callback comes back.
+const DirectFrame = struct {
+    allocator: std.mem.Allocator,
+    frame: pipeline_mod.Frame,
+};
+
+fn releaseDirectFrame(user: ?*anyopaque) void {
+    const direct: *DirectFrame = @ptrCast(@alignCast(user.?));
+    direct.frame.unref();
+    const allocator = direct.allocator;
+    allocator.destroy(direct);
+}
+
+/// Conversion context for `present.Engine.presentFill`: renders a YUV
+/// frame as RGBA into whatever destination the engine hands out — the
+/// mapped slot itself when its stride is tight.
+const YuvFill = struct {
+    frame: *const pipeline_mod.Frame,
+};
+
+fn fillYuvRgba(user: ?*anyopaque, dest: []u8) void {
+    const ctx: *YuvFill = @ptrCast(@alignCast(user.?));
+    const frame = ctx.frame;
+    const yuv_layout: yuv.Layout = switch (frame.format) {
+        .nv12 => .nv12,
+        .i420 => .i420,
+        .p010 => .p010,
+        else => unreachable,
+    };
+    yuv.toRgba(yuv_layout, frame.pixels, frame.width, frame.height, frame.colorimetry, dest);
+}
+
+/// Presents one frame on the layer-shell engine: importing the decoder's
+/// dmabuf directly when it has one in an importable layout, converting
+/// YUV straight into the presentation buffer otherwise. Takes ownership
+/// of `frame`; returns true when at least one surface took it.
+fn presentLayerFrame(
+    allocator: std.mem.Allocator,
+    engine: *present.Engine,
+    scratch: *std.ArrayList(u8),
+    frame: pipeline_mod.Frame,
+) !bool {
+    var current = frame;
+    if (current.dmabuf_fd) |fd| {
+        const import_format: ?dmabuf_import.Format = switch (current.format) {
+            .nv12 => .nv12,
+            .p010 => .p010,
+            // Packed RGBA is cheap to copy, and our byte order differs from
+            // the little-endian DRM ARGB layouts; stay on the slot path.
+            else => null,
+        };
+        if (import_format) |direct_format| {
+            const direct = try allocator.create(DirectFrame);
+            direct.* = .{ .allocator = allocator, .frame = current };
+            const took = engine.presentDmabuf(
+                fd,
+                direct_format,
+                current.width,
+                current.height,
+                direct,
+                releaseDirectFrame,
+            );
+            if (took > 0) return true;
+            allocator.destroy(direct);
+        }
+    }
+
+    defer current.unref();
+    switch (current.format) {
+        .nv12, .i420, .p010 => {
+            var ctx: YuvFill = .{ .frame = &current };
+            return engine.presentFill(
+                current.width,
+                current.height,
+                &ctx,
+                fillYuvRgba,
+                scratch,
+            ) > 0;
+        },
+        .rgba8 => return engine.presentFrame(
+            current.pixels,
+            current.width,
+            current.height,
+        ) > 0,
+        // 16-bit frames have no slot format; the caller falls back to the
+        // window path for HDR.
+        .rgba16 => return false,
+    }
+}
+
+/// Cycles the images in `options.video` (a directory) with an optional
+/// crossfade, reusing the playlist order/repeat semantics.
+fn runSlideshow(allocator: std.mem.Allocator, options: Options) !void {
+    const images = try slideshow.scanImages(allocator, options.video);
+    defer slideshow.freeImages(allocator, images);
+    if (images.len == 0) {
+        std.log.err("no images found in {s}", .{options.video});
+        return error.NoImages;
+    }
+
+    var playlist = try playlist_mod.Playlist.init(
+        allocator,
+        images,
+        options.order,
+        if (options.loop) options.repeat else .off,
+        @bitCast(std.time.milliTimestamp()),
+    );
+    defer playlist.deinit();
+
+    rl.initWindow(800, 450, "waystream");
+    defer rl.closeWindow();
+    rl.setTargetFPS(60);
+    const surface: layout.Size = .{
+        .width = @intCast(rl.getScreenWidth()),
+        .height = @intCast(rl.getScreenHeight()),
+    };
+
+    var current: ?rl.Texture2D = loadSlide(allocator, playlist.current());
+    defer if (current) |tex| rl.unloadTexture(tex);
+    // The outgoing slide, kept alive while the crossfade runs.
+    var previous: ?rl.Texture2D = null;
+    defer if (previous) |tex| rl.unloadTexture(tex);
+
+    const metrics_path = try snapshot_mod.defaultPath(allocator, options.target);
+    defer allocator.free(metrics_path);
+    var last_metrics_ms = std.time.milliTimestamp();
+
+    const duration_ms: i64 = @as(i64, options.slide_duration_s) * std.time.ms_per_s;
+    const fade_ms: i64 = @intFromFloat(options.fade_s * std.time.ms_per_s);
+    var slide_started_ms = std.time.milliTimestamp();
+    var paused = false;
+    var finished = false;
+
+    while (!rl.windowShouldClose() and !signals.quitRequested() and !finished) {
+        if (signals.takeTogglePause()) paused = !paused;
+        // SIGUSR2 expires the current slide, skipping to the next image.
+        if (signals.takeForceRedraw()) slide_started_ms = 0;
+        const now_ms = std.time.milliTimestamp();
+
+        if (!paused and now_ms - slide_started_ms >= duration_ms) {
+            switch (playlist.onEos()) {
+                .stop => finished = true,
+                .restart => slide_started_ms = now_ms,
+                .next => |next| {
+                    if (previous) |tex| rl.unloadTexture(tex);
+                    previous = current;
+                    current = loadSlide(allocator, next);
+                    slide_started_ms = now_ms;
+                },
+            }
+        }
+
+        if (now_ms - last_metrics_ms >= metrics_interval_ms) {
+            snapshot_mod.save(allocator, metrics_path, .{
+                .updated_unix_ms = now_ms,
+                .target = options.target,
+                .video = playlist.current(),
+                .paused = paused,
+                .notes = "slideshow",
+            }) catch |err| std.log.warn("metrics write failed: {s}", .{@errorName(err)});
+            last_metrics_ms = now_ms;
+        }
+
+        const fade_elapsed = now_ms - slide_started_ms;
+        const fading = previous != null and fade_ms > 0 and fade_elapsed < fade_ms;
+
+        // Between transitions the slide is static; re-present the cached
+        // texture at a trickle instead of redrawing it 60 times a second.
+        if (!fading) std.Thread.sleep(100 * std.time.ns_per_ms);
+
+        rl.beginDrawing();
+        defer rl.endDrawing();
+        rl.clearBackground(.black);
+        if (fading) {
+            const t: f32 = @as(f32, @floatFromInt(fade_elapsed)) / @as(f32, @floatFromInt(fade_ms));
+            const alpha: u8 = @intFromFloat(@round(t * 255));
+            if (previous) |tex| drawPlaced(tex, surface, options.scale_mode, .white);
+            if (current) |tex| drawPlaced(tex, surface, options.scale_mode, .{ .r = 255, .g = 255, .b = 255, .a = alpha });
+        } else {
+            if (previous) |tex| {
+                rl.unloadTexture(tex);
+                previous = null;
+            }
+            if (current) |tex| drawPlaced(tex, surface, options.scale_mode, .white);
+        }
+    }
+}
+
+/// Loads one image as a texture; failures log and leave the slot empty so
+/// a broken file skips to a black frame instead of killing the slideshow.
+fn loadSlide(allocator: std.mem.Allocator, path: []const u8) ?rl.Texture2D {
+    const path_z = std.fmt.allocPrintSentinel(allocator, "{s}", .{path}, 0) catch return null;
+    defer allocator.free(path_z);
+    const texture = rl.loadTexture(path_z) catch {
+        std.log.warn("could not load image {s}", .{path});
+        return null;
+    };
+    return texture;
+}
+
+/// Replaces the metrics status note (and logs it); keeps the old note on OOM.
+fn setNote(
+    allocator: std.mem.Allocator,
+    note: *[]const u8,
+    comptime fmt: []const u8,
+    args: anytype,
+) void {
+    const text = std.fmt.allocPrint(allocator, fmt, args) catch return;
+    if (note.*.len > 0) allocator.free(note.*);
+    note.* = text;
+    std.log.info("{s}", .{text});
+}
+
+/// Converts planar YUV frames to RGBA; RGBA frames pass through untouched.
+fn prepareFrame(
+    allocator: std.mem.Allocator,
+    scratch: *std.ArrayList(u8),
+    frame: pipeline_mod.Frame,
+) !struct { pixels: []const u8, format: pipeline_mod.PixelFormat } {
+    const yuv_layout: yuv.Layout = switch (frame.format) {
+        .nv12 => .nv12,
+        .i420 => .i420,
+        .p010 => .p010,
+        .rgba8, .rgba16 => return .{ .pixels = frame.pixels, .format = frame.format },
+    };
+    try scratch.resize(allocator, frame.width * frame.height * 4);
+    yuv.toRgba(yuv_layout, frame.pixels, frame.width, frame.height, frame.colorimetry, scratch.items);
+    return .{ .pixels = scratch.items, .format = .rgba8 };
+}
+
+/// Background half of the startup overlap: opens (and prerolls) the
+/// pipeline while the render thread brings up the window.
+fn openPipelineTask(
+    allocator: std.mem.Allocator,
+    uri: []const u8,
+    open_options: pipeline_mod.OpenOptions,
+    out: *?Pipeline,
+    out_error: *?anyerror,
+) void {
+    out.* = Pipeline.open(allocator, uri, open_options) catch |err| {
+        out_error.* = err;
+        return;
+    };
+}
+
+const ComposeJob = struct {
+    /// Decoded frame; owned by the worker once submitted.
+    frame: pipeline_mod.Frame,
+    surface: layout.Size,
+    scale_mode: layout.ScaleMode,
+    icc_transform: ?*icc.Transform,
+    /// When appsink handed the frame over; carried through so present can
+    /// compute the delivery-to-screen latency.
+    received_ms: i64,
+
+    fn discard(self: *ComposeJob, allocator: std.mem.Allocator) void {
+        _ = allocator;
+        self.frame.unref();
+    }
+};
+
+const ComposeResult = struct {
+    /// Owned, tightly packed pixels ready for upload.
+    pixels: []u8,
+    width: u32,
+    height: u32,
+    format: pipeline_mod.PixelFormat,
+    received_ms: i64,
+
+    fn discard(self: *ComposeResult, allocator: std.mem.Allocator) void {
+        allocator.free(self.pixels);
+        self.* = undefined;
+    }
+};
+
+const ComposeWorker = worker.Worker(ComposeJob, ComposeResult, composeFrame);
+
+/// Worker-thread version of the compose path: YUV conversion, ICC, and
+/// box filtering, producing an owned buffer the render loop only uploads.
+fn composeFrame(allocator: std.mem.Allocator, job: *ComposeJob) ?ComposeResult {
+    const frame = &job.frame;
+
+    // HDR frames pass through untouched: no ICC, no CPU filtering.
+    if (frame.format == .rgba16) {
+        const pixels = allocator.dupe(u8, frame.pixels) catch return null;
+        return .{
+            .pixels = pixels,
+            .width = frame.width,
+            .height = frame.height,
+            .format = .rgba16,
+            .received_ms = job.received_ms,
+        };
+    }
+
+    var pixels = allocator.alloc(u8, @as(usize, frame.width) * frame.height * 4) catch
+        return null;
+    switch (frame.format) {
+        .rgba8 => if (job.icc_transform) |transform| {
+            transform.apply(frame.pixels, pixels, frame.width * frame.height);
+        } else {
+            @memcpy(pixels, frame.pixels);
+        },
+        .nv12, .i420, .p010 => {
+            const yuv_layout: yuv.Layout = switch (frame.format) {
+                .nv12 => .nv12,
+                .i420 => .i420,
+                .p010 => .p010,
+                else => unreachable,
+            };
+            yuv.toRgba(yuv_layout, frame.pixels, frame.width, frame.height, frame.colorimetry, pixels);
+            if (job.icc_transform) |transform| {
+                const corrected = allocator.alloc(u8, pixels.len) catch {
+                    allocator.free(pixels);
+                    return null;
+                };
+                transform.apply(pixels, corrected, frame.width * frame.height);
+                allocator.free(pixels);
+                pixels = corrected;
+            }
+        },
+        .rgba16 => unreachable,
+    }
+
+    // Strong downscales shimmer under the GPU's bilinear sampling;
+    // box-filter to the fitted size so the final draw only magnifies by a
+    // small factor.
+    var width = frame.width;
+    var height = frame.height;
+    const video_size: layout.Size = .{ .width = width, .height = height };
+    const fitted = layout.placeVideo(video_size, job.surface, job.scale_mode);
+    const target: layout.Size = .{ .width = fitted.width, .height = fitted.height };
+    if (scale.shouldBoxFilter(video_size, target)) {
+        const small = allocator.alloc(u8, @as(usize, target.width) * target.height * 4) catch {
+            allocator.free(pixels);
+            return null;
+        };
+        scale.boxDownscale(pixels, width, height, small, target.width, target.height);
+        allocator.free(pixels);
+        pixels = small;
+        width = target.width;
+        height = target.height;
+    }
+
+    return .{
+        .pixels = pixels,
+        .width = width,
+        .height = height,
+        .format = .rgba8,
+        .received_ms = job.received_ms,
+    };
+}
+
+fn uploadFrame(
+    texture: *?rl.Texture2D,
+    width: u32,
+    height: u32,
+    format: pipeline_mod.PixelFormat,
+    pixels: []const u8,
+) void {
+    const needs_realloc = if (texture.*) |tex|
+        tex.width != @as(i32, @intCast(width)) or
+            tex.height != @as(i32, @intCast(height))
+    else
+        true;
+
+    if (needs_realloc) {
+        if (texture.*) |tex| rl.unloadTexture(tex);
+        const image: rl.Image = .{
+            .data = @constCast(@ptrCast(pixels.ptr)),
+            .width = @intCast(width),
+            .height = @intCast(height),
+            .mipmaps = 1,
+            // The window backend has no real HDR surface; 16-bit frames are
+            // uploaded as-is and only the Wayland color-management path can
+            // present them faithfully.
+            .format = switch (format) {
+                .rgba8 => .uncompressed_r8g8b8a8,
+                .rgba16 => .uncompressed_r16g16b16a16,
+                // prepareFrame already converted planar YUV.
+                .nv12, .i420, .p010 => unreachable,
+            },
+        };
+        texture.* = rl.loadTextureFromImage(image) catch null;
+        return;
+    }
+
+    rl.updateTexture(texture.*.?, pixels.ptr);
+}
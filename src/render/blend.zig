@@ -0,0 +1,107 @@
+//! Schedule-driven blending between two wallpaper sources.
+//!
+//! Given a time window like 17:00-19:00, computes how far the transition
+//! from the primary source to the blend target has progressed (0 before the
+//! window, 1 after it, linear in between), including windows that wrap past
+//! midnight. The renderer draws the target over the primary with this
+//! weight as alpha.
+
+const std = @import("std");
+
+const minutes_per_day: f64 = 24 * 60;
+
+pub const Window = struct {
+    /// Minutes since midnight, local time.
+    start_minutes: u16,
+    end_minutes: u16,
+};
+
+pub const ParseError = error{InvalidWindow};
+
+/// Parses "HH:MM-HH:MM".
+pub fn parseWindow(text: []const u8) ParseError!Window {
+    const dash = std.mem.indexOfScalar(u8, text, '-') orelse return ParseError.InvalidWindow;
+    return .{
+        .start_minutes = try parseClock(text[0..dash]),
+        .end_minutes = try parseClock(text[dash + 1 ..]),
+    };
+}
+
+/// Parses "HH:MM" into minutes since midnight.
+pub fn parseClock(text: []const u8) ParseError!u16 {
+    const colon = std.mem.indexOfScalar(u8, text, ':') orelse return ParseError.InvalidWindow;
+    const hours = std.fmt.parseInt(u16, text[0..colon], 10) catch return ParseError.InvalidWindow;
+    const minutes = std.fmt.parseInt(u16, text[colon + 1 ..], 10) catch return ParseError.InvalidWindow;
+    if (hours > 23 or minutes > 59) return ParseError.InvalidWindow;
+    return hours * 60 + minutes;
+}
+
+/// Blend weight at `now_minutes` (minutes since midnight, may be
+/// fractional). Zero-length windows switch instantly at start.
+pub fn weightAt(window: Window, now_minutes: f64) f32 {
+    const start: f64 = @floatFromInt(window.start_minutes);
+    const end_raw: f64 = @floatFromInt(window.end_minutes);
+    // A window ending at or before its start wraps past midnight.
+    const end = if (end_raw <= start) end_raw + minutes_per_day else end_raw;
+    const now = if (now_minutes < start and end > minutes_per_day)
+        now_minutes + minutes_per_day
+    else
+        now_minutes;
+
+    if (now <= start) return 0;
+    if (now >= end) return 1;
+    return @floatCast((now - start) / (end - start));
+}
+
+const tm = extern struct {
+    tm_sec: c_int,
+    tm_min: c_int,
+    tm_hour: c_int,
+    tm_mday: c_int,
+    tm_mon: c_int,
+    tm_year: c_int,
+    tm_wday: c_int,
+    tm_yday: c_int,
+    tm_isdst: c_int,
+    tm_gmtoff: c_long,
+    tm_zone: ?[*:0]const u8,
+};
+
+extern fn localtime_r(timep: *const i64, result: *tm) ?*tm;
+
+/// Current local wall-clock time in (fractional) minutes since midnight.
+pub fn localMinutesNow() f64 {
+    const now: i64 = std.time.timestamp();
+    var broken_down: tm = undefined;
+    if (localtime_r(&now, &broken_down) == null) return 0;
+    return @as(f64, @floatFromInt(broken_down.tm_hour)) * 60 +
+        @as(f64, @floatFromInt(broken_down.tm_min)) +
+        @as(f64, @floatFromInt(broken_down.tm_sec)) / 60;
+}
+
+/// Current local calendar day as .{ month 1-12, day 1-31 }.
+pub fn localMonthDayNow() ?[2]u8 {
+    const now: i64 = std.time.timestamp();
+    var broken_down: tm = undefined;
+    if (localtime_r(&now, &broken_down) == null) return null;
+    return .{ @intCast(broken_down.tm_mon + 1), @intCast(broken_down.tm_mday) };
+}
+
+test "weight ramps linearly inside the window" {
+    const window = try parseWindow("17:00-19:00");
+    try std.testing.expectEqual(@as(f32, 0), weightAt(window, 16 * 60));
+    try std.testing.expectEqual(@as(f32, 1), weightAt(window, 20 * 60));
+    try std.testing.expectApproxEqAbs(@as(f32, 0.5), weightAt(window, 18 * 60), 0.001);
+}
+
+test "window wrapping past midnight" {
+    const window = try parseWindow("23:00-01:00");
+    try std.testing.expectEqual(@as(f32, 0), weightAt(window, 22 * 60));
+    try std.testing.expectApproxEqAbs(@as(f32, 0.5), weightAt(window, 0), 0.001);
+    try std.testing.expectEqual(@as(f32, 1), weightAt(window, 2 * 60));
+}
+
+test "rejects malformed windows" {
+    try std.testing.expectError(ParseError.InvalidWindow, parseWindow("17:00"));
+    try std.testing.expectError(ParseError.InvalidWindow, parseWindow("25:00-26:00"));
+}
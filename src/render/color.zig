@@ -0,0 +1,67 @@
+//! Colorimetry handling.
+//!
+//! Tracks what color space and transfer function the decoder negotiated so
+//! HDR content can be passed through to HDR-capable presentation paths
+//! instead of being silently squashed to 8-bit SDR.
+
+const std = @import("std");
+
+pub const Primaries = enum {
+    bt601,
+    bt709,
+    bt2020,
+    dci_p3,
+    unknown,
+};
+
+pub const TransferFunction = enum {
+    srgb,
+    bt709,
+    /// SMPTE ST 2084 perceptual quantizer (HDR10).
+    pq,
+    /// Hybrid log-gamma.
+    hlg,
+    unknown,
+};
+
+pub const Colorimetry = struct {
+    primaries: Primaries = .unknown,
+    transfer: TransferFunction = .unknown,
+    full_range: bool = false,
+
+    pub const sdr_default: Colorimetry = .{ .primaries = .bt709, .transfer = .bt709 };
+
+    pub fn isHdr(self: Colorimetry) bool {
+        return self.transfer == .pq or self.transfer == .hlg;
+    }
+};
+
+/// Parses the well-known shorthand names GStreamer uses in the caps
+/// `colorimetry` field. Unrecognized (including raw `R:M:T:P` tuples)
+/// yields `.unknown` fields rather than an error.
+pub fn parseGstColorimetry(value: []const u8) Colorimetry {
+    const Known = struct {
+        name: []const u8,
+        colorimetry: Colorimetry,
+    };
+    const known = [_]Known{
+        .{ .name = "bt601", .colorimetry = .{ .primaries = .bt601, .transfer = .bt709 } },
+        .{ .name = "bt709", .colorimetry = .{ .primaries = .bt709, .transfer = .bt709 } },
+        .{ .name = "srgb", .colorimetry = .{ .primaries = .bt709, .transfer = .srgb, .full_range = true } },
+        .{ .name = "bt2020", .colorimetry = .{ .primaries = .bt2020, .transfer = .bt709 } },
+        .{ .name = "bt2100-pq", .colorimetry = .{ .primaries = .bt2020, .transfer = .pq } },
+        .{ .name = "bt2100-hlg", .colorimetry = .{ .primaries = .bt2020, .transfer = .hlg } },
+    };
+    for (known) |entry| {
+        if (std.mem.eql(u8, value, entry.name)) return entry.colorimetry;
+    }
+    return .{};
+}
+
+test "parses HDR shorthand" {
+    const pq = parseGstColorimetry("bt2100-pq");
+    try std.testing.expectEqual(Primaries.bt2020, pq.primaries);
+    try std.testing.expect(pq.isHdr());
+    try std.testing.expect(!parseGstColorimetry("bt709").isHdr());
+    try std.testing.expectEqual(TransferFunction.unknown, parseGstColorimetry("1:4:0:0").transfer);
+}
@@ -0,0 +1,81 @@
+//! ICC-aware color conversion backed by Little CMS.
+//!
+//! Loads a display ICC profile and transforms decoded sRGB frames into the
+//! display's space, so wallpapers match other color-managed content on
+//! calibrated monitors.
+
+const std = @import("std");
+
+const cms = struct {
+    const HPROFILE = ?*anyopaque;
+    const HTRANSFORM = ?*anyopaque;
+
+    /// TYPE_RGBA_8 from lcms2.h (RGB + 1 extra channel, 1 byte each).
+    const TYPE_RGBA_8: u32 = (4 << 16) | (1 << 7) | (3 << 3) | 1;
+    const INTENT_PERCEPTUAL: u32 = 0;
+
+    extern fn cmsOpenProfileFromFile(filename: [*:0]const u8, access: [*:0]const u8) HPROFILE;
+    extern fn cmsCreate_sRGBProfile() HPROFILE;
+    extern fn cmsCloseProfile(profile: HPROFILE) c_int;
+    extern fn cmsCreateTransform(
+        input: HPROFILE,
+        input_format: u32,
+        output: HPROFILE,
+        output_format: u32,
+        intent: u32,
+        flags: u32,
+    ) HTRANSFORM;
+    extern fn cmsDeleteTransform(transform: HTRANSFORM) void;
+    extern fn cmsDoTransform(
+        transform: HTRANSFORM,
+        input: *const anyopaque,
+        output: *anyopaque,
+        size: u32,
+    ) void;
+};
+
+pub const IccError = error{
+    ProfileLoadFailed,
+    TransformCreateFailed,
+};
+
+/// An sRGB -> display transform for 8-bit RGBA frames.
+pub const Transform = struct {
+    handle: cms.HTRANSFORM,
+
+    /// Builds a transform from sRGB content into the profile at `path`.
+    pub fn open(allocator: std.mem.Allocator, path: []const u8) !Transform {
+        const path_z = try allocator.dupeZ(u8, path);
+        defer allocator.free(path_z);
+
+        const display = cms.cmsOpenProfileFromFile(path_z, "r") orelse
+            return IccError.ProfileLoadFailed;
+        defer _ = cms.cmsCloseProfile(display);
+        const srgb = cms.cmsCreate_sRGBProfile() orelse
+            return IccError.ProfileLoadFailed;
+        defer _ = cms.cmsCloseProfile(srgb);
+
+        const handle = cms.cmsCreateTransform(
+            srgb,
+            cms.TYPE_RGBA_8,
+            display,
+            cms.TYPE_RGBA_8,
+            cms.INTENT_PERCEPTUAL,
+            0,
+        ) orelse return IccError.TransformCreateFailed;
+
+        return .{ .handle = handle };
+    }
+
+    pub fn deinit(self: *Transform) void {
+        cms.cmsDeleteTransform(self.handle);
+        self.* = undefined;
+    }
+
+    /// Transforms `pixel_count` RGBA8 pixels from `input` into `output`.
+    /// The slices may alias.
+    pub fn apply(self: Transform, input: []const u8, output: []u8, pixel_count: u32) void {
+        std.debug.assert(input.len >= pixel_count * 4 and output.len >= pixel_count * 4);
+        cms.cmsDoTransform(self.handle, input.ptr, output.ptr, pixel_count);
+    }
+};
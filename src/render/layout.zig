@@ -0,0 +1,100 @@
+//! Output geometry and video placement math.
+//!
+//! Pure integer math shared by the renderer and the test harness: how big a
+//! buffer an output wants (scale and transform applied) and where a video of
+//! a given size lands on it (letterboxing/cropping).
+
+const std = @import("std");
+
+/// Output transform, mirroring wl_output.transform.
+pub const Transform = enum {
+    normal,
+    rotate_90,
+    rotate_180,
+    rotate_270,
+    flipped,
+    flipped_90,
+    flipped_180,
+    flipped_270,
+
+    /// True when the transform exchanges width and height.
+    pub fn swapsAxes(self: Transform) bool {
+        return switch (self) {
+            .rotate_90, .rotate_270, .flipped_90, .flipped_270 => true,
+            else => false,
+        };
+    }
+};
+
+pub const ScaleMode = enum {
+    /// Letterbox: whole video visible, bars where aspect ratios differ.
+    fit,
+    /// Cover: fill the output, cropping the video where needed.
+    fill,
+    /// Ignore aspect ratio.
+    stretch,
+};
+
+pub const Size = struct {
+    width: u32,
+    height: u32,
+};
+
+pub const Rect = struct {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+};
+
+/// Buffer size an output needs: logical size times integer scale, with
+/// width/height swapped for 90/270 degree transforms.
+pub fn bufferSize(logical_width: u32, logical_height: u32, scale: u32, transform: Transform) Size {
+    const w = logical_width * scale;
+    const h = logical_height * scale;
+    return if (transform.swapsAxes())
+        .{ .width = h, .height = w }
+    else
+        .{ .width = w, .height = h };
+}
+
+/// Where a `video`-sized frame lands on a `surface`-sized buffer.
+/// For `.fill` the rect extends past the surface; callers clip.
+pub fn placeVideo(video: Size, surface: Size, mode: ScaleMode) Rect {
+    if (video.width == 0 or video.height == 0) {
+        return .{ .x = 0, .y = 0, .width = surface.width, .height = surface.height };
+    }
+    if (mode == .stretch) {
+        return .{ .x = 0, .y = 0, .width = surface.width, .height = surface.height };
+    }
+
+    const video_w: u64 = video.width;
+    const video_h: u64 = video.height;
+    const surface_w: u64 = surface.width;
+    const surface_h: u64 = surface.height;
+
+    // Compare aspect ratios without floats: video_w/video_h vs surface_w/surface_h.
+    const video_wider = video_w * surface_h > surface_w * video_h;
+    const scale_to_width = switch (mode) {
+        .fit => video_wider,
+        .fill => !video_wider,
+        .stretch => unreachable,
+    };
+
+    var width: u64 = undefined;
+    var height: u64 = undefined;
+    if (scale_to_width) {
+        width = surface_w;
+        height = std.math.divCeil(u64, surface_w * video_h, video_w) catch unreachable;
+    } else {
+        height = surface_h;
+        width = std.math.divCeil(u64, surface_h * video_w, video_h) catch unreachable;
+    }
+
+    return .{
+        .x = @intCast(@divTrunc(@as(i64, @intCast(surface_w)) - @as(i64, @intCast(width)), 2)),
+        .y = @intCast(@divTrunc(@as(i64, @intCast(surface_h)) - @as(i64, @intCast(height)), 2)),
+        .width = @intCast(width),
+        .height = @intCast(height),
+    };
+}
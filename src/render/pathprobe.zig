@@ -0,0 +1,107 @@
+//! Startup measurement for the shm-vs-dmabuf decision.
+//!
+//! Whether dmabuf import actually beats the shm copy path differs per
+//! machine: some drivers fail allocation outright, some compositors copy
+//! imported buffers anyway, and on fast desktops the shm memcpy is nearly
+//! free. Instead of guessing, auto mode measures once at startup — frame
+//! copy cost for shm, allocation/import viability for dmabuf — and records
+//! both the decision and the numbers in the metrics snapshot.
+
+const std = @import("std");
+const gbm = @import("../drm/gbm.zig");
+const drm_c = @import("../drm/c.zig");
+
+pub const Mode = enum { auto, shm, dmabuf };
+
+pub const Path = enum {
+    shm,
+    dmabuf,
+
+    pub fn describe(self: Path) []const u8 {
+        return switch (self) {
+            .shm => "shm",
+            .dmabuf => "dmabuf",
+        };
+    }
+};
+
+pub const Probe = struct {
+    /// Measured cost of copying one frame into an shm buffer.
+    shm_copy_ms: f64,
+    /// Whether a scanout-capable dmabuf of the frame size could be
+    /// allocated on this machine.
+    dmabuf_ok: bool,
+};
+
+/// Measures both paths for `width`x`height` RGBA frames.
+pub fn run(allocator: std.mem.Allocator, width: u32, height: u32) Probe {
+    return .{
+        .shm_copy_ms = measureCopyMs(allocator, width, height),
+        .dmabuf_ok = probeDmabuf(width, height),
+    };
+}
+
+/// Resolves the configured mode; auto picks from the probe.
+pub fn decide(mode: Mode, probe: Probe) Path {
+    return switch (mode) {
+        .shm => .shm,
+        .dmabuf => .dmabuf,
+        // A working import path removes the per-frame copy entirely, so it
+        // wins whenever allocation succeeds; the copy cost is recorded for
+        // the fallback case.
+        .auto => if (probe.dmabuf_ok) .dmabuf else .shm,
+    };
+}
+
+/// Times one full-frame copy, the per-frame overhead of the shm path.
+/// Averaged over a few rounds; worst case this costs a few milliseconds
+/// at startup.
+fn measureCopyMs(allocator: std.mem.Allocator, width: u32, height: u32) f64 {
+    const size = @as(usize, width) * height * 4;
+    const source = allocator.alloc(u8, size) catch return 0;
+    defer allocator.free(source);
+    const dest = allocator.alloc(u8, size) catch return 0;
+    defer allocator.free(dest);
+    @memset(source, 0x42);
+
+    const rounds = 4;
+    var timer = std.time.Timer.start() catch return 0;
+    for (0..rounds) |_| {
+        @memcpy(dest, source);
+        std.mem.doNotOptimizeAway(dest[size - 1]);
+    }
+    const elapsed_ns = timer.read();
+    return @as(f64, @floatFromInt(elapsed_ns)) / rounds / std.time.ns_per_ms;
+}
+
+/// True when a scanout-capable buffer of the frame size can actually be
+/// allocated, not merely when a render node exists.
+fn probeDmabuf(width: u32, height: u32) bool {
+    var gbm_allocator = gbm.GbmAllocator.init() catch return false;
+    defer gbm_allocator.deinit();
+    var buffer = gbm_allocator.allocate(
+        width,
+        height,
+        drm_c.DRM_FORMAT_ARGB8888,
+        &.{},
+    ) catch return false;
+    buffer.deinit();
+    return true;
+}
+
+test "explicit modes bypass the probe" {
+    const failed: Probe = .{ .shm_copy_ms = 99, .dmabuf_ok = false };
+    try std.testing.expectEqual(Path.dmabuf, decide(.dmabuf, failed));
+    try std.testing.expectEqual(Path.shm, decide(.shm, .{ .shm_copy_ms = 0, .dmabuf_ok = true }));
+}
+
+test "auto follows dmabuf viability" {
+    try std.testing.expectEqual(
+        Path.dmabuf,
+        decide(.auto, .{ .shm_copy_ms = 1, .dmabuf_ok = true }),
+    );
+    try std.testing.expectEqual(
+        Path.shm,
+        decide(.auto, .{ .shm_copy_ms = 1, .dmabuf_ok = false }),
+    );
+}
@@ -0,0 +1,120 @@
+//! CPU downscaling with area averaging.
+//!
+//! Bilinear sampling only reads four source pixels per output pixel, so a
+//! 4K frame squeezed onto a 1080p surface skips most of the detail and
+//! shimmers on motion. For strong downscales a box filter averages every
+//! source pixel inside each destination pixel's footprint, which is what
+//! the in-pipeline scaler would do — this path covers full-decode mode
+//! where scaling happens after conversion.
+
+const std = @import("std");
+const layout = @import("layout.zig");
+
+/// Downscale factor above which bilinear visibly shimmers and the box
+/// filter takes over.
+pub const box_threshold = 1.5;
+
+/// True when drawing `video` at `target` size needs the box filter.
+pub fn shouldBoxFilter(video: layout.Size, target: layout.Size) bool {
+    if (target.width == 0 or target.height == 0) return false;
+    // factor > 1.5 without floats: 2*video > 3*target on either axis.
+    return @as(u64, video.width) * 2 > @as(u64, target.width) * 3 or
+        @as(u64, video.height) * 2 > @as(u64, target.height) * 3;
+}
+
+/// Linear downscale factor beyond which the CPU path is not attempted at
+/// all: an 8K source on a 1080p output is a 4x factor and >250MB of frame
+/// copies per second, which no amount of filtering makes reasonable.
+pub const guard_threshold = 3;
+
+/// True when the source so outsizes the output that frames must be scaled
+/// inside the pipeline; callers rebuild with a target size instead of
+/// brute-forcing the CPU path.
+pub fn needsDecodeGuard(video: layout.Size, target: layout.Size) bool {
+    if (target.width == 0 or target.height == 0) return false;
+    return @as(u64, video.width) > @as(u64, target.width) * guard_threshold or
+        @as(u64, video.height) > @as(u64, target.height) * guard_threshold;
+}
+
+/// Area-average downscale of a tightly packed RGBA image. `dst` must hold
+/// `dst_w * dst_h * 4` bytes; both dimensions must shrink or stay equal.
+pub fn boxDownscale(
+    src: []const u8,
+    src_w: u32,
+    src_h: u32,
+    dst: []u8,
+    dst_w: u32,
+    dst_h: u32,
+) void {
+    var out_y: u32 = 0;
+    while (out_y < dst_h) : (out_y += 1) {
+        // Source row span covered by this output row, end exclusive and
+        // never empty even when the ratio is 1.
+        const y_start = out_y * src_h / dst_h;
+        const y_end = @max((out_y + 1) * src_h / dst_h, y_start + 1);
+
+        var out_x: u32 = 0;
+        while (out_x < dst_w) : (out_x += 1) {
+            const x_start = out_x * src_w / dst_w;
+            const x_end = @max((out_x + 1) * src_w / dst_w, x_start + 1);
+
+            var sums: [4]u64 = @splat(0);
+            var y = y_start;
+            while (y < y_end) : (y += 1) {
+                var x = x_start;
+                while (x < x_end) : (x += 1) {
+                    const index = (y * src_w + x) * 4;
+                    for (0..4) |channel| sums[channel] += src[index + channel];
+                }
+            }
+
+            const count = @as(u64, y_end - y_start) * (x_end - x_start);
+            const out_index = (out_y * dst_w + out_x) * 4;
+            for (0..4) |channel| {
+                dst[out_index + channel] = @intCast((sums[channel] + count / 2) / count);
+            }
+        }
+    }
+}
+
+test "threshold kicks in past 1.5x" {
+    const target: layout.Size = .{ .width = 1920, .height = 1080 };
+    try std.testing.expect(!shouldBoxFilter(.{ .width = 2560, .height = 1440 }, target));
+    try std.testing.expect(shouldBoxFilter(.{ .width = 3840, .height = 2160 }, target));
+}
+
+test "guardrail fires for 8K on 1080p but not 4K" {
+    const target: layout.Size = .{ .width = 1920, .height = 1080 };
+    try std.testing.expect(needsDecodeGuard(.{ .width = 7680, .height = 4320 }, target));
+    try std.testing.expect(!needsDecodeGuard(.{ .width = 3840, .height = 2160 }, target));
+}
+
+test "4x4 to 2x2 averages each quadrant" {
+    // Top-left quadrant all 100, the rest 0; alpha opaque throughout.
+    var src: [4 * 4 * 4]u8 = undefined;
+    for (0..16) |i| {
+        const x = i % 4;
+        const y = i / 4;
+        const value: u8 = if (x < 2 and y < 2) 100 else 0;
+        src[i * 4 + 0] = value;
+        src[i * 4 + 1] = value;
+        src[i * 4 + 2] = value;
+        src[i * 4 + 3] = 255;
+    }
+
+    var dst: [2 * 2 * 4]u8 = undefined;
+    boxDownscale(&src, 4, 4, &dst, 2, 2);
+    try std.testing.expectEqual(@as(u8, 100), dst[0]);
+    try std.testing.expectEqual(@as(u8, 0), dst[4]);
+    try std.testing.expectEqual(@as(u8, 0), dst[8]);
+    try std.testing.expectEqual(@as(u8, 255), dst[3]);
+}
+
+test "non-integer ratios cover every source pixel" {
+    // 3x1 all-white into 2x1: both outputs must stay white.
+    const src = [_]u8{255} ** (3 * 4);
+    var dst: [2 * 4]u8 = undefined;
+    boxDownscale(&src, 3, 1, &dst, 2, 1);
+    try std.testing.expectEqual(@as(u8, 255), dst[0]);
+    try std.testing.expectEqual(@as(u8, 255), dst[4]);
+}
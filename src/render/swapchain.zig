@@ -0,0 +1,105 @@
+//! Fixed-depth buffer rotation for presentation paths.
+//!
+//! Both the dmabuf and shm paths hand buffers to the compositor, which can
+//! hold one (or more, with direct scanout) across a frame. With only two
+//! buffers the renderer stalls whenever the compositor is slow to release;
+//! a third absorbs that. The depth is a setting rather than a constant so
+//! memory-constrained setups can drop to double buffering and direct-scanout
+//! ones can go deeper.
+
+const std = @import("std");
+
+pub const min_depth = 2;
+pub const max_depth = 4;
+pub const default_depth = 3;
+
+const SlotState = enum {
+    /// Ours to draw into.
+    free,
+    /// Handed out by `acquire`, being rendered.
+    drawing,
+    /// Attached and committed; the compositor owns it until release.
+    held,
+};
+
+pub const Swapchain = struct {
+    states: [max_depth]SlotState = @splat(.free),
+    depth: u32,
+    /// Times `acquire` found every buffer busy; a steadily climbing count
+    /// means the depth is too small for this compositor.
+    stalls: u64 = 0,
+
+    pub fn init(depth: u32) Swapchain {
+        return .{ .depth = std.math.clamp(depth, min_depth, max_depth) };
+    }
+
+    /// Index of a buffer that is safe to draw into, or null when the
+    /// compositor holds everything (caller waits for a release event).
+    pub fn acquire(self: *Swapchain) ?u32 {
+        for (self.states[0..self.depth], 0..) |state, i| {
+            if (state == .free) {
+                self.states[i] = .drawing;
+                return @intCast(i);
+            }
+        }
+        self.stalls += 1;
+        return null;
+    }
+
+    /// The buffer was attached and committed; the compositor owns it now.
+    pub fn submit(self: *Swapchain, index: u32) void {
+        self.states[index] = .held;
+    }
+
+    /// wl_buffer.release: the compositor is done with the buffer.
+    pub fn release(self: *Swapchain, index: u32) void {
+        self.states[index] = .free;
+    }
+
+    /// Buffers currently held by the compositor.
+    pub fn heldCount(self: *const Swapchain) u32 {
+        var count: u32 = 0;
+        for (self.states[0..self.depth]) |state| {
+            if (state == .held) count += 1;
+        }
+        return count;
+    }
+};
+
+test "triple buffering survives a held buffer without stalling" {
+    var chain = Swapchain.init(3);
+
+    const a = chain.acquire().?;
+    chain.submit(a);
+    const b = chain.acquire().?;
+    chain.submit(b);
+
+    // Compositor still holds both; the third buffer keeps us drawing.
+    const c = chain.acquire().?;
+    try std.testing.expect(c != a and c != b);
+    chain.submit(c);
+
+    try std.testing.expectEqual(@as(?u32, null), chain.acquire());
+    try std.testing.expectEqual(@as(u64, 1), chain.stalls);
+
+    chain.release(a);
+    try std.testing.expectEqual(a, chain.acquire().?);
+}
+
+test "double buffering stalls while the compositor holds one" {
+    var chain = Swapchain.init(2);
+    const a = chain.acquire().?;
+    chain.submit(a);
+    const b = chain.acquire().?;
+    chain.submit(b);
+    try std.testing.expectEqual(@as(?u32, null), chain.acquire());
+
+    chain.release(b);
+    try std.testing.expectEqual(b, chain.acquire().?);
+    try std.testing.expectEqual(@as(u32, 1), chain.heldCount());
+}
+
+test "depth is clamped to the supported range" {
+    try std.testing.expectEqual(@as(u32, min_depth), Swapchain.init(0).depth);
+    try std.testing.expectEqual(@as(u32, max_depth), Swapchain.init(99).depth);
+}
@@ -0,0 +1,188 @@
+//! Background compose workers.
+//!
+//! CPU compose work (YUV conversion, ICC, box filtering) used to run inline
+//! in the render loop, so on large outputs the loop spent most of each
+//! iteration converting pixels and frame pacing suffered. A `Worker` moves
+//! that work onto its own thread: the loop submits decoded frames, the
+//! worker converts them, and finished buffers come back through a
+//! latest-wins mailbox for the loop to upload (GL uploads must stay on the
+//! thread that owns the context). Each surface's player process gets its own
+//! worker, so multiple outputs compose in parallel.
+
+const std = @import("std");
+
+/// Single-slot worker thread. `Job` and `Result` must provide
+/// `discard(self: *X, allocator) void` releasing whatever they own;
+/// `process` runs on the worker thread and returns null on failure.
+pub fn Worker(
+    comptime Job: type,
+    comptime Result: type,
+    comptime process: fn (allocator: std.mem.Allocator, job: *Job) ?Result,
+) type {
+    return struct {
+        const Self = @This();
+
+        allocator: std.mem.Allocator,
+        thread: ?std.Thread = null,
+
+        mutex: std.Thread.Mutex = .{},
+        work_ready: std.Thread.Condition = .{},
+        pending: ?Job = null,
+        finished: ?Result = null,
+        stopping: bool = false,
+        /// Jobs replaced before the worker got to them.
+        dropped: u64 = 0,
+
+        /// Spawns the worker thread.
+        pub fn start(allocator: std.mem.Allocator) !*Self {
+            const self = try allocator.create(Self);
+            errdefer allocator.destroy(self);
+            self.* = .{ .allocator = allocator };
+            self.thread = try std.Thread.spawn(.{}, loop, .{self});
+            return self;
+        }
+
+        /// Joins the thread and frees anything still in the mailbox.
+        pub fn stop(self: *Self) void {
+            self.mutex.lock();
+            self.stopping = true;
+            self.work_ready.signal();
+            self.mutex.unlock();
+            if (self.thread) |thread| thread.join();
+
+            if (self.pending) |*job| job.discard(self.allocator);
+            if (self.finished) |*result| result.discard(self.allocator);
+            const allocator = self.allocator;
+            allocator.destroy(self);
+        }
+
+        /// Hands a job to the worker, replacing any job it has not started
+        /// yet; the worker owns `job` from here on.
+        pub fn submit(self: *Self, job: Job) void {
+            self.mutex.lock();
+            defer self.mutex.unlock();
+            if (self.pending) |*stale| {
+                stale.discard(self.allocator);
+                self.dropped += 1;
+            }
+            self.pending = job;
+            self.work_ready.signal();
+        }
+
+        /// Takes the latest finished result; the caller owns it.
+        pub fn take(self: *Self) ?Result {
+            self.mutex.lock();
+            defer self.mutex.unlock();
+            const result = self.finished;
+            self.finished = null;
+            return result;
+        }
+
+        pub fn droppedJobs(self: *Self) u64 {
+            self.mutex.lock();
+            defer self.mutex.unlock();
+            return self.dropped;
+        }
+
+        fn loop(self: *Self) void {
+            while (true) {
+                self.mutex.lock();
+                while (self.pending == null and !self.stopping)
+                    self.work_ready.wait(&self.mutex);
+                if (self.stopping) {
+                    self.mutex.unlock();
+                    return;
+                }
+                self.mutex.unlock();
+                self.runPending();
+            }
+        }
+
+        /// Processes one pending job, if any, and publishes the result.
+        /// Split from `loop` so tests can drive the mailbox synchronously.
+        fn runPending(self: *Self) void {
+            self.mutex.lock();
+            var job = self.pending orelse {
+                self.mutex.unlock();
+                return;
+            };
+            self.pending = null;
+            self.mutex.unlock();
+
+            const result = process(self.allocator, &job);
+            job.discard(self.allocator);
+
+            self.mutex.lock();
+            defer self.mutex.unlock();
+            if (result) |value| {
+                if (self.finished) |*stale| stale.discard(self.allocator);
+                self.finished = value;
+            }
+        }
+    };
+}
+
+const TestJob = struct {
+    value: u32,
+
+    fn discard(self: *TestJob, allocator: std.mem.Allocator) void {
+        _ = allocator;
+        self.* = undefined;
+    }
+};
+
+const TestResult = struct {
+    value: u32,
+
+    fn discard(self: *TestResult, allocator: std.mem.Allocator) void {
+        _ = allocator;
+        self.* = undefined;
+    }
+};
+
+fn doubleJob(allocator: std.mem.Allocator, job: *TestJob) ?TestResult {
+    _ = allocator;
+    if (job.value == 0) return null;
+    return .{ .value = job.value * 2 };
+}
+
+const TestWorker = Worker(TestJob, TestResult, doubleJob);
+
+test "newest job wins and results pass through" {
+    var worker: TestWorker = .{ .allocator = std.testing.allocator };
+    worker.submit(.{ .value = 1 });
+    worker.submit(.{ .value = 5 });
+    try std.testing.expectEqual(@as(u64, 1), worker.droppedJobs());
+
+    worker.runPending();
+    const result = worker.take() orelse return error.TestUnexpectedResult;
+    try std.testing.expectEqual(@as(u32, 10), result.value);
+    try std.testing.expectEqual(@as(?TestResult, null), worker.take());
+}
+
+test "failed jobs keep the previous result" {
+    var worker: TestWorker = .{ .allocator = std.testing.allocator };
+    worker.submit(.{ .value = 3 });
+    worker.runPending();
+    worker.submit(.{ .value = 0 });
+    worker.runPending();
+
+    const result = worker.take() orelse return error.TestUnexpectedResult;
+    try std.testing.expectEqual(@as(u32, 6), result.value);
+}
+
+test "threaded worker round-trips a job" {
+    const worker = try TestWorker.start(std.testing.allocator);
+    defer worker.stop();
+
+    worker.submit(.{ .value = 7 });
+    var waited_ms: u32 = 0;
+    while (waited_ms < 1000) : (waited_ms += 1) {
+        if (worker.take()) |result| {
+            try std.testing.expectEqual(@as(u32, 14), result.value);
+            return;
+        }
+        std.Thread.sleep(std.time.ns_per_ms);
+    }
+    return error.TestTimeout;
+}
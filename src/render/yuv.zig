@@ -0,0 +1,163 @@
+//! CPU conversion of planar YUV frames to RGBA.
+//!
+//! Accepting NV12/I420 straight from the decoder lets videoconvert pass
+//! buffers through untouched; the colorspace conversion then happens here at
+//! blit time with fixed-point integer math, which is cheaper than a full
+//! videoconvert pass on software-decoded content.
+
+const std = @import("std");
+const color = @import("color.zig");
+
+/// Fixed-point (x256) YUV->RGB coefficients.
+const Coefficients = struct {
+    y: i32,
+    rv: i32,
+    gu: i32,
+    gv: i32,
+    bu: i32,
+    y_offset: i32,
+};
+
+// BT.709 / BT.601, limited and full range. The luma coefficient folds in the
+// 16..235 expansion for limited range.
+const bt709_limited: Coefficients = .{ .y = 298, .rv = 459, .gu = -55, .gv = -136, .bu = 541, .y_offset = 16 };
+const bt601_limited: Coefficients = .{ .y = 298, .rv = 409, .gu = -100, .gv = -208, .bu = 516, .y_offset = 16 };
+const bt709_full: Coefficients = .{ .y = 256, .rv = 403, .gu = -48, .gv = -120, .bu = 475, .y_offset = 0 };
+const bt601_full: Coefficients = .{ .y = 256, .rv = 359, .gu = -88, .gv = -183, .bu = 454, .y_offset = 0 };
+
+fn coefficientsFor(colorimetry: color.Colorimetry) Coefficients {
+    const bt601 = colorimetry.primaries == .bt601;
+    if (colorimetry.full_range) {
+        return if (bt601) bt601_full else bt709_full;
+    }
+    return if (bt601) bt601_limited else bt709_limited;
+}
+
+pub const Layout = enum {
+    /// Y plane followed by an interleaved half-resolution UV plane.
+    nv12,
+    /// Y plane followed by separate half-resolution U and V planes.
+    i420,
+    /// NV12 layout with 16-bit little-endian samples, 10 bits used in the
+    /// MSBs. The conversion keeps the top 8 bits; real 10-bit output needs
+    /// the dmabuf path.
+    p010,
+};
+
+/// Converts a tightly packed NV12 or I420 frame into RGBA. `out` must hold
+/// `width * height * 4` bytes.
+pub fn toRgba(
+    layout: Layout,
+    data: []const u8,
+    width: u32,
+    height: u32,
+    colorimetry: color.Colorimetry,
+    out: []u8,
+) void {
+    const coeffs = coefficientsFor(colorimetry);
+    const chroma_width = (width + 1) / 2;
+    const luma_size = width * height;
+
+    var row: u32 = 0;
+    while (row < height) : (row += 1) {
+        const chroma_row = row / 2;
+        var col: u32 = 0;
+        while (col < width) : (col += 1) {
+            const chroma_col = col / 2;
+            var y: u8 = undefined;
+            var u: u8 = undefined;
+            var v: u8 = undefined;
+            switch (layout) {
+                .nv12 => {
+                    y = data[row * width + col];
+                    const uv_index = luma_size + (chroma_row * chroma_width + chroma_col) * 2;
+                    u = data[uv_index];
+                    v = data[uv_index + 1];
+                },
+                .i420 => {
+                    y = data[row * width + col];
+                    const chroma_size = chroma_width * ((height + 1) / 2);
+                    const index = chroma_row * chroma_width + chroma_col;
+                    u = data[luma_size + index];
+                    v = data[luma_size + chroma_size + index];
+                },
+                .p010 => {
+                    y = sample10(data, row * width + col);
+                    const uv_index = luma_size + (chroma_row * chroma_width + chroma_col) * 2;
+                    u = sample10(data, uv_index);
+                    v = sample10(data, uv_index + 1);
+                },
+            }
+
+            const c = (@as(i32, y) - coeffs.y_offset) * coeffs.y;
+            const d = @as(i32, u) - 128;
+            const e = @as(i32, v) - 128;
+            const out_index = (row * width + col) * 4;
+            out[out_index] = clamp8((c + coeffs.rv * e + 128) >> 8);
+            out[out_index + 1] = clamp8((c + coeffs.gu * d + coeffs.gv * e + 128) >> 8);
+            out[out_index + 2] = clamp8((c + coeffs.bu * d + 128) >> 8);
+            out[out_index + 3] = 255;
+        }
+    }
+}
+
+fn clamp8(value: i32) u8 {
+    return @intCast(std.math.clamp(value, 0, 255));
+}
+
+/// Reads the `index`th 16-bit sample and keeps the top 8 bits; P010 stores
+/// its 10 significant bits MSB-aligned, so this is a straight truncation.
+fn sample10(data: []const u8, index: u32) u8 {
+    const value = std.mem.readInt(u16, data[index * 2 ..][0..2], .little);
+    return @intCast(value >> 8);
+}
+
+test "limited-range black and white map to full swing" {
+    // 2x2 NV12 frame: Y plane then one UV pair.
+    const frame = [_]u8{ 16, 235, 16, 235, 128, 128 };
+    var out: [16]u8 = undefined;
+    toRgba(.nv12, &frame, 2, 2, color.Colorimetry.sdr_default, &out);
+
+    try std.testing.expectEqual(@as(u8, 0), out[0]);
+    try std.testing.expectEqual(@as(u8, 255), out[4]);
+    try std.testing.expectEqual(@as(u8, 255), out[3]);
+}
+
+test "full-range gray passes through" {
+    const frame = [_]u8{ 128, 128, 128, 128, 128, 128 };
+    var out: [16]u8 = undefined;
+    var colorimetry = color.Colorimetry.sdr_default;
+    colorimetry.full_range = true;
+    toRgba(.nv12, &frame, 2, 2, colorimetry, &out);
+
+    try std.testing.expectEqual(@as(u8, 128), out[0]);
+    try std.testing.expectEqual(@as(u8, 128), out[1]);
+    try std.testing.expectEqual(@as(u8, 128), out[2]);
+}
+
+test "p010 keeps the top bits of limited-range white" {
+    // 2x2 P010, 16-bit LE samples: Y = 235<<8, neutral chroma = 128<<8.
+    var frame: [12]u8 = undefined;
+    for (0..4) |i| std.mem.writeInt(u16, frame[i * 2 ..][0..2], 235 << 8, .little);
+    std.mem.writeInt(u16, frame[8..10], 128 << 8, .little);
+    std.mem.writeInt(u16, frame[10..12], 128 << 8, .little);
+
+    var out: [16]u8 = undefined;
+    toRgba(.p010, &frame, 2, 2, color.Colorimetry.sdr_default, &out);
+    try std.testing.expectEqual(@as(u8, 255), out[0]);
+    try std.testing.expectEqual(@as(u8, 255), out[1]);
+    try std.testing.expectEqual(@as(u8, 255), out[2]);
+}
+
+test "i420 plane order" {
+    // 2x2 I420: Y plane, U plane, V plane. Strong red chroma under BT.601.
+    const frame = [_]u8{ 81, 81, 81, 81, 90, 240 };
+    var out: [16]u8 = undefined;
+    var colorimetry = color.Colorimetry.sdr_default;
+    colorimetry.primaries = .bt601;
+    toRgba(.i420, &frame, 2, 2, colorimetry, &out);
+
+    try std.testing.expect(out[0] > 200); // red
+    try std.testing.expect(out[1] < 60); // green
+    try std.testing.expect(out[2] < 60); // blue
+}
@@ -0,0 +1,50 @@
+//! Unix signal plumbing for the play process.
+//!
+//! Handlers only flip atomic flags; the playback loop drains them once per
+//! iteration so all real work happens outside signal context.
+//!
+//! - SIGUSR1 toggles pause (scripts can freeze the wallpaper before a
+//!   screen recording).
+//! - SIGUSR2 forces a redraw/re-sync of the current frame.
+//! - SIGINT/SIGTERM request a clean shutdown.
+
+const std = @import("std");
+
+var toggle_pause = std.atomic.Value(bool).init(false);
+var force_redraw = std.atomic.Value(bool).init(false);
+var quit = std.atomic.Value(bool).init(false);
+
+fn handleSignal(sig: c_int) callconv(.c) void {
+    switch (sig) {
+        std.posix.SIG.USR1 => toggle_pause.store(true, .release),
+        std.posix.SIG.USR2 => force_redraw.store(true, .release),
+        std.posix.SIG.INT, std.posix.SIG.TERM => quit.store(true, .release),
+        else => {},
+    }
+}
+
+pub fn install() void {
+    const action: std.posix.Sigaction = .{
+        .handler = .{ .handler = handleSignal },
+        .mask = std.posix.sigemptyset(),
+        .flags = 0,
+    };
+    std.posix.sigaction(std.posix.SIG.USR1, &action, null);
+    std.posix.sigaction(std.posix.SIG.USR2, &action, null);
+    std.posix.sigaction(std.posix.SIG.INT, &action, null);
+    std.posix.sigaction(std.posix.SIG.TERM, &action, null);
+}
+
+/// Returns true once per SIGUSR1 received since the last call.
+pub fn takeTogglePause() bool {
+    return toggle_pause.swap(false, .acq_rel);
+}
+
+/// Returns true once per SIGUSR2 received since the last call.
+pub fn takeForceRedraw() bool {
+    return force_redraw.swap(false, .acq_rel);
+}
+
+pub fn quitRequested() bool {
+    return quit.load(.acquire);
+}
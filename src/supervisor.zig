@@ -0,0 +1,167 @@
+//! Tracking of running play processes.
+//!
+//! Every player registers itself in a runtime directory (one JSON record per
+//! target, containing its pid). Registration enforces a soft limit on the
+//! number of simultaneously alive players and refuses duplicate targets, and
+//! the `status` command reports the active set. Records of dead processes
+//! are pruned on every scan, so stale files from crashes are harmless.
+
+const std = @import("std");
+
+pub const default_max_players: u32 = 8;
+
+pub const Entry = struct {
+    pid: i32,
+    target: []const u8,
+    video: []const u8,
+    started_unix_ms: i64,
+};
+
+pub const RegisterError = error{
+    /// The soft player limit was reached.
+    TooManyPlayers,
+    /// A live player already owns this target; drive it via its control
+    /// socket instead of starting a second one.
+    TargetBusy,
+} || std.mem.Allocator.Error || std.fs.File.OpenError || std.fs.File.WriteError;
+
+fn playersDir(allocator: std.mem.Allocator) ![]u8 {
+    const runtime_dir = std.posix.getenv("XDG_RUNTIME_DIR") orelse "/tmp";
+    return std.fmt.allocPrint(allocator, "{s}/waystream/players", .{runtime_dir});
+}
+
+fn entryPath(allocator: std.mem.Allocator, target: []const u8) ![]u8 {
+    const dir = try playersDir(allocator);
+    defer allocator.free(dir);
+    return std.fmt.allocPrint(allocator, "{s}/{s}.json", .{ dir, target });
+}
+
+fn pidAlive(pid: i32) bool {
+    std.posix.kill(pid, 0) catch |err| return err == error.PermissionDenied;
+    return true;
+}
+
+/// Registers the calling process as the player for `target`, enforcing the
+/// soft limit. Call `unregister` on clean shutdown.
+pub fn register(
+    allocator: std.mem.Allocator,
+    target: []const u8,
+    video: []const u8,
+    max_players: u32,
+) RegisterError!void {
+    var active = try activePlayers(allocator);
+    defer freePlayers(allocator, active);
+
+    for (active) |entry| {
+        if (std.mem.eql(u8, entry.target, target)) return RegisterError.TargetBusy;
+    }
+    if (active.len >= max_players) {
+        std.log.err("player limit reached ({d} active, max {d})", .{ active.len, max_players });
+        return RegisterError.TooManyPlayers;
+    }
+
+    const path = try entryPath(allocator, target);
+    defer allocator.free(path);
+    if (std.fs.path.dirname(path)) |dir| std.fs.cwd().makePath(dir) catch {};
+
+    const json = try std.fmt.allocPrint(
+        allocator,
+        "{{\"pid\":{d},\"target\":\"{s}\",\"video\":\"{s}\",\"started_unix_ms\":{d}}}\n",
+        .{ std.os.linux.getpid(), target, video, std.time.milliTimestamp() },
+    );
+    defer allocator.free(json);
+
+    const file = try std.fs.cwd().createFile(path, .{});
+    defer file.close();
+    try file.writeAll(json);
+}
+
+pub fn unregister(allocator: std.mem.Allocator, target: []const u8) void {
+    const path = entryPath(allocator, target) catch return;
+    defer allocator.free(path);
+    std.fs.cwd().deleteFile(path) catch {};
+}
+
+/// Lists live players, deleting records whose pid is gone.
+/// Free the result with `freePlayers`.
+pub fn activePlayers(allocator: std.mem.Allocator) ![]Entry {
+    var entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (entries.items) |entry| freeEntry(allocator, entry);
+        entries.deinit(allocator);
+    }
+
+    const dir_path = try playersDir(allocator);
+    defer allocator.free(dir_path);
+
+    var dir = std.fs.cwd().openDir(dir_path, .{ .iterate = true }) catch
+        return entries.toOwnedSlice(allocator);
+    defer dir.close();
+
+    var it = dir.iterate();
+    while (it.next() catch null) |dirent| {
+        if (dirent.kind != .file or !std.mem.endsWith(u8, dirent.name, ".json")) continue;
+        const entry = readEntry(allocator, dir, dirent.name) orelse continue;
+        if (!pidAlive(entry.pid)) {
+            freeEntry(allocator, entry);
+            dir.deleteFile(dirent.name) catch {};
+            continue;
+        }
+        try entries.append(allocator, entry);
+    }
+
+    return entries.toOwnedSlice(allocator);
+}
+
+pub fn freePlayers(allocator: std.mem.Allocator, players: []Entry) void {
+    for (players) |entry| freeEntry(allocator, entry);
+    allocator.free(players);
+}
+
+fn freeEntry(allocator: std.mem.Allocator, entry: Entry) void {
+    allocator.free(entry.target);
+    allocator.free(entry.video);
+}
+
+fn readEntry(allocator: std.mem.Allocator, dir: std.fs.Dir, name: []const u8) ?Entry {
+    const data = dir.readFileAlloc(allocator, name, 64 * 1024) catch return null;
+    defer allocator.free(data);
+
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const parsed = std.json.parseFromSliceLeaky(std.json.Value, arena.allocator(), data, .{}) catch
+        return null;
+    const root = switch (parsed) {
+        .object => |object| object,
+        else => return null,
+    };
+
+    const pid = switch (root.get("pid") orelse return null) {
+        .integer => |value| value,
+        else => return null,
+    };
+    const target = switch (root.get("target") orelse return null) {
+        .string => |value| value,
+        else => return null,
+    };
+    const video = switch (root.get("video") orelse return null) {
+        .string => |value| value,
+        else => return null,
+    };
+    const started = switch (root.get("started_unix_ms") orelse std.json.Value{ .integer = 0 }) {
+        .integer => |value| value,
+        else => 0,
+    };
+
+    const target_owned = allocator.dupe(u8, target) catch return null;
+    const video_owned = allocator.dupe(u8, video) catch {
+        allocator.free(target_owned);
+        return null;
+    };
+    return .{
+        .pid = @intCast(pid),
+        .target = target_owned,
+        .video = video_owned,
+        .started_unix_ms = started,
+    };
+}
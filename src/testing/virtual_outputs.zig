@@ -0,0 +1,175 @@
+//! Test-time virtual outputs.
+//!
+//! Multi-monitor behavior (which video goes to which output, and how it is
+//! placed there) previously had no coverage at all. This harness stands in
+//! for a compositor: it holds a set of virtual outputs with differing sizes,
+//! scales, and transforms, and computes the render plan the real backend
+//! would execute, so the per-output logic is testable on any machine.
+
+const std = @import("std");
+const layout = @import("../render/layout.zig");
+const outputmatch = @import("../config/outputmatch.zig");
+
+pub const VirtualOutput = struct {
+    name: []const u8,
+    /// Logical (compositor-side) size.
+    width: u32,
+    height: u32,
+    scale: u32 = 1,
+    transform: layout.Transform = .normal,
+    /// Identity fields as a compositor would advertise them, for
+    /// selector matching (see config/outputmatch.zig).
+    description: []const u8 = "",
+    make: []const u8 = "",
+    model: []const u8 = "",
+    serial: []const u8 = "",
+
+    pub fn info(self: VirtualOutput) outputmatch.OutputInfo {
+        return .{
+            .name = self.name,
+            .description = self.description,
+            .make = self.make,
+            .model = self.model,
+            .serial = self.serial,
+        };
+    }
+};
+
+pub const VideoSpec = struct {
+    path: []const u8,
+    width: u32,
+    height: u32,
+    /// Restrict this video to outputs matching this selector (connector
+    /// name, glob, or `desc:`/`make:`/`model:`/`serial:` pattern); null
+    /// matches any output.
+    output: ?[]const u8 = null,
+};
+
+pub const Assignment = struct {
+    output: VirtualOutput,
+    video: VideoSpec,
+    /// Buffer size the output requires.
+    buffer: layout.Size,
+    /// Where the video lands on that buffer.
+    placement: layout.Rect,
+};
+
+pub const Harness = struct {
+    outputs: []const VirtualOutput,
+
+    pub fn init(outputs: []const VirtualOutput) Harness {
+        return .{ .outputs = outputs };
+    }
+
+    /// Computes one assignment per output. Output-specific videos win over
+    /// wildcard entries; outputs with no eligible video are skipped.
+    pub fn renderPlan(
+        self: Harness,
+        allocator: std.mem.Allocator,
+        videos: []const VideoSpec,
+        mode: layout.ScaleMode,
+    ) ![]Assignment {
+        var plan: std.ArrayList(Assignment) = .empty;
+        errdefer plan.deinit(allocator);
+
+        for (self.outputs) |output| {
+            const video = pickVideo(videos, output.info()) orelse continue;
+            const buffer = layout.bufferSize(output.width, output.height, output.scale, output.transform);
+            try plan.append(allocator, .{
+                .output = output,
+                .video = video,
+                .buffer = buffer,
+                .placement = layout.placeVideo(
+                    .{ .width = video.width, .height = video.height },
+                    buffer,
+                    mode,
+                ),
+            });
+        }
+
+        return plan.toOwnedSlice(allocator);
+    }
+
+    fn pickVideo(videos: []const VideoSpec, info: outputmatch.OutputInfo) ?VideoSpec {
+        for (videos) |video| {
+            if (video.output) |selector| {
+                if (outputmatch.matches(selector, info)) return video;
+            }
+        }
+        for (videos) |video| {
+            if (video.output == null) return video;
+        }
+        return null;
+    }
+};
+
+test "each output gets its dedicated video, wildcard fills the rest" {
+    const harness = Harness.init(&.{
+        .{ .name = "DP-1", .width = 2560, .height = 1440 },
+        .{ .name = "HDMI-A-1", .width = 1920, .height = 1080 },
+    });
+    const plan = try harness.renderPlan(std.testing.allocator, &.{
+        .{ .path = "any.mp4", .width = 1920, .height = 1080 },
+        .{ .path = "hdmi.mp4", .width = 1280, .height = 720, .output = "HDMI-A-1" },
+    }, .fit);
+    defer std.testing.allocator.free(plan);
+
+    try std.testing.expectEqual(@as(usize, 2), plan.len);
+    try std.testing.expectEqualStrings("any.mp4", plan[0].video.path);
+    try std.testing.expectEqualStrings("hdmi.mp4", plan[1].video.path);
+}
+
+test "a description selector survives a connector rename" {
+    const harness = Harness.init(&.{
+        .{
+            .name = "DP-5",
+            .width = 3840,
+            .height = 2160,
+            .description = "Dell Inc. DELL U2720Q 123ABC",
+        },
+    });
+    const plan = try harness.renderPlan(std.testing.allocator, &.{
+        .{ .path = "4k.mp4", .width = 3840, .height = 2160, .output = "desc:*U2720Q*" },
+    }, .fit);
+    defer std.testing.allocator.free(plan);
+
+    try std.testing.expectEqual(@as(usize, 1), plan.len);
+    try std.testing.expectEqualStrings("4k.mp4", plan[0].video.path);
+}
+
+test "scale and 90-degree transform produce a swapped, scaled buffer" {
+    const harness = Harness.init(&.{
+        .{ .name = "eDP-1", .width = 1280, .height = 800, .scale = 2, .transform = .rotate_90 },
+    });
+    const plan = try harness.renderPlan(std.testing.allocator, &.{
+        .{ .path = "v.mp4", .width = 1920, .height = 1080 },
+    }, .fit);
+    defer std.testing.allocator.free(plan);
+
+    try std.testing.expectEqual(@as(u32, 1600), plan[0].buffer.width);
+    try std.testing.expectEqual(@as(u32, 2560), plan[0].buffer.height);
+}
+
+test "fit letterboxes a wide video on a tall surface" {
+    const placement = layout.placeVideo(
+        .{ .width = 1920, .height = 1080 },
+        .{ .width = 1080, .height = 1920 },
+        .fit,
+    );
+    try std.testing.expectEqual(@as(u32, 1080), placement.width);
+    try std.testing.expectEqual(@as(i32, 0), placement.x);
+    // 1080 * 1080 / 1920 = 608 (rounded up from 607.5).
+    try std.testing.expectEqual(@as(u32, 608), placement.height);
+    try std.testing.expect(placement.y > 0);
+}
+
+test "fill covers the surface and overflows the wider axis" {
+    const placement = layout.placeVideo(
+        .{ .width = 1920, .height = 1080 },
+        .{ .width = 1080, .height = 1920 },
+        .fill,
+    );
+    try std.testing.expectEqual(@as(u32, 1920), placement.height);
+    try std.testing.expect(placement.width >= 1080);
+    try std.testing.expect(placement.x < 0);
+}
@@ -0,0 +1,119 @@
+//! Hand-written libwayland-client bindings.
+//!
+//! Core protocol requests are inline macros in C, so the wrappers here go
+//! through the wl_proxy marshalling entry points directly. Extension
+//! protocols (layer-shell, viewporter, dmabuf, ...) are bound by name via
+//! the registry; their interface tables live in `protocols.zig`.
+
+pub const wl_display = opaque {};
+pub const wl_registry = opaque {};
+pub const wl_proxy = opaque {};
+
+pub const wl_interface = extern struct {
+    name: [*:0]const u8,
+    version: c_int,
+    method_count: c_int,
+    methods: ?[*]const wl_message,
+    event_count: c_int,
+    events: ?[*]const wl_message,
+};
+
+pub const wl_message = extern struct {
+    name: [*:0]const u8,
+    signature: [*:0]const u8,
+    types: ?[*]const ?*const wl_interface,
+};
+
+/// Event payload for array-typed arguments (`a` in signatures).
+pub const wl_array = extern struct {
+    size: usize,
+    alloc: usize,
+    data: ?*anyopaque,
+};
+
+pub extern const wl_registry_interface: wl_interface;
+
+pub extern fn wl_display_connect(name: ?[*:0]const u8) ?*wl_display;
+pub extern fn wl_display_disconnect(display: *wl_display) void;
+pub extern fn wl_display_roundtrip(display: *wl_display) c_int;
+pub extern fn wl_display_dispatch(display: *wl_display) c_int;
+pub extern fn wl_display_dispatch_pending(display: *wl_display) c_int;
+pub extern fn wl_display_flush(display: *wl_display) c_int;
+pub extern fn wl_display_get_fd(display: *wl_display) c_int;
+
+pub extern fn wl_proxy_add_listener(
+    proxy: *wl_proxy,
+    implementation: *const anyopaque,
+    data: ?*anyopaque,
+) c_int;
+pub extern fn wl_proxy_destroy(proxy: *wl_proxy) void;
+pub extern fn wl_proxy_get_version(proxy: *wl_proxy) u32;
+pub extern fn wl_proxy_marshal(proxy: *wl_proxy, opcode: u32, ...) void;
+pub extern fn wl_proxy_marshal_constructor(
+    proxy: *wl_proxy,
+    opcode: u32,
+    interface: *const wl_interface,
+    ...
+) ?*wl_proxy;
+pub extern fn wl_proxy_marshal_constructor_versioned(
+    proxy: *wl_proxy,
+    opcode: u32,
+    interface: *const wl_interface,
+    version: u32,
+    ...
+) ?*wl_proxy;
+
+const WL_DISPLAY_GET_REGISTRY: u32 = 1;
+const WL_REGISTRY_BIND: u32 = 0;
+
+pub fn displayGetRegistry(display: *wl_display) ?*wl_registry {
+    const proxy = wl_proxy_marshal_constructor(
+        @ptrCast(display),
+        WL_DISPLAY_GET_REGISTRY,
+        &wl_registry_interface,
+        @as(?*anyopaque, null),
+    ) orelse return null;
+    return @ptrCast(proxy);
+}
+
+/// wl_registry.global / wl_registry.global_remove
+pub const wl_registry_listener = extern struct {
+    global: *const fn (
+        data: ?*anyopaque,
+        registry: *wl_registry,
+        name: u32,
+        interface: [*:0]const u8,
+        version: u32,
+    ) callconv(.c) void,
+    global_remove: *const fn (
+        data: ?*anyopaque,
+        registry: *wl_registry,
+        name: u32,
+    ) callconv(.c) void,
+};
+
+pub fn registryAddListener(
+    registry: *wl_registry,
+    listener: *const wl_registry_listener,
+    data: ?*anyopaque,
+) c_int {
+    return wl_proxy_add_listener(@ptrCast(registry), listener, data);
+}
+
+pub fn registryBind(
+    registry: *wl_registry,
+    name: u32,
+    interface: *const wl_interface,
+    version: u32,
+) ?*wl_proxy {
+    return wl_proxy_marshal_constructor_versioned(
+        @ptrCast(registry),
+        WL_REGISTRY_BIND,
+        interface,
+        version,
+        name,
+        interface.name,
+        version,
+        @as(?*anyopaque, null),
+    );
+}
@@ -0,0 +1,81 @@
+//! Mapping from our colorimetry model to the wp-color-management-v1 and
+//! wp-color-representation-v1 protocol enums.
+//!
+//! The Wayland presentation path uses this to build a parametric image
+//! description for HDR buffers so the compositor receives the original
+//! primaries/transfer function instead of tone-mapped SDR.
+
+const std = @import("std");
+const color = @import("../render/color.zig");
+
+/// wp_color_manager_v1.primaries
+pub const WpPrimaries = enum(u32) {
+    srgb = 1,
+    pal_m = 2,
+    pal = 3,
+    ntsc = 4,
+    generic_film = 5,
+    bt2020 = 6,
+    cie1931_xyz = 7,
+    dci_p3 = 8,
+    display_p3 = 9,
+    adobe_rgb = 10,
+};
+
+/// wp_color_manager_v1.transfer_function
+pub const WpTransferFunction = enum(u32) {
+    bt1886 = 1,
+    gamma22 = 2,
+    gamma28 = 3,
+    st240 = 4,
+    ext_linear = 5,
+    log_100 = 6,
+    log_316 = 7,
+    xvycc = 8,
+    srgb = 9,
+    ext_srgb = 10,
+    st2084_pq = 11,
+    st428 = 12,
+    hlg = 13,
+};
+
+pub const ImageDescriptionParams = struct {
+    primaries: WpPrimaries,
+    transfer: WpTransferFunction,
+};
+
+/// Protocol parameters for a negotiated colorimetry, or null when the
+/// content is plain SDR and needs no image description at all.
+pub fn imageDescription(colorimetry: color.Colorimetry) ?ImageDescriptionParams {
+    if (!colorimetry.isHdr()) return null;
+    return .{
+        .primaries = switch (colorimetry.primaries) {
+            .bt2020 => .bt2020,
+            .dci_p3 => .dci_p3,
+            .bt601 => .ntsc,
+            .bt709, .unknown => .srgb,
+        },
+        .transfer = switch (colorimetry.transfer) {
+            .pq => .st2084_pq,
+            .hlg => .hlg,
+            else => .ext_srgb,
+        },
+    };
+}
+
+test "SDR content needs no image description" {
+    try std.testing.expectEqual(
+        @as(?ImageDescriptionParams, null),
+        imageDescription(color.Colorimetry.sdr_default),
+    );
+}
+
+test "HDR10 and HLG map to the protocol enums" {
+    const pq = imageDescription(.{ .primaries = .bt2020, .transfer = .pq }).?;
+    try std.testing.expectEqual(WpPrimaries.bt2020, pq.primaries);
+    try std.testing.expectEqual(WpTransferFunction.st2084_pq, pq.transfer);
+
+    const hlg = imageDescription(.{ .primaries = .dci_p3, .transfer = .hlg }).?;
+    try std.testing.expectEqual(WpPrimaries.dci_p3, hlg.primaries);
+    try std.testing.expectEqual(WpTransferFunction.hlg, hlg.transfer);
+}
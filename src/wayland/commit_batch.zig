@@ -0,0 +1,97 @@
+//! Commit coalescing across surfaces.
+//!
+//! When one decoded frame feeds several outputs, committing each surface as
+//! its blit finishes sends attach/damage/commit trios interleaved with
+//! other traffic and lets the compositor latch the outputs on different
+//! vblanks. Staging the commits and releasing them together at the end of
+//! the dispatch cycle keeps sibling outputs on the same frame and ends the
+//! cycle with a single display flush instead of one per surface.
+//!
+//! Generic over the surface handle so the staging and dedup logic is
+//! testable without a compositor; the presentation glue flushes the
+//! display after `flush` reports how many commits went out.
+
+const std = @import("std");
+
+pub fn CommitBatch(comptime Surface: type) type {
+    return struct {
+        const Self = @This();
+
+        allocator: std.mem.Allocator,
+        pending: std.ArrayList(*Surface) = .empty,
+        /// Re-stages of an already-staged surface within one cycle; each
+        /// is a commit the batch saved.
+        coalesced: u64 = 0,
+
+        pub fn init(allocator: std.mem.Allocator) Self {
+            return .{ .allocator = allocator };
+        }
+
+        pub fn deinit(self: *Self) void {
+            self.pending.deinit(self.allocator);
+            self.* = undefined;
+        }
+
+        /// Marks a surface as having new content this cycle. Staging the
+        /// same surface again is free: the later attach already replaced
+        /// the earlier one, only the commit is still owed.
+        pub fn stage(self: *Self, surface: *Surface) !void {
+            for (self.pending.items) |staged| {
+                if (staged == surface) {
+                    self.coalesced += 1;
+                    return;
+                }
+            }
+            try self.pending.append(self.allocator, surface);
+        }
+
+        /// Commits every staged surface back to back and empties the
+        /// batch; returns how many commits went out so the caller knows
+        /// whether a display flush is due.
+        pub fn flush(self: *Self) u32 {
+            const count: u32 = @intCast(self.pending.items.len);
+            for (self.pending.items) |surface| surface.commit();
+            self.pending.clearRetainingCapacity();
+            return count;
+        }
+    };
+}
+
+const TestSurface = struct {
+    commits: u32 = 0,
+
+    fn commit(self: *TestSurface) void {
+        self.commits += 1;
+    }
+};
+
+test "staged surfaces commit once per flush" {
+    var batch = CommitBatch(TestSurface).init(std.testing.allocator);
+    defer batch.deinit();
+
+    var a: TestSurface = .{};
+    var b: TestSurface = .{};
+    try batch.stage(&a);
+    try batch.stage(&b);
+    try batch.stage(&a);
+
+    try std.testing.expectEqual(@as(u32, 2), batch.flush());
+    try std.testing.expectEqual(@as(u32, 1), a.commits);
+    try std.testing.expectEqual(@as(u32, 1), b.commits);
+    try std.testing.expectEqual(@as(u64, 1), batch.coalesced);
+
+    // The batch is empty again; a flush with nothing staged is a no-op.
+    try std.testing.expectEqual(@as(u32, 0), batch.flush());
+}
+
+test "surfaces staged after a flush commit in the next cycle" {
+    var batch = CommitBatch(TestSurface).init(std.testing.allocator);
+    defer batch.deinit();
+
+    var a: TestSurface = .{};
+    try batch.stage(&a);
+    _ = batch.flush();
+    try batch.stage(&a);
+    try std.testing.expectEqual(@as(u32, 1), batch.flush());
+    try std.testing.expectEqual(@as(u32, 2), a.commits);
+}
@@ -0,0 +1,177 @@
+//! zwp_linux_dmabuf_v1 feedback handling.
+//!
+//! Version 4 of the protocol replaces the flat format list with feedback:
+//! a format/modifier table delivered as an mmapped file plus preference
+//! tranches referencing it by index. Assuming linear ARGB8888 works almost
+//! everywhere but forces a compositor-side copy; consuming the tranches
+//! lets us allocate with a modifier the compositor can scan out directly.
+//!
+//! The accumulator below mirrors the event sequence (tranche properties,
+//! then tranche_done, then done); the protocol glue feeds events in and
+//! asks `select` for the buffer setup once `done` arrives.
+
+const std = @import("std");
+
+/// One row of the feedback format table (wire layout, 16 bytes).
+pub const TableEntry = extern struct {
+    format: u32,
+    padding: u32 = 0,
+    modifier: u64,
+};
+
+/// zwp_linux_dmabuf_feedback_v1.tranche_flags.scanout
+pub const TRANCHE_FLAG_SCANOUT: u32 = 1;
+
+/// Reinterprets the mmapped feedback table. Returns null when the size is
+/// not a whole number of entries or the mapping is misaligned.
+pub fn parseTable(bytes: []const u8) ?[]const TableEntry {
+    if (bytes.len % @sizeOf(TableEntry) != 0) return null;
+    if (@intFromPtr(bytes.ptr) % @alignOf(TableEntry) != 0) return null;
+    const entries: [*]const TableEntry = @ptrCast(@alignCast(bytes.ptr));
+    return entries[0 .. bytes.len / @sizeOf(TableEntry)];
+}
+
+pub const Tranche = struct {
+    /// Buffers in a scanout tranche can go to a hardware plane.
+    scanout: bool = false,
+    /// Indices into the format table, in compositor preference order.
+    indices: []const u16 = &.{},
+};
+
+/// What to allocate with, picked from the feedback.
+pub const Choice = struct {
+    format: u32,
+    modifier: u64,
+    scanout: bool,
+};
+
+/// Accumulates feedback events until `done`. Tranches arrive in preference
+/// order and are kept that way.
+pub const Feedback = struct {
+    allocator: std.mem.Allocator,
+    tranches: std.ArrayList(Tranche) = .empty,
+
+    /// Properties of the tranche currently being described.
+    pending_scanout: bool = false,
+    pending_indices: ?[]const u16 = null,
+
+    pub fn init(allocator: std.mem.Allocator) Feedback {
+        return .{ .allocator = allocator };
+    }
+
+    pub fn deinit(self: *Feedback) void {
+        self.clear();
+        self.tranches.deinit(self.allocator);
+        self.* = undefined;
+    }
+
+    /// Resets everything; the compositor resends full feedback after
+    /// changes (e.g. an output moved to another GPU).
+    pub fn clear(self: *Feedback) void {
+        for (self.tranches.items) |tranche| self.allocator.free(tranche.indices);
+        self.tranches.clearRetainingCapacity();
+        if (self.pending_indices) |indices| self.allocator.free(indices);
+        self.pending_indices = null;
+        self.pending_scanout = false;
+    }
+
+    pub fn handleTrancheFlags(self: *Feedback, flags: u32) void {
+        self.pending_scanout = flags & TRANCHE_FLAG_SCANOUT != 0;
+    }
+
+    /// The `tranche_formats` event payload: a wl_array of u16 indices.
+    pub fn handleTrancheFormats(self: *Feedback, indices: []const u16) !void {
+        if (self.pending_indices) |old| self.allocator.free(old);
+        self.pending_indices = try self.allocator.dupe(u16, indices);
+    }
+
+    pub fn handleTrancheDone(self: *Feedback) !void {
+        try self.tranches.append(self.allocator, .{
+            .scanout = self.pending_scanout,
+            .indices = self.pending_indices orelse &.{},
+        });
+        self.pending_indices = null;
+        self.pending_scanout = false;
+    }
+
+    /// Picks the first table entry, in tranche preference order, whose
+    /// format appears in `wanted` (itself in our preference order) and
+    /// whose modifier passes `acceptable`. Pass null to accept any
+    /// modifier the compositor lists; pass the decoder's modifier set to
+    /// constrain the choice to layouts we can actually produce.
+    pub fn select(
+        self: *const Feedback,
+        table: []const TableEntry,
+        wanted: []const u32,
+        acceptable: ?[]const u64,
+    ) ?Choice {
+        for (self.tranches.items) |tranche| {
+            for (wanted) |format| {
+                for (tranche.indices) |index| {
+                    if (index >= table.len) continue;
+                    const entry = table[index];
+                    if (entry.format != format) continue;
+                    if (acceptable) |modifiers| {
+                        if (std.mem.indexOfScalar(u64, modifiers, entry.modifier) == null)
+                            continue;
+                    }
+                    return .{
+                        .format = entry.format,
+                        .modifier = entry.modifier,
+                        .scanout = tranche.scanout,
+                    };
+                }
+            }
+        }
+        return null;
+    }
+};
+
+test "select prefers the scanout tranche and honours format order" {
+    const c = @import("../drm/c.zig");
+    const table = [_]TableEntry{
+        .{ .format = c.DRM_FORMAT_XRGB8888, .modifier = 0x0100_0000_0000_0002 },
+        .{ .format = c.DRM_FORMAT_ARGB8888, .modifier = c.DRM_FORMAT_MOD_LINEAR },
+        .{ .format = c.DRM_FORMAT_ARGB8888, .modifier = 0x0100_0000_0000_0002 },
+    };
+
+    var feedback = Feedback.init(std.testing.allocator);
+    defer feedback.deinit();
+
+    // Scanout tranche first (tiled only), then the render fallback.
+    feedback.handleTrancheFlags(TRANCHE_FLAG_SCANOUT);
+    try feedback.handleTrancheFormats(&.{ 0, 2 });
+    try feedback.handleTrancheDone();
+    feedback.handleTrancheFlags(0);
+    try feedback.handleTrancheFormats(&.{1});
+    try feedback.handleTrancheDone();
+
+    const wanted = [_]u32{ c.DRM_FORMAT_ARGB8888, c.DRM_FORMAT_XRGB8888 };
+
+    // Unconstrained: tiled ARGB8888 from the scanout tranche wins.
+    const best = feedback.select(&table, &wanted, null).?;
+    try std.testing.expectEqual(c.DRM_FORMAT_ARGB8888, best.format);
+    try std.testing.expect(best.scanout);
+
+    // Decoder can only produce linear: falls through to the render tranche.
+    const linear_only = [_]u64{c.DRM_FORMAT_MOD_LINEAR};
+    const fallback = feedback.select(&table, &wanted, &linear_only).?;
+    try std.testing.expectEqual(c.DRM_FORMAT_MOD_LINEAR, fallback.modifier);
+    try std.testing.expect(!fallback.scanout);
+
+    // Nothing the compositor lists: null, caller drops to shm.
+    const impossible = [_]u64{0xdead};
+    try std.testing.expectEqual(
+        @as(?Choice, null),
+        feedback.select(&table, &wanted, &impossible),
+    );
+}
+
+test "parseTable rejects truncated tables" {
+    const bytes align(@alignOf(TableEntry)) = [_]u8{0} ** (@sizeOf(TableEntry) + 3);
+    try std.testing.expectEqual(@as(?[]const TableEntry, null), parseTable(&bytes));
+    try std.testing.expectEqual(
+        @as(usize, 1),
+        parseTable(bytes[0..@sizeOf(TableEntry)]).?.len,
+    );
+}
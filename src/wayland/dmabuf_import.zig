@@ -0,0 +1,120 @@
+//! Plane layouts for zwp_linux_buffer_params_v1 dmabuf import.
+//!
+//! Hardware decoders hand out NV12 (and P010 for 10-bit) dmabufs. Forcing
+//! those through a conversion stage to BGRA defeats zero-copy, so the
+//! import path describes the decoder's planes directly: each `add` request
+//! needs an fd, offset and stride per plane, and the create request the
+//! matching DRM fourcc. This module computes those descriptions; the
+//! protocol glue issues the requests.
+
+const std = @import("std");
+const c = @import("../drm/c.zig");
+
+/// Video memory layouts the importer understands.
+pub const Format = enum {
+    /// Single plane, 8-bit, what CPU-rendered buffers use.
+    argb8888,
+    /// Two planes: full-res Y, half-res interleaved UV.
+    nv12,
+    /// NV12 layout with 16-bit samples (10 bits used, MSB-aligned).
+    p010,
+    /// Single plane, 10 bits per color packed into 32-bit words.
+    xrgb2101010,
+
+    pub fn drmFourcc(self: Format) u32 {
+        return switch (self) {
+            .argb8888 => c.DRM_FORMAT_ARGB8888,
+            .nv12 => c.DRM_FORMAT_NV12,
+            .p010 => c.fourcc("P010"),
+            .xrgb2101010 => c.fourcc("XR30"),
+        };
+    }
+
+    pub fn planeCount(self: Format) u32 {
+        return switch (self) {
+            .argb8888, .xrgb2101010 => 1,
+            .nv12, .p010 => 2,
+        };
+    }
+
+    /// Bytes per sample in the luma/primary plane.
+    fn bytesPerSample(self: Format) u32 {
+        return switch (self) {
+            .argb8888, .xrgb2101010 => 4,
+            .nv12 => 1,
+            .p010 => 2,
+        };
+    }
+};
+
+/// One `zwp_linux_buffer_params_v1.add` request.
+pub const PlaneDesc = struct {
+    offset: u32,
+    stride: u32,
+};
+
+/// Plane descriptions for a tightly packed single-fd buffer, as produced
+/// by decoders that allocate all planes in one BO. Buffers with per-plane
+/// fds (e.g. from our own GBM allocator) carry their layout already and
+/// skip this.
+pub fn contiguousPlanes(format: Format, width: u32, height: u32) [2]PlaneDesc {
+    const stride = width * format.bytesPerSample();
+    return switch (format) {
+        .argb8888, .xrgb2101010 => .{
+            .{ .offset = 0, .stride = stride },
+            undefined,
+        },
+        // UV plane starts after the full luma plane; same stride because
+        // the half-width chroma samples come in pairs.
+        .nv12, .p010 => .{
+            .{ .offset = 0, .stride = stride },
+            .{ .offset = stride * height, .stride = stride },
+        },
+    };
+}
+
+/// Total size of a tightly packed buffer, for bounds-checking imports.
+pub fn contiguousSize(format: Format, width: u32, height: u32) u32 {
+    const stride = width * format.bytesPerSample();
+    return switch (format) {
+        .argb8888, .xrgb2101010 => stride * height,
+        // 4:2:0 chroma adds half the luma plane again.
+        .nv12, .p010 => stride * height + stride * ((height + 1) / 2),
+    };
+}
+
+/// Maps a GStreamer caps format string onto an import layout, or null for
+/// formats that must stay on the conversion path.
+pub fn fromCapsFormat(name: []const u8) ?Format {
+    if (std.mem.eql(u8, name, "NV12")) return .nv12;
+    if (std.mem.eql(u8, name, "P010_10LE")) return .p010;
+    if (std.mem.eql(u8, name, "BGR10A2_LE")) return .xrgb2101010;
+    if (std.mem.eql(u8, name, "BGRA") or std.mem.eql(u8, name, "ARGB")) return .argb8888;
+    return null;
+}
+
+test "nv12 planes cover the buffer exactly" {
+    const planes = contiguousPlanes(.nv12, 1920, 1080);
+    try std.testing.expectEqual(@as(u32, 1920), planes[0].stride);
+    try std.testing.expectEqual(@as(u32, 1920 * 1080), planes[1].offset);
+    try std.testing.expectEqual(
+        @as(u32, 1920 * 1080 * 3 / 2),
+        contiguousSize(.nv12, 1920, 1080),
+    );
+}
+
+test "p010 doubles the sample size and rounds odd heights up" {
+    const planes = contiguousPlanes(.p010, 1280, 721);
+    try std.testing.expectEqual(@as(u32, 2560), planes[0].stride);
+    try std.testing.expectEqual(@as(u32, 2560 * 721), planes[1].offset);
+    try std.testing.expectEqual(
+        @as(u32, 2560 * 721 + 2560 * 361),
+        contiguousSize(.p010, 1280, 721),
+    );
+}
+
+test "caps format mapping" {
+    try std.testing.expectEqual(@as(?Format, .nv12), fromCapsFormat("NV12"));
+    try std.testing.expectEqual(@as(?Format, .p010), fromCapsFormat("P010_10LE"));
+    try std.testing.expectEqual(@as(?Format, null), fromCapsFormat("I420"));
+}
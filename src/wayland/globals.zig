@@ -0,0 +1,203 @@
+//! Registry global tracking with runtime loss handling.
+//!
+//! Optional protocols (viewporter, linux-dmabuf) can disappear mid-session
+//! when a compositor reloads. Instead of treating that as fatal, the tracker
+//! records announce/remove events and notifies the renderer so affected
+//! surfaces can drop to the CPU/shm path — and upgrade again when the global
+//! returns.
+
+const std = @import("std");
+const c = @import("c.zig");
+
+/// Protocols playback can run without.
+pub const OptionalProtocol = enum {
+    viewporter,
+    linux_dmabuf,
+    layer_shell,
+    /// Explicit sync for imported dmabufs; without it presentation falls
+    /// back to implicit sync.
+    syncobj,
+
+    pub fn interfaceName(self: OptionalProtocol) []const u8 {
+        return switch (self) {
+            .viewporter => "wp_viewporter",
+            .linux_dmabuf => "zwp_linux_dmabuf_v1",
+            .layer_shell => "zwlr_layer_shell_v1",
+            .syncobj => "wp_linux_drm_syncobj_manager_v1",
+        };
+    }
+};
+
+pub const Event = enum {
+    /// Global announced (initially or after a compositor reload).
+    available,
+    /// Global removed at runtime; switch to the fallback path.
+    lost,
+};
+
+pub const ChangeFn = *const fn (ctx: ?*anyopaque, protocol: OptionalProtocol, event: Event) void;
+
+/// How buffers should be presented given what the compositor offers.
+pub const BufferPath = enum {
+    /// Dmabuf import with compositor-side viewport scaling.
+    dmabuf_viewport,
+    /// Dmabuf attached 1:1, no scaling. Mapping a GPU buffer back to the
+    /// CPU just to scale it costs more than never using dmabuf, so when
+    /// the viewporter is missing we instead decode at the surface size
+    /// (the pipeline scales in-GPU) and attach directly.
+    dmabuf_direct,
+    /// CPU conversion into shm buffers.
+    shm,
+};
+
+pub const Tracker = struct {
+    const Slot = struct {
+        /// Registry name while the global is announced.
+        name: ?u32 = null,
+        version: u32 = 0,
+    };
+
+    slots: std.EnumArray(OptionalProtocol, Slot) = .initFill(.{}),
+    on_change: ?ChangeFn = null,
+    on_change_ctx: ?*anyopaque = null,
+
+    pub fn handleGlobal(self: *Tracker, name: u32, interface: []const u8, version: u32) void {
+        inline for (comptime std.enums.values(OptionalProtocol)) |protocol| {
+            if (std.mem.eql(u8, interface, protocol.interfaceName())) {
+                const was_available = self.slots.get(protocol).name != null;
+                self.slots.set(protocol, .{ .name = name, .version = version });
+                if (!was_available) self.notify(protocol, .available);
+                return;
+            }
+        }
+    }
+
+    pub fn handleGlobalRemove(self: *Tracker, name: u32) void {
+        inline for (comptime std.enums.values(OptionalProtocol)) |protocol| {
+            const slot = self.slots.get(protocol);
+            if (slot.name == name) {
+                self.slots.set(protocol, .{});
+                self.notify(protocol, .lost);
+                return;
+            }
+        }
+    }
+
+    pub fn has(self: *const Tracker, protocol: OptionalProtocol) bool {
+        return self.slots.get(protocol).name != null;
+    }
+
+    /// Announced version of the global, or null while it is absent.
+    pub fn version(self: *const Tracker, protocol: OptionalProtocol) ?u32 {
+        const slot = self.slots.get(protocol);
+        if (slot.name == null) return null;
+        return slot.version;
+    }
+
+    /// Best presentation path with the currently available globals.
+    /// `exact_size` says whether frames arrive at the surface size already
+    /// (decode-at-output); without it the direct path would show a
+    /// wrongly-sized picture and shm has to scale instead.
+    pub fn preferredBufferPath(self: *const Tracker, exact_size: bool) BufferPath {
+        if (self.has(.linux_dmabuf)) {
+            if (self.has(.viewporter)) return .dmabuf_viewport;
+            if (exact_size) return .dmabuf_direct;
+        }
+        return .shm;
+    }
+
+    fn notify(self: *Tracker, protocol: OptionalProtocol, event: Event) void {
+        if (self.on_change) |callback| callback(self.on_change_ctx, protocol, event);
+    }
+};
+
+/// Connection owning the display, registry, and tracker, with the registry
+/// listener wired up.
+pub const Connection = struct {
+    display: *c.wl_display,
+    registry: *c.wl_registry,
+    tracker: Tracker = .{},
+
+    const registry_listener: c.wl_registry_listener = .{
+        .global = onGlobal,
+        .global_remove = onGlobalRemove,
+    };
+
+    pub fn connect(self: *Connection) !void {
+        const display = c.wl_display_connect(null) orelse return error.ConnectFailed;
+        errdefer c.wl_display_disconnect(display);
+        const registry = c.displayGetRegistry(display) orelse return error.ConnectFailed;
+
+        self.display = display;
+        self.registry = registry;
+        _ = c.registryAddListener(registry, &registry_listener, self);
+        _ = c.wl_display_roundtrip(display);
+    }
+
+    pub fn disconnect(self: *Connection) void {
+        c.wl_proxy_destroy(@ptrCast(self.registry));
+        c.wl_display_disconnect(self.display);
+        self.* = undefined;
+    }
+
+    fn onGlobal(
+        data: ?*anyopaque,
+        registry: *c.wl_registry,
+        name: u32,
+        interface: [*:0]const u8,
+        version: u32,
+    ) callconv(.c) void {
+        _ = registry;
+        const self: *Connection = @ptrCast(@alignCast(data.?));
+        self.tracker.handleGlobal(name, std.mem.span(interface), version);
+    }
+
+    fn onGlobalRemove(data: ?*anyopaque, registry: *c.wl_registry, name: u32) callconv(.c) void {
+        _ = registry;
+        const self: *Connection = @ptrCast(@alignCast(data.?));
+        self.tracker.handleGlobalRemove(name);
+    }
+};
+
+test "losing dmabuf downgrades the buffer path, re-announce restores it" {
+    var tracker: Tracker = .{};
+    tracker.handleGlobal(1, "wp_viewporter", 1);
+    tracker.handleGlobal(2, "zwp_linux_dmabuf_v1", 4);
+    try std.testing.expectEqual(BufferPath.dmabuf_viewport, tracker.preferredBufferPath(false));
+
+    tracker.handleGlobalRemove(2);
+    try std.testing.expectEqual(BufferPath.shm, tracker.preferredBufferPath(false));
+    try std.testing.expect(tracker.has(.viewporter));
+
+    tracker.handleGlobal(7, "zwp_linux_dmabuf_v1", 4);
+    try std.testing.expectEqual(BufferPath.dmabuf_viewport, tracker.preferredBufferPath(false));
+}
+
+test "without the viewporter, dmabuf is only used at exact size" {
+    var tracker: Tracker = .{};
+    tracker.handleGlobal(2, "zwp_linux_dmabuf_v1", 4);
+
+    // Frames not at surface size would need a CPU readback to scale, which
+    // is worse than plain shm; refuse the dmabuf path entirely.
+    try std.testing.expectEqual(BufferPath.shm, tracker.preferredBufferPath(false));
+    try std.testing.expectEqual(BufferPath.dmabuf_direct, tracker.preferredBufferPath(true));
+}
+
+test "change callback fires on loss and restoration" {
+    const Recorder = struct {
+        events: std.BoundedArray(Event, 8) = .{},
+        fn onChange(ctx: ?*anyopaque, protocol: OptionalProtocol, event: Event) void {
+            _ = protocol;
+            const self: *@This() = @ptrCast(@alignCast(ctx.?));
+            self.events.append(event) catch {};
+        }
+    };
+    var recorder: Recorder = .{};
+    var tracker: Tracker = .{ .on_change = Recorder.onChange, .on_change_ctx = &recorder };
+
+    tracker.handleGlobal(3, "wp_viewporter", 1);
+    tracker.handleGlobalRemove(3);
+    tracker.handleGlobal(9, "wp_viewporter", 1);
+
+    try std.testing.expectEqualSlices(Event, &.{ .available, .lost, .available }, recorder.events.slice());
+}
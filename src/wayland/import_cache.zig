@@ -0,0 +1,197 @@
+//! Identity-keyed cache of imported wl_buffers.
+//!
+//! Decoders cycle through a small ring of dmabufs, but a naive import path
+//! creates fresh zwp_linux_buffer_params and a fresh wl_buffer for every
+//! frame even when the fd refers to a buffer the compositor has already
+//! seen. The kernel hands out a new fd number for each export, so fds are
+//! identified by the underlying inode (st_dev/st_ino), which is stable for
+//! the lifetime of the dmabuf. Keyed on that identity plus the import
+//! parameters, an imported wl_buffer can be attached again without another
+//! protocol round-trip.
+
+const std = @import("std");
+
+/// Identity of one imported buffer: which dmabuf, imported how.
+pub const Key = struct {
+    /// st_dev/st_ino of the dmabuf; together they identify the underlying
+    /// buffer across duplicated fds.
+    dev: u64,
+    inode: u64,
+    offset: u32,
+    stride: u32,
+    modifier: u64,
+
+    /// Resolves an fd to its identity via fstat.
+    pub fn fromFd(fd: std.posix.fd_t, offset: u32, stride: u32, modifier: u64) !Key {
+        const stat = try std.posix.fstat(fd);
+        return .{
+            .dev = @intCast(stat.dev),
+            .inode = @intCast(stat.ino),
+            .offset = offset,
+            .stride = stride,
+            .modifier = modifier,
+        };
+    }
+
+    pub fn eql(self: Key, other: Key) bool {
+        return self.dev == other.dev and
+            self.inode == other.inode and
+            self.offset == other.offset and
+            self.stride == other.stride and
+            self.modifier == other.modifier;
+    }
+};
+
+/// Imports kept alive; decoders rarely rotate through more buffers.
+pub const default_capacity = 8;
+
+/// Generic over the wl_buffer proxy type so the lookup and eviction logic
+/// is testable without a compositor. Unlike `drm.BufferCache`, entries stay
+/// in the cache while attached: `get` marks the buffer busy and `release`
+/// (driven by the wl_buffer release event) returns it, so eviction never
+/// destroys a buffer the compositor still reads from.
+pub fn ImportCache(comptime WlBuffer: type) type {
+    return struct {
+        const Self = @This();
+
+        const Entry = struct {
+            key: Key,
+            buffer: WlBuffer,
+            last_used: u64,
+            busy: bool,
+        };
+
+        allocator: std.mem.Allocator,
+        entries: std.ArrayList(Entry) = .empty,
+        capacity: usize = default_capacity,
+        clock: u64 = 0,
+        /// Frames served without a protocol import.
+        hits: u64 = 0,
+        misses: u64 = 0,
+
+        pub fn init(allocator: std.mem.Allocator) Self {
+            return .{ .allocator = allocator };
+        }
+
+        pub fn deinit(self: *Self) void {
+            for (self.entries.items) |*entry| entry.buffer.deinit();
+            self.entries.deinit(self.allocator);
+            self.* = undefined;
+        }
+
+        /// Looks up an already-imported wl_buffer and marks it busy until
+        /// `release`; null means the caller must import and `insert`.
+        pub fn get(self: *Self, key: Key) ?*WlBuffer {
+            for (self.entries.items) |*entry| {
+                if (!entry.key.eql(key)) continue;
+                self.clock += 1;
+                entry.last_used = self.clock;
+                entry.busy = true;
+                self.hits += 1;
+                return &entry.buffer;
+            }
+            self.misses += 1;
+            return null;
+        }
+
+        /// Registers a freshly imported wl_buffer as busy, evicting the
+        /// least recently used idle import when over capacity.
+        pub fn insert(self: *Self, key: Key, buffer: WlBuffer) !void {
+            self.clock += 1;
+            try self.entries.append(self.allocator, .{
+                .key = key,
+                .buffer = buffer,
+                .last_used = self.clock,
+                .busy = true,
+            });
+            while (self.entries.items.len > self.capacity) {
+                if (!self.evictOldestIdle()) break;
+            }
+        }
+
+        /// Marks an import idle again once the compositor releases it.
+        pub fn release(self: *Self, key: Key) void {
+            for (self.entries.items) |*entry| {
+                if (entry.key.eql(key)) {
+                    entry.busy = false;
+                    return;
+                }
+            }
+        }
+
+        fn evictOldestIdle(self: *Self) bool {
+            var oldest: ?usize = null;
+            for (self.entries.items, 0..) |entry, i| {
+                if (entry.busy) continue;
+                if (oldest == null or
+                    entry.last_used < self.entries.items[oldest.?].last_used)
+                {
+                    oldest = i;
+                }
+            }
+            const index = oldest orelse return false;
+            var entry = self.entries.swapRemove(index);
+            entry.buffer.deinit();
+            return true;
+        }
+    };
+}
+
+const TestBuffer = struct {
+    destroyed: *u32,
+
+    fn deinit(self: *TestBuffer) void {
+        self.destroyed.* += 1;
+    }
+};
+
+fn testKey(inode: u64) Key {
+    return .{ .dev = 1, .inode = inode, .offset = 0, .stride = 7680, .modifier = 0 };
+}
+
+test "repeat imports hit the cache after release" {
+    var destroyed: u32 = 0;
+    var cache = ImportCache(TestBuffer).init(std.testing.allocator);
+    defer cache.deinit();
+
+    const key = testKey(100);
+    try std.testing.expectEqual(@as(?*TestBuffer, null), cache.get(key));
+    try cache.insert(key, .{ .destroyed = &destroyed });
+    cache.release(key);
+
+    try std.testing.expect(cache.get(key) != null);
+    try std.testing.expectEqual(@as(u64, 1), cache.hits);
+    try std.testing.expectEqual(@as(u32, 0), destroyed);
+}
+
+test "same inode with different stride is a different buffer" {
+    var destroyed: u32 = 0;
+    var cache = ImportCache(TestBuffer).init(std.testing.allocator);
+    defer cache.deinit();
+
+    try cache.insert(testKey(100), .{ .destroyed = &destroyed });
+    cache.release(testKey(100));
+
+    var other = testKey(100);
+    other.stride = 3840;
+    try std.testing.expectEqual(@as(?*TestBuffer, null), cache.get(other));
+}
+
+test "busy imports survive eviction" {
+    var destroyed: u32 = 0;
+    var cache = ImportCache(TestBuffer).init(std.testing.allocator);
+    defer cache.deinit();
+    cache.capacity = 2;
+
+    try cache.insert(testKey(1), .{ .destroyed = &destroyed });
+    try cache.insert(testKey(2), .{ .destroyed = &destroyed });
+    cache.release(testKey(1));
+
+    // Only the idle import is evictable; the busy ones stay even though
+    // the cache runs over capacity.
+    try cache.insert(testKey(3), .{ .destroyed = &destroyed });
+    try std.testing.expectEqual(@as(u32, 1), destroyed);
+    try cache.insert(testKey(4), .{ .destroyed = &destroyed });
+    try std.testing.expectEqual(@as(u32, 1), destroyed);
+    try std.testing.expectEqual(@as(usize, 3), cache.entries.items.len);
+}
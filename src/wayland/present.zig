@@ -0,0 +1,1241 @@
+//! Layer-shell dmabuf presentation.
+//!
+//! The raylib window path works everywhere but pays a CPU upload per frame
+//! and lives in a floating window. This engine is what the dmabuf
+//! groundwork modules exist for: it owns a Wayland connection, puts one
+//! `zwlr_layer_shell_v1` background surface on every output, negotiates a
+//! format/modifier from dmabuf feedback, and attaches GBM-allocated
+//! buffers the compositor imports zero-copy. Surface scaling is
+//! compositor-side through `wp_viewporter`, so frames are presented at
+//! whatever size they are decoded at.
+//!
+//! The player activates this path with `--buffer-mode dmabuf`; any missing
+//! prerequisite (no compositor, no layer shell, dmabuf below v4, no render
+//! node) surfaces as an error so the caller can fall back to the window.
+
+const std = @import("std");
+const c = @import("c.zig");
+const proto = @import("protocols.zig");
+const globals = @import("globals.zig");
+const feedback_mod = @import("dmabuf_feedback.zig");
+const dmabuf_import = @import("dmabuf_import.zig");
+const import_cache = @import("import_cache.zig");
+const commit_batch = @import("commit_batch.zig");
+const syncobj = @import("syncobj.zig");
+const gbm = @import("../drm/gbm.zig");
+const drm_c = @import("../drm/c.zig");
+const buffer_cache_mod = @import("../drm/buffer_cache.zig");
+const swapchain = @import("../render/swapchain.zig");
+
+pub const EngineError = error{
+    ConnectFailed,
+    /// A required global (compositor, layer shell, viewporter, dmabuf v4)
+    /// is not offered.
+    MissingProtocol,
+    /// The compositor never delivered usable dmabuf feedback.
+    NoFeedback,
+    /// No output to put a surface on.
+    NoOutputs,
+    ImportFailed,
+    MapFailed,
+    Stalled,
+} || std.mem.Allocator.Error || gbm.AllocatorError;
+
+/// Formats we can render into, most preferred first. Both are RGBA byte
+/// order in memory (little-endian DRM ABGR/XBGR), which is exactly what
+/// the YUV conversion and compose paths produce — no swizzle on import.
+const wanted_formats = [_]u32{ drm_c.DRM_FORMAT_ABGR8888, drm_c.DRM_FORMAT_XBGR8888 };
+
+/// One imported presentation buffer of a surface's swapchain.
+const Slot = struct {
+    buffer: gbm.Buffer,
+    wl_buffer: *proto.wl_buffer,
+    /// Listener data for the release event; heap-allocated so the address
+    /// outlives slot moves.
+    ctx: *ReleaseCtx,
+    /// Explicit-sync state; null when the compositor lacks the protocol or
+    /// the timeline setup failed, leaving the slot on implicit sync.
+    sync: ?SlotSync = null,
+};
+
+/// A DRM timeline syncobj shared with the compositor, plus the point
+/// bookkeeping for one slot.
+const SlotSync = struct {
+    /// Syncobj handle on the allocator's render node.
+    handle: u32,
+    proxy: *proto.wp_linux_drm_syncobj_timeline_v1,
+    timeline: syncobj.Timeline = .{},
+    /// Release point of the latest commit; cleared once it signals and the
+    /// slot goes back to the swapchain.
+    pending_release: ?u64 = null,
+};
+
+const ReleaseCtx = struct {
+    output: *Output,
+    index: u32,
+};
+
+/// Notifies the owner of a directly imported frame that the compositor is
+/// done with it; the dmabuf behind the import must stay alive until then.
+pub const ReleaseFn = *const fn (user: ?*anyopaque) void;
+
+/// Renders one frame's pixels into `dest`, tightly packed RGBA sized for
+/// the frame passed to `presentFill`. The destination is the slot's own
+/// mapping when its stride allows, so what would otherwise be a staging
+/// buffer plus a copy is a single conversion pass.
+pub const FillFn = *const fn (user: ?*anyopaque, dest: []u8) void;
+
+/// Where `presentOn` takes a frame's pixels from.
+const FrameSource = union(enum) {
+    /// Caller-owned, tightly packed RGBA; copied row by row.
+    pixels: []const u8,
+    /// On-demand renderer writing straight into the mapping; falls back
+    /// to `scratch` for slots with a padded stride. `filled` remembers a
+    /// staged render so it is produced at most once per frame.
+    fill: struct {
+        user: ?*anyopaque,
+        fill: FillFn,
+        scratch: *std.ArrayList(u8),
+        filled: bool = false,
+    },
+};
+
+/// One decoder dmabuf imported as a wl_buffer. Kept in `Engine.imports` so
+/// the next frame backed by the same dmabuf — decoders cycle a small ring
+/// of them — reuses the import instead of re-running the params
+/// round-trip; the frame behind the latest commit is handed back through
+/// `on_release` when the compositor releases the buffer.
+const DirectImport = struct {
+    engine: *Engine,
+    wl_buffer: *proto.wl_buffer,
+    key: import_cache.Key,
+    /// Owner callback for the frame attached by the latest commit; null
+    /// while the import sits idle in the cache.
+    user: ?*anyopaque = null,
+    on_release: ?ReleaseFn = null,
+
+    /// Cache eviction / engine teardown. The release event never arrives
+    /// for a destroyed buffer, so a still-attached frame is handed back
+    /// here.
+    fn deinit(self: *DirectImport) void {
+        proto.bufferDestroy(self.wl_buffer);
+        if (self.on_release) |hand_back| hand_back(self.user);
+        self.engine.allocator.destroy(self);
+    }
+};
+
+pub const Output = struct {
+    engine: *Engine,
+    proxy: *proto.wl_output,
+    registry_name: u32,
+    /// wl_output.name (since v4); empty on older compositors.
+    name_storage: [64]u8 = undefined,
+    name_len: usize = 0,
+
+    surface: ?*proto.wl_surface = null,
+    layer_surface: ?*proto.zwlr_layer_surface_v1 = null,
+    viewport: ?*proto.wp_viewport = null,
+    /// Explicit-sync handle for the surface, created lazily on the first
+    /// slot present; see `ensureSyncSurface`.
+    sync_surface: ?*proto.wp_linux_drm_syncobj_surface_v1 = null,
+    /// Surface size from the latest configure; zero until then.
+    width: u32 = 0,
+    height: u32 = 0,
+    configured: bool = false,
+    /// The compositor closed the layer surface; resources are torn down on
+    /// the next `pump`.
+    closed: bool = false,
+
+    chain: swapchain.Swapchain,
+    slots: [swapchain.max_depth]?Slot = @splat(null),
+    /// Frame size the slots were allocated for.
+    slot_width: u32 = 0,
+    slot_height: u32 = 0,
+    frames_presented: u64 = 0,
+
+    pub fn name(self: *const Output) []const u8 {
+        return self.name_storage[0..self.name_len];
+    }
+
+    /// CommitBatch hook: releases the staged attach/damage state. Only
+    /// called while the batch holds the output, i.e. the surface exists.
+    fn commit(self: *Output) void {
+        proto.surfaceCommit(self.surface.?);
+    }
+};
+
+pub const Engine = struct {
+    allocator: std.mem.Allocator,
+    display: *c.wl_display,
+    registry: *c.wl_registry,
+    /// Optional-protocol bookkeeping shared with the rest of the tree;
+    /// used here for the availability/version checks and loss events.
+    tracker: globals.Tracker = .{},
+
+    compositor: ?*proto.wl_compositor = null,
+    layer_shell: ?*proto.zwlr_layer_shell_v1 = null,
+    viewporter: ?*proto.wp_viewporter = null,
+    dmabuf: ?*proto.zwp_linux_dmabuf_v1 = null,
+    syncobj_manager: ?*proto.wp_linux_drm_syncobj_manager_v1 = null,
+    outputs: std.ArrayList(*Output) = .empty,
+    /// Direct decoder-dmabuf imports, keyed by the dmabuf's identity so a
+    /// recycled decoder buffer skips the params round-trip.
+    imports: import_cache.ImportCache(*DirectImport),
+    /// Surface commits staged during a present cycle and released back to
+    /// back, so sibling outputs latch the same frame on the same vblank.
+    commits: commit_batch.CommitBatch(Output),
+
+    gbm_allocator: gbm.GbmAllocator,
+    /// Allocations kept across size changes; toggling between two output
+    /// configurations reuses buffers instead of reallocating the pool.
+    buffer_cache: buffer_cache_mod.DmabufCache,
+    /// Buffers per surface (double/triple buffering).
+    depth: u32,
+
+    feedback: feedback_mod.Feedback,
+    feedback_proxy: ?*proto.zwp_linux_dmabuf_feedback_v1 = null,
+    /// mmapped format/modifier table referenced by the tranche indices.
+    table: ?[]align(std.heap.page_size_min) u8 = null,
+    feedback_done: bool = false,
+    /// Format/modifier every buffer is allocated with.
+    negotiated: ?feedback_mod.Choice = null,
+    /// Set once init finished; outputs hot-plugged after that get their
+    /// surface immediately in the registry callback.
+    ready: bool = false,
+
+    const registry_listener: c.wl_registry_listener = .{
+        .global = onGlobal,
+        .global_remove = onGlobalRemove,
+    };
+
+    pub fn init(allocator: std.mem.Allocator, depth: u32) EngineError!*Engine {
+        const self = try allocator.create(Engine);
+        errdefer allocator.destroy(self);
+
+        const display = c.wl_display_connect(null) orelse return EngineError.ConnectFailed;
+        errdefer c.wl_display_disconnect(display);
+        const registry = c.displayGetRegistry(display) orelse return EngineError.ConnectFailed;
+
+        self.* = .{
+            .allocator = allocator,
+            .display = display,
+            .registry = registry,
+            .gbm_allocator = undefined,
+            .imports = import_cache.ImportCache(*DirectImport).init(allocator),
+            .commits = commit_batch.CommitBatch(Output).init(allocator),
+            .buffer_cache = buffer_cache_mod.DmabufCache.init(allocator),
+            .depth = depth,
+            .feedback = feedback_mod.Feedback.init(allocator),
+        };
+        errdefer {
+            self.feedback.deinit();
+            for (self.outputs.items) |output| self.destroyOutput(output);
+            self.outputs.deinit(allocator);
+            self.imports.deinit();
+            self.commits.deinit();
+            self.buffer_cache.deinit();
+        }
+
+        _ = c.registryAddListener(registry, &registry_listener, self);
+        if (c.wl_display_roundtrip(display) < 0) return EngineError.ConnectFailed;
+
+        if (self.compositor == null or
+            self.layer_shell == null or
+            self.viewporter == null or
+            self.dmabuf == null)
+        {
+            return EngineError.MissingProtocol;
+        }
+        if (self.outputs.items.len == 0) return EngineError.NoOutputs;
+
+        self.gbm_allocator = try gbm.GbmAllocator.init();
+        errdefer self.gbm_allocator.deinit();
+
+        // Default feedback covers the whole display; per-surface feedback
+        // (different GPUs per output) can refine this later.
+        self.feedback_proxy = proto.dmabufGetDefaultFeedback(self.dmabuf.?) orelse
+            return EngineError.ConnectFailed;
+        _ = proto.feedbackAddListener(self.feedback_proxy.?, &feedback_listener, self);
+
+        for (self.outputs.items) |output| try self.createSurface(output);
+
+        // Wait for the feedback `done` and every surface's first configure;
+        // both normally land within a round trip or two.
+        var rounds: u32 = 0;
+        while (rounds < 8 and !(self.feedback_done and self.allConfigured())) : (rounds += 1) {
+            if (c.wl_display_roundtrip(display) < 0) return EngineError.ConnectFailed;
+        }
+        if (self.negotiated == null) return EngineError.NoFeedback;
+
+        self.ready = true;
+        return self;
+    }
+
+    pub fn deinit(self: *Engine) void {
+        for (self.outputs.items) |output| self.destroyOutput(output);
+        self.outputs.deinit(self.allocator);
+
+        // Destroying the cache destroys every import; still-attached frames
+        // are handed back from DirectImport.deinit, since their release
+        // events never arrive once the surfaces are gone.
+        self.imports.deinit();
+        // Always empty between present cycles; nothing left to commit.
+        self.commits.deinit();
+
+        if (self.feedback_proxy) |feedback| proto.feedbackDestroy(feedback);
+        if (self.table) |table| std.posix.munmap(table);
+        self.feedback.deinit();
+
+        if (self.syncobj_manager) |manager| proto.syncobjManagerDestroy(manager);
+        if (self.dmabuf) |dmabuf| proto.dmabufDestroy(dmabuf);
+        if (self.viewporter) |viewporter| c.wl_proxy_destroy(@ptrCast(viewporter));
+        if (self.layer_shell) |shell| c.wl_proxy_destroy(@ptrCast(shell));
+        if (self.compositor) |compositor| c.wl_proxy_destroy(@ptrCast(compositor));
+        // The cache holds live GBM buffers; free it before the device goes.
+        self.buffer_cache.deinit();
+        self.gbm_allocator.deinit();
+
+        c.wl_proxy_destroy(@ptrCast(self.registry));
+        c.wl_display_disconnect(self.display);
+        const allocator = self.allocator;
+        allocator.destroy(self);
+    }
+
+    /// Attaches one RGBA frame (tightly packed, `width`x`height`) to every
+    /// configured surface. Returns how many surfaces took it; surfaces
+    /// whose buffers the compositor still holds skip the frame.
+    pub fn presentFrame(self: *Engine, pixels: []const u8, width: u32, height: u32) u32 {
+        var source: FrameSource = .{ .pixels = pixels };
+        return self.presentWith(&source, width, height);
+    }
+
+    /// Like `presentFrame`, but the pixels are rendered on demand by
+    /// `fill` straight into the surface's mapped slot, so conversion
+    /// output lands in the memory the compositor imports instead of going
+    /// through a staging buffer first. `fill` must write exactly
+    /// `width * height * 4` tightly packed bytes; `scratch` (resized with
+    /// the engine's allocator) is only touched when a slot's stride is
+    /// padded, or when several surfaces need the frame.
+    pub fn presentFill(
+        self: *Engine,
+        width: u32,
+        height: u32,
+        user: ?*anyopaque,
+        fill: FillFn,
+        scratch: *std.ArrayList(u8),
+    ) u32 {
+        // With several surfaces to paint, rendering once into staging and
+        // copying beats re-running the conversion per output; the direct
+        // fill only wins when exactly one surface takes the frame.
+        var eligible: u32 = 0;
+        for (self.outputs.items) |output| {
+            if (output.configured and !output.closed) eligible += 1;
+        }
+        if (eligible > 1) {
+            const tight = @as(usize, width) * height * 4;
+            scratch.resize(self.allocator, tight) catch return 0;
+            fill(user, scratch.items);
+            return self.presentFrame(scratch.items, width, height);
+        }
+
+        var source: FrameSource = .{
+            .fill = .{ .user = user, .fill = fill, .scratch = scratch },
+        };
+        return self.presentWith(&source, width, height);
+    }
+
+    fn presentWith(self: *Engine, source: *FrameSource, width: u32, height: u32) u32 {
+        var presented: u32 = 0;
+        for (self.outputs.items) |output| {
+            if (!output.configured or output.closed) continue;
+            self.presentOn(output, source, width, height) catch |err| switch (err) {
+                // Every slot held: the swapchain already counted the stall.
+                EngineError.Stalled => continue,
+                else => {
+                    std.log.warn("present on {s} failed: {s}", .{
+                        output.name(),
+                        @errorName(err),
+                    });
+                    continue;
+                },
+            };
+            presented += 1;
+        }
+        if (self.commits.flush() > 0) _ = c.wl_display_flush(self.display);
+        return presented;
+    }
+
+    /// Attaches a decoder-provided dmabuf (all planes tightly packed in one
+    /// fd) to every configured surface without the pixels ever crossing the
+    /// CPU. Returns how many surfaces took it; 0 when the compositor does
+    /// not list the format, in which case the caller converts and falls
+    /// back to `presentFrame`. `on_release(user)` fires exactly once, when
+    /// every surface's compositor use of the import ended (or at teardown)
+    /// — the caller keeps the decoded frame alive exactly that long.
+    pub fn presentDmabuf(
+        self: *Engine,
+        fd: std.posix.fd_t,
+        format: dmabuf_import.Format,
+        width: u32,
+        height: u32,
+        user: ?*anyopaque,
+        on_release: ReleaseFn,
+    ) u32 {
+        if (!self.tableSupports(format.drmFourcc())) return 0;
+
+        // Bounds-check the claimed layout against the dmabuf's actual size;
+        // an undersized import would have the compositor read past the end.
+        const stat = std.posix.fstat(fd) catch return 0;
+        if (stat.size < dmabuf_import.contiguousSize(format, width, height)) {
+            std.log.warn("dmabuf frame smaller than its {s} layout; not importing", .{
+                @tagName(format),
+            });
+            return 0;
+        }
+
+        const planes = dmabuf_import.contiguousPlanes(format, width, height);
+        const key = import_cache.Key.fromFd(
+            fd,
+            planes[0].offset,
+            planes[0].stride,
+            drm_c.DRM_FORMAT_MOD_LINEAR,
+        ) catch return 0;
+        const import = self.lookupOrImport(key, fd, format, width, height, planes) orelse
+            return 0;
+        // The compositor has not released the previous frame behind this
+        // very dmabuf; attaching again would clobber that frame's
+        // hand-back. Let the caller take the copy path for this one.
+        if (import.on_release != null) return 0;
+
+        var presented: u32 = 0;
+        for (self.outputs.items) |output| {
+            if (!output.configured or output.closed) continue;
+            // A surface with a sync object requires points on every buffer
+            // commit, which an implicitly synced decoder import cannot
+            // provide; such outputs stay on the slot path.
+            if (output.sync_surface != null) continue;
+            self.attachDirect(output, import.wl_buffer);
+            presented += 1;
+        }
+        if (presented == 0) {
+            // Nothing committed, so no release event will arrive; put the
+            // import back to idle instead of leaving it busy forever.
+            self.imports.release(key);
+            return 0;
+        }
+        import.user = user;
+        import.on_release = on_release;
+        if (self.commits.flush() > 0) _ = c.wl_display_flush(self.display);
+        return presented;
+    }
+
+    /// Services the connection without blocking: dispatches queued events,
+    /// reads whatever the socket has, and tears down closed surfaces.
+    pub fn pump(self: *Engine) void {
+        _ = c.wl_display_dispatch_pending(self.display);
+        var fds = [_]std.posix.pollfd{
+            .{ .fd = c.wl_display_get_fd(self.display), .events = std.posix.POLL.IN, .revents = 0 },
+        };
+        const ready = std.posix.poll(&fds, 0) catch 0;
+        if (ready > 0 and fds[0].revents & std.posix.POLL.IN != 0) {
+            _ = c.wl_display_dispatch(self.display);
+        }
+
+        for (self.outputs.items) |output| {
+            if (output.closed and output.surface != null) self.destroySurface(output);
+            self.reapReleases(output);
+        }
+        _ = c.wl_display_flush(self.display);
+    }
+
+    /// Connection fd, for sleeping in poll() alongside the pipeline bus.
+    pub fn displayFd(self: *Engine) std.posix.fd_t {
+        return c.wl_display_get_fd(self.display);
+    }
+
+    /// Swapchain stalls summed over all surfaces, for the metrics snapshot.
+    pub fn stallCount(self: *const Engine) u64 {
+        var total: u64 = 0;
+        for (self.outputs.items) |output| total += output.chain.stalls;
+        return total;
+    }
+
+    fn allConfigured(self: *const Engine) bool {
+        for (self.outputs.items) |output| {
+            if (!output.closed and !output.configured) return false;
+        }
+        return true;
+    }
+
+    fn presentOn(
+        self: *Engine,
+        output: *Output,
+        source: *FrameSource,
+        width: u32,
+        height: u32,
+    ) EngineError!void {
+        try self.ensureSlots(output, width, height);
+        const index = output.chain.acquire() orelse return EngineError.Stalled;
+        errdefer output.chain.release(index);
+        const slot = &output.slots[index].?;
+
+        // For tiled layouts the map is a staging buffer; unmap performs
+        // the detiling blit, after which the contents are import-ready.
+        var stride: u32 = 0;
+        var map_data: ?*anyopaque = null;
+        const base = drm_c.gbm_bo_map(
+            slot.buffer.bo,
+            0,
+            0,
+            width,
+            height,
+            drm_c.GBM_BO_TRANSFER_WRITE,
+            &stride,
+            &map_data,
+        ) orelse return EngineError.MapFailed;
+        const dest: [*]u8 = @ptrCast(base);
+        const row_bytes = @as(usize, width) * 4;
+        switch (source.*) {
+            .pixels => |pixels| for (0..height) |row| {
+                @memcpy(
+                    dest[row * stride ..][0..row_bytes],
+                    pixels[row * row_bytes ..][0..row_bytes],
+                );
+            },
+            .fill => |*filler| if (!filler.filled and stride == row_bytes) {
+                // Tight rows: the conversion renders directly into the
+                // mapping, no staging copy.
+                filler.fill(filler.user, dest[0 .. row_bytes * height]);
+            } else {
+                // Padded stride: render once into staging, then place the
+                // rows at the slot's pitch.
+                if (!filler.filled) {
+                    filler.scratch.resize(self.allocator, row_bytes * height) catch |err| {
+                        drm_c.gbm_bo_unmap(slot.buffer.bo, map_data);
+                        return err;
+                    };
+                    filler.fill(filler.user, filler.scratch.items);
+                    filler.filled = true;
+                }
+                for (0..height) |row| {
+                    @memcpy(
+                        dest[row * stride ..][0..row_bytes],
+                        filler.scratch.items[row * row_bytes ..][0..row_bytes],
+                    );
+                }
+            },
+        }
+        drm_c.gbm_bo_unmap(slot.buffer.bo, map_data);
+
+        // The CPU write is complete, so the acquire point can be signalled
+        // right away; the compositor signals the release point when it
+        // stops reading, which `pump` polls to recycle the slot.
+        if (slot.sync != null) self.ensureSyncSurface(output);
+        if (output.sync_surface) |sync_surface| {
+            if (slot.sync) |*sync| {
+                const points = sync.timeline.nextFrame();
+                var handles = [_]u32{sync.handle};
+                var acquire = [_]u64{points.acquire};
+                if (drm_c.drmSyncobjTimelineSignal(
+                    self.gbm_allocator.fd,
+                    &handles,
+                    &acquire,
+                    1,
+                ) != 0) {
+                    std.log.warn("signalling the acquire point failed", .{});
+                }
+                proto.syncobjSurfaceSetAcquirePoint(sync_surface, sync.proxy, points.acquire);
+                proto.syncobjSurfaceSetReleasePoint(sync_surface, sync.proxy, points.release);
+                sync.pending_release = points.release;
+            }
+        }
+
+        proto.surfaceAttach(output.surface.?, slot.wl_buffer, 0, 0);
+        proto.surfaceDamage(output.surface.?, 0, 0, std.math.maxInt(i32), std.math.maxInt(i32));
+        proto.viewportSetDestination(
+            output.viewport.?,
+            @intCast(output.width),
+            @intCast(output.height),
+        );
+        self.commits.stage(output) catch proto.surfaceCommit(output.surface.?);
+        output.chain.submit(index);
+        output.frames_presented += 1;
+    }
+
+    /// Returns the cached import for `key`, marked busy, or runs the
+    /// params round-trip and registers the result. Null when the import
+    /// fails; the compositor's protocol error, if any, surfaces on a later
+    /// dispatch.
+    fn lookupOrImport(
+        self: *Engine,
+        key: import_cache.Key,
+        fd: std.posix.fd_t,
+        format: dmabuf_import.Format,
+        width: u32,
+        height: u32,
+        planes: [2]dmabuf_import.PlaneDesc,
+    ) ?*DirectImport {
+        if (self.imports.get(key)) |cached| return cached.*;
+
+        const params = proto.dmabufCreateParams(self.dmabuf.?) orelse return null;
+        for (planes[0..format.planeCount()], 0..) |plane, i| {
+            proto.paramsAdd(
+                params,
+                fd,
+                @intCast(i),
+                plane.offset,
+                plane.stride,
+                drm_c.DRM_FORMAT_MOD_LINEAR,
+            );
+        }
+        const wl_buffer = proto.paramsCreateImmed(
+            params,
+            @intCast(width),
+            @intCast(height),
+            format.drmFourcc(),
+            0,
+        ) orelse {
+            proto.paramsDestroy(params);
+            return null;
+        };
+        proto.paramsDestroy(params);
+
+        const import = self.allocator.create(DirectImport) catch {
+            proto.bufferDestroy(wl_buffer);
+            return null;
+        };
+        import.* = .{ .engine = self, .wl_buffer = wl_buffer, .key = key };
+        self.imports.insert(key, import) catch {
+            import.deinit();
+            return null;
+        };
+        _ = proto.bufferAddListener(wl_buffer, &direct_listener, import);
+        return import;
+    }
+
+    fn attachDirect(self: *Engine, output: *Output, wl_buffer: *proto.wl_buffer) void {
+        proto.surfaceAttach(output.surface.?, wl_buffer, 0, 0);
+        proto.surfaceDamage(output.surface.?, 0, 0, std.math.maxInt(i32), std.math.maxInt(i32));
+        proto.viewportSetDestination(
+            output.viewport.?,
+            @intCast(output.width),
+            @intCast(output.height),
+        );
+        self.commits.stage(output) catch proto.surfaceCommit(output.surface.?);
+        output.frames_presented += 1;
+    }
+
+    /// Hands synced slots back to the swapchain once their release point
+    /// signalled. Explicit-sync compositors release through the timeline,
+    /// not through wl_buffer.release.
+    fn reapReleases(self: *Engine, output: *Output) void {
+        for (&output.slots, 0..) |*maybe_slot, index| {
+            if (maybe_slot.*) |*slot| {
+                if (slot.sync == null) continue;
+                const sync = &slot.sync.?;
+                const gate = sync.pending_release orelse continue;
+                var handles = [_]u32{sync.handle};
+                var points = [_]u64{gate};
+                if (drm_c.drmSyncobjTimelineWait(
+                    self.gbm_allocator.fd,
+                    &handles,
+                    &points,
+                    1,
+                    0,
+                    drm_c.DRM_SYNCOBJ_WAIT_FLAGS_WAIT_FOR_SUBMIT,
+                    null,
+                ) != 0) continue;
+                sync.pending_release = null;
+                output.chain.release(@intCast(index));
+            }
+        }
+    }
+
+    /// Sets up explicit sync for `output`'s surface on the first slot
+    /// present. Created lazily because once the sync object exists every
+    /// buffer commit must carry points, which direct decoder imports
+    /// (implicitly synced) cannot provide.
+    fn ensureSyncSurface(self: *Engine, output: *Output) void {
+        if (output.sync_surface != null) return;
+        const manager = self.syncobj_manager orelse return;
+        const surface = output.surface orelse return;
+        output.sync_surface = proto.syncobjGetSurface(manager, surface);
+    }
+
+    /// Creates a per-slot DRM timeline and shares it with the compositor.
+    /// Failure leaves the slot on implicit sync.
+    fn createSlotSync(self: *Engine, manager: *proto.wp_linux_drm_syncobj_manager_v1) ?SlotSync {
+        var handle: u32 = 0;
+        if (drm_c.drmSyncobjCreate(self.gbm_allocator.fd, 0, &handle) != 0) {
+            std.log.warn("creating a syncobj timeline failed; staying on implicit sync", .{});
+            return null;
+        }
+        var obj_fd: c_int = -1;
+        if (drm_c.drmSyncobjHandleToFD(self.gbm_allocator.fd, handle, &obj_fd) != 0) {
+            _ = drm_c.drmSyncobjDestroy(self.gbm_allocator.fd, handle);
+            std.log.warn("exporting the syncobj timeline failed; staying on implicit sync", .{});
+            return null;
+        }
+        // libwayland dups the fd while marshalling import_timeline.
+        defer std.posix.close(obj_fd);
+        const proxy = proto.syncobjImportTimeline(manager, obj_fd) orelse {
+            _ = drm_c.drmSyncobjDestroy(self.gbm_allocator.fd, handle);
+            return null;
+        };
+        return .{ .handle = handle, .proxy = proxy };
+    }
+
+    fn destroySlotSync(self: *Engine, slot: *Slot) void {
+        const sync = slot.sync orelse return;
+        proto.syncobjTimelineDestroy(sync.proxy);
+        _ = drm_c.drmSyncobjDestroy(self.gbm_allocator.fd, sync.handle);
+        slot.sync = null;
+    }
+
+    /// wl_buffer.release for a direct import: hands the frame behind the
+    /// latest commit back and marks the import idle, so the cache can
+    /// serve it again or evict it. The wl_buffer itself stays alive.
+    fn handleDirectRelease(self: *Engine, import: *DirectImport) void {
+        if (import.on_release) |hand_back| hand_back(import.user);
+        import.user = null;
+        import.on_release = null;
+        self.imports.release(import.key);
+    }
+
+    /// True when the feedback table lists `format` with a modifier a
+    /// tightly packed buffer satisfies (linear, or implicit which every
+    /// driver treats as linear for dumb imports).
+    fn tableSupports(self: *const Engine, format: u32) bool {
+        const bytes = self.table orelse return false;
+        const table = feedback_mod.parseTable(bytes) orelse return false;
+        for (table) |entry| {
+            if (entry.format != format) continue;
+            if (entry.modifier == drm_c.DRM_FORMAT_MOD_LINEAR or
+                entry.modifier == drm_c.DRM_FORMAT_MOD_INVALID)
+            {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// Makes sure `output` has `depth` imported buffers at the frame size,
+    /// reallocating when the decode size changed.
+    fn ensureSlots(self: *Engine, output: *Output, width: u32, height: u32) EngineError!void {
+        if (output.slot_width == width and output.slot_height == height and
+            output.slots[0] != null)
+        {
+            return;
+        }
+
+        self.destroySlots(output);
+        const choice = self.negotiated.?;
+        const key: buffer_cache_mod.Key = .{
+            .width = width,
+            .height = height,
+            .format = choice.format,
+        };
+        for (0..self.depth) |i| {
+            var buffer = self.buffer_cache.take(key) orelse try self.gbm_allocator.allocate(
+                width,
+                height,
+                choice.format,
+                &.{choice.modifier},
+            );
+            errdefer buffer.deinit();
+            output.slots[i] = try self.importSlot(output, @intCast(i), buffer);
+        }
+        output.slot_width = width;
+        output.slot_height = height;
+
+        // Once the surface carries a sync object, every commit must name
+        // points, so explicit sync is all-or-nothing across the slots.
+        var synced: u32 = 0;
+        for (output.slots[0..self.depth]) |maybe_slot| {
+            if (maybe_slot.?.sync != null) synced += 1;
+        }
+        if (synced != 0 and synced != self.depth) {
+            for (&output.slots) |*maybe_slot| {
+                if (maybe_slot.*) |*slot| self.destroySlotSync(slot);
+            }
+        }
+
+        // Fresh buffers, fresh rotation; anything the compositor still held
+        // from the old size was destroyed above and repaints on next attach.
+        output.chain = swapchain.Swapchain.init(self.depth);
+    }
+
+    /// Imports one allocated buffer as a wl_buffer via buffer_params.
+    fn importSlot(self: *Engine, output: *Output, index: u32, buffer: gbm.Buffer) EngineError!Slot {
+        const params = proto.dmabufCreateParams(self.dmabuf.?) orelse
+            return EngineError.ImportFailed;
+        for (buffer.planes[0..buffer.plane_count], 0..) |plane, i| {
+            proto.paramsAdd(params, plane.fd, @intCast(i), plane.offset, plane.stride, buffer.modifier);
+        }
+        const wl_buffer = proto.paramsCreateImmed(
+            params,
+            @intCast(buffer.width),
+            @intCast(buffer.height),
+            buffer.format,
+            0,
+        ) orelse {
+            proto.paramsDestroy(params);
+            return EngineError.ImportFailed;
+        };
+        proto.paramsDestroy(params);
+
+        const ctx = try self.allocator.create(ReleaseCtx);
+        ctx.* = .{ .output = output, .index = index };
+        _ = proto.bufferAddListener(wl_buffer, &buffer_listener, ctx);
+
+        const sync: ?SlotSync = if (self.syncobj_manager) |manager|
+            self.createSlotSync(manager)
+        else
+            null;
+        return .{ .buffer = buffer, .wl_buffer = wl_buffer, .ctx = ctx, .sync = sync };
+    }
+
+    fn destroySlots(self: *Engine, output: *Output) void {
+        for (&output.slots) |*maybe_slot| {
+            if (maybe_slot.*) |*held| self.destroySlotSync(held);
+            var slot = maybe_slot.* orelse continue;
+            proto.bufferDestroy(slot.wl_buffer);
+            self.allocator.destroy(slot.ctx);
+            // The allocation outlives the import: revisiting this size
+            // takes it back from the cache instead of reallocating.
+            const key: buffer_cache_mod.Key = .{
+                .width = slot.buffer.width,
+                .height = slot.buffer.height,
+                .format = slot.buffer.format,
+            };
+            self.buffer_cache.put(key, slot.buffer) catch slot.buffer.deinit();
+            maybe_slot.* = null;
+        }
+        output.slot_width = 0;
+        output.slot_height = 0;
+    }
+
+    fn createSurface(self: *Engine, output: *Output) EngineError!void {
+        const surface = proto.compositorCreateSurface(self.compositor.?) orelse
+            return EngineError.ConnectFailed;
+        // A wallpaper takes no input; an empty region lets the compositor
+        // route clicks to whatever sits above.
+        if (proto.compositorCreateRegion(self.compositor.?)) |region| {
+            proto.surfaceSetInputRegion(surface, region);
+            proto.regionDestroy(region);
+        }
+        const viewport = proto.viewporterGetViewport(self.viewporter.?, surface) orelse {
+            proto.surfaceDestroy(surface);
+            return EngineError.ConnectFailed;
+        };
+        const layer_surface = proto.layerShellGetLayerSurface(
+            self.layer_shell.?,
+            surface,
+            output.proxy,
+            proto.layer_background,
+            "waystream",
+        ) orelse {
+            proto.viewportDestroy(viewport);
+            proto.surfaceDestroy(surface);
+            return EngineError.ConnectFailed;
+        };
+        _ = proto.layerSurfaceAddListener(layer_surface, &layer_surface_listener, output);
+        // Zero size with all edges anchored: the compositor assigns the
+        // full output size in the configure.
+        proto.layerSurfaceSetSize(layer_surface, 0, 0);
+        proto.layerSurfaceSetAnchor(layer_surface, proto.anchor_all);
+        proto.layerSurfaceSetExclusiveZone(layer_surface, -1);
+
+        output.surface = surface;
+        output.viewport = viewport;
+        output.layer_surface = layer_surface;
+
+        // The buffer-less commit asks for the first configure.
+        proto.surfaceCommit(surface);
+    }
+
+    fn destroySurface(self: *Engine, output: *Output) void {
+        self.destroySlots(output);
+        if (output.sync_surface) |sync_surface| proto.syncobjSurfaceDestroy(sync_surface);
+        output.sync_surface = null;
+        if (output.layer_surface) |layer_surface| proto.layerSurfaceDestroy(layer_surface);
+        if (output.viewport) |viewport| proto.viewportDestroy(viewport);
+        if (output.surface) |surface| proto.surfaceDestroy(surface);
+        output.layer_surface = null;
+        output.viewport = null;
+        output.surface = null;
+        output.configured = false;
+    }
+
+    fn destroyOutput(self: *Engine, output: *Output) void {
+        self.destroySurface(output);
+        proto.outputDestroy(output.proxy);
+        self.allocator.destroy(output);
+    }
+
+    fn addOutput(self: *Engine, registry_name: u32, version: u32) !void {
+        const proxy: *proto.wl_output = @ptrCast(c.registryBind(
+            self.registry,
+            registry_name,
+            &proto.wl_output_interface,
+            @min(version, 4),
+        ) orelse return EngineError.ConnectFailed);
+        const output = try self.allocator.create(Output);
+        output.* = .{
+            .engine = self,
+            .proxy = proxy,
+            .registry_name = registry_name,
+            .chain = swapchain.Swapchain.init(self.depth),
+        };
+        _ = proto.outputAddListener(proxy, &output_listener, output);
+        try self.outputs.append(self.allocator, output);
+        if (self.ready) try self.createSurface(output);
+    }
+
+    fn removeOutput(self: *Engine, registry_name: u32) void {
+        for (self.outputs.items, 0..) |output, i| {
+            if (output.registry_name != registry_name) continue;
+            std.log.info("output {s} removed", .{output.name()});
+            self.destroyOutput(output);
+            _ = self.outputs.swapRemove(i);
+            return;
+        }
+    }
+
+    // ---------------------------------------------------------- listeners
+
+    fn onGlobal(
+        data: ?*anyopaque,
+        registry: *c.wl_registry,
+        registry_name: u32,
+        interface_z: [*:0]const u8,
+        version: u32,
+    ) callconv(.c) void {
+        _ = registry;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        const interface = std.mem.span(interface_z);
+        self.tracker.handleGlobal(registry_name, interface, version);
+
+        if (std.mem.eql(u8, interface, "wl_compositor")) {
+            self.compositor = @ptrCast(c.registryBind(
+                self.registry,
+                registry_name,
+                &proto.wl_compositor_interface,
+                @min(version, 4),
+            ));
+        } else if (std.mem.eql(u8, interface, "wl_output")) {
+            self.addOutput(registry_name, version) catch |err|
+                std.log.warn("binding output failed: {s}", .{@errorName(err)});
+        } else if (std.mem.eql(u8, interface, "zwlr_layer_shell_v1")) {
+            self.layer_shell = @ptrCast(c.registryBind(
+                self.registry,
+                registry_name,
+                &proto.zwlr_layer_shell_v1_interface,
+                @min(version, 4),
+            ));
+        } else if (std.mem.eql(u8, interface, "wp_viewporter")) {
+            self.viewporter = @ptrCast(c.registryBind(
+                self.registry,
+                registry_name,
+                &proto.wp_viewporter_interface,
+                1,
+            ));
+        } else if (std.mem.eql(u8, interface, "zwp_linux_dmabuf_v1") and version >= 4) {
+            self.dmabuf = @ptrCast(c.registryBind(
+                self.registry,
+                registry_name,
+                &proto.zwp_linux_dmabuf_v1_interface,
+                4,
+            ));
+        } else if (std.mem.eql(u8, interface, "wp_linux_drm_syncobj_manager_v1")) {
+            self.syncobj_manager = @ptrCast(c.registryBind(
+                self.registry,
+                registry_name,
+                &proto.wp_linux_drm_syncobj_manager_v1_interface,
+                1,
+            ));
+        }
+    }
+
+    fn onGlobalRemove(data: ?*anyopaque, registry: *c.wl_registry, registry_name: u32) callconv(.c) void {
+        _ = registry;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        self.tracker.handleGlobalRemove(registry_name);
+        self.removeOutput(registry_name);
+    }
+
+    const output_listener: proto.wl_output_listener = .{
+        .geometry = onOutputGeometry,
+        .mode = onOutputMode,
+        .done = onOutputDone,
+        .scale = onOutputScale,
+        .name = onOutputName,
+        .description = onOutputDescription,
+    };
+
+    fn onOutputGeometry(
+        data: ?*anyopaque,
+        output: *proto.wl_output,
+        x: i32,
+        y: i32,
+        physical_width: i32,
+        physical_height: i32,
+        subpixel: i32,
+        make: [*:0]const u8,
+        model: [*:0]const u8,
+        transform: i32,
+    ) callconv(.c) void {
+        _ = data;
+        _ = output;
+        _ = x;
+        _ = y;
+        _ = physical_width;
+        _ = physical_height;
+        _ = subpixel;
+        _ = make;
+        _ = model;
+        _ = transform;
+    }
+
+    fn onOutputMode(
+        data: ?*anyopaque,
+        output: *proto.wl_output,
+        flags: u32,
+        width: i32,
+        height: i32,
+        refresh: i32,
+    ) callconv(.c) void {
+        _ = data;
+        _ = output;
+        _ = flags;
+        _ = width;
+        _ = height;
+        _ = refresh;
+    }
+
+    fn onOutputDone(data: ?*anyopaque, output: *proto.wl_output) callconv(.c) void {
+        _ = data;
+        _ = output;
+    }
+
+    fn onOutputScale(data: ?*anyopaque, output: *proto.wl_output, factor: i32) callconv(.c) void {
+        _ = data;
+        _ = output;
+        _ = factor;
+    }
+
+    fn onOutputName(
+        data: ?*anyopaque,
+        output_proxy: *proto.wl_output,
+        name_z: [*:0]const u8,
+    ) callconv(.c) void {
+        _ = output_proxy;
+        const output: *Output = @ptrCast(@alignCast(data.?));
+        const name = std.mem.span(name_z);
+        output.name_len = @min(name.len, output.name_storage.len);
+        @memcpy(output.name_storage[0..output.name_len], name[0..output.name_len]);
+    }
+
+    fn onOutputDescription(
+        data: ?*anyopaque,
+        output: *proto.wl_output,
+        description: [*:0]const u8,
+    ) callconv(.c) void {
+        _ = data;
+        _ = output;
+        _ = description;
+    }
+
+    const layer_surface_listener: proto.zwlr_layer_surface_v1_listener = .{
+        .configure = onLayerConfigure,
+        .closed = onLayerClosed,
+    };
+
+    fn onLayerConfigure(
+        data: ?*anyopaque,
+        layer_surface: *proto.zwlr_layer_surface_v1,
+        serial: u32,
+        width: u32,
+        height: u32,
+    ) callconv(.c) void {
+        const output: *Output = @ptrCast(@alignCast(data.?));
+        proto.layerSurfaceAckConfigure(layer_surface, serial);
+        // All four edges are anchored, so the compositor always assigns a
+        // concrete size; zero would mean a broken handshake.
+        if (width == 0 or height == 0) {
+            std.log.warn("layer surface on {s} configured with no size", .{output.name()});
+            return;
+        }
+        output.width = width;
+        output.height = height;
+        output.configured = true;
+    }
+
+    fn onLayerClosed(
+        data: ?*anyopaque,
+        layer_surface: *proto.zwlr_layer_surface_v1,
+    ) callconv(.c) void {
+        _ = layer_surface;
+        const output: *Output = @ptrCast(@alignCast(data.?));
+        std.log.warn("compositor closed the surface on {s}", .{output.name()});
+        output.closed = true;
+        output.configured = false;
+    }
+
+    const buffer_listener: proto.wl_buffer_listener = .{
+        .release = onBufferRelease,
+    };
+
+    fn onBufferRelease(data: ?*anyopaque, buffer: *proto.wl_buffer) callconv(.c) void {
+        _ = buffer;
+        const ctx: *ReleaseCtx = @ptrCast(@alignCast(data.?));
+        // Synced slots are released through their timeline point; a stray
+        // wl_buffer.release on top of it must not free the slot twice.
+        if (ctx.output.slots[ctx.index]) |slot| {
+            if (slot.sync) |sync| {
+                if (sync.pending_release != null) return;
+            }
+        }
+        ctx.output.chain.release(ctx.index);
+    }
+
+    const direct_listener: proto.wl_buffer_listener = .{
+        .release = onDirectRelease,
+    };
+
+    fn onDirectRelease(data: ?*anyopaque, buffer: *proto.wl_buffer) callconv(.c) void {
+        _ = buffer;
+        const import: *DirectImport = @ptrCast(@alignCast(data.?));
+        import.engine.handleDirectRelease(import);
+    }
+
+    const feedback_listener: proto.zwp_linux_dmabuf_feedback_v1_listener = .{
+        .done = onFeedbackDone,
+        .format_table = onFormatTable,
+        .main_device = onMainDevice,
+        .tranche_done = onTrancheDone,
+        .tranche_target_device = onTrancheTargetDevice,
+        .tranche_formats = onTrancheFormats,
+        .tranche_flags = onTrancheFlags,
+    };
+
+    fn onFormatTable(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+        fd: std.posix.fd_t,
+        size: u32,
+    ) callconv(.c) void {
+        _ = feedback;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        defer std.posix.close(fd);
+        if (self.table) |table| {
+            std.posix.munmap(table);
+            self.table = null;
+        }
+        self.table = std.posix.mmap(
+            null,
+            size,
+            std.posix.PROT.READ,
+            .{ .TYPE = .PRIVATE },
+            fd,
+            0,
+        ) catch |err| {
+            std.log.warn("mapping the dmabuf format table failed: {s}", .{@errorName(err)});
+            return;
+        };
+    }
+
+    fn onMainDevice(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+        device: *c.wl_array,
+    ) callconv(.c) void {
+        // The allocator opens whatever render node exists; cross-GPU setups
+        // where that differs from the compositor's device still work, just
+        // without direct scanout.
+        _ = data;
+        _ = feedback;
+        _ = device;
+    }
+
+    fn onTrancheTargetDevice(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+        device: *c.wl_array,
+    ) callconv(.c) void {
+        _ = data;
+        _ = feedback;
+        _ = device;
+    }
+
+    fn onTrancheFormats(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+        indices: *c.wl_array,
+    ) callconv(.c) void {
+        _ = feedback;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        const raw = indices.data orelse return;
+        const entries: [*]const u16 = @ptrCast(@alignCast(raw));
+        self.feedback.handleTrancheFormats(entries[0 .. indices.size / 2]) catch |err|
+            std.log.warn("recording feedback tranche failed: {s}", .{@errorName(err)});
+    }
+
+    fn onTrancheFlags(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+        flags: u32,
+    ) callconv(.c) void {
+        _ = feedback;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        self.feedback.handleTrancheFlags(flags);
+    }
+
+    fn onTrancheDone(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+    ) callconv(.c) void {
+        _ = feedback;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        self.feedback.handleTrancheDone() catch |err|
+            std.log.warn("recording feedback tranche failed: {s}", .{@errorName(err)});
+    }
+
+    fn onFeedbackDone(
+        data: ?*anyopaque,
+        feedback: *proto.zwp_linux_dmabuf_feedback_v1,
+    ) callconv(.c) void {
+        _ = feedback;
+        const self: *Engine = @ptrCast(@alignCast(data.?));
+        defer {
+            // The compositor resends full feedback after changes; start the
+            // accumulator over for the next cycle.
+            self.feedback.clear();
+            self.feedback_done = true;
+        }
+
+        const table_bytes = self.table orelse return;
+        const table = feedback_mod.parseTable(table_bytes) orelse {
+            std.log.warn("dmabuf format table is malformed", .{});
+            return;
+        };
+        if (self.feedback.select(table, &wanted_formats, null)) |choice| {
+            self.negotiated = choice;
+            std.log.info("dmabuf: format {x} modifier {x}{s}", .{
+                choice.format,
+                choice.modifier,
+                if (choice.scanout) " (scanout)" else "",
+            });
+        } else if (self.negotiated == null) {
+            // Nothing we can render into; fall back to linear RGBA, which
+            // every compositor in practice imports even when the feedback
+            // only advertises GPU-internal layouts.
+            self.negotiated = .{
+                .format = drm_c.DRM_FORMAT_ABGR8888,
+                .modifier = drm_c.DRM_FORMAT_MOD_LINEAR,
+                .scanout = false,
+            };
+            std.log.warn("feedback offers no RGBA layout; assuming linear import works", .{});
+        }
+    }
+};
@@ -0,0 +1,749 @@
+//! Extension-protocol glue: interface tables and request wrappers.
+//!
+//! wayland-scanner generates C for these; rather than adding a codegen
+//! step to the build, the handful of interfaces the presentation path
+//! speaks is transcribed here. Tables must match the installed protocol
+//! XML exactly — the signature strings drive libwayland's (un)marshalling,
+//! and the `types` arrays are consulted for object and new_id arguments.
+//! Requests we never issue (e.g. `get_popup`) keep their slot so opcodes
+//! line up, but carry no type pointers.
+
+const std = @import("std");
+const c = @import("c.zig");
+
+pub const wl_compositor = opaque {};
+pub const wl_surface = opaque {};
+pub const wl_region = opaque {};
+pub const wl_callback = opaque {};
+pub const wl_output = opaque {};
+pub const wl_buffer = opaque {};
+pub const zwlr_layer_shell_v1 = opaque {};
+pub const zwlr_layer_surface_v1 = opaque {};
+pub const wp_viewporter = opaque {};
+pub const wp_viewport = opaque {};
+pub const zwp_linux_dmabuf_v1 = opaque {};
+pub const zwp_linux_buffer_params_v1 = opaque {};
+pub const zwp_linux_dmabuf_feedback_v1 = opaque {};
+pub const wp_linux_drm_syncobj_manager_v1 = opaque {};
+pub const wp_linux_drm_syncobj_timeline_v1 = opaque {};
+pub const wp_linux_drm_syncobj_surface_v1 = opaque {};
+
+const no_types: [8]?*const c.wl_interface = @splat(null);
+
+fn message(
+    name: [*:0]const u8,
+    signature: [*:0]const u8,
+    types: [*]const ?*const c.wl_interface,
+) c.wl_message {
+    return .{ .name = name, .signature = signature, .types = types };
+}
+
+// ---------------------------------------------------------------- core
+
+const compositor_requests = [_]c.wl_message{
+    message("create_surface", "n", &[_]?*const c.wl_interface{&wl_surface_interface}),
+    message("create_region", "n", &[_]?*const c.wl_interface{&wl_region_interface}),
+};
+
+pub const wl_compositor_interface: c.wl_interface = .{
+    .name = "wl_compositor",
+    .version = 4,
+    .method_count = compositor_requests.len,
+    .methods = &compositor_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+const surface_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("attach", "?oii", &[_]?*const c.wl_interface{ &wl_buffer_interface, null, null }),
+    message("damage", "iiii", &no_types),
+    message("frame", "n", &[_]?*const c.wl_interface{&wl_callback_interface}),
+    message("set_opaque_region", "?o", &[_]?*const c.wl_interface{&wl_region_interface}),
+    message("set_input_region", "?o", &[_]?*const c.wl_interface{&wl_region_interface}),
+    message("commit", "", &no_types),
+    message("set_buffer_transform", "2i", &no_types),
+    message("set_buffer_scale", "3i", &no_types),
+    message("damage_buffer", "4iiii", &no_types),
+};
+
+const surface_events = [_]c.wl_message{
+    message("enter", "o", &[_]?*const c.wl_interface{&wl_output_interface}),
+    message("leave", "o", &[_]?*const c.wl_interface{&wl_output_interface}),
+};
+
+pub const wl_surface_interface: c.wl_interface = .{
+    .name = "wl_surface",
+    .version = 4,
+    .method_count = surface_requests.len,
+    .methods = &surface_requests,
+    .event_count = surface_events.len,
+    .events = &surface_events,
+};
+
+const region_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("add", "iiii", &no_types),
+    message("subtract", "iiii", &no_types),
+};
+
+pub const wl_region_interface: c.wl_interface = .{
+    .name = "wl_region",
+    .version = 1,
+    .method_count = region_requests.len,
+    .methods = &region_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+const callback_events = [_]c.wl_message{
+    message("done", "u", &no_types),
+};
+
+pub const wl_callback_interface: c.wl_interface = .{
+    .name = "wl_callback",
+    .version = 1,
+    .method_count = 0,
+    .methods = null,
+    .event_count = callback_events.len,
+    .events = &callback_events,
+};
+
+const output_requests = [_]c.wl_message{
+    message("release", "3", &no_types),
+};
+
+const output_events = [_]c.wl_message{
+    message("geometry", "iiiiissi", &no_types),
+    message("mode", "uiii", &no_types),
+    message("done", "2", &no_types),
+    message("scale", "2i", &no_types),
+    message("name", "4s", &no_types),
+    message("description", "4s", &no_types),
+};
+
+pub const wl_output_interface: c.wl_interface = .{
+    .name = "wl_output",
+    .version = 4,
+    .method_count = output_requests.len,
+    .methods = &output_requests,
+    .event_count = output_events.len,
+    .events = &output_events,
+};
+
+const buffer_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+};
+
+const buffer_events = [_]c.wl_message{
+    message("release", "", &no_types),
+};
+
+pub const wl_buffer_interface: c.wl_interface = .{
+    .name = "wl_buffer",
+    .version = 1,
+    .method_count = buffer_requests.len,
+    .methods = &buffer_requests,
+    .event_count = buffer_events.len,
+    .events = &buffer_events,
+};
+
+// ---------------------------------------------------- wlr-layer-shell
+
+const layer_shell_requests = [_]c.wl_message{
+    message("get_layer_surface", "no?ous", &[_]?*const c.wl_interface{
+        &zwlr_layer_surface_v1_interface,
+        &wl_surface_interface,
+        &wl_output_interface,
+        null,
+        null,
+    }),
+    message("destroy", "3", &no_types),
+};
+
+pub const zwlr_layer_shell_v1_interface: c.wl_interface = .{
+    .name = "zwlr_layer_shell_v1",
+    .version = 4,
+    .method_count = layer_shell_requests.len,
+    .methods = &layer_shell_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+const layer_surface_requests = [_]c.wl_message{
+    message("set_size", "uu", &no_types),
+    message("set_anchor", "u", &no_types),
+    message("set_exclusive_zone", "i", &no_types),
+    message("set_margin", "iiii", &no_types),
+    message("set_keyboard_interactivity", "u", &no_types),
+    // Never issued; present only so the following opcodes line up.
+    message("get_popup", "o", &no_types),
+    message("ack_configure", "u", &no_types),
+    message("destroy", "", &no_types),
+    message("set_layer", "2u", &no_types),
+};
+
+const layer_surface_events = [_]c.wl_message{
+    message("configure", "uuu", &no_types),
+    message("closed", "", &no_types),
+};
+
+pub const zwlr_layer_surface_v1_interface: c.wl_interface = .{
+    .name = "zwlr_layer_surface_v1",
+    .version = 4,
+    .method_count = layer_surface_requests.len,
+    .methods = &layer_surface_requests,
+    .event_count = layer_surface_events.len,
+    .events = &layer_surface_events,
+};
+
+// --------------------------------------------------------- viewporter
+
+const viewporter_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("get_viewport", "no", &[_]?*const c.wl_interface{
+        &wp_viewport_interface,
+        &wl_surface_interface,
+    }),
+};
+
+pub const wp_viewporter_interface: c.wl_interface = .{
+    .name = "wp_viewporter",
+    .version = 1,
+    .method_count = viewporter_requests.len,
+    .methods = &viewporter_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+const viewport_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("set_source", "ffff", &no_types),
+    message("set_destination", "ii", &no_types),
+};
+
+pub const wp_viewport_interface: c.wl_interface = .{
+    .name = "wp_viewport",
+    .version = 1,
+    .method_count = viewport_requests.len,
+    .methods = &viewport_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+// ------------------------------------------------------- linux-dmabuf
+
+const dmabuf_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("create_params", "n", &[_]?*const c.wl_interface{
+        &zwp_linux_buffer_params_v1_interface,
+    }),
+    message("get_default_feedback", "4n", &[_]?*const c.wl_interface{
+        &zwp_linux_dmabuf_feedback_v1_interface,
+    }),
+    message("get_surface_feedback", "4no", &[_]?*const c.wl_interface{
+        &zwp_linux_dmabuf_feedback_v1_interface,
+        &wl_surface_interface,
+    }),
+};
+
+const dmabuf_events = [_]c.wl_message{
+    message("format", "u", &no_types),
+    message("modifier", "3uuu", &no_types),
+};
+
+pub const zwp_linux_dmabuf_v1_interface: c.wl_interface = .{
+    .name = "zwp_linux_dmabuf_v1",
+    .version = 4,
+    .method_count = dmabuf_requests.len,
+    .methods = &dmabuf_requests,
+    .event_count = dmabuf_events.len,
+    .events = &dmabuf_events,
+};
+
+const buffer_params_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("add", "huuuuu", &no_types),
+    message("create", "iiuu", &no_types),
+    message("create_immed", "2niiuu", &[_]?*const c.wl_interface{
+        &wl_buffer_interface,
+        null,
+        null,
+        null,
+        null,
+    }),
+};
+
+const buffer_params_events = [_]c.wl_message{
+    message("created", "n", &[_]?*const c.wl_interface{&wl_buffer_interface}),
+    message("failed", "", &no_types),
+};
+
+pub const zwp_linux_buffer_params_v1_interface: c.wl_interface = .{
+    .name = "zwp_linux_buffer_params_v1",
+    .version = 4,
+    .method_count = buffer_params_requests.len,
+    .methods = &buffer_params_requests,
+    .event_count = buffer_params_events.len,
+    .events = &buffer_params_events,
+};
+
+const feedback_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+};
+
+const feedback_events = [_]c.wl_message{
+    message("done", "", &no_types),
+    message("format_table", "hu", &no_types),
+    message("main_device", "a", &no_types),
+    message("tranche_done", "", &no_types),
+    message("tranche_target_device", "a", &no_types),
+    message("tranche_formats", "a", &no_types),
+    message("tranche_flags", "u", &no_types),
+};
+
+pub const zwp_linux_dmabuf_feedback_v1_interface: c.wl_interface = .{
+    .name = "zwp_linux_dmabuf_feedback_v1",
+    .version = 4,
+    .method_count = feedback_requests.len,
+    .methods = &feedback_requests,
+    .event_count = feedback_events.len,
+    .events = &feedback_events,
+};
+
+// -------------------------------------------------- linux-drm-syncobj
+
+const syncobj_manager_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("get_surface", "no", &[_]?*const c.wl_interface{
+        &wp_linux_drm_syncobj_surface_v1_interface,
+        &wl_surface_interface,
+    }),
+    message("import_timeline", "nh", &[_]?*const c.wl_interface{
+        &wp_linux_drm_syncobj_timeline_v1_interface,
+        null,
+    }),
+};
+
+pub const wp_linux_drm_syncobj_manager_v1_interface: c.wl_interface = .{
+    .name = "wp_linux_drm_syncobj_manager_v1",
+    .version = 1,
+    .method_count = syncobj_manager_requests.len,
+    .methods = &syncobj_manager_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+const syncobj_timeline_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+};
+
+pub const wp_linux_drm_syncobj_timeline_v1_interface: c.wl_interface = .{
+    .name = "wp_linux_drm_syncobj_timeline_v1",
+    .version = 1,
+    .method_count = syncobj_timeline_requests.len,
+    .methods = &syncobj_timeline_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+const syncobj_surface_requests = [_]c.wl_message{
+    message("destroy", "", &no_types),
+    message("set_acquire_point", "ouu", &[_]?*const c.wl_interface{
+        &wp_linux_drm_syncobj_timeline_v1_interface,
+        null,
+        null,
+    }),
+    message("set_release_point", "ouu", &[_]?*const c.wl_interface{
+        &wp_linux_drm_syncobj_timeline_v1_interface,
+        null,
+        null,
+    }),
+};
+
+pub const wp_linux_drm_syncobj_surface_v1_interface: c.wl_interface = .{
+    .name = "wp_linux_drm_syncobj_surface_v1",
+    .version = 1,
+    .method_count = syncobj_surface_requests.len,
+    .methods = &syncobj_surface_requests,
+    .event_count = 0,
+    .events = null,
+};
+
+// ------------------------------------------------------------ wrappers
+
+/// zwlr_layer_shell_v1.layer
+pub const layer_background: u32 = 0;
+
+/// zwlr_layer_surface_v1.anchor, all four edges.
+pub const anchor_all: u32 = 1 | 2 | 4 | 8;
+
+pub fn compositorCreateSurface(compositor: *wl_compositor) ?*wl_surface {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(compositor),
+        0,
+        &wl_surface_interface,
+        @as(?*anyopaque, null),
+    ));
+}
+
+pub fn compositorCreateRegion(compositor: *wl_compositor) ?*wl_region {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(compositor),
+        1,
+        &wl_region_interface,
+        @as(?*anyopaque, null),
+    ));
+}
+
+pub fn surfaceAttach(surface: *wl_surface, buffer: ?*wl_buffer, x: i32, y: i32) void {
+    c.wl_proxy_marshal(@ptrCast(surface), 1, buffer, x, y);
+}
+
+pub fn surfaceDamage(surface: *wl_surface, x: i32, y: i32, width: i32, height: i32) void {
+    c.wl_proxy_marshal(@ptrCast(surface), 2, x, y, width, height);
+}
+
+pub fn surfaceSetInputRegion(surface: *wl_surface, region: ?*wl_region) void {
+    c.wl_proxy_marshal(@ptrCast(surface), 5, region);
+}
+
+pub fn surfaceCommit(surface: *wl_surface) void {
+    c.wl_proxy_marshal(@ptrCast(surface), 6);
+}
+
+pub fn surfaceDestroy(surface: *wl_surface) void {
+    c.wl_proxy_marshal(@ptrCast(surface), 0);
+    c.wl_proxy_destroy(@ptrCast(surface));
+}
+
+pub fn regionDestroy(region: *wl_region) void {
+    c.wl_proxy_marshal(@ptrCast(region), 0);
+    c.wl_proxy_destroy(@ptrCast(region));
+}
+
+pub fn outputDestroy(output: *wl_output) void {
+    if (c.wl_proxy_get_version(@ptrCast(output)) >= 3) {
+        c.wl_proxy_marshal(@ptrCast(output), 0);
+        c.wl_proxy_destroy(@ptrCast(output));
+    } else {
+        c.wl_proxy_destroy(@ptrCast(output));
+    }
+}
+
+pub fn bufferDestroy(buffer: *wl_buffer) void {
+    c.wl_proxy_marshal(@ptrCast(buffer), 0);
+    c.wl_proxy_destroy(@ptrCast(buffer));
+}
+
+pub const wl_buffer_listener = extern struct {
+    release: *const fn (data: ?*anyopaque, buffer: *wl_buffer) callconv(.c) void,
+};
+
+pub fn bufferAddListener(
+    buffer: *wl_buffer,
+    listener: *const wl_buffer_listener,
+    data: ?*anyopaque,
+) c_int {
+    return c.wl_proxy_add_listener(@ptrCast(buffer), listener, data);
+}
+
+pub const wl_output_listener = extern struct {
+    geometry: *const fn (
+        data: ?*anyopaque,
+        output: *wl_output,
+        x: i32,
+        y: i32,
+        physical_width: i32,
+        physical_height: i32,
+        subpixel: i32,
+        make: [*:0]const u8,
+        model: [*:0]const u8,
+        transform: i32,
+    ) callconv(.c) void,
+    mode: *const fn (
+        data: ?*anyopaque,
+        output: *wl_output,
+        flags: u32,
+        width: i32,
+        height: i32,
+        refresh: i32,
+    ) callconv(.c) void,
+    done: *const fn (data: ?*anyopaque, output: *wl_output) callconv(.c) void,
+    scale: *const fn (data: ?*anyopaque, output: *wl_output, factor: i32) callconv(.c) void,
+    name: *const fn (data: ?*anyopaque, output: *wl_output, name: [*:0]const u8) callconv(.c) void,
+    description: *const fn (
+        data: ?*anyopaque,
+        output: *wl_output,
+        description: [*:0]const u8,
+    ) callconv(.c) void,
+};
+
+pub fn outputAddListener(
+    output: *wl_output,
+    listener: *const wl_output_listener,
+    data: ?*anyopaque,
+) c_int {
+    return c.wl_proxy_add_listener(@ptrCast(output), listener, data);
+}
+
+pub fn layerShellGetLayerSurface(
+    shell: *zwlr_layer_shell_v1,
+    surface: *wl_surface,
+    output: ?*wl_output,
+    layer: u32,
+    namespace: [*:0]const u8,
+) ?*zwlr_layer_surface_v1 {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(shell),
+        0,
+        &zwlr_layer_surface_v1_interface,
+        @as(?*anyopaque, null),
+        surface,
+        output,
+        layer,
+        namespace,
+    ));
+}
+
+pub fn layerSurfaceSetSize(layer_surface: *zwlr_layer_surface_v1, width: u32, height: u32) void {
+    c.wl_proxy_marshal(@ptrCast(layer_surface), 0, width, height);
+}
+
+pub fn layerSurfaceSetAnchor(layer_surface: *zwlr_layer_surface_v1, anchor: u32) void {
+    c.wl_proxy_marshal(@ptrCast(layer_surface), 1, anchor);
+}
+
+pub fn layerSurfaceSetExclusiveZone(layer_surface: *zwlr_layer_surface_v1, zone: i32) void {
+    c.wl_proxy_marshal(@ptrCast(layer_surface), 2, zone);
+}
+
+pub fn layerSurfaceAckConfigure(layer_surface: *zwlr_layer_surface_v1, serial: u32) void {
+    c.wl_proxy_marshal(@ptrCast(layer_surface), 6, serial);
+}
+
+pub fn layerSurfaceDestroy(layer_surface: *zwlr_layer_surface_v1) void {
+    c.wl_proxy_marshal(@ptrCast(layer_surface), 7);
+    c.wl_proxy_destroy(@ptrCast(layer_surface));
+}
+
+pub const zwlr_layer_surface_v1_listener = extern struct {
+    configure: *const fn (
+        data: ?*anyopaque,
+        layer_surface: *zwlr_layer_surface_v1,
+        serial: u32,
+        width: u32,
+        height: u32,
+    ) callconv(.c) void,
+    closed: *const fn (data: ?*anyopaque, layer_surface: *zwlr_layer_surface_v1) callconv(.c) void,
+};
+
+pub fn layerSurfaceAddListener(
+    layer_surface: *zwlr_layer_surface_v1,
+    listener: *const zwlr_layer_surface_v1_listener,
+    data: ?*anyopaque,
+) c_int {
+    return c.wl_proxy_add_listener(@ptrCast(layer_surface), listener, data);
+}
+
+pub fn viewporterGetViewport(viewporter: *wp_viewporter, surface: *wl_surface) ?*wp_viewport {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(viewporter),
+        1,
+        &wp_viewport_interface,
+        @as(?*anyopaque, null),
+        surface,
+    ));
+}
+
+pub fn viewportSetDestination(viewport: *wp_viewport, width: i32, height: i32) void {
+    c.wl_proxy_marshal(@ptrCast(viewport), 2, width, height);
+}
+
+pub fn viewportDestroy(viewport: *wp_viewport) void {
+    c.wl_proxy_marshal(@ptrCast(viewport), 0);
+    c.wl_proxy_destroy(@ptrCast(viewport));
+}
+
+pub fn dmabufCreateParams(dmabuf: *zwp_linux_dmabuf_v1) ?*zwp_linux_buffer_params_v1 {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(dmabuf),
+        1,
+        &zwp_linux_buffer_params_v1_interface,
+        @as(?*anyopaque, null),
+    ));
+}
+
+pub fn dmabufGetDefaultFeedback(dmabuf: *zwp_linux_dmabuf_v1) ?*zwp_linux_dmabuf_feedback_v1 {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(dmabuf),
+        2,
+        &zwp_linux_dmabuf_feedback_v1_interface,
+        @as(?*anyopaque, null),
+    ));
+}
+
+pub fn dmabufDestroy(dmabuf: *zwp_linux_dmabuf_v1) void {
+    c.wl_proxy_marshal(@ptrCast(dmabuf), 0);
+    c.wl_proxy_destroy(@ptrCast(dmabuf));
+}
+
+pub fn paramsAdd(
+    params: *zwp_linux_buffer_params_v1,
+    fd: std.posix.fd_t,
+    plane_index: u32,
+    offset: u32,
+    stride: u32,
+    modifier: u64,
+) void {
+    c.wl_proxy_marshal(
+        @ptrCast(params),
+        1,
+        fd,
+        plane_index,
+        offset,
+        stride,
+        @as(u32, @intCast(modifier >> 32)),
+        @as(u32, @truncate(modifier)),
+    );
+}
+
+pub fn paramsCreateImmed(
+    params: *zwp_linux_buffer_params_v1,
+    width: i32,
+    height: i32,
+    format: u32,
+    flags: u32,
+) ?*wl_buffer {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(params),
+        3,
+        &wl_buffer_interface,
+        @as(?*anyopaque, null),
+        width,
+        height,
+        format,
+        flags,
+    ));
+}
+
+pub fn paramsDestroy(params: *zwp_linux_buffer_params_v1) void {
+    c.wl_proxy_marshal(@ptrCast(params), 0);
+    c.wl_proxy_destroy(@ptrCast(params));
+}
+
+pub const zwp_linux_dmabuf_feedback_v1_listener = extern struct {
+    done: *const fn (data: ?*anyopaque, feedback: *zwp_linux_dmabuf_feedback_v1) callconv(.c) void,
+    format_table: *const fn (
+        data: ?*anyopaque,
+        feedback: *zwp_linux_dmabuf_feedback_v1,
+        fd: std.posix.fd_t,
+        size: u32,
+    ) callconv(.c) void,
+    main_device: *const fn (
+        data: ?*anyopaque,
+        feedback: *zwp_linux_dmabuf_feedback_v1,
+        device: *c.wl_array,
+    ) callconv(.c) void,
+    tranche_done: *const fn (
+        data: ?*anyopaque,
+        feedback: *zwp_linux_dmabuf_feedback_v1,
+    ) callconv(.c) void,
+    tranche_target_device: *const fn (
+        data: ?*anyopaque,
+        feedback: *zwp_linux_dmabuf_feedback_v1,
+        device: *c.wl_array,
+    ) callconv(.c) void,
+    tranche_formats: *const fn (
+        data: ?*anyopaque,
+        feedback: *zwp_linux_dmabuf_feedback_v1,
+        indices: *c.wl_array,
+    ) callconv(.c) void,
+    tranche_flags: *const fn (
+        data: ?*anyopaque,
+        feedback: *zwp_linux_dmabuf_feedback_v1,
+        flags: u32,
+    ) callconv(.c) void,
+};
+
+pub fn feedbackAddListener(
+    feedback: *zwp_linux_dmabuf_feedback_v1,
+    listener: *const zwp_linux_dmabuf_feedback_v1_listener,
+    data: ?*anyopaque,
+) c_int {
+    return c.wl_proxy_add_listener(@ptrCast(feedback), listener, data);
+}
+
+pub fn feedbackDestroy(feedback: *zwp_linux_dmabuf_feedback_v1) void {
+    c.wl_proxy_marshal(@ptrCast(feedback), 0);
+    c.wl_proxy_destroy(@ptrCast(feedback));
+}
+
+pub fn syncobjGetSurface(
+    manager: *wp_linux_drm_syncobj_manager_v1,
+    surface: *wl_surface,
+) ?*wp_linux_drm_syncobj_surface_v1 {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(manager),
+        1,
+        &wp_linux_drm_syncobj_surface_v1_interface,
+        @as(?*anyopaque, null),
+        surface,
+    ));
+}
+
+pub fn syncobjImportTimeline(
+    manager: *wp_linux_drm_syncobj_manager_v1,
+    fd: std.posix.fd_t,
+) ?*wp_linux_drm_syncobj_timeline_v1 {
+    return @ptrCast(c.wl_proxy_marshal_constructor(
+        @ptrCast(manager),
+        2,
+        &wp_linux_drm_syncobj_timeline_v1_interface,
+        @as(?*anyopaque, null),
+        fd,
+    ));
+}
+
+pub fn syncobjManagerDestroy(manager: *wp_linux_drm_syncobj_manager_v1) void {
+    c.wl_proxy_marshal(@ptrCast(manager), 0);
+    c.wl_proxy_destroy(@ptrCast(manager));
+}
+
+pub fn syncobjTimelineDestroy(timeline: *wp_linux_drm_syncobj_timeline_v1) void {
+    c.wl_proxy_marshal(@ptrCast(timeline), 0);
+    c.wl_proxy_destroy(@ptrCast(timeline));
+}
+
+pub fn syncobjSurfaceSetAcquirePoint(
+    sync_surface: *wp_linux_drm_syncobj_surface_v1,
+    timeline: *wp_linux_drm_syncobj_timeline_v1,
+    point: u64,
+) void {
+    c.wl_proxy_marshal(
+        @ptrCast(sync_surface),
+        1,
+        timeline,
+        @as(u32, @intCast(point >> 32)),
+        @as(u32, @truncate(point)),
+    );
+}
+
+pub fn syncobjSurfaceSetReleasePoint(
+    sync_surface: *wp_linux_drm_syncobj_surface_v1,
+    timeline: *wp_linux_drm_syncobj_timeline_v1,
+    point: u64,
+) void {
+    c.wl_proxy_marshal(
+        @ptrCast(sync_surface),
+        2,
+        timeline,
+        @as(u32, @intCast(point >> 32)),
+        @as(u32, @truncate(point)),
+    );
+}
+
+pub fn syncobjSurfaceDestroy(sync_surface: *wp_linux_drm_syncobj_surface_v1) void {
+    c.wl_proxy_marshal(@ptrCast(sync_surface), 0);
+    c.wl_proxy_destroy(@ptrCast(sync_surface));
+}